@@ -5,7 +5,7 @@ use compositor_pipeline::error::{ErrorType, PipelineErrorInfo};
 use compositor_render::error::ErrorStack;
 use serde::Serialize;
 
-use crate::types::TypeError;
+use crate::types::{ScenePatchError, TypeError};
 
 pub struct ApiError {
     pub error_code: &'static str,
@@ -60,6 +60,25 @@ impl From<TypeError> for ApiError {
     }
 }
 
+impl From<ScenePatchError> for ApiError {
+    fn from(err: ScenePatchError) -> Self {
+        let (error_code, http_status_code) = match err {
+            ScenePatchError::ComponentNotFound(_) => ("COMPONENT_NOT_FOUND", StatusCode::NOT_FOUND),
+            ScenePatchError::NoChildren(_) | ScenePatchError::ChildIndexOutOfBounds { .. } => {
+                ("INVALID_SCENE_PATCH", StatusCode::BAD_REQUEST)
+            }
+            ScenePatchError::InvalidScene(_) => ("MALFORMED_REQUEST", StatusCode::BAD_REQUEST),
+        };
+        let stack: Vec<String> = ErrorStack::new(&err).map(ToString::to_string).collect();
+        ApiError {
+            error_code,
+            message: stack.first().unwrap().clone(),
+            stack,
+            http_status_code,
+        }
+    }
+}
+
 impl std::fmt::Display for ApiError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(&self.message)
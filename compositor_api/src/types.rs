@@ -22,15 +22,20 @@ mod from_video;
 mod register_input;
 mod register_output;
 mod renderer;
+mod scene_patch;
 mod util;
 mod video;
 
 #[cfg(test)]
 mod from_util_test;
+#[cfg(test)]
+mod scene_patch_test;
 
 pub use component::Component;
+pub use component::ComputeShader;
 pub use component::Image;
 pub use component::InputStream;
+pub use component::Lottie;
 pub use component::Rescaler;
 pub use component::Shader;
 pub use component::Text;
@@ -38,6 +43,11 @@ pub use component::Tiles;
 pub use component::View;
 pub use component::WebView;
 
+pub use from_component::ComponentConversionError;
+
+pub use scene_patch::ScenePatch;
+pub use scene_patch::ScenePatchError;
+
 pub use register_input::DeckLink;
 pub use register_input::Mp4Input;
 pub use register_input::RtpInput;
@@ -46,7 +56,9 @@ pub use register_output::Mp4Output;
 pub use register_output::RtpOutput;
 pub use register_output::WhipOutput;
 
+pub use renderer::ComputeShaderSpec;
 pub use renderer::ImageSpec;
+pub use renderer::LottieSpec;
 pub use renderer::ShaderSpec;
 pub use renderer::WebRendererSpec;
 
@@ -78,12 +90,43 @@ pub struct UpdateOutputRequest {
     pub schedule_time_ms: Option<f64>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RenderPreviewRequest {
+    /// Scene to render.
+    pub video: Video,
+    /// Resolution of the rendered image.
+    pub resolution: Resolution,
+    /// (**default=`0`**) Timestamp of the rendered frame in milliseconds.
+    pub pts_ms: Option<f64>,
+    /// Still images used as frames of the inputs. Inputs that are not listed here
+    /// are rendered as if they did not deliver any frames yet.
+    pub inputs: Option<Vec<PreviewInput>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PreviewInput {
+    /// Id of the input in the scene.
+    pub input_id: InputId,
+    /// Path to a PNG or JPEG image. Exactly one of `url` and `path` has to be specified.
+    pub path: Option<String>,
+    /// URL of a PNG or JPEG image. Exactly one of `url` and `path` has to be specified.
+    pub url: Option<String>,
+}
+
 impl Display for InputId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.0.fmt(f)
     }
 }
 
+impl Display for ComponentId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 impl Display for OutputId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.0.fmt(f)
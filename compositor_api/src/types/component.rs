@@ -13,7 +13,9 @@ pub enum Component {
     View(View),
     WebView(WebView),
     Shader(Shader),
+    ComputeShader(ComputeShader),
     Image(Image),
+    Lottie(Lottie),
     Text(Text),
     Tiles(Tiles),
     Rescaler(Rescaler),
@@ -256,6 +258,45 @@ pub struct Image {
     pub image_id: RendererId,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Lottie {
+    /// Id of a component.
+    pub id: Option<ComponentId>,
+
+    /// Id of a Lottie animation. It identifies an animation registered using a [`register lottie`](../routes.md#register-lottie) request.
+    pub lottie_id: RendererId,
+
+    /// (**default=`true`**) Restart the animation after the last frame. If `false`, the last frame
+    /// stays on screen after the animation ends.
+    pub repeat: Option<bool>,
+
+    /// Replace content of text layers.
+    pub text_overrides: Option<Vec<LottieTextOverride>>,
+
+    /// Replace colors of fills and strokes.
+    pub color_overrides: Option<Vec<LottieColorOverride>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct LottieTextOverride {
+    /// Name of a text layer in the animation.
+    pub layer_name: Arc<str>,
+    /// Text that should replace the content of the layer.
+    pub text: Arc<str>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct LottieColorOverride {
+    /// Name of a layer, shape group, fill or stroke in the animation. Override applies
+    /// to all fills and strokes inside the matching element.
+    pub name: Arc<str>,
+    /// Color in a `"#RRGGBBAA"` format.
+    pub color: RGBAColor,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct Shader {
@@ -273,15 +314,37 @@ pub struct Shader {
     /// ```
     /// :::note
     ///   This object's structure must match the structure defined in a shader source code.
-    ///   Currently, we do not handle memory layout automatically. To achieve the correct memory
-    ///   alignment, you might need to pad your data with additional fields. See
-    ///   [WGSL documentation](https://www.w3.org/TR/WGSL/#alignment-and-size) for more details.
+    ///   Memory layout (alignment and padding of struct fields, vectors, matrices and arrays)
+    ///   is derived from the shader, so you don't need to add any padding fields.
     /// :::
     pub shader_param: Option<ShaderParam>,
     /// Resolution of a texture where shader will be executed.
     pub resolution: Resolution,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ComputeShader {
+    /// Id of a component.
+    pub id: Option<ComponentId>,
+    /// List of component's children.
+    pub children: Option<Vec<Component>>,
+
+    /// Id of a compute shader. It identifies a shader registered using a [`register compute shader`](../routes.md#register-compute-shader) request.
+    pub compute_shader_id: RendererId,
+    /// Object that will be serialized into a `struct` and passed inside the shader as:
+    ///
+    /// ```wgsl
+    /// @group(1) @binding(0) var<uniform>
+    /// ```
+    /// :::note
+    ///   This object's structure must match the structure defined in a shader source code.
+    /// :::
+    pub shader_param: Option<ShaderParam>,
+    /// Resolution of the output texture written by the compute shader.
+    pub resolution: Resolution,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(
     tag = "type",
@@ -293,6 +356,19 @@ pub enum ShaderParam {
     F32(f32),
     U32(u32),
     I32(i32),
+    /// Booleans are not allowed in uniform buffers, the value is passed to the shader as
+    /// a `u32` field equal to `0` or `1`.
+    Bool(bool),
+    /// Value for a `vec2<f32>` field.
+    Vec2([f32; 2]),
+    /// Value for a `vec3<f32>` field.
+    Vec3([f32; 3]),
+    /// Value for a `vec4<f32>` field.
+    Vec4([f32; 4]),
+    /// Value for a `matCxR<f32>` field provided as a list of `C` columns with `R` elements each.
+    Matrix(Vec<Vec<f32>>),
+    /// Value for an array, or for a `matCxR` field provided as a list of `R` rows with `C` elements
+    /// each. Use `matrix` to provide a matrix as a list of columns.
     List(Vec<ShaderParam>),
     Struct(Vec<ShaderParamStructField>),
 }
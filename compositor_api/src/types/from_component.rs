@@ -7,6 +7,7 @@ use compositor_render::MAX_NODE_RESOLUTION;
 
 use super::component::*;
 use super::util::*;
+use super::InputId;
 
 impl TryFrom<Component> for scene::Component {
     type Error = TypeError;
@@ -17,7 +18,9 @@ impl TryFrom<Component> for scene::Component {
             Component::View(view) => Ok(Self::View(view.try_into()?)),
             Component::WebView(web) => Ok(Self::WebView(web.try_into()?)),
             Component::Shader(shader) => Ok(Self::Shader(shader.try_into()?)),
+            Component::ComputeShader(shader) => Ok(Self::ComputeShader(shader.try_into()?)),
             Component::Image(image) => Ok(Self::Image(image.into())),
+            Component::Lottie(lottie) => Ok(Self::Lottie(lottie.try_into()?)),
             Component::Text(text) => Ok(Self::Text(text.try_into()?)),
             Component::Tiles(tiles) => Ok(Self::Tiles(tiles.try_into()?)),
             Component::Rescaler(rescaler) => Ok(Self::Rescaler(rescaler.try_into()?)),
@@ -241,6 +244,25 @@ impl TryFrom<Shader> for scene::ShaderComponent {
     }
 }
 
+impl TryFrom<ComputeShader> for scene::ComputeShaderComponent {
+    type Error = TypeError;
+
+    fn try_from(shader: ComputeShader) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: shader.id.map(Into::into),
+            compute_shader_id: shader.compute_shader_id.into(),
+            shader_param: shader.shader_param.map(Into::into),
+            size: shader.resolution.into(),
+            children: shader
+                .children
+                .unwrap_or_default()
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
 impl From<ShaderParam> for scene::ShaderParam {
     fn from(param: ShaderParam) -> Self {
         fn from_struct_field(field: ShaderParamStructField) -> scene::ShaderParamStructField {
@@ -253,6 +275,11 @@ impl From<ShaderParam> for scene::ShaderParam {
             ShaderParam::F32(v) => scene::ShaderParam::F32(v),
             ShaderParam::U32(v) => scene::ShaderParam::U32(v),
             ShaderParam::I32(v) => scene::ShaderParam::I32(v),
+            ShaderParam::Bool(v) => scene::ShaderParam::Bool(v),
+            ShaderParam::Vec2(v) => scene::ShaderParam::Vec2(v),
+            ShaderParam::Vec3(v) => scene::ShaderParam::Vec3(v),
+            ShaderParam::Vec4(v) => scene::ShaderParam::Vec4(v),
+            ShaderParam::Matrix(v) => scene::ShaderParam::Matrix(v),
             ShaderParam::List(v) => {
                 scene::ShaderParam::List(v.into_iter().map(Into::into).collect())
             }
@@ -272,6 +299,40 @@ impl From<Image> for scene::ImageComponent {
     }
 }
 
+impl TryFrom<Lottie> for scene::LottieComponent {
+    type Error = TypeError;
+
+    fn try_from(lottie: Lottie) -> Result<Self, Self::Error> {
+        let color_overrides = lottie
+            .color_overrides
+            .unwrap_or_default()
+            .into_iter()
+            .map(|color_override| {
+                Ok(scene::LottieColorOverride {
+                    name: color_override.name,
+                    color: color_override.color.try_into()?,
+                })
+            })
+            .collect::<Result<Vec<_>, TypeError>>()?;
+        let text_overrides = lottie
+            .text_overrides
+            .unwrap_or_default()
+            .into_iter()
+            .map(|text_override| scene::LottieTextOverride {
+                layer_name: text_override.layer_name,
+                text: text_override.text,
+            })
+            .collect();
+        Ok(Self {
+            id: lottie.id.map(Into::into),
+            lottie_id: lottie.lottie_id.into(),
+            repeat: lottie.repeat.unwrap_or(true),
+            text_overrides,
+            color_overrides,
+        })
+    }
+}
+
 impl TryFrom<Text> for scene::TextComponent {
     type Error = TypeError;
 
@@ -408,3 +469,65 @@ impl TryFrom<BoxShadow> for scene::BoxShadow {
         })
     }
 }
+
+/// Conversion error together with a path to the component that caused it.
+#[derive(Debug)]
+pub struct ComponentConversionError {
+    pub path: String,
+    pub error: TypeError,
+}
+
+impl Component {
+    /// Converts every component in the tree separately, so all invalid components are
+    /// reported instead of just the first one. `path` is a location of this component
+    /// in the request, e.g. `video.root`.
+    pub fn conversion_errors(self, path: String) -> Vec<ComponentConversionError> {
+        let mut errors = Vec::new();
+        let mut components = vec![(path, self)];
+        while let Some((path, mut component)) = components.pop() {
+            let children = take_children(&mut component);
+            if let Err(error) = scene::Component::try_from(component) {
+                errors.push(ComponentConversionError {
+                    path: path.clone(),
+                    error,
+                });
+            }
+            let children = children
+                .into_iter()
+                .map(|(suffix, child)| (format!("{path}{suffix}"), child));
+            // reversed, so errors are reported in the order of the components in the request
+            components.extend(children.rev());
+        }
+        errors
+    }
+}
+
+/// Removes children from the component, so it can be converted on its own. Returned
+/// children are paired with their path relative to the parent.
+fn take_children(component: &mut Component) -> Vec<(String, Component)> {
+    let children = match component {
+        Component::View(View { children, .. })
+        | Component::WebView(WebView { children, .. })
+        | Component::Shader(Shader { children, .. })
+        | Component::ComputeShader(ComputeShader { children, .. })
+        | Component::Tiles(Tiles { children, .. }) => children.take().unwrap_or_default(),
+        Component::Rescaler(rescaler) => {
+            // rescaler requires exactly one child, so it is replaced with a placeholder
+            let placeholder = Component::InputStream(InputStream {
+                id: None,
+                input_id: InputId(Arc::from("")),
+            });
+            let child = std::mem::replace(rescaler.child.as_mut(), placeholder);
+            return vec![(".child".to_string(), child)];
+        }
+        Component::InputStream(_)
+        | Component::Image(_)
+        | Component::Lottie(_)
+        | Component::Text(_) => Vec::new(),
+    };
+    children
+        .into_iter()
+        .enumerate()
+        .map(|(index, child)| (format!(".children[{index}]"), child))
+        .collect()
+}
@@ -81,6 +81,78 @@ impl TryFrom<InputRtpAudioOptions> for rtp::InputAudioStream {
     }
 }
 
+const DEFAULT_ADAPTIVE_BUFFER_MIN_DURATION_MS: f64 = 20.0;
+const DEFAULT_ADAPTIVE_BUFFER_MAX_DURATION_MS: f64 = 1000.0;
+
+fn buffer_duration(duration_ms: f64) -> Result<Duration, TypeError> {
+    const INVALID_BUFFER_DURATION: &str =
+        "Buffer duration has to be a non-negative finite number of milliseconds.";
+    if !duration_ms.is_finite() || duration_ms < 0.0 {
+        return Err(TypeError::new(INVALID_BUFFER_DURATION));
+    }
+    Ok(Duration::from_secs_f64(duration_ms / 1000.0))
+}
+
+impl TryFrom<InputAdaptiveBuffer> for queue::AdaptiveBufferOptions {
+    type Error = TypeError;
+
+    fn try_from(value: InputAdaptiveBuffer) -> Result<Self, Self::Error> {
+        let min_duration = buffer_duration(
+            value
+                .min_duration_ms
+                .unwrap_or(DEFAULT_ADAPTIVE_BUFFER_MIN_DURATION_MS),
+        )?;
+        let max_duration = buffer_duration(
+            value
+                .max_duration_ms
+                .unwrap_or(DEFAULT_ADAPTIVE_BUFFER_MAX_DURATION_MS),
+        )?;
+
+        const MIN_LARGER_THAN_MAX: &str =
+            "`min_duration_ms` can't be larger than `max_duration_ms` in `adaptive_buffer`.";
+        if min_duration > max_duration {
+            return Err(TypeError::new(MIN_LARGER_THAN_MAX));
+        }
+
+        Ok(Self {
+            min_duration,
+            max_duration,
+        })
+    }
+}
+
+impl From<FrameRateConversion> for queue::FrameRateConversion {
+    fn from(value: FrameRateConversion) -> Self {
+        match value {
+            FrameRateConversion::Nearest => queue::FrameRateConversion::Nearest,
+            FrameRateConversion::Blend => queue::FrameRateConversion::Blend,
+        }
+    }
+}
+
+impl TryFrom<InputRtpJitterBuffer> for rtp::RtpJitterBufferOptions {
+    type Error = TypeError;
+
+    fn try_from(value: InputRtpJitterBuffer) -> Result<Self, Self::Error> {
+        let default = rtp::RtpJitterBufferOptions::default();
+
+        const INVALID_LATENCY: &str =
+            "Jitter buffer `latency_ms` has to be a non-negative finite number.";
+        let latency = match value.latency_ms {
+            Some(latency_ms) if !latency_ms.is_finite() || latency_ms < 0.0 => {
+                return Err(TypeError::new(INVALID_LATENCY));
+            }
+            Some(latency_ms) => Duration::from_secs_f64(latency_ms / 1000.0),
+            None => default.latency,
+        };
+
+        Ok(Self {
+            latency,
+            rtcp_feedback: value.rtcp_feedback.unwrap_or(default.rtcp_feedback),
+        })
+    }
+}
+
 #[cfg(not(feature = "vk-video"))]
 const NO_VULKAN_VIDEO: &str =
     "Requested `vulkan_video` decoder, but this binary was compiled without the `vk-video` feature.";
@@ -96,6 +168,10 @@ impl TryFrom<RtpInput> for pipeline::RegisterInputOptions {
             required,
             offset_ms,
             transport_protocol,
+            buffer_duration_ms,
+            adaptive_buffer,
+            jitter_buffer,
+            frame_rate_conversion,
         } = value;
 
         const NO_VIDEO_AUDIO_SPEC: &str =
@@ -105,6 +181,14 @@ impl TryFrom<RtpInput> for pipeline::RegisterInputOptions {
             return Err(TypeError::new(NO_VIDEO_AUDIO_SPEC));
         }
 
+        const INVALID_RTX_PAYLOAD_TYPE: &str = "`rtx_payload_type` has to be in the range 98-127.";
+        let rtx_payload_type = video.as_ref().and_then(|video| video.rtx_payload_type);
+        if let Some(rtx_payload_type) = rtx_payload_type {
+            if !(98..=127).contains(&rtx_payload_type) {
+                return Err(TypeError::new(INVALID_RTX_PAYLOAD_TYPE));
+            }
+        }
+
         let rtp_stream = input::rtp::RtpStream {
             video: video
                 .as_ref()
@@ -125,22 +209,38 @@ impl TryFrom<RtpInput> for pipeline::RegisterInputOptions {
                                 return Err(TypeError::new(NO_VULKAN_VIDEO))
                             }
                         },
+                        rtx_payload_type,
                     })
                 })
                 .transpose()?,
             audio: audio.map(TryFrom::try_from).transpose()?,
         };
 
+        let transport_protocol = transport_protocol.unwrap_or(TransportProtocol::Udp);
+        let rtcp_feedback = jitter_buffer.as_ref().and_then(|opts| opts.rtcp_feedback);
+        let mut jitter_buffer: rtp::RtpJitterBufferOptions = jitter_buffer
+            .map(TryFrom::try_from)
+            .transpose()?
+            .unwrap_or_default();
+        // TCP client has to read from the connection to receive the feedback,
+        // so it is only sent if explicitly enabled.
+        if rtcp_feedback.is_none() && matches!(transport_protocol, TransportProtocol::TcpServer) {
+            jitter_buffer.rtcp_feedback = false;
+        }
+
         let input_options = input::InputOptions::Rtp(input::rtp::RtpReceiverOptions {
             port: port.try_into()?,
             stream: rtp_stream,
-            transport_protocol: transport_protocol.unwrap_or(TransportProtocol::Udp).into(),
+            transport_protocol: transport_protocol.into(),
+            jitter_buffer,
         });
 
         let queue_options = queue::QueueInputOptions {
             required: required.unwrap_or(false),
             offset: offset_ms.map(|offset_ms| Duration::from_secs_f64(offset_ms / 1000.0)),
-            buffer_duration: None,
+            buffer_duration: buffer_duration_ms.map(buffer_duration).transpose()?,
+            adaptive_buffer: adaptive_buffer.map(TryFrom::try_from).transpose()?,
+            frame_rate_conversion: frame_rate_conversion.map(Into::into).unwrap_or_default(),
         };
 
         Ok(pipeline::RegisterInputOptions {
@@ -178,6 +278,9 @@ impl TryFrom<WhipInput> for pipeline::RegisterInputOptions {
             audio,
             required,
             offset_ms,
+            buffer_duration_ms,
+            adaptive_buffer,
+            frame_rate_conversion,
         } = value;
 
         const NO_VIDEO_AUDIO_SPEC: &str =
@@ -216,7 +319,9 @@ impl TryFrom<WhipInput> for pipeline::RegisterInputOptions {
         let queue_options = queue::QueueInputOptions {
             required: required.unwrap_or(false),
             offset: offset_ms.map(|offset_ms| Duration::from_secs_f64(offset_ms / 1000.0)),
-            buffer_duration: None,
+            buffer_duration: buffer_duration_ms.map(buffer_duration).transpose()?,
+            adaptive_buffer: adaptive_buffer.map(TryFrom::try_from).transpose()?,
+            frame_rate_conversion: frame_rate_conversion.map(Into::into).unwrap_or_default(),
         };
 
         Ok(pipeline::RegisterInputOptions {
@@ -237,6 +342,7 @@ impl TryFrom<Mp4Input> for pipeline::RegisterInputOptions {
             offset_ms,
             should_loop,
             video_decoder,
+            frame_rate_conversion,
         } = value;
 
         const BAD_URL_PATH_SPEC: &str =
@@ -254,6 +360,8 @@ impl TryFrom<Mp4Input> for pipeline::RegisterInputOptions {
             required: required.unwrap_or(false),
             offset: offset_ms.map(|offset_ms| Duration::from_secs_f64(offset_ms / 1000.0)),
             buffer_duration: None,
+            adaptive_buffer: None,
+            frame_rate_conversion: frame_rate_conversion.map(Into::into).unwrap_or_default(),
         };
 
         let video_decoder = match video_decoder.unwrap_or(VideoDecoder::FfmpegH264) {
@@ -309,6 +417,8 @@ impl TryFrom<DeckLink> for pipeline::RegisterInputOptions {
                 required: value.required.unwrap_or(false),
                 offset: None,
                 buffer_duration: Some(Duration::from_millis(5)),
+                adaptive_buffer: None,
+                frame_rate_conversion: queue::FrameRateConversion::Nearest,
             },
         })
     }
@@ -286,9 +286,20 @@ fn maybe_video_options(
         }),
     };
 
+    let framerate = options
+        .framerate
+        .map(compositor_render::Framerate::try_from)
+        .transpose()?;
+    if framerate.is_some_and(|framerate| framerate.num == 0 || framerate.den == 0) {
+        return Err(TypeError::new(
+            "Framerate numerator and denominator need to be greater than 0.",
+        ));
+    }
+
     let output_options = pipeline::OutputVideoOptions {
         initial: options.initial.try_into()?,
         end_condition: options.send_eos_when.unwrap_or_default().try_into()?,
+        framerate,
     };
 
     Ok((Some(encoder_options), Some(output_options)))
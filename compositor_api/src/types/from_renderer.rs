@@ -1,4 +1,6 @@
+use compositor_render::compute_shader;
 use compositor_render::image;
+use compositor_render::lottie;
 use compositor_render::shader;
 use compositor_render::web_renderer;
 
@@ -9,13 +11,67 @@ impl TryFrom<ShaderSpec> for compositor_render::RendererSpec {
     type Error = TypeError;
 
     fn try_from(spec: ShaderSpec) -> Result<Self, Self::Error> {
+        fn from_inputs(inputs: Vec<ShaderPassInput>) -> Vec<shader::ShaderPassInput> {
+            inputs
+                .into_iter()
+                .map(|input| match input {
+                    ShaderPassInput::Input { index } => shader::ShaderPassInput::Input(index),
+                    ShaderPassInput::Pass { name } => shader::ShaderPassInput::Pass(name.into()),
+                })
+                .collect()
+        }
+
+        let passes = spec
+            .passes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|pass| shader::ShaderPassSpec {
+                name: pass.name.into(),
+                source: pass.source.into(),
+                resolution: match pass.resolution {
+                    Some(ShaderPassResolution::Scale { factor }) => {
+                        shader::ShaderPassResolution::Scale(factor)
+                    }
+                    Some(ShaderPassResolution::Fixed { width, height }) => {
+                        shader::ShaderPassResolution::Fixed(compositor_render::Resolution {
+                            width,
+                            height,
+                        })
+                    }
+                    None => shader::ShaderPassResolution::Scale(1.0),
+                },
+                inputs: pass.inputs.map(from_inputs),
+            })
+            .collect();
+
         let spec = shader::ShaderSpec {
             source: spec.source.into(),
+            previous_frame: spec.previous_frame.unwrap_or(false),
+            input_history_len: spec.input_history_len.unwrap_or(0),
+            inputs: spec.inputs.map(from_inputs),
+            passes,
         };
         Ok(Self::Shader(spec))
     }
 }
 
+impl TryFrom<ComputeShaderSpec> for compositor_render::RendererSpec {
+    type Error = TypeError;
+
+    fn try_from(spec: ComputeShaderSpec) -> Result<Self, Self::Error> {
+        let spec = compute_shader::ComputeShaderSpec {
+            source: spec.source.into(),
+            storage_buffers: spec
+                .storage_buffers
+                .unwrap_or_default()
+                .into_iter()
+                .map(|buffer| buffer.size)
+                .collect(),
+        };
+        Ok(Self::ComputeShader(spec))
+    }
+}
+
 impl TryFrom<WebRendererSpec> for compositor_render::RendererSpec {
     type Error = TypeError;
 
@@ -88,3 +144,28 @@ impl TryFrom<ImageSpec> for compositor_render::RendererSpec {
         Ok(Self::Image(image))
     }
 }
+
+impl TryFrom<LottieSpec> for compositor_render::RendererSpec {
+    type Error = TypeError;
+
+    fn try_from(spec: LottieSpec) -> Result<Self, Self::Error> {
+        let src = match (spec.url, spec.path) {
+            (None, None) => {
+                return Err(TypeError::new(
+                    "\"url\" or \"path\" field is required when registering a Lottie animation.",
+                ))
+            }
+            (None, Some(path)) => lottie::LottieSource::LocalPath { path },
+            (Some(url), None) => lottie::LottieSource::Url { url },
+            (Some(_), Some(_)) => {
+                return Err(TypeError::new(
+                    "\"url\" and \"path\" fields are mutually exclusive when registering a Lottie animation.",
+                ))
+            }
+        };
+        Ok(Self::Lottie(lottie::LottieSpec {
+            src,
+            resolution: spec.resolution.map(Into::into),
+        }))
+    }
+}
@@ -25,3 +25,15 @@ impl From<InputId> for compositor_render::InputId {
         id.0.into()
     }
 }
+
+impl From<compositor_render::OutputId> for OutputId {
+    fn from(id: compositor_render::OutputId) -> Self {
+        Self(id.0)
+    }
+}
+
+impl From<compositor_render::InputId> for InputId {
+    fn from(id: compositor_render::InputId) -> Self {
+        Self(id.0)
+    }
+}
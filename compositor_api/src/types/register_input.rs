@@ -25,6 +25,32 @@ pub struct RtpInput {
     /// not defined then the stream will be synchronized based on the delivery time of the initial
     /// frames.
     pub offset_ms: Option<f64>,
+    /// Duration in milliseconds of the stream that is buffered before the input starts. Higher values
+    /// increase latency, but make the input more resilient to network jitter. Defaults to the value of
+    /// `SMELTER_INPUT_BUFFER_DURATION_MS` environment variable.
+    pub buffer_duration_ms: Option<f64>,
+    /// Adjust buffer duration based on the jitter measured on the input stream. If enabled, the
+    /// `buffer_duration_ms` is used as an initial value until enough frames are received to
+    /// estimate the jitter. Ignored if `offset_ms` is defined.
+    pub adaptive_buffer: Option<InputAdaptiveBuffer>,
+    /// Parameters of the jitter buffer that reorders RTP packets and requests retransmission
+    /// of the lost ones.
+    pub jitter_buffer: Option<InputRtpJitterBuffer>,
+    /// (**default=`"nearest"`**) Defines how frames are selected if the input framerate does not
+    /// match the framerate of an output.
+    pub frame_rate_conversion: Option<FrameRateConversion>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct InputRtpJitterBuffer {
+    /// (**default=`100`**) Maximal time in milliseconds to wait for a missing packet. Packets
+    /// received in order are not delayed.
+    pub latency_ms: Option<f64>,
+    /// (**default=`true` for `udp`, `false` for `tcp_server`**) Send RTCP feedback to the sender:
+    /// receiver reports, NACKs for missing packets, and PLI/FIR keyframe requests if a video packet
+    /// could not be recovered.
+    pub rtcp_feedback: Option<bool>,
 }
 
 /// Parameters for an input stream for WHIP server.
@@ -43,6 +69,37 @@ pub struct WhipInput {
     /// not defined then the stream will be synchronized based on the delivery time of the initial
     /// frames.
     pub offset_ms: Option<f64>,
+    /// Duration in milliseconds of the stream that is buffered before the input starts. Higher values
+    /// increase latency, but make the input more resilient to network jitter. Defaults to the value of
+    /// `SMELTER_INPUT_BUFFER_DURATION_MS` environment variable.
+    pub buffer_duration_ms: Option<f64>,
+    /// Adjust buffer duration based on the jitter measured on the input stream. If enabled, the
+    /// `buffer_duration_ms` is used as an initial value until enough frames are received to
+    /// estimate the jitter. Ignored if `offset_ms` is defined.
+    pub adaptive_buffer: Option<InputAdaptiveBuffer>,
+    /// (**default=`"nearest"`**) Defines how frames are selected if the input framerate does not
+    /// match the framerate of an output.
+    pub frame_rate_conversion: Option<FrameRateConversion>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameRateConversion {
+    /// Use the frame closest to the PTS of an output frame.
+    Nearest,
+    /// Mix two neighbouring frames, weighted by their distance to the PTS of an output frame.
+    /// Reduces judder, e.g. when 24 fps input is used in 30 fps or 60 fps output.
+    Blend,
+}
+
+/// Bounds for the input buffer duration when it is adjusted to the measured jitter.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct InputAdaptiveBuffer {
+    /// (**default=`20`**) Minimal buffer duration in milliseconds.
+    pub min_duration_ms: Option<f64>,
+    /// (**default=`1000`**) Maximal buffer duration in milliseconds.
+    pub max_duration_ms: Option<f64>,
 }
 
 /// Input stream from MP4 file.
@@ -65,6 +122,9 @@ pub struct Mp4Input {
     pub offset_ms: Option<f64>,
     /// (**default=`ffmpeg_h264`**) The decoder to use for decoding video.
     pub video_decoder: Option<VideoDecoder>,
+    /// (**default=`"nearest"`**) Defines how frames are selected if the input framerate does not
+    /// match the framerate of an output.
+    pub frame_rate_conversion: Option<FrameRateConversion>,
 }
 
 /// Capture streams from devices connected to Blackmagic DeckLink card.
@@ -156,6 +216,9 @@ pub enum InputWhipAudioOptions {
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct InputRtpVideoOptions {
     pub decoder: VideoDecoder,
+    /// Payload type of retransmission packets ([RFC 4588](https://datatracker.ietf.org/doc/html/rfc4588)).
+    /// Retransmissions are not supported if not defined. Value has to be in the range 98-127.
+    pub rtx_payload_type: Option<u8>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
@@ -56,6 +56,8 @@ pub struct WhipOutput {
 pub struct OutputVideoOptions {
     /// Output resolution in pixels.
     pub resolution: Resolution,
+    /// (**default=`SMELTER_OUTPUT_FRAMERATE`**) Framerate of the output video stream. This value can be a number or string in the `NUM/DEN` format, where both `NUM` and `DEN` are unsigned integers.
+    pub framerate: Option<Framerate>,
     /// Defines when output stream should end if some of the input streams are finished. If output includes both audio and video streams, then EOS needs to be sent on both.
     pub send_eos_when: Option<OutputEndCondition>,
     /// Video encoder options.
@@ -8,6 +8,66 @@ use super::util::*;
 pub struct ShaderSpec {
     /// Shader source code. [Learn more.](../../concept/shaders)
     pub source: String,
+    /// (**default=`false`**) Bind the previous frame rendered by this shader. [Learn more.](../../concept/shaders#frame-history)
+    pub previous_frame: Option<bool>,
+    /// (**default=`0`**) Number of past frames of each input that are bound to the shader
+    /// (max 4). [Learn more.](../../concept/shaders#frame-history)
+    pub input_history_len: Option<u32>,
+    /// Textures bound to the main shader (`source`). Defaults to all inputs of the component.
+    /// [Learn more.](../../concept/shaders#multi-pass-shaders)
+    pub inputs: Option<Vec<ShaderPassInput>>,
+    /// Intermediate passes rendered in order before the main shader. Each pass can read outputs
+    /// of earlier passes. [Learn more.](../../concept/shaders#multi-pass-shaders)
+    pub passes: Option<Vec<ShaderPass>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ShaderPass {
+    /// Name of a pass. Used to reference the pass output in `inputs` of later passes.
+    pub name: String,
+    /// Shader source code of the pass.
+    pub source: String,
+    /// (**default=`{ "type": "scale", "factor": 1.0 }`**) Resolution of the pass output.
+    pub resolution: Option<ShaderPassResolution>,
+    /// Textures bound to the pass. Defaults to all inputs of the component.
+    pub inputs: Option<Vec<ShaderPassInput>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ShaderPassResolution {
+    /// Resolution of the shader component multiplied by `factor` (max 4).
+    Scale { factor: f32 },
+    /// Fixed resolution, limited by the maximal texture size supported by the GPU.
+    Fixed { width: usize, height: usize },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ShaderPassInput {
+    /// Input of the shader component with the specified index.
+    Input { index: usize },
+    /// Output of an earlier pass with the specified name.
+    Pass { name: String },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ComputeShaderSpec {
+    /// Compute shader source code. [Learn more.](../../concept/shaders#compute-shaders)
+    pub source: String,
+    /// Storage buffers bound to the shader as `@group(3) @binding(i)`, where `i` is an index of
+    /// a buffer on this list. Buffers are zero-initialized and their content is preserved between
+    /// frames. [Learn more.](../../concept/shaders#compute-shaders)
+    pub storage_buffers: Option<Vec<StorageBuffer>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StorageBuffer {
+    /// Size of a buffer in bytes. Has to be a multiple of 4.
+    pub size: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
@@ -58,3 +118,15 @@ pub enum ImageSpec {
         path: Option<String>,
     },
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct LottieSpec {
+    /// Url of a Lottie JSON file.
+    pub url: Option<String>,
+    /// Path to a Lottie JSON file.
+    pub path: Option<String>,
+    /// Resolution of the rendered animation. Defaults to the size defined in the file.
+    /// Aspect ratio of the animation is preserved.
+    pub resolution: Option<Resolution>,
+}
@@ -0,0 +1,241 @@
+use std::{error::Error, fmt::Display};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{Component, ComponentId, Video};
+
+/// Modification of a single component in the scene. The component is identified
+/// by its `id`.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ScenePatch {
+    /// Replace the component (together with its children) with a new definition.
+    Replace {
+        component_id: ComponentId,
+        component: Component,
+    },
+    /// Merge properties into the component definition (JSON merge patch). Nested
+    /// objects are merged recursively and `null` removes a property.
+    Merge {
+        component_id: ComponentId,
+        properties: serde_json::Map<String, Value>,
+    },
+    /// Insert a new child into the component.
+    InsertChild {
+        component_id: ComponentId,
+        /// (**default=number of children**) Position of the new child.
+        index: Option<usize>,
+        component: Component,
+    },
+    /// Remove a child from the component.
+    RemoveChild {
+        component_id: ComponentId,
+        /// Position of the removed child.
+        index: usize,
+    },
+}
+
+#[derive(Debug)]
+pub enum ScenePatchError {
+    ComponentNotFound(ComponentId),
+    /// Component does not have a list of children, e.g. it is an image or a rescaler.
+    NoChildren(ComponentId),
+    ChildIndexOutOfBounds {
+        component_id: ComponentId,
+        index: usize,
+        children_count: usize,
+    },
+    /// Scene after applying the patch is not a valid scene definition.
+    InvalidScene(serde_json::Error),
+}
+
+/// Component types that store children in the `children` array.
+const TYPES_WITH_CHILDREN: [&str; 5] = ["view", "tiles", "shader", "compute_shader", "web_view"];
+
+impl ScenePatch {
+    pub fn component_id(&self) -> &ComponentId {
+        match self {
+            ScenePatch::Replace { component_id, .. }
+            | ScenePatch::Merge { component_id, .. }
+            | ScenePatch::InsertChild { component_id, .. }
+            | ScenePatch::RemoveChild { component_id, .. } => component_id,
+        }
+    }
+}
+
+impl Video {
+    /// Returns a copy of the scene with the patch applied.
+    pub fn patch(&self, patch: &ScenePatch) -> Result<Video, ScenePatchError> {
+        let component_id = patch.component_id();
+        let mut root = serde_json::to_value(&self.root).map_err(ScenePatchError::InvalidScene)?;
+        let component = find_component(&mut root, &component_id.0)
+            .ok_or_else(|| ScenePatchError::ComponentNotFound(component_id.clone()))?;
+
+        match patch {
+            ScenePatch::Replace { component: new, .. } => {
+                *component = serde_json::to_value(new).map_err(ScenePatchError::InvalidScene)?;
+            }
+            ScenePatch::Merge { properties, .. } => {
+                merge_patch(component, &Value::Object(properties.clone()));
+            }
+            ScenePatch::InsertChild {
+                index,
+                component: new,
+                ..
+            } => {
+                let children = children_mut(component, component_id)?;
+                let index = index.unwrap_or(children.len());
+                if index > children.len() {
+                    return Err(ScenePatchError::ChildIndexOutOfBounds {
+                        component_id: component_id.clone(),
+                        index,
+                        children_count: children.len(),
+                    });
+                }
+                let new = serde_json::to_value(new).map_err(ScenePatchError::InvalidScene)?;
+                children.insert(index, new);
+            }
+            ScenePatch::RemoveChild { index, .. } => {
+                let children = children_mut(component, component_id)?;
+                if *index >= children.len() {
+                    return Err(ScenePatchError::ChildIndexOutOfBounds {
+                        component_id: component_id.clone(),
+                        index: *index,
+                        children_count: children.len(),
+                    });
+                }
+                children.remove(*index);
+            }
+        }
+
+        let root = serde_json::from_value(root).map_err(ScenePatchError::InvalidScene)?;
+        Ok(Video { root })
+    }
+
+    /// Returns a copy of the scene where all components, except the one with
+    /// `component_id`, have their transitions removed.
+    ///
+    /// When such scene is applied, components that are not affected by the patch
+    /// continue their ongoing transitions instead of starting them again.
+    pub fn without_transitions_except(&self, component_id: &ComponentId) -> Video {
+        let mut root = match serde_json::to_value(&self.root) {
+            Ok(root) => root,
+            Err(_) => return self.clone(),
+        };
+        remove_transitions(&mut root, &component_id.0);
+        match serde_json::from_value(root) {
+            Ok(root) => Video { root },
+            Err(_) => self.clone(),
+        }
+    }
+}
+
+fn child_components(component: &mut Value) -> Vec<&mut Value> {
+    let Some(component) = component.as_object_mut() else {
+        return Vec::new();
+    };
+    component
+        .iter_mut()
+        .filter(|(key, _)| *key == "children" || *key == "child")
+        .flat_map(|(_, value)| match value {
+            Value::Array(children) => children.iter_mut().collect(),
+            child => vec![child],
+        })
+        .collect()
+}
+
+fn find_component<'a>(component: &'a mut Value, component_id: &str) -> Option<&'a mut Value> {
+    if component.get("id").and_then(Value::as_str) == Some(component_id) {
+        return Some(component);
+    }
+    for child in child_components(component) {
+        if let Some(found) = find_component(child, component_id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+fn children_mut<'a>(
+    component: &'a mut Value,
+    component_id: &ComponentId,
+) -> Result<&'a mut Vec<Value>, ScenePatchError> {
+    let component_type = component.get("type").and_then(Value::as_str);
+    if !component_type.is_some_and(|ty| TYPES_WITH_CHILDREN.contains(&ty)) {
+        return Err(ScenePatchError::NoChildren(component_id.clone()));
+    }
+    let children = &mut component["children"];
+    if children.is_null() {
+        *children = Value::Array(Vec::new());
+    }
+    children
+        .as_array_mut()
+        .ok_or_else(|| ScenePatchError::NoChildren(component_id.clone()))
+}
+
+/// JSON merge patch as defined in RFC 7386.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(serde_json::Map::new());
+    }
+    let Some(target) = target.as_object_mut() else {
+        return;
+    };
+    for (key, value) in patch {
+        match value {
+            Value::Null => {
+                target.remove(key);
+            }
+            value => merge_patch(target.entry(key).or_insert(Value::Null), value),
+        }
+    }
+}
+
+fn remove_transitions(component: &mut Value, except_id: &str) {
+    let is_excluded = component.get("id").and_then(Value::as_str) == Some(except_id);
+    if let (false, Some(component)) = (is_excluded, component.as_object_mut()) {
+        component.remove("transition");
+    }
+    for child in child_components(component) {
+        remove_transitions(child, except_id);
+    }
+}
+
+impl Display for ScenePatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScenePatchError::ComponentNotFound(id) => {
+                write!(f, "Component with id \"{id}\" does not exist in the scene.")
+            }
+            ScenePatchError::NoChildren(id) => {
+                write!(f, "Component with id \"{id}\" can't have a list of children.")
+            }
+            ScenePatchError::ChildIndexOutOfBounds {
+                component_id,
+                index,
+                children_count,
+            } => write!(
+                f,
+                "Child index {index} is out of bounds, component \"{component_id}\" has {children_count} children."
+            ),
+            ScenePatchError::InvalidScene(_) => {
+                write!(f, "Scene after applying the patch is not valid.")
+            }
+        }
+    }
+}
+
+impl Error for ScenePatchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ScenePatchError::InvalidScene(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use serde_json::{json, Value};
+
+use crate::types::{ScenePatch, ScenePatchError, Video};
+
+fn scene() -> Video {
+    serde_json::from_value(json!({
+        "root": {
+            "type": "view",
+            "id": "root",
+            "transition": { "duration_ms": 500 },
+            "children": [
+                { "type": "text", "id": "caption", "text": "old", "font_size": 20 },
+                {
+                    "type": "rescaler",
+                    "id": "rescaler",
+                    "transition": { "duration_ms": 500 },
+                    "child": { "type": "input_stream", "id": "input", "input_id": "input_1" },
+                },
+            ]
+        }
+    }))
+    .unwrap()
+}
+
+fn patch(patch: Value) -> ScenePatch {
+    serde_json::from_value(patch).unwrap()
+}
+
+fn to_json(video: &Video) -> Value {
+    serde_json::to_value(video).unwrap()
+}
+
+#[test]
+fn test_merge() {
+    let result = scene().patch(&patch(json!({
+        "type": "merge",
+        "component_id": "caption",
+        "properties": { "text": "new", "font_size": null },
+    })));
+    // `font_size` is required, so it can't be removed
+    assert!(matches!(result, Err(ScenePatchError::InvalidScene(_))));
+
+    let patched = scene()
+        .patch(&patch(json!({
+            "type": "merge",
+            "component_id": "caption",
+            "properties": { "text": "new", "color": "#FF0000FF" },
+        })))
+        .unwrap();
+    let caption = &to_json(&patched)["root"]["children"][0];
+    assert_eq!(caption["text"], "new");
+    assert_eq!(caption["color"], "#FF0000FF");
+    assert_eq!(caption["font_size"], 20.0);
+}
+
+#[test]
+fn test_insert_and_remove_child() {
+    let patched = scene()
+        .patch(&patch(json!({
+            "type": "insert_child",
+            "component_id": "root",
+            "index": 1,
+            "component": { "type": "input_stream", "input_id": "input_2" },
+        })))
+        .unwrap();
+    let children = &to_json(&patched)["root"]["children"];
+    assert_eq!(children.as_array().unwrap().len(), 3);
+    assert_eq!(children[1]["input_id"], "input_2");
+    assert_eq!(children[2]["id"], "rescaler");
+
+    let patched = patched
+        .patch(&patch(json!({
+            "type": "remove_child",
+            "component_id": "root",
+            "index": 0,
+        })))
+        .unwrap();
+    let children = &to_json(&patched)["root"]["children"];
+    assert_eq!(children.as_array().unwrap().len(), 2);
+    assert_eq!(children[0]["input_id"], "input_2");
+
+    let result = patched.patch(&patch(json!({
+        "type": "remove_child",
+        "component_id": "root",
+        "index": 2,
+    })));
+    assert!(matches!(
+        result,
+        Err(ScenePatchError::ChildIndexOutOfBounds { .. })
+    ));
+
+    let result = patched.patch(&patch(json!({
+        "type": "remove_child",
+        "component_id": "rescaler",
+        "index": 0,
+    })));
+    assert!(matches!(result, Err(ScenePatchError::NoChildren(_))));
+}
+
+#[test]
+fn test_replace_nested() {
+    let patched = scene()
+        .patch(&patch(json!({
+            "type": "replace",
+            "component_id": "input",
+            "component": { "type": "input_stream", "id": "input", "input_id": "input_3" },
+        })))
+        .unwrap();
+    assert_eq!(
+        to_json(&patched)["root"]["children"][1]["child"]["input_id"],
+        "input_3"
+    );
+
+    let result = scene().patch(&patch(json!({
+        "type": "replace",
+        "component_id": "missing",
+        "component": { "type": "input_stream", "input_id": "input_3" },
+    })));
+    assert!(matches!(result, Err(ScenePatchError::ComponentNotFound(_))));
+}
+
+#[test]
+fn test_without_transitions_except() {
+    let patch = patch(json!({
+        "type": "remove_child",
+        "component_id": "root",
+        "index": 0,
+    }));
+    let video = scene().without_transitions_except(patch.component_id());
+    let root = &to_json(&video)["root"];
+    assert!(!root["transition"].is_null());
+    assert!(root["children"][1]["transition"].is_null());
+}
@@ -1,12 +1,14 @@
 use compositor_render::{
     error::{
-        InitRendererEngineError, RegisterError, RegisterRendererError, RequestKeyframeError,
-        UnregisterRendererError, UpdateSceneError, WgpuError,
+        InitRendererEngineError, RegisterError, RegisterRendererError, RenderPreviewError,
+        RenderSceneError, RequestKeyframeError, UnregisterRendererError, UpdateSceneError,
+        WgpuError,
     },
     InputId, OutputId,
 };
 
 use crate::pipeline::{decoder::AacDecoderError, output::whip, VideoCodec};
+use crate::queue::ScheduledEventId;
 use fdk_aac_sys as fdk;
 
 #[derive(Debug, thiserror::Error)]
@@ -80,6 +82,44 @@ pub enum UnregisterOutputError {
     StillInUse(OutputId),
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum ApplyChangesError {
+    #[error(transparent)]
+    UpdateScene(#[from] UpdateSceneError),
+
+    #[error(transparent)]
+    UnregisterInput(#[from] UnregisterInputError),
+
+    #[error(transparent)]
+    UnregisterOutput(#[from] UnregisterOutputError),
+
+    #[error(transparent)]
+    UnregisterRenderer(#[from] UnregisterRendererError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CancelScheduledEventError {
+    #[error(
+        "Scheduled event \"{0}\" does not exist. It was already executed, cancelled or discarded."
+    )]
+    NotFound(ScheduledEventId),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    #[error("Input stream \"{0}\" does not exist.")]
+    InputNotFound(InputId),
+
+    #[error("Output stream \"{0}\" does not exist.")]
+    OutputNotFound(OutputId),
+
+    #[error("No frame was rendered yet.")]
+    NoFrame,
+
+    #[error("Failed to download frame from the GPU.")]
+    Download(#[source] wgpu::BufferAsyncError),
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum OutputInitError {
     #[error("An unsupported codec was requested: {0:?}.")]
@@ -291,6 +331,69 @@ impl From<&UpdateSceneError> for PipelineErrorInfo {
     }
 }
 
+impl From<&ApplyChangesError> for PipelineErrorInfo {
+    fn from(err: &ApplyChangesError) -> Self {
+        match err {
+            ApplyChangesError::UpdateScene(err) => err.into(),
+            ApplyChangesError::UnregisterInput(err) => err.into(),
+            ApplyChangesError::UnregisterOutput(err) => err.into(),
+            ApplyChangesError::UnregisterRenderer(err) => err.into(),
+        }
+    }
+}
+
+impl From<&RenderPreviewError> for PipelineErrorInfo {
+    fn from(err: &RenderPreviewError) -> Self {
+        match err {
+            RenderPreviewError::UpdateScene(err) => err.into(),
+            RenderPreviewError::Render(RenderSceneError::WgpuError(err)) => err.into(),
+            RenderPreviewError::Download(_) => {
+                PipelineErrorInfo::new(SNAPSHOT_DOWNLOAD_ERROR, ErrorType::ServerError)
+            }
+            RenderPreviewError::ResolutionTooLarge(_, _)
+            | RenderPreviewError::InputTooLarge(_, _, _) => {
+                PipelineErrorInfo::new(PREVIEW_TOO_LARGE, ErrorType::UserError)
+            }
+        }
+    }
+}
+
+const PREVIEW_TOO_LARGE: &str = "PREVIEW_TOO_LARGE";
+
+const SCHEDULED_EVENT_NOT_FOUND: &str = "SCHEDULED_EVENT_NOT_FOUND";
+
+impl From<&CancelScheduledEventError> for PipelineErrorInfo {
+    fn from(err: &CancelScheduledEventError) -> Self {
+        match err {
+            CancelScheduledEventError::NotFound(_) => {
+                PipelineErrorInfo::new(SCHEDULED_EVENT_NOT_FOUND, ErrorType::EntityNotFound)
+            }
+        }
+    }
+}
+
+const SNAPSHOT_NOT_AVAILABLE: &str = "SNAPSHOT_NOT_AVAILABLE";
+const SNAPSHOT_DOWNLOAD_ERROR: &str = "SNAPSHOT_DOWNLOAD_ERROR";
+
+impl From<&SnapshotError> for PipelineErrorInfo {
+    fn from(err: &SnapshotError) -> Self {
+        match err {
+            SnapshotError::InputNotFound(_) => {
+                PipelineErrorInfo::new(INPUT_STREAM_NOT_FOUND, ErrorType::EntityNotFound)
+            }
+            SnapshotError::OutputNotFound(_) => {
+                PipelineErrorInfo::new(OUTPUT_STREAM_NOT_FOUND, ErrorType::EntityNotFound)
+            }
+            SnapshotError::NoFrame => {
+                PipelineErrorInfo::new(SNAPSHOT_NOT_AVAILABLE, ErrorType::UserError)
+            }
+            SnapshotError::Download(_) => {
+                PipelineErrorInfo::new(SNAPSHOT_DOWNLOAD_ERROR, ErrorType::ServerError)
+            }
+        }
+    }
+}
+
 const REQUEST_KEYFRAME_ERROR: &str = "REQUEST_KEYFRAME_ERROR";
 
 impl From<&RequestKeyframeError> for PipelineErrorInfo {
@@ -324,7 +427,9 @@ impl From<&InitRendererEngineError> for PipelineErrorInfo {
 
 const ENTITY_ALREADY_REGISTERED: &str = "ENTITY_ALREADY_REGISTERED";
 const INVALID_SHADER: &str = "INVALID_SHADER";
+const INVALID_COMPUTE_SHADER: &str = "INVALID_COMPUTE_SHADER";
 const REGISTER_IMAGE_ERROR: &str = "REGISTER_IMAGE_ERROR";
+const REGISTER_LOTTIE_ERROR: &str = "REGISTER_LOTTIE_ERROR";
 const REGISTER_WEB_RENDERER_ERROR: &str = "REGISTER_WEB_RENDERER_ERROR";
 
 impl From<&RegisterRendererError> for PipelineErrorInfo {
@@ -338,9 +443,15 @@ impl From<&RegisterRendererError> for PipelineErrorInfo {
             RegisterRendererError::Shader(_, _) => {
                 PipelineErrorInfo::new(INVALID_SHADER, ErrorType::UserError)
             }
+            RegisterRendererError::ComputeShader(_, _) => {
+                PipelineErrorInfo::new(INVALID_COMPUTE_SHADER, ErrorType::UserError)
+            }
             RegisterRendererError::Image(_, _) => {
                 PipelineErrorInfo::new(REGISTER_IMAGE_ERROR, ErrorType::UserError)
             }
+            RegisterRendererError::Lottie(_, _) => {
+                PipelineErrorInfo::new(REGISTER_LOTTIE_ERROR, ErrorType::UserError)
+            }
             RegisterRendererError::Web(_, _) => {
                 PipelineErrorInfo::new(REGISTER_WEB_RENDERER_ERROR, ErrorType::ServerError)
             }
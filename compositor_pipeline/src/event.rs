@@ -1,10 +1,14 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, time::Duration};
 
 use compositor_render::{
+    error::ErrorStack,
     event_handler::{self, emit_event, Emitter},
-    InputId, OutputId,
+    InputId, OutputId, Resolution,
 };
 use crossbeam_channel::Receiver;
+use serde_json::json;
+
+use crate::queue::ScheduledEventDescription;
 
 #[derive(Debug, Clone)]
 pub enum Event {
@@ -14,20 +18,96 @@ pub enum Event {
     VideoInputStreamPlaying(InputId),
     AudioInputStreamEos(InputId),
     VideoInputStreamEos(InputId),
+    InputBufferDurationChanged {
+        input_id: InputId,
+        buffer_duration: Duration,
+    },
     OutputDone(OutputId),
+    /// Client connected to the input, e.g. TCP connection was accepted or WHIP peer
+    /// connection was established.
+    InputConnected(InputId),
+    InputDisconnected(InputId),
+    /// Resolution of the frames produced by the input changed. It is also emitted
+    /// for the first frame.
+    InputResolutionChanged {
+        input_id: InputId,
+        resolution: Resolution,
+    },
+    DecoderError {
+        input_id: InputId,
+        error: EventError,
+    },
+    OutputConnectionStateChanged {
+        output_id: OutputId,
+        state: OutputConnectionState,
+    },
+    ScheduledOutputUpdateFailed {
+        output_id: OutputId,
+        schedule_time: Duration,
+        error: EventError,
+    },
+    ScheduledBatchFailed {
+        schedule_time: Duration,
+        error: EventError,
+    },
+    ScheduledRegisterFailed {
+        description: ScheduledEventDescription,
+        schedule_time: Duration,
+        error: EventError,
+    },
+    ScheduledUnregisterFailed {
+        description: ScheduledEventDescription,
+        schedule_time: Duration,
+        error: EventError,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputConnectionState {
+    Connected,
+    Disconnected,
+    Failed,
+}
+
+/// Error attached to an event.
+#[derive(Debug, Clone)]
+pub struct EventError {
+    pub error_code: &'static str,
+    pub message: String,
+    /// Messages of the error and all of its sources.
+    pub stack: Vec<String>,
+}
+
+impl EventError {
+    pub fn new(error_code: &'static str, err: &(dyn std::error::Error + 'static)) -> Self {
+        let stack: Vec<String> = ErrorStack::new(err).map(|err| err.to_string()).collect();
+        Self {
+            error_code,
+            message: err.to_string(),
+            stack,
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "error_code": self.error_code,
+            "message": self.message,
+            "stack": self.stack,
+        })
+    }
 }
 
 fn input_event(kind: &str, input_id: InputId) -> event_handler::Event {
     event_handler::Event {
         kind: kind.to_string(),
-        properties: vec![("input_id".to_string(), input_id.to_string())],
+        properties: vec![("input_id".to_string(), input_id.to_string().into())],
     }
 }
 
 fn output_event(kind: &str, output_id: OutputId) -> event_handler::Event {
     event_handler::Event {
         kind: kind.to_string(),
-        properties: vec![("output_id".to_string(), output_id.to_string())],
+        properties: vec![("output_id".to_string(), output_id.to_string().into())],
     }
 }
 
@@ -40,7 +120,108 @@ impl From<Event> for event_handler::Event {
             Event::VideoInputStreamPlaying(id) => input_event("VIDEO_INPUT_PLAYING", id),
             Event::AudioInputStreamEos(id) => input_event("AUDIO_INPUT_EOS", id),
             Event::VideoInputStreamEos(id) => input_event("VIDEO_INPUT_EOS", id),
+            Event::InputBufferDurationChanged {
+                input_id,
+                buffer_duration,
+            } => {
+                let mut event = input_event("INPUT_BUFFER_DURATION_CHANGED", input_id);
+                event.properties.push((
+                    "buffer_duration_ms".to_string(),
+                    buffer_duration.as_millis().to_string().into(),
+                ));
+                event
+            }
             Event::OutputDone(id) => output_event("OUTPUT_DONE", id),
+            Event::InputConnected(id) => input_event("INPUT_CONNECTED", id),
+            Event::InputDisconnected(id) => input_event("INPUT_DISCONNECTED", id),
+            Event::InputResolutionChanged {
+                input_id,
+                resolution,
+            } => {
+                let mut event = input_event("INPUT_RESOLUTION_CHANGED", input_id);
+                event.properties.push((
+                    "resolution".to_string(),
+                    json!({ "width": resolution.width, "height": resolution.height }),
+                ));
+                event
+            }
+            Event::DecoderError { input_id, error } => {
+                let mut event = input_event("DECODER_ERROR", input_id);
+                event
+                    .properties
+                    .push(("error".to_string(), error.to_json()));
+                event
+            }
+            Event::OutputConnectionStateChanged { output_id, state } => {
+                let state = match state {
+                    OutputConnectionState::Connected => "connected",
+                    OutputConnectionState::Disconnected => "disconnected",
+                    OutputConnectionState::Failed => "failed",
+                };
+                let mut event = output_event("OUTPUT_CONNECTION_STATE_CHANGED", output_id);
+                event.properties.push(("state".to_string(), state.into()));
+                event
+            }
+            Event::ScheduledOutputUpdateFailed {
+                output_id,
+                schedule_time,
+                error,
+            } => {
+                let mut event = output_event("SCHEDULED_OUTPUT_UPDATE_FAILED", output_id);
+                event.properties.push((
+                    "schedule_time_ms".to_string(),
+                    (schedule_time.as_secs_f64() * 1000.0).into(),
+                ));
+                event
+                    .properties
+                    .push(("error".to_string(), error.to_json()));
+                event
+            }
+            Event::ScheduledBatchFailed {
+                schedule_time,
+                error,
+            } => event_handler::Event {
+                kind: "SCHEDULED_BATCH_FAILED".to_string(),
+                properties: vec![
+                    (
+                        "schedule_time_ms".to_string(),
+                        (schedule_time.as_secs_f64() * 1000.0).into(),
+                    ),
+                    ("error".to_string(), error.to_json()),
+                ],
+            },
+            Event::ScheduledRegisterFailed {
+                description,
+                schedule_time,
+                error,
+            } => event_handler::Event {
+                kind: "SCHEDULED_REGISTER_FAILED".to_string(),
+                properties: vec![
+                    ("event_type".to_string(), description.kind.into()),
+                    ("entity_id".to_string(), description.entity_id.into()),
+                    (
+                        "schedule_time_ms".to_string(),
+                        (schedule_time.as_secs_f64() * 1000.0).into(),
+                    ),
+                    ("error".to_string(), error.to_json()),
+                ],
+            },
+            Event::ScheduledUnregisterFailed {
+                description,
+                schedule_time,
+                error,
+            } => event_handler::Event {
+                kind: "SCHEDULED_UNREGISTER_FAILED".to_string(),
+                properties: vec![
+                    ("event_type".to_string(), description.kind.into()),
+                    ("entity_id".to_string(), description.entity_id.into()),
+                    (
+                        "schedule_time_ms".to_string(),
+                        (schedule_time.as_secs_f64() * 1000.0).into(),
+                    ),
+                    ("error".to_string(), error.to_json()),
+                ],
+            },
         }
     }
 }
@@ -1,6 +1,7 @@
 pub mod audio_mixer;
 pub mod error;
 pub mod event;
+pub mod metrics;
 pub mod pipeline;
 pub mod queue;
 
@@ -0,0 +1,172 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use compositor_render::{InputId, OutputId};
+
+use crate::pipeline::EncodedChunk;
+
+/// Counters collected by the pipeline. Inputs and outputs get their own
+/// set of counters that is shared with the threads processing them.
+#[derive(Debug, Default)]
+pub struct PipelineMetrics {
+    inputs: Mutex<HashMap<InputId, Arc<InputMetrics>>>,
+    outputs: Mutex<HashMap<OutputId, Arc<OutputMetrics>>>,
+    /// Time spent on rendering a single frame set.
+    pub render_time: Summary,
+    /// Delay between the moment when frames for a PTS should be pushed from
+    /// the queue and when it actually happened.
+    pub queue_lateness: Summary,
+}
+
+/// Collected on the decoder boundary, except late frames which are counted
+/// by the queue and RTP reception statistics.
+#[derive(Debug, Default)]
+pub struct InputMetrics {
+    pub received_bytes: Counter,
+    pub received_packets: Counter,
+    /// Video frames and audio sample batches produced by the decoders.
+    pub decoded_frames: Counter,
+    /// Video frames produced by the decoder, used to calculate the frame rate.
+    pub decoded_video_frames: Counter,
+    pub decode_errors: Counter,
+    /// Video frames that reached the queue after their PTS was already rendered.
+    pub late_frames: Counter,
+    /// Cumulative number of lost RTP packets.
+    pub packets_lost: Gauge,
+    /// RTP interarrival jitter in microseconds.
+    pub jitter_micros: Gauge,
+}
+
+/// Collected on the encoder boundary, except dropped frames which are
+/// counted by the queue and the renderer.
+#[derive(Debug, Default)]
+pub struct OutputMetrics {
+    pub encoded_bytes: Counter,
+    pub encoded_packets: Counter,
+    /// Video frames passed to the encoder.
+    pub encoded_frames: Counter,
+    pub dropped_frames: Counter,
+    /// Time between receiving a raw video frame and producing encoded packets.
+    pub encoder_latency: Summary,
+}
+
+#[derive(Debug, Default)]
+pub struct Counter(AtomicU64);
+
+/// Value that can go up and down, e.g. a value calculated elsewhere.
+#[derive(Debug, Default)]
+pub struct Gauge(AtomicU64);
+
+/// Sum and count of observed durations.
+#[derive(Debug, Default)]
+pub struct Summary {
+    sum_micros: AtomicU64,
+    count: AtomicU64,
+}
+
+impl PipelineMetrics {
+    /// Returns counters for the input, creating them if necessary.
+    pub(crate) fn input(&self, input_id: &InputId) -> Arc<InputMetrics> {
+        self.inputs
+            .lock()
+            .unwrap()
+            .entry(input_id.clone())
+            .or_default()
+            .clone()
+    }
+
+    /// Returns counters for the output, creating them if necessary.
+    pub(crate) fn output(&self, output_id: &OutputId) -> Arc<OutputMetrics> {
+        self.outputs
+            .lock()
+            .unwrap()
+            .entry(output_id.clone())
+            .or_default()
+            .clone()
+    }
+
+    pub(crate) fn remove_input(&self, input_id: &InputId) {
+        self.inputs.lock().unwrap().remove(input_id);
+    }
+
+    pub(crate) fn remove_output(&self, output_id: &OutputId) {
+        self.outputs.lock().unwrap().remove(output_id);
+    }
+
+    pub(crate) fn on_output_frame_dropped(&self, output_id: &OutputId) {
+        if let Some(metrics) = self.outputs.lock().unwrap().get(output_id) {
+            metrics.dropped_frames.inc();
+        }
+    }
+
+    pub fn inputs(&self) -> Vec<(InputId, Arc<InputMetrics>)> {
+        self.inputs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, metrics)| (id.clone(), metrics.clone()))
+            .collect()
+    }
+
+    pub fn outputs(&self) -> Vec<(OutputId, Arc<OutputMetrics>)> {
+        self.outputs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, metrics)| (id.clone(), metrics.clone()))
+            .collect()
+    }
+}
+
+impl OutputMetrics {
+    pub(crate) fn on_chunk_encoded(&self, chunk: &EncodedChunk) {
+        self.encoded_packets.inc();
+        self.encoded_bytes.add(chunk.data.len() as u64);
+    }
+}
+
+impl Counter {
+    pub(crate) fn inc(&self) {
+        self.add(1);
+    }
+
+    pub(crate) fn add(&self, value: u64) {
+        self.0.fetch_add(value, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Gauge {
+    pub(crate) fn set(&self, value: u64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Summary {
+    pub(crate) fn observe(&self, value: Duration) {
+        self.sum_micros
+            .fetch_add(value.as_micros() as u64, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn sum(&self) -> Duration {
+        Duration::from_micros(self.sum_micros.load(Ordering::Relaxed))
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+}
@@ -1,23 +1,30 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::mem;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::Weak;
 use std::thread;
 use std::time::Duration;
+use std::time::Instant;
 
 use compositor_render::error::{
-    ErrorStack, RegisterRendererError, RequestKeyframeError, UnregisterRendererError,
+    ErrorStack, RegisterRendererError, RenderPreviewError, RequestKeyframeError,
+    UnregisterRendererError,
 };
-use compositor_render::scene::Component;
+use compositor_render::scene::{self, Component, SceneValidationError};
 use compositor_render::web_renderer::WebRendererInitOptions;
 use compositor_render::FrameSet;
 use compositor_render::Framerate;
+use compositor_render::OutputFrameFormat;
 use compositor_render::RegistryType;
 use compositor_render::RendererOptions;
+use compositor_render::Resolution;
 use compositor_render::WgpuFeatures;
 use compositor_render::{error::UpdateSceneError, Renderer};
 use compositor_render::{EventLoop, InputId, OutputId, RendererId, RendererSpec};
+use compositor_render::{Snapshot, SnapshotDownload};
 use crossbeam_channel::{bounded, Receiver};
 use glyphon::fontdb;
 use input::InputInitInfo;
@@ -38,11 +45,13 @@ use crate::audio_mixer::MixingStrategy;
 use crate::audio_mixer::{AudioChannels, AudioMixingParams};
 use crate::error::InitPipelineError;
 use crate::error::{
-    RegisterInputError, RegisterOutputError, UnregisterInputError, UnregisterOutputError,
+    ApplyChangesError, RegisterInputError, RegisterOutputError, SnapshotError,
+    UnregisterInputError, UnregisterOutputError,
 };
 
 use crate::event::Event;
 use crate::event::EventEmitter;
+use crate::metrics::PipelineMetrics;
 use crate::pipeline::pipeline_output::OutputSender;
 use crate::queue::PipelineEvent;
 use crate::queue::QueueAudioOutput;
@@ -54,6 +63,7 @@ use self::input::InputOptions;
 pub mod decoder;
 pub mod encoder;
 mod graphics_context;
+mod info;
 pub mod input;
 pub mod output;
 mod pipeline_input;
@@ -72,6 +82,11 @@ pub use self::types::{
 };
 pub use pipeline_output::PipelineOutputEndCondition;
 
+pub use self::info::{
+    InputAudioInfo, InputInfo, InputType, InputVideoInfo, OutputAudioInfo, OutputInfo, OutputType,
+    OutputVideoInfo,
+};
+
 pub use graphics_context::GraphicsContext;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -93,6 +108,8 @@ pub struct RegisterOutputOptions<T> {
 pub struct OutputVideoOptions {
     pub initial: Component,
     pub end_condition: PipelineOutputEndCondition,
+    /// If not specified, `PipelineCtx::output_framerate` is used.
+    pub framerate: Option<Framerate>,
 }
 
 #[derive(Debug, Clone)]
@@ -109,6 +126,24 @@ pub struct OutputScene {
     pub scene_root: Component,
 }
 
+/// Arguments of a single `Pipeline::update_output` call, used to update multiple
+/// outputs at once.
+#[derive(Debug, Clone)]
+pub struct OutputUpdate {
+    pub output_id: OutputId,
+    pub video: Option<Component>,
+    pub audio: Option<AudioMixingParams>,
+}
+
+/// Changes applied together by `Pipeline::apply_changes`.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineChanges {
+    pub output_updates: Vec<OutputUpdate>,
+    pub unregister_inputs: Vec<InputId>,
+    pub unregister_outputs: Vec<OutputId>,
+    pub unregister_renderers: Vec<(RendererId, RegistryType)>,
+}
+
 pub struct Pipeline {
     ctx: PipelineCtx,
     inputs: HashMap<InputId, PipelineInput>,
@@ -144,6 +179,7 @@ pub struct PipelineCtx {
     pub stun_servers: Arc<Vec<String>>,
     pub download_dir: Arc<PathBuf>,
     pub event_emitter: Arc<EventEmitter>,
+    pub metrics: Arc<PipelineMetrics>,
     pub whip_whep_state: Arc<WhipWhepState>,
     pub tokio_rt: Arc<Runtime>,
     pub start_whip_whep: bool,
@@ -200,8 +236,9 @@ impl Pipeline {
             Some(tokio_rt) => tokio_rt,
             None => Arc::new(Runtime::new().map_err(InitPipelineError::CreateTokioRuntime)?),
         };
+        let event_emitter = Arc::new(EventEmitter::new());
         let stun_servers = opts.stun_servers;
-        let whip_whep_state = WhipWhepState::new(stun_servers.clone());
+        let whip_whep_state = WhipWhepState::new(stun_servers.clone(), event_emitter.clone());
         let start_whip_whep = opts.start_whip_whep;
         let shutdown_whip_whep_sender = if start_whip_whep {
             if let Some(port) = opts.whip_whep_server_port {
@@ -228,11 +265,11 @@ impl Pipeline {
         } else {
             None
         };
-        let event_emitter = Arc::new(EventEmitter::new());
+        let metrics = Arc::new(PipelineMetrics::default());
         let pipeline = Pipeline {
             outputs: HashMap::new(),
             inputs: HashMap::new(),
-            queue: Queue::new(opts.queue_options, &event_emitter),
+            queue: Queue::new(opts.queue_options, &event_emitter, &metrics),
             renderer,
             audio_mixer: AudioMixer::new(opts.mixing_sample_rate),
             is_started: false,
@@ -243,6 +280,7 @@ impl Pipeline {
                 stun_servers,
                 download_dir: download_dir.into(),
                 event_emitter,
+                metrics,
                 tokio_rt,
                 whip_whep_state,
                 start_whip_whep,
@@ -254,14 +292,28 @@ impl Pipeline {
         Ok((pipeline, event_loop))
     }
 
-    pub fn queue(&self) -> &Queue {
+    pub fn queue(&self) -> &Arc<Queue> {
         &self.queue
     }
 
+    pub fn is_started(&self) -> bool {
+        self.is_started
+    }
+
+    pub fn metrics(&self) -> &Arc<PipelineMetrics> {
+        &self.ctx.metrics
+    }
+
     pub fn subscribe_pipeline_events(&self) -> Receiver<Event> {
         self.ctx.event_emitter.subscribe()
     }
 
+    /// Emit event for errors that happen outside of the pipeline, e.g. in
+    /// a callback scheduled on the queue.
+    pub fn emit_event(&self, event: Event) {
+        self.ctx.event_emitter.emit(event)
+    }
+
     pub fn register_input(
         pipeline: &Arc<Mutex<Self>>,
         input_id: InputId,
@@ -292,6 +344,7 @@ impl Pipeline {
         self.inputs.remove(input_id);
         self.queue.remove_input(input_id);
         self.renderer.unregister_input(input_id);
+        self.ctx.metrics.remove_input(input_id);
         for output in self.outputs.values_mut() {
             if let Some(ref mut cond) = output.audio_end_condition {
                 cond.on_input_unregistered(input_id);
@@ -352,7 +405,9 @@ impl Pipeline {
 
         self.audio_mixer.unregister_output(output_id);
         self.outputs.remove(output_id);
+        self.queue.remove_output(output_id);
         self.renderer.unregister_output(output_id);
+        self.ctx.metrics.remove_output(output_id);
         Ok(())
     }
 
@@ -375,6 +430,10 @@ impl Pipeline {
             .unregister_renderer(renderer_id, registry_type)
     }
 
+    pub fn registered_renderers(&self, registry_type: RegistryType) -> Vec<RendererId> {
+        self.renderer.registered_renderers(registry_type)
+    }
+
     pub fn update_output(
         &mut self,
         output_id: OutputId,
@@ -393,6 +452,153 @@ impl Pipeline {
         Ok(())
     }
 
+    /// Updates multiple outputs at once. All updates are checked before anything is
+    /// applied and scenes are validated together, so either all outputs are updated
+    /// or none of them is.
+    pub fn update_outputs(&mut self, updates: Vec<OutputUpdate>) -> Result<(), UpdateSceneError> {
+        let mut output_scenes = Vec::new();
+        let mut audio_updates = Vec::new();
+        for OutputUpdate {
+            output_id,
+            video,
+            audio,
+        } in updates
+        {
+            self.check_output_spec(&output_id, &video, &audio)?;
+            if let Some(video) = video {
+                output_scenes.extend(self.output_scene(output_id.clone(), video)?);
+            }
+            if let Some(audio) = audio {
+                audio_updates.push((output_id, audio));
+            }
+        }
+
+        self.renderer.update_scenes(output_scenes)?;
+        for (output_id, audio) in audio_updates {
+            self.update_audio(&output_id, audio)?;
+        }
+        Ok(())
+    }
+
+    /// Applies output updates and then unregisters inputs, outputs and renderers.
+    /// Everything is checked before anything is applied, so if an error is returned
+    /// the pipeline is not modified. The pipeline lock is held for the whole call,
+    /// so the renderer never sees only part of the changes.
+    pub fn apply_changes(&mut self, changes: PipelineChanges) -> Result<(), ApplyChangesError> {
+        let PipelineChanges {
+            output_updates,
+            unregister_inputs,
+            unregister_outputs,
+            unregister_renderers,
+        } = changes;
+        // Entities listed more than once are unregistered once.
+        let unregister_inputs = unique(unregister_inputs);
+        let unregister_outputs = unique(unregister_outputs);
+        let unregister_renderers = unique(unregister_renderers);
+
+        for input_id in &unregister_inputs {
+            if !self.inputs.contains_key(input_id) {
+                return Err(UnregisterInputError::NotFound(input_id.clone()).into());
+            }
+        }
+        for output_id in &unregister_outputs {
+            if !self.outputs.contains_key(output_id) {
+                return Err(UnregisterOutputError::NotFound(output_id.clone()).into());
+            }
+        }
+        for (renderer_id, registry_type) in &unregister_renderers {
+            self.renderer
+                .check_unregister_renderer(renderer_id, *registry_type)?;
+        }
+
+        self.update_outputs(output_updates)?;
+        for input_id in &unregister_inputs {
+            self.unregister_input(input_id)?;
+        }
+        for output_id in &unregister_outputs {
+            self.unregister_output(output_id)?;
+        }
+        for (renderer_id, registry_type) in &unregister_renderers {
+            self.unregister_renderer(renderer_id, *registry_type)?;
+        }
+        Ok(())
+    }
+
+    /// Updates only the scene of the output, audio mixing configuration stays unchanged.
+    pub fn update_output_scene(
+        &mut self,
+        output_id: OutputId,
+        scene_root: Component,
+    ) -> Result<(), UpdateSceneError> {
+        self.update_scene_root(output_id, scene_root)
+    }
+
+    /// Checks whether `update_output` with the same arguments would succeed, without
+    /// applying anything. Errors of scene components are returned with their paths,
+    /// other errors that the update would return are returned as `Err`.
+    pub fn validate_output_update(
+        &self,
+        output_id: &OutputId,
+        video: Option<Component>,
+        audio: &Option<AudioMixingParams>,
+    ) -> Result<Vec<SceneValidationError>, UpdateSceneError> {
+        self.check_output_spec(output_id, &video, audio)?;
+        let Some(scene_root) = video else {
+            return Ok(Vec::new());
+        };
+        match self.output_scene(output_id.clone(), scene_root)? {
+            Some((output_scene, output_format)) => {
+                self.renderer.validate_scene(output_scene, output_format)
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Maximal width and height of frames that can be rendered by the GPU.
+    pub fn max_texture_dimension(&self) -> usize {
+        self.renderer.max_texture_dimension()
+    }
+
+    /// Renders a single frame of the scene in isolation from the registered outputs.
+    pub fn render_preview(
+        &self,
+        resolution: Resolution,
+        scene_root: Component,
+        pts: Duration,
+        inputs: HashMap<InputId, Snapshot>,
+    ) -> Result<Snapshot, RenderPreviewError> {
+        self.renderer
+            .render_preview(resolution, scene_root, pts, inputs)
+    }
+
+    /// Starts reading back the last frame of the input that was used by the renderer.
+    /// The returned download should be awaited after the pipeline lock is released.
+    pub fn start_input_snapshot(
+        &self,
+        input_id: &InputId,
+    ) -> Result<SnapshotDownload, SnapshotError> {
+        if !self.inputs.contains_key(input_id) {
+            return Err(SnapshotError::InputNotFound(input_id.clone()));
+        }
+        self.renderer
+            .start_input_snapshot(input_id)
+            .ok_or(SnapshotError::NoFrame)
+    }
+
+    /// Starts reading back the last frame rendered for the output. The returned
+    /// download should be awaited after the pipeline lock is released.
+    pub fn start_output_snapshot(
+        &self,
+        output_id: &OutputId,
+    ) -> Result<SnapshotDownload, SnapshotError> {
+        if !self.outputs.contains_key(output_id) {
+            return Err(SnapshotError::OutputNotFound(output_id.clone()));
+        }
+        self.renderer
+            .start_output_snapshot(output_id)
+            .ok_or(SnapshotError::NoFrame)
+    }
+
     pub fn request_keyframe(&self, output_id: OutputId) -> Result<(), RequestKeyframeError> {
         let Some(output) = self.outputs.get(&output_id) else {
             return Err(RequestKeyframeError::OutputNotRegistered(output_id.clone()));
@@ -405,6 +611,10 @@ impl Pipeline {
         self.renderer.register_font(font_source);
     }
 
+    pub fn font_families(&self) -> Vec<String> {
+        self.renderer.font_families()
+    }
+
     fn check_output_spec(
         &self,
         output_id: &OutputId,
@@ -430,6 +640,18 @@ impl Pipeline {
         output_id: OutputId,
         scene_root: Component,
     ) -> Result<(), UpdateSceneError> {
+        match self.output_scene(output_id, scene_root)? {
+            Some(output_scene) => self.renderer.update_scenes(vec![output_scene]),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns `None` if the output already finished and the scene should be ignored.
+    fn output_scene(
+        &self,
+        output_id: OutputId,
+        scene_root: Component,
+    ) -> Result<Option<(scene::OutputScene, OutputFrameFormat)>, UpdateSceneError> {
         let output = self
             .outputs
             .get(&output_id)
@@ -439,7 +661,7 @@ impl Pipeline {
             if cond.did_output_end() {
                 // Ignore updates after EOS
                 warn!("Received output update on a finished output");
-                return Ok(());
+                return Ok(None);
             }
         }
 
@@ -452,8 +674,12 @@ impl Pipeline {
 
         info!(?output_id, "Update scene {:#?}", scene_root);
 
-        self.renderer
-            .update_scene(output_id, resolution, frame_format, scene_root)
+        let output_scene = scene::OutputScene {
+            output_id,
+            scene_root,
+            resolution,
+        };
+        Ok(Some((output_scene, frame_format)))
     }
 
     fn update_audio(
@@ -516,12 +742,23 @@ impl Drop for Pipeline {
     }
 }
 
+fn unique<T: Clone + Eq + Hash>(items: Vec<T>) -> Vec<T> {
+    let mut seen = HashSet::new();
+    items
+        .into_iter()
+        .filter(|item| seen.insert(item.clone()))
+        .collect()
+}
+
 fn run_renderer_thread(
     pipeline: Weak<Mutex<Pipeline>>,
     frames_receiver: Receiver<QueueVideoOutput>,
 ) {
-    let renderer = match pipeline.upgrade() {
-        Some(pipeline) => pipeline.lock().unwrap().renderer.clone(),
+    let (renderer, metrics) = match pipeline.upgrade() {
+        Some(pipeline) => {
+            let guard = pipeline.lock().unwrap();
+            (guard.renderer.clone(), guard.ctx.metrics.clone())
+        }
         None => {
             warn!("Pipeline stopped before render thread was started.");
             return;
@@ -558,14 +795,23 @@ fn run_renderer_thread(
                 })
                 .collect();
 
+        if input_frames.outputs.is_empty() {
+            continue;
+        }
+        let outputs = mem::take(&mut input_frames.outputs);
         let input_frames: FrameSet<InputId> = input_frames.into();
-        trace!(?input_frames, "Rendering frames");
-        let output_frames = renderer.render(input_frames);
+        trace!(?input_frames, ?outputs, "Rendering frames");
+        let render_start = Instant::now();
+        let output_frames = renderer.render_outputs(input_frames, &outputs);
+        metrics.render_time.observe(render_start.elapsed());
         let Ok(output_frames) = output_frames else {
             error!(
                 "Error while rendering: {}",
                 ErrorStack::new(&output_frames.unwrap_err()).into_string()
             );
+            for output_id in outputs.iter() {
+                metrics.on_output_frame_dropped(output_id);
+            }
             continue;
         };
 
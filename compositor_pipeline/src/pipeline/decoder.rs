@@ -1,9 +1,19 @@
-use crate::{audio_mixer::InputSamples, queue::PipelineEvent};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use super::types::VideoDecoder;
+use crate::{
+    audio_mixer::InputSamples,
+    event::{Event, EventEmitter, EventError},
+    metrics::InputMetrics,
+    queue::{ClockDrift, PipelineEvent},
+};
+
+use super::types::{AudioCodec, VideoDecoder};
 
 use bytes::Bytes;
-use compositor_render::Frame;
+use compositor_render::{Frame, InputId};
 use crossbeam_channel::Receiver;
 
 pub use audio::AacDecoderError;
@@ -15,6 +25,9 @@ pub(super) use audio::start_audio_decoder_thread;
 pub(super) use audio::start_audio_resampler_only_thread;
 pub(super) use video::start_video_decoder_thread;
 
+/// Minimal time between two `DECODER_ERROR` events for the same input.
+const DECODER_ERROR_EVENT_INTERVAL: Duration = Duration::from_secs(1);
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VideoDecoderOptions {
     pub decoder: VideoDecoder,
@@ -26,6 +39,15 @@ pub enum AudioDecoderOptions {
     Aac(AacDecoderOptions),
 }
 
+impl AudioDecoderOptions {
+    pub fn codec(&self) -> AudioCodec {
+        match self {
+            AudioDecoderOptions::Opus(_) => AudioCodec::Opus,
+            AudioDecoderOptions::Aac(_) => AudioCodec::Aac,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OpusDecoderOptions {
     pub forward_error_correction: bool,
@@ -35,6 +57,12 @@ pub struct OpusDecoderOptions {
 pub struct DecodedDataReceiver {
     pub video: Option<Receiver<PipelineEvent<Frame>>>,
     pub audio: Option<Receiver<PipelineEvent<InputSamples>>>,
+    /// Clock drift of the input source, `None` if it should not be compensated.
+    pub clock_drift: Option<ClockDrift>,
+    /// Decoder used for the video track, `None` if the input produces raw frames.
+    pub video_decoder: Option<VideoDecoder>,
+    /// Codec of the audio track, `None` if the input produces raw samples.
+    pub audio_codec: Option<AudioCodec>,
 }
 
 /// [RFC 3640, section 3.3.5. Low Bit-rate AAC](https://datatracker.ietf.org/doc/html/rfc3640#section-3.3.5)
@@ -50,3 +78,42 @@ pub struct AacDecoderOptions {
     pub depayloader_mode: Option<AacDepayloaderMode>,
     pub asc: Option<Bytes>,
 }
+
+/// Counts decoding errors and reports them as events. Events are throttled, so
+/// a corrupted stream does not flood the subscribers.
+struct DecoderErrorReporter {
+    input_id: InputId,
+    metrics: Arc<InputMetrics>,
+    event_emitter: Arc<EventEmitter>,
+    last_event_time: Option<Instant>,
+}
+
+impl DecoderErrorReporter {
+    fn new(
+        input_id: InputId,
+        metrics: Arc<InputMetrics>,
+        event_emitter: Arc<EventEmitter>,
+    ) -> Self {
+        Self {
+            input_id,
+            metrics,
+            event_emitter,
+            last_event_time: None,
+        }
+    }
+
+    fn report(&mut self, err: &(dyn std::error::Error + 'static)) {
+        self.metrics.decode_errors.inc();
+        if self
+            .last_event_time
+            .is_some_and(|time| time.elapsed() < DECODER_ERROR_EVENT_INTERVAL)
+        {
+            return;
+        }
+        self.last_event_time = Some(Instant::now());
+        self.event_emitter.emit(Event::DecoderError {
+            input_id: self.input_id.clone(),
+            error: EventError::new("DECODER_ERROR", err),
+        });
+    }
+}
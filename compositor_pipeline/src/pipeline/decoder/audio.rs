@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use compositor_render::InputId;
 use crossbeam_channel::{bounded, Receiver, Sender};
 use log::{debug, error};
@@ -7,13 +9,15 @@ extern crate opus as lib_opus;
 use crate::{
     audio_mixer::InputSamples,
     error::InputInitError,
+    event::EventEmitter,
+    metrics::InputMetrics,
     pipeline::types::{DecodedSamples, EncodedChunk},
-    queue::PipelineEvent,
+    queue::{ClockDrift, PipelineEvent},
 };
 
 use self::{fdk_aac::AacDecoder, opus::OpusDecoder, resampler::Resampler};
 
-use super::{AudioDecoderOptions, OpusDecoderOptions};
+use super::{AudioDecoderOptions, DecoderErrorReporter, OpusDecoderOptions};
 pub use fdk_aac::AacDecoderError;
 
 mod fdk_aac;
@@ -41,6 +45,7 @@ pub fn start_audio_resampler_only_thread(
     raw_samples_receiver: Receiver<PipelineEvent<DecodedSamples>>,
     samples_sender: Sender<PipelineEvent<InputSamples>>,
     input_id: InputId,
+    clock_drift: ClockDrift,
 ) -> Result<(), InputInitError> {
     let (decoder_init_result_sender, decoder_init_result_receiver) = bounded(0);
     std::thread::Builder::new()
@@ -59,6 +64,7 @@ pub fn start_audio_resampler_only_thread(
                 raw_samples_receiver,
                 samples_sender,
                 decoder_init_result_sender,
+                clock_drift,
             );
         })
         .unwrap();
@@ -76,8 +82,9 @@ fn run_resampler_only_thread(
     raw_samples_receiver: Receiver<PipelineEvent<DecodedSamples>>,
     samples_sender: Sender<PipelineEvent<InputSamples>>,
     init_result_sender: Sender<Result<(), InputInitError>>,
+    clock_drift: ClockDrift,
 ) {
-    let mut resampler = match Resampler::new(input_sample_rate, mixing_sample_rate) {
+    let mut resampler = match Resampler::new(input_sample_rate, mixing_sample_rate, clock_drift) {
         Ok(resampler) => {
             if init_result_sender.send(Ok(())).is_err() {
                 error!("Failed to send rescaler init result.");
@@ -118,8 +125,13 @@ pub fn start_audio_decoder_thread(
     chunks_receiver: Receiver<PipelineEvent<EncodedChunk>>,
     samples_sender: Sender<PipelineEvent<InputSamples>>,
     input_id: InputId,
+    clock_drift: ClockDrift,
+    metrics: Arc<InputMetrics>,
+    event_emitter: Arc<EventEmitter>,
 ) -> Result<(), InputInitError> {
     let (init_result_sender, init_result_receiver) = bounded(0);
+    let mut error_reporter =
+        DecoderErrorReporter::new(input_id.clone(), metrics.clone(), event_emitter);
     std::thread::Builder::new()
         .name(format!("Decoder thread for input {}", input_id.clone()))
         .spawn(move || {
@@ -143,6 +155,9 @@ pub fn start_audio_decoder_thread(
                 chunks_receiver,
                 sender,
                 init_result_sender,
+                clock_drift,
+                &metrics,
+                &mut error_reporter,
             );
 
             if samples_sender.send(PipelineEvent::EOS).is_err() {
@@ -167,6 +182,9 @@ fn run_decoding<F>(
     chunks_receiver: Receiver<PipelineEvent<EncodedChunk>>,
     samples_sender: F,
     init_result_sender: Sender<Result<(), InputInitError>>,
+    clock_drift: ClockDrift,
+    metrics: &InputMetrics,
+    error_reporter: &mut DecoderErrorReporter,
 ) where
     F: Fn(InputSamples),
 {
@@ -191,7 +209,7 @@ fn run_decoding<F>(
         AudioDecoderOptions::Opus(opus_decoder_opts) => {
             // Opus decoder initialization doesn't require input stream data,
             // so this can wait and send init result
-            match init_opus_decoder(opus_decoder_opts, mixing_sample_rate) {
+            match init_opus_decoder(opus_decoder_opts, mixing_sample_rate, clock_drift) {
                 Ok((mut decoder, mut resampler)) => {
                     send_result(Ok(()));
                     run_decoding_loop(
@@ -199,6 +217,8 @@ fn run_decoding<F>(
                         &mut decoder,
                         &mut resampler,
                         samples_sender,
+                        metrics,
+                        error_reporter,
                     )
                 }
                 Err(err) => {
@@ -223,8 +243,11 @@ fn run_decoding<F>(
             };
             let init_res = AacDecoder::new(aac_decoder_opts, &first_chunk)
                 .map(|decoder| {
-                    let resampler =
-                        Resampler::new(decoder.decoded_sample_rate(), mixing_sample_rate)?;
+                    let resampler = Resampler::new(
+                        decoder.decoded_sample_rate(),
+                        mixing_sample_rate,
+                        clock_drift,
+                    )?;
                     Ok((decoder, resampler))
                 })
                 .and_then(|res| res);
@@ -235,9 +258,12 @@ fn run_decoding<F>(
                     &mut decoder,
                     &mut resampler,
                     samples_sender,
+                    metrics,
+                    error_reporter,
                 ),
                 Err(err) => {
                     error!("Fatal AAC decoder initialization error. {}", err);
+                    error_reporter.report(&err);
                 }
             }
         }
@@ -249,6 +275,8 @@ fn run_decoding_loop<Decoder, F>(
     decoder: &mut Decoder,
     resampler: &mut Resampler,
     samples_sender: F,
+    metrics: &InputMetrics,
+    error_reporter: &mut DecoderErrorReporter,
 ) where
     Decoder: AudioDecoderExt,
     F: Fn(InputSamples),
@@ -257,14 +285,18 @@ fn run_decoding_loop<Decoder, F>(
         let PipelineEvent::Data(encoded_chunk) = event else {
             break;
         };
+        metrics.received_packets.inc();
+        metrics.received_bytes.add(encoded_chunk.data.len() as u64);
 
         let decoded_samples_vec = match decoder.decode(encoded_chunk) {
             Ok(decoded_samples) => decoded_samples,
             Err(err) => {
                 error!("Failed to decode samples. Error: {}", err);
+                error_reporter.report(&err);
                 continue;
             }
         };
+        metrics.decoded_frames.add(decoded_samples_vec.len() as u64);
 
         trace!(?decoded_samples_vec, "Audio decoder produced samples");
         for decoded_samples in decoded_samples_vec {
@@ -278,8 +310,13 @@ fn run_decoding_loop<Decoder, F>(
 fn init_opus_decoder(
     opus_decoder_opts: OpusDecoderOptions,
     mixing_sample_rate: u32,
+    clock_drift: ClockDrift,
 ) -> Result<(OpusDecoder, Resampler), InputInitError> {
     let decoder = OpusDecoder::new(opus_decoder_opts, mixing_sample_rate)?;
-    let resampler = Resampler::new(decoder.decoded_sample_rate(), mixing_sample_rate)?;
+    let resampler = Resampler::new(
+        decoder.decoded_sample_rate(),
+        mixing_sample_rate,
+        clock_drift,
+    )?;
     Ok((decoder, resampler))
 }
@@ -2,40 +2,118 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use log::{debug, error};
-use rubato::{FftFixedOut, Resampler as _};
-use tracing::trace;
+use rubato::{FastFixedOut, FftFixedOut, PolynomialDegree, Resampler as _};
+use tracing::{info, trace};
 
 use crate::{
     audio_mixer::InputSamples,
     error::InputInitError,
     pipeline::types::{DecodedSamples, Samples},
+    queue::ClockDrift,
 };
 
 const SAMPLE_BATCH_DURATION: Duration = Duration::from_millis(20);
 
-pub(super) enum Resampler {
+/// Maximal relative change of resampling ratio used for clock drift compensation.
+const MAX_DRIFT_RATIO_RELATIVE: f64 = 1.01;
+
+pub(super) struct Resampler {
+    input_sample_rate: u32,
+    output_sample_rate: u32,
+    kind: ResamplerKind,
+    clock_drift: ClockDrift,
+}
+
+enum ResamplerKind {
     Passthrough(PassthroughResampler),
-    Fft(Box<FftResampler>),
+    Fft(Box<RubatoResampler<FftFixedOut<f64>>>),
+    /// Used after clock drift of the input is detected, ratio of this resampler
+    /// is adjusted to compensate the drift.
+    DriftCompensating {
+        resampler: Box<RubatoResampler<FastFixedOut<f64>>>,
+        correction: f64,
+    },
 }
 
 impl Resampler {
-    pub fn new(input_sample_rate: u32, output_sample_rate: u32) -> Result<Self, InputInitError> {
-        if input_sample_rate == output_sample_rate {
-            Ok(Self::Passthrough(PassthroughResampler::new(
+    pub fn new(
+        input_sample_rate: u32,
+        output_sample_rate: u32,
+        clock_drift: ClockDrift,
+    ) -> Result<Self, InputInitError> {
+        let kind = if input_sample_rate == output_sample_rate {
+            ResamplerKind::Passthrough(PassthroughResampler::new(
                 input_sample_rate,
                 output_sample_rate,
-            )))
+            ))
         } else {
-            FftResampler::new(input_sample_rate, output_sample_rate)
+            RubatoResampler::new_fft(input_sample_rate, output_sample_rate)
                 .map(Box::new)
-                .map(Self::Fft)
-        }
+                .map(ResamplerKind::Fft)?
+        };
+        Ok(Self {
+            input_sample_rate,
+            output_sample_rate,
+            kind,
+            clock_drift,
+        })
     }
 
     pub fn resample(&mut self, decoded_samples: DecodedSamples) -> Vec<InputSamples> {
-        match self {
-            Resampler::Passthrough(resampler) => resampler.resample(decoded_samples),
-            Resampler::Fft(resampler) => resampler.resample(decoded_samples),
+        self.compensate_drift();
+        match &mut self.kind {
+            ResamplerKind::Passthrough(resampler) => resampler.resample(decoded_samples),
+            ResamplerKind::Fft(resampler) => resampler.resample(decoded_samples),
+            ResamplerKind::DriftCompensating { resampler, .. } => {
+                resampler.resample(decoded_samples)
+            }
+        }
+    }
+
+    /// Produces fewer samples when the clock of the source runs faster than the local
+    /// one, so PTS of resampled audio progresses at the pace of the local clock.
+    fn compensate_drift(&mut self) {
+        let new_correction = self.clock_drift.correction();
+        if new_correction == 0.0 {
+            return;
+        }
+
+        if let ResamplerKind::Passthrough(_) | ResamplerKind::Fft(_) = self.kind {
+            // Samples buffered in the previous resampler are dropped, but that
+            // happens at most once per input.
+            let resampler = match RubatoResampler::new_drift_compensating(
+                self.input_sample_rate,
+                self.output_sample_rate,
+            ) {
+                Ok(resampler) => resampler,
+                Err(err) => {
+                    error!("Failed to create drift compensating resampler: {err}");
+                    return;
+                }
+            };
+            info!(
+                drift_ppm = new_correction * 1_000_000.0,
+                "Compensating clock drift of the audio input."
+            );
+            self.kind = ResamplerKind::DriftCompensating {
+                resampler: Box::new(resampler),
+                correction: 0.0,
+            };
+        }
+
+        if let ResamplerKind::DriftCompensating {
+            resampler,
+            correction,
+        } = &mut self.kind
+        {
+            if *correction == new_correction {
+                return;
+            }
+            let ratio = 1.0 / (1.0 + new_correction);
+            match resampler.resampler.set_resample_ratio_relative(ratio, true) {
+                Ok(()) => *correction = new_correction,
+                Err(err) => error!("Failed to adjust resampling ratio: {err}"),
+            }
         }
     }
 }
@@ -78,29 +156,26 @@ impl PassthroughResampler {
     }
 }
 
-pub(super) struct FftResampler {
+pub(super) struct RubatoResampler<R: rubato::Resampler<f64>> {
     input_sample_rate: u32,
     output_sample_rate: u32,
     input_buffer: [Vec<f64>; 2],
     output_buffer: [Vec<f64>; 2],
-    resampler: FftFixedOut<f64>,
+    resampler: R,
     first_batch_pts: Option<Duration>,
     resampler_input_samples: u64,
     resampler_output_samples: u64,
 }
 
-impl FftResampler {
-    fn new(
-        input_sample_rate: u32,
-        output_sample_rate: u32,
-    ) -> Result<FftResampler, InputInitError> {
-        /// This part of pipeline use stereo
-        const CHANNELS: usize = 2;
+/// This part of pipeline use stereo
+const CHANNELS: usize = 2;
+
+impl RubatoResampler<FftFixedOut<f64>> {
+    fn new_fft(input_sample_rate: u32, output_sample_rate: u32) -> Result<Self, InputInitError> {
         /// Not sure what should be here, but rubato example used 2
         /// https://github.com/HEnquist/rubato/blob/master/examples/process_f64.rs#L174
         const SUB_CHUNKS: usize = 2;
-        let output_batch_size =
-            (output_sample_rate as f64 * SAMPLE_BATCH_DURATION.as_secs_f64()).round() as usize;
+        let output_batch_size = output_batch_size(output_sample_rate);
 
         let resampler = rubato::FftFixedOut::<f64>::new(
             input_sample_rate as usize,
@@ -109,6 +184,33 @@ impl FftResampler {
             SUB_CHUNKS,
             CHANNELS,
         )?;
+        Ok(Self::new(input_sample_rate, output_sample_rate, resampler))
+    }
+}
+
+impl RubatoResampler<FastFixedOut<f64>> {
+    fn new_drift_compensating(
+        input_sample_rate: u32,
+        output_sample_rate: u32,
+    ) -> Result<Self, InputInitError> {
+        let resampler = rubato::FastFixedOut::<f64>::new(
+            output_sample_rate as f64 / input_sample_rate as f64,
+            MAX_DRIFT_RATIO_RELATIVE,
+            PolynomialDegree::Cubic,
+            output_batch_size(output_sample_rate),
+            CHANNELS,
+        )?;
+        Ok(Self::new(input_sample_rate, output_sample_rate, resampler))
+    }
+}
+
+fn output_batch_size(output_sample_rate: u32) -> usize {
+    (output_sample_rate as f64 * SAMPLE_BATCH_DURATION.as_secs_f64()).round() as usize
+}
+
+impl<R: rubato::Resampler<f64>> RubatoResampler<R> {
+    fn new(input_sample_rate: u32, output_sample_rate: u32, resampler: R) -> Self {
+        let output_batch_size = output_batch_size(output_sample_rate);
 
         // Input buffer is preallocated, to push input samples and fill missing samples between them.
         // Reallocation happens per every output batch, due to drain from the begging,
@@ -119,7 +221,7 @@ impl FftResampler {
         // Output buffer is preallocated to avoid allocating it on every output batch.
         let output_buffer = [vec![0.0; output_batch_size], vec![0.0; output_batch_size]];
 
-        Ok(Self {
+        Self {
             input_sample_rate,
             output_sample_rate,
             input_buffer,
@@ -128,7 +230,7 @@ impl FftResampler {
             first_batch_pts: None,
             resampler_input_samples: 0,
             resampler_output_samples: 0,
-        })
+        }
     }
 
     fn resample(&mut self, decoded_samples: DecodedSamples) -> Vec<InputSamples> {
@@ -1,8 +1,11 @@
+use std::sync::Arc;
+
 use compositor_render::{Frame, InputId};
 use crossbeam_channel::{Receiver, Sender};
 
 use crate::{
     error::InputInitError,
+    metrics::InputMetrics,
     pipeline::{types::EncodedChunk, PipelineCtx, VideoDecoder},
     queue::PipelineEvent,
 };
@@ -19,6 +22,7 @@ pub fn start_video_decoder_thread(
     chunks_receiver: Receiver<PipelineEvent<EncodedChunk>>,
     frame_sender: Sender<PipelineEvent<Frame>>,
     input_id: InputId,
+    metrics: Arc<InputMetrics>,
 ) -> Result<(), InputInitError> {
     match options.decoder {
         VideoDecoder::FFmpegH264 => ffmpeg_h264::start_ffmpeg_decoder_thread(
@@ -26,6 +30,7 @@ pub fn start_video_decoder_thread(
             chunks_receiver,
             frame_sender,
             input_id,
+            metrics,
         ),
 
         #[cfg(feature = "vk-video")]
@@ -34,6 +39,7 @@ pub fn start_video_decoder_thread(
             chunks_receiver,
             frame_sender,
             input_id,
+            metrics,
         ),
     }
 }
@@ -1,8 +1,10 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use crate::{
     error::InputInitError,
+    metrics::InputMetrics,
     pipeline::{
+        decoder::DecoderErrorReporter,
         types::{EncodedChunk, EncodedChunkKind, VideoCodec},
         PipelineCtx,
     },
@@ -21,12 +23,18 @@ use ffmpeg_next::{
 use tracing::{debug, error, span, trace, warn, Level};
 
 pub fn start_ffmpeg_decoder_thread(
-    _pipeline_ctx: &PipelineCtx,
+    pipeline_ctx: &PipelineCtx,
     chunks_receiver: Receiver<PipelineEvent<EncodedChunk>>,
     frame_sender: Sender<PipelineEvent<Frame>>,
     input_id: InputId,
+    metrics: Arc<InputMetrics>,
 ) -> Result<(), InputInitError> {
     let (init_result_sender, init_result_receiver) = crossbeam_channel::bounded(0);
+    let error_reporter = DecoderErrorReporter::new(
+        input_id.clone(),
+        metrics.clone(),
+        pipeline_ctx.event_emitter.clone(),
+    );
 
     let mut parameters = ffmpeg_next::codec::Parameters::new();
     unsafe {
@@ -50,6 +58,8 @@ pub fn start_ffmpeg_decoder_thread(
                 init_result_sender,
                 chunks_receiver,
                 frame_sender,
+                metrics,
+                error_reporter,
             )
         })
         .unwrap();
@@ -72,6 +82,8 @@ fn run_decoder_thread(
     init_result_sender: Sender<Result<(), InputInitError>>,
     chunks_receiver: Receiver<PipelineEvent<EncodedChunk>>,
     frame_sender: Sender<PipelineEvent<Frame>>,
+    metrics: Arc<InputMetrics>,
+    mut error_reporter: DecoderErrorReporter,
 ) {
     let decoder = Context::from_parameters(parameters.clone())
         .map_err(InputInitError::FfmpegError)
@@ -108,6 +120,8 @@ fn run_decoder_thread(
                 break;
             }
         };
+        metrics.received_packets.inc();
+        metrics.received_bytes.add(chunk.data.len() as u64);
         if chunk.kind != EncodedChunkKind::Video(VideoCodec::H264) {
             error!(
                 "H264 decoder received chunk of wrong kind: {:?}",
@@ -120,6 +134,7 @@ fn run_decoder_thread(
             Ok(packet) => packet,
             Err(err) => {
                 warn!("Dropping frame: {}", err);
+                error_reporter.report(&err);
                 continue;
             }
         };
@@ -128,6 +143,7 @@ fn run_decoder_thread(
             Ok(()) => {}
             Err(e) => {
                 warn!("Failed to send a packet to decoder: {:?}", e);
+                error_reporter.report(&e);
                 continue;
             }
         }
@@ -137,9 +153,12 @@ fn run_decoder_thread(
                 Ok(frame) => frame,
                 Err(err) => {
                     warn!("Dropping frame: {}", err);
+                    error_reporter.report(&err);
                     continue;
                 }
             };
+            metrics.decoded_frames.inc();
+            metrics.decoded_video_frames.inc();
 
             trace!(pts=?frame.pts, "H264 decoder produced a frame.");
             if frame_sender.send(PipelineEvent::Data(frame)).is_err() {
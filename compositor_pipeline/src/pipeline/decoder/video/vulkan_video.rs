@@ -7,7 +7,10 @@ use vk_video::VulkanDevice;
 
 use crate::{
     error::InputInitError,
-    pipeline::{EncodedChunk, EncodedChunkKind, PipelineCtx, VideoCodec},
+    metrics::InputMetrics,
+    pipeline::{
+        decoder::DecoderErrorReporter, EncodedChunk, EncodedChunkKind, PipelineCtx, VideoCodec,
+    },
     queue::PipelineEvent,
 };
 
@@ -16,12 +19,18 @@ pub fn start_vulkan_video_decoder_thread(
     chunks_receiver: Receiver<PipelineEvent<EncodedChunk>>,
     frame_sender: Sender<PipelineEvent<Frame>>,
     input_id: InputId,
+    metrics: Arc<InputMetrics>,
 ) -> Result<(), InputInitError> {
     let Some(vulkan_ctx) = pipeline_ctx.vulkan_ctx.clone() else {
         return Err(InputInitError::VulkanContextRequiredForVulkanDecoder);
     };
 
     let (init_result_sender, init_result_receiver) = crossbeam_channel::bounded(0);
+    let error_reporter = DecoderErrorReporter::new(
+        input_id.clone(),
+        metrics.clone(),
+        pipeline_ctx.event_emitter.clone(),
+    );
 
     std::thread::Builder::new()
         .name(format!("h264 vulkan video decoder {}", input_id.0))
@@ -37,6 +46,8 @@ pub fn start_vulkan_video_decoder_thread(
                 init_result_sender,
                 chunks_receiver,
                 frame_sender,
+                metrics,
+                error_reporter,
             )
         })
         .unwrap();
@@ -51,6 +62,8 @@ fn run_decoder_thread(
     init_result_sender: Sender<Result<(), InputInitError>>,
     chunks_receiver: Receiver<PipelineEvent<EncodedChunk>>,
     frame_sender: Sender<PipelineEvent<Frame>>,
+    metrics: Arc<InputMetrics>,
+    mut error_reporter: DecoderErrorReporter,
 ) {
     let mut decoder = match vulkan_device.create_wgpu_textures_decoder() {
         Ok(decoder) => {
@@ -70,6 +83,8 @@ fn run_decoder_thread(
                 break;
             }
         };
+        metrics.received_packets.inc();
+        metrics.received_bytes.add(chunk.data.len() as u64);
 
         if chunk.kind != EncodedChunkKind::Video(VideoCodec::H264) {
             error!(
@@ -83,6 +98,7 @@ fn run_decoder_thread(
             Ok(res) => res,
             Err(err) => {
                 warn!("Failed to decode frame: {err}");
+                error_reporter.report(&err);
                 continue;
             }
         };
@@ -99,6 +115,8 @@ fn run_decoder_thread(
                 resolution,
             };
 
+            metrics.decoded_frames.inc();
+            metrics.decoded_video_frames.inc();
             trace!(pts=?frame.pts, "H264 decoder produced a frame.");
             if frame_sender.send(PipelineEvent::Data(frame)).is_err() {
                 debug!("Failed to send frame from H264 decoder. Channel closed.");
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use compositor_render::{Frame, OutputId, Resolution};
 use crossbeam_channel::{bounded, Receiver, Sender};
 use fdk_aac::AacEncoder;
@@ -7,6 +9,7 @@ use resampler::OutputResampler;
 use crate::{
     audio_mixer::{AudioChannels, OutputSamples},
     error::EncoderInitError,
+    metrics::OutputMetrics,
     queue::PipelineEvent,
 };
 
@@ -19,6 +22,7 @@ pub mod ffmpeg_h264;
 pub mod opus;
 mod resampler;
 
+#[derive(Debug, Clone)]
 pub struct EncoderOptions {
     pub video: Option<VideoEncoderOptions>,
     pub audio: Option<AudioEncoderOptions>,
@@ -45,6 +49,7 @@ pub enum AudioEncoderPreset {
 pub struct Encoder {
     pub video: Option<VideoEncoder>,
     audio: Option<AudioEncoder>,
+    options: EncoderOptions,
 }
 
 pub enum VideoEncoder {
@@ -61,24 +66,27 @@ impl Encoder {
         output_id: &OutputId,
         options: EncoderOptions,
         sample_rate: u32,
+        metrics: Arc<OutputMetrics>,
     ) -> Result<(Self, Receiver<EncoderOutputEvent>), EncoderInitError> {
         let (encoded_chunks_sender, encoded_chunks_receiver) = bounded(1);
 
-        let video_encoder = match options.video {
+        let video_encoder = match options.video.clone() {
             Some(video_encoder_options) => Some(VideoEncoder::new(
                 output_id,
                 video_encoder_options,
                 encoded_chunks_sender.clone(),
+                metrics.clone(),
             )?),
             None => None,
         };
 
-        let audio_encoder = match options.audio {
+        let audio_encoder = match options.audio.clone() {
             Some(audio_encoder_options) => Some(AudioEncoder::new(
                 output_id,
                 audio_encoder_options,
                 sample_rate,
                 encoded_chunks_sender,
+                metrics,
             )?),
             None => None,
         };
@@ -87,11 +95,16 @@ impl Encoder {
             Self {
                 video: video_encoder,
                 audio: audio_encoder,
+                options,
             },
             encoded_chunks_receiver,
         ))
     }
 
+    pub fn options(&self) -> &EncoderOptions {
+        &self.options
+    }
+
     pub fn frame_sender(&self) -> Option<&Sender<PipelineEvent<Frame>>> {
         match &self.video {
             Some(VideoEncoder::H264(encoder)) => Some(encoder.frame_sender()),
@@ -136,10 +149,11 @@ impl VideoEncoder {
         output_id: &OutputId,
         options: VideoEncoderOptions,
         sender: Sender<EncoderOutputEvent>,
+        metrics: Arc<OutputMetrics>,
     ) -> Result<Self, EncoderInitError> {
         match options {
             VideoEncoderOptions::H264(options) => Ok(Self::H264(LibavH264Encoder::new(
-                output_id, options, sender,
+                output_id, options, sender, metrics,
             )?)),
         }
     }
@@ -163,6 +177,7 @@ impl AudioEncoder {
         options: AudioEncoderOptions,
         mixing_sample_rate: u32,
         sender: Sender<EncoderOutputEvent>,
+        metrics: Arc<OutputMetrics>,
     ) -> Result<Self, EncoderInitError> {
         let resampler = if options.sample_rate() != mixing_sample_rate {
             Some(OutputResampler::new(
@@ -175,10 +190,11 @@ impl AudioEncoder {
 
         match options {
             AudioEncoderOptions::Opus(options) => {
-                OpusEncoder::new(options, sender, resampler).map(AudioEncoder::Opus)
+                OpusEncoder::new(options, sender, resampler, metrics).map(AudioEncoder::Opus)
             }
             AudioEncoderOptions::Aac(options) => {
-                AacEncoder::new(output_id, options, sender, resampler).map(AudioEncoder::Aac)
+                AacEncoder::new(output_id, options, sender, resampler, metrics)
+                    .map(AudioEncoder::Aac)
             }
         }
     }
@@ -2,6 +2,7 @@ use std::{
     mem::{self, MaybeUninit},
     os::raw::{c_int, c_void},
     ptr,
+    sync::Arc,
     time::Duration,
 };
 
@@ -14,6 +15,7 @@ use tracing::{debug, error, span, Level};
 use crate::{
     audio_mixer::{AudioChannels, AudioSamples, OutputSamples},
     error::EncoderInitError,
+    metrics::OutputMetrics,
     pipeline::{types::IsKeyframe, AudioCodec, EncodedChunk, EncodedChunkKind, EncoderOutputEvent},
     queue::PipelineEvent,
 };
@@ -39,6 +41,7 @@ impl AacEncoder {
         options: AacEncoderOptions,
         packets_sender: Sender<EncoderOutputEvent>,
         resampler: Option<OutputResampler>,
+        metrics: Arc<OutputMetrics>,
     ) -> Result<Self, EncoderInitError> {
         let (samples_batch_sender, samples_batch_receiver) = bounded(5);
         // Since AAC encoder holds ref to internal structure (handler), it's unsafe to send it between threads.
@@ -56,6 +59,7 @@ impl AacEncoder {
                     samples_batch_receiver,
                     packets_sender,
                     resampler,
+                    &metrics,
                 );
                 debug!("Closing AAC encoder thread.");
             })
@@ -308,6 +312,7 @@ fn run_encoder_thread(
     samples_batch_receiver: Receiver<PipelineEvent<OutputSamples>>,
     packets_sender: Sender<EncoderOutputEvent>,
     mut resampler: Option<OutputResampler>,
+    metrics: &OutputMetrics,
 ) {
     let mut encoder = match AacEncoderInner::new(options) {
         Ok(encoder) => {
@@ -334,6 +339,7 @@ fn run_encoder_thread(
         for samples in output_samples {
             match encoder.encode(samples) {
                 Ok(Some(encoded_samples)) => {
+                    metrics.on_chunk_encoded(&encoded_samples);
                     let send_result =
                         packets_sender.send(EncoderOutputEvent::Data(encoded_samples));
                     if send_result.is_err() {
@@ -353,6 +359,7 @@ fn run_encoder_thread(
     if encoder.start_pts.is_some() {
         match encoder.flush() {
             Ok(Some(encoded_samples)) => {
+                metrics.on_chunk_encoded(&encoded_samples);
                 let send_result = packets_sender.send(EncoderOutputEvent::Data(encoded_samples));
                 if send_result.is_err() {
                     debug!("Failed to send AAC encoded samples.");
@@ -1,4 +1,7 @@
-use std::time::Duration;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use compositor_render::{Frame, FrameData, OutputId, Resolution};
 use crossbeam_channel::{Receiver, Sender};
@@ -12,6 +15,7 @@ use tracing::{debug, error, span, trace, warn, Level};
 
 use crate::{
     error::EncoderInitError,
+    metrics::OutputMetrics,
     pipeline::types::{
         ChunkFromFfmpegError, EncodedChunk, EncodedChunkKind, EncoderOutputEvent, IsKeyframe,
         VideoCodec,
@@ -34,7 +38,7 @@ pub enum EncoderPreset {
 }
 
 impl EncoderPreset {
-    fn to_str(&self) -> &'static str {
+    pub fn to_str(&self) -> &'static str {
         match self {
             EncoderPreset::Ultrafast => "ultrafast",
             EncoderPreset::Superfast => "superfast",
@@ -98,6 +102,7 @@ impl LibavH264Encoder {
         output_id: &OutputId,
         options: Options,
         chunks_sender: Sender<EncoderOutputEvent>,
+        metrics: Arc<OutputMetrics>,
     ) -> Result<Self, EncoderInitError> {
         let (frame_sender, frame_receiver) = crossbeam_channel::bounded(5);
         let (result_sender, result_receiver) = crossbeam_channel::bounded(0);
@@ -121,6 +126,7 @@ impl LibavH264Encoder {
                     keyframe_req_receiver,
                     chunks_sender,
                     &result_sender,
+                    &metrics,
                 );
 
                 if let Err(err) = encoder_result {
@@ -161,6 +167,7 @@ fn run_encoder_thread(
     keyframe_req_receiver: Receiver<()>,
     packet_sender: Sender<EncoderOutputEvent>,
     result_sender: &Sender<Result<(), EncoderInitError>>,
+    metrics: &OutputMetrics,
 ) -> Result<(), EncoderInitError> {
     let codec = ffmpeg_next::codec::encoder::find(Id::H264).ok_or(EncoderInitError::NoCodec)?;
 
@@ -217,6 +224,7 @@ fn run_encoder_thread(
             Ok(PipelineEvent::EOS) => break,
             Err(_) => break,
         };
+        let encode_start = Instant::now();
 
         let mut av_frame = frame::Video::new(
             Pixel::YUV420P,
@@ -229,6 +237,7 @@ fn run_encoder_thread(
                 "Failed to convert a frame to an ffmpeg frame: {}. Dropping",
                 e.0
             );
+            metrics.dropped_frames.inc();
             continue;
         }
 
@@ -238,10 +247,19 @@ fn run_encoder_thread(
 
         if let Err(e) = encoder.send_frame(&av_frame) {
             error!("Encoder error: {e}.");
+            metrics.dropped_frames.inc();
             continue;
         }
+        metrics.encoded_frames.inc();
 
+        let mut chunks = Vec::new();
         while let Some(chunk) = receive_chunk(&mut encoder, &mut packet) {
+            chunks.push(chunk);
+        }
+        metrics.encoder_latency.observe(encode_start.elapsed());
+
+        for chunk in chunks {
+            metrics.on_chunk_encoded(&chunk);
             if packet_sender.send(EncoderOutputEvent::Data(chunk)).is_err() {
                 warn!("Failed to send encoded video from H264 encoder. Channel closed.");
                 return Ok(());
@@ -254,6 +272,7 @@ fn run_encoder_thread(
         error!("Failed to enter draining mode on encoder: {e}.");
     }
     while let Some(chunk) = receive_chunk(&mut encoder, &mut packet) {
+        metrics.on_chunk_encoded(&chunk);
         if packet_sender.send(EncoderOutputEvent::Data(chunk)).is_err() {
             warn!("Failed to send encoded video from H264 encoder. Channel closed.");
             return Ok(());
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crossbeam_channel::{bounded, Receiver, Sender};
 use log::error;
 use tracing::{span, trace, warn, Level};
@@ -5,6 +7,7 @@ use tracing::{span, trace, warn, Level};
 use crate::{
     audio_mixer::{AudioChannels, AudioSamples, OutputSamples},
     error::EncoderInitError,
+    metrics::OutputMetrics,
     pipeline::{
         types::{EncodedChunk, EncodedChunkKind, EncoderOutputEvent, IsKeyframe},
         AudioCodec,
@@ -30,6 +33,7 @@ impl OpusEncoder {
         options: OpusEncoderOptions,
         packets_sender: Sender<EncoderOutputEvent>,
         resampler: Option<OutputResampler>,
+        metrics: Arc<OutputMetrics>,
     ) -> Result<Self, EncoderInitError> {
         let (samples_batch_sender, samples_batch_receiver) = bounded(2);
 
@@ -43,7 +47,13 @@ impl OpusEncoder {
             .name("Opus encoder thread".to_string())
             .spawn(move || {
                 let _span = span!(Level::INFO, "Opus encoder thread").entered();
-                run_encoder_thread(encoder, resampler, samples_batch_receiver, packets_sender)
+                run_encoder_thread(
+                    encoder,
+                    resampler,
+                    samples_batch_receiver,
+                    packets_sender,
+                    &metrics,
+                )
             })
             .unwrap();
 
@@ -62,6 +72,7 @@ fn run_encoder_thread(
     mut resampler: Option<OutputResampler>,
     samples_batch_receiver: Receiver<PipelineEvent<OutputSamples>>,
     packets_sender: Sender<EncoderOutputEvent>,
+    metrics: &OutputMetrics,
 ) {
     let mut output_buffer = vec![0u8; 1024 * 1024];
 
@@ -110,6 +121,7 @@ fn run_encoder_thread(
             };
 
             trace!(pts=?chunk.pts, "OPUS encoder produced an encoded chunk.");
+            metrics.on_chunk_encoded(&chunk);
             if let Err(_err) = packets_sender.send(EncoderOutputEvent::Data(chunk)) {
                 warn!("Failed to send encoded audio from OPUS encoder. Channel closed.");
                 return;
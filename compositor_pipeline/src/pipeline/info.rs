@@ -0,0 +1,161 @@
+use compositor_render::{Framerate, InputId, OutputId, Resolution};
+
+use crate::queue::QueueInputState;
+
+use super::{
+    encoder::{AudioEncoderOptions, VideoEncoderOptions},
+    input::Input,
+    output::Output,
+    AudioCodec, Pipeline, Port, VideoDecoder,
+};
+
+#[derive(Debug, Clone)]
+pub struct InputInfo {
+    pub input_id: InputId,
+    pub input_type: InputType,
+    /// Port that RTP input is listening on.
+    pub port: Option<Port>,
+    pub video: Option<InputVideoInfo>,
+    pub audio: Option<InputAudioInfo>,
+    /// Estimated clock drift of the input source, `None` if it is not compensated.
+    pub clock_drift_ppm: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputType {
+    Rtp,
+    Mp4,
+    Whip,
+    DeckLink,
+    RawData,
+}
+
+#[derive(Debug, Clone)]
+pub struct InputVideoInfo {
+    /// `None` if input produces raw frames.
+    pub decoder: Option<VideoDecoder>,
+    /// Resolution of the last frame that was rendered.
+    pub resolution: Option<Resolution>,
+    pub state: Option<QueueInputState>,
+    pub eos_received: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct InputAudioInfo {
+    /// `None` if input produces raw samples.
+    pub codec: Option<AudioCodec>,
+    pub state: Option<QueueInputState>,
+    pub eos_received: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputInfo {
+    pub output_id: OutputId,
+    pub output_type: OutputType,
+    /// Port used by RTP output.
+    pub port: Option<Port>,
+    pub video: Option<OutputVideoInfo>,
+    pub audio: Option<OutputAudioInfo>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputType {
+    Rtp,
+    Mp4,
+    Whip,
+    EncodedData,
+    RawData,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputVideoInfo {
+    pub resolution: Option<Resolution>,
+    pub framerate: Option<Framerate>,
+    /// `None` for raw outputs.
+    pub encoder: Option<VideoEncoderOptions>,
+    pub eos_sent: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputAudioInfo {
+    /// `None` for raw outputs.
+    pub encoder: Option<AudioEncoderOptions>,
+    pub eos_sent: bool,
+}
+
+impl Pipeline {
+    pub fn inputs_info(&self) -> Vec<InputInfo> {
+        self.inputs()
+            .map(|(input_id, input)| {
+                let stats = self.queue.input_stats(input_id);
+                let (input_type, port) = match &input.input {
+                    Input::Rtp(receiver) => (InputType::Rtp, Some(Port(receiver.port))),
+                    Input::Mp4(_) => (InputType::Mp4, None),
+                    Input::Whip(_) => (InputType::Whip, None),
+                    #[cfg(feature = "decklink")]
+                    Input::DeckLink(_) => (InputType::DeckLink, None),
+                    Input::RawDataInput => (InputType::RawData, None),
+                };
+                let video = input.video_eos_received.map(|eos_received| InputVideoInfo {
+                    decoder: input.video_decoder,
+                    resolution: self.renderer.input_resolution(input_id),
+                    state: stats.and_then(|stats| stats.video_state),
+                    eos_received,
+                });
+                let audio = input.audio_eos_received.map(|eos_received| InputAudioInfo {
+                    codec: input.audio_codec,
+                    state: stats.and_then(|stats| stats.audio_state),
+                    eos_received,
+                });
+                InputInfo {
+                    input_id: input_id.clone(),
+                    input_type,
+                    port,
+                    video,
+                    audio,
+                    clock_drift_ppm: stats.and_then(|stats| stats.clock_drift_ppm),
+                }
+            })
+            .collect()
+    }
+
+    pub fn outputs_info(&self) -> Vec<OutputInfo> {
+        self.outputs()
+            .map(|(output_id, output)| {
+                let (output_type, port) = match &output.output {
+                    Output::Rtp { sender, .. } => (OutputType::Rtp, Some(sender.port)),
+                    Output::Mp4 { .. } => (OutputType::Mp4, None),
+                    Output::Whip { .. } => (OutputType::Whip, None),
+                    Output::EncodedData { .. } => (OutputType::EncodedData, None),
+                    Output::RawData { .. } => (OutputType::RawData, None),
+                };
+                let encoder_options = output.output.encoder_options();
+                let video =
+                    output
+                        .video_end_condition
+                        .as_ref()
+                        .map(|end_condition| OutputVideoInfo {
+                            resolution: output.output.resolution(),
+                            framerate: self.queue.output_framerate(output_id),
+                            encoder: encoder_options.and_then(|opts| opts.video.clone()),
+                            eos_sent: end_condition.did_send_eos(),
+                        });
+                let audio =
+                    output
+                        .audio_end_condition
+                        .as_ref()
+                        .map(|end_condition| OutputAudioInfo {
+                            encoder: encoder_options.and_then(|opts| opts.audio.clone()),
+                            eos_sent: end_condition.did_send_eos(),
+                        });
+                OutputInfo {
+                    output_id: output_id.clone(),
+                    output_type,
+                    port,
+                    video,
+                    audio,
+                }
+            })
+            .collect()
+    }
+}
@@ -2,7 +2,8 @@ use std::time::Duration;
 
 use crate::{
     error::{InputInitError, RegisterInputError},
-    queue::PipelineEvent,
+    metrics::InputMetrics,
+    queue::{ClockDrift, PipelineEvent},
 };
 
 use compositor_render::{Frame, InputId};
@@ -140,6 +141,10 @@ impl InputOptionsExt<RawDataSender> for RawDataInputOptions {
             DecodedDataReceiver {
                 video: video_receiver,
                 audio: audio_receiver,
+                // raw data is produced by the caller, so it is not affected by clock drift
+                clock_drift: None,
+                video_decoder: None,
+                audio_codec: None,
             },
             RawDataSender {
                 video: video_sender,
@@ -155,13 +160,14 @@ fn start_input_threads(
     options: InputOptions,
     pipeline_ctx: &PipelineCtx,
 ) -> Result<(Input, DecodedDataReceiver, InputInitInfo), InputInitError> {
+    let metrics = pipeline_ctx.metrics.input(input_id);
     let InputInitResult {
         input,
         video,
         audio,
         init_info,
     } = match options {
-        InputOptions::Rtp(opts) => RtpReceiver::start_new_input(input_id, opts)?,
+        InputOptions::Rtp(opts) => RtpReceiver::start_new_input(input_id, opts, pipeline_ctx)?,
         InputOptions::Mp4(opts) => {
             Mp4::start_new_input(input_id, opts, &pipeline_ctx.download_dir)?
         }
@@ -169,6 +175,19 @@ fn start_input_threads(
         #[cfg(feature = "decklink")]
         InputOptions::DeckLink(opts) => decklink::DeckLink::start_new_input(input_id, opts)?,
     };
+    let clock_drift = ClockDrift::new();
+    let video_decoder = match &video {
+        Some(VideoInputReceiver::Encoded {
+            decoder_options, ..
+        }) => Some(decoder_options.decoder),
+        _ => None,
+    };
+    let audio_codec = match &audio {
+        Some(AudioInputReceiver::Encoded {
+            decoder_options, ..
+        }) => Some(decoder_options.codec()),
+        _ => None,
+    };
 
     let video = if let Some(video) = video {
         match video {
@@ -184,6 +203,7 @@ fn start_input_threads(
                     chunk_receiver,
                     sender,
                     input_id.clone(),
+                    metrics.clone(),
                 )?;
                 Some(receiver)
             }
@@ -205,6 +225,7 @@ fn start_input_threads(
                     sample_receiver,
                     sender,
                     input_id.clone(),
+                    clock_drift.clone(),
                 )?;
                 Some(receiver)
             }
@@ -219,6 +240,9 @@ fn start_input_threads(
                     chunk_receiver,
                     sender,
                     input_id.clone(),
+                    clock_drift.clone(),
+                    metrics,
+                    pipeline_ctx.event_emitter.clone(),
                 )?;
                 Some(receiver)
             }
@@ -226,5 +250,15 @@ fn start_input_threads(
     } else {
         None
     };
-    Ok((input, DecodedDataReceiver { video, audio }, init_info))
+    Ok((
+        input,
+        DecodedDataReceiver {
+            video,
+            audio,
+            clock_drift: Some(clock_drift),
+            video_decoder,
+            audio_codec,
+        },
+        init_info,
+    ))
 }
@@ -1,32 +1,55 @@
-use std::sync::{atomic::AtomicBool, Arc};
+use std::{
+    collections::HashMap,
+    sync::{atomic::AtomicBool, Arc},
+    time::{Duration, Instant},
+};
 
 use crate::{
+    metrics::InputMetrics,
     pipeline::{
         decoder::{self},
         encoder,
-        rtp::{BindToPortError, RequestedPort, TransportProtocol},
+        rtp::{
+            BindToPortError, RequestedPort, TransportProtocol, AUDIO_PAYLOAD_TYPE,
+            VIDEO_PAYLOAD_TYPE,
+        },
         types::{EncodedChunk, EncodedChunkKind},
+        PipelineCtx,
     },
     queue::PipelineEvent,
 };
 use compositor_render::InputId;
-use crossbeam_channel::{bounded, Receiver, Sender};
-use rtcp::header::PacketType;
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender};
+use rtcp::{header::PacketType, sender_report::SenderReport};
 use tracing::{debug, error, span, warn, Level};
 use webrtc_util::Unmarshal;
 
 use self::{
+    av_sync::{AvSync, TrackKind},
     depayloader::{Depayloader, DepayloaderNewError},
+    jitter_buffer::{unwrap_rtx_packet, RtpJitterBuffer},
+    reception_stats::ReceptionStats,
+    rtcp_feedback::{RtcpFeedback, RtcpFeedbackSender},
     tcp_server::start_tcp_server_thread,
     udp::start_udp_reader_thread,
 };
 
+pub use jitter_buffer::RtpJitterBufferOptions;
+
 use super::{AudioInputReceiver, Input, InputInitInfo, InputInitResult, VideoInputReceiver};
 
+mod av_sync;
 mod depayloader;
+mod jitter_buffer;
+mod reception_stats;
+mod rtcp_feedback;
 mod tcp_server;
 mod udp;
 
+/// How often jitter buffers are checked for packets that can be released
+/// and for missing packets that should be requested again.
+const JITTER_BUFFER_TICK: Duration = Duration::from_millis(5);
+
 #[derive(Debug, thiserror::Error)]
 pub enum RtpReceiverError {
     #[error("Error while setting socket options.")]
@@ -50,11 +73,14 @@ pub struct RtpReceiverOptions {
     pub port: RequestedPort,
     pub transport_protocol: TransportProtocol,
     pub stream: RtpStream,
+    pub jitter_buffer: RtpJitterBufferOptions,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct InputVideoStream {
     pub options: decoder::VideoDecoderOptions,
+    /// Payload type of retransmission packets ([RFC 4588](https://datatracker.ietf.org/doc/html/rfc4588)).
+    pub rtx_payload_type: Option<u8>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -73,6 +99,13 @@ pub struct RtpStream {
     pub audio: Option<InputAudioStream>,
 }
 
+struct DepayloaderThreadOptions {
+    jitter_buffer: RtpJitterBufferOptions,
+    video_rtx_payload_type: Option<u8>,
+    feedback_sender: RtcpFeedbackSender,
+    metrics: Arc<InputMetrics>,
+}
+
 struct DepayloaderThreadReceivers {
     video: Option<Receiver<PipelineEvent<EncodedChunk>>>,
     audio: Option<Receiver<PipelineEvent<EncodedChunk>>>,
@@ -87,22 +120,39 @@ impl RtpReceiver {
     pub(super) fn start_new_input(
         input_id: &InputId,
         opts: RtpReceiverOptions,
+        pipeline_ctx: &PipelineCtx,
     ) -> Result<InputInitResult, RtpReceiverError> {
         let should_close = Arc::new(AtomicBool::new(false));
 
-        let (port, packets_rx) = match opts.transport_protocol {
+        let (port, packets_rx, feedback_sender) = match opts.transport_protocol {
             TransportProtocol::Udp => {
                 start_udp_reader_thread(input_id, &opts, should_close.clone())?
             }
-            TransportProtocol::TcpServer => {
-                start_tcp_server_thread(input_id, &opts, should_close.clone())?
-            }
+            TransportProtocol::TcpServer => start_tcp_server_thread(
+                input_id,
+                &opts,
+                should_close.clone(),
+                pipeline_ctx.event_emitter.clone(),
+            )?,
         };
 
         let depayloader = Depayloader::new(&opts.stream)?;
 
-        let depayloader_receivers =
-            Self::start_depayloader_thread(input_id, packets_rx, depayloader);
+        let depayloader_receivers = Self::start_depayloader_thread(
+            input_id,
+            packets_rx,
+            depayloader,
+            DepayloaderThreadOptions {
+                jitter_buffer: opts.jitter_buffer,
+                video_rtx_payload_type: opts
+                    .stream
+                    .video
+                    .as_ref()
+                    .and_then(|video| video.rtx_payload_type),
+                feedback_sender,
+                metrics: pipeline_ctx.metrics.input(input_id),
+            },
+        );
 
         let video = match (depayloader_receivers.video, opts.stream.video) {
             (Some(chunk_receiver), Some(stream)) => Some(VideoInputReceiver::Encoded {
@@ -134,6 +184,7 @@ impl RtpReceiver {
         input_id: &InputId,
         receiver: Receiver<bytes::Bytes>,
         depayloader: Depayloader,
+        opts: DepayloaderThreadOptions,
     ) -> DepayloaderThreadReceivers {
         let (video_sender, video_receiver) = depayloader
             .video
@@ -156,7 +207,7 @@ impl RtpReceiver {
                     input_id = input_id.to_string()
                 )
                 .entered();
-                run_depayloader_thread(receiver, depayloader, video_sender, audio_sender)
+                run_depayloader_thread(receiver, depayloader, video_sender, audio_sender, opts)
             })
             .unwrap();
 
@@ -179,11 +230,18 @@ fn run_depayloader_thread(
     mut depayloader: Depayloader,
     video_sender: Option<Sender<PipelineEvent<EncodedChunk>>>,
     audio_sender: Option<Sender<PipelineEvent<EncodedChunk>>>,
+    opts: DepayloaderThreadOptions,
 ) {
     let mut audio_eos_received = audio_sender.as_ref().map(|_| false);
     let mut video_eos_received = video_sender.as_ref().map(|_| false);
     let mut audio_ssrc = None;
     let mut video_ssrc = None;
+    let mut jitter_buffers: HashMap<u32, RtpJitterBuffer> = HashMap::new();
+    let mut reception_stats: HashMap<u32, ReceptionStats> = HashMap::new();
+    let mut rtcp_feedback = RtcpFeedback::new(opts.feedback_sender);
+    let mut av_sync = AvSync::default();
+    let video_clock_rate = depayloader.clock_rate(VIDEO_PAYLOAD_TYPE);
+    let audio_clock_rate = depayloader.clock_rate(AUDIO_PAYLOAD_TYPE);
 
     let mut maybe_send_video_eos = || {
         if let (Some(sender), Some(false)) = (&video_sender, video_eos_received) {
@@ -201,56 +259,137 @@ fn run_depayloader_thread(
             }
         }
     };
+    let mut depayload = |packets: Vec<rtp::packet::Packet>, av_sync: &mut AvSync| {
+        for packet in packets {
+            let timestamp = packet.header.timestamp;
+            let chunks = match depayloader.depayload(packet) {
+                Ok(chunks) => chunks,
+                Err(err) => {
+                    warn!("RTP depayloading error: {}", err);
+                    continue;
+                }
+            };
+            // the first chunk starts at the packet's timestamp
+            if let Some(chunk) = chunks.first() {
+                let kind = match chunk.kind {
+                    EncodedChunkKind::Video(_) => TrackKind::Video,
+                    EncodedChunkKind::Audio(_) => TrackKind::Audio,
+                };
+                av_sync.on_chunk(kind, timestamp, chunk.pts);
+            }
+            for mut chunk in chunks {
+                match &chunk.kind {
+                    EncodedChunkKind::Video(_) => video_sender
+                        .as_ref()
+                        .map(|video_sender| video_sender.send(PipelineEvent::Data(chunk))),
+                    EncodedChunkKind::Audio(_) => audio_sender.as_ref().map(|audio_sender| {
+                        chunk.pts = av_sync.correct_audio_pts(chunk.pts);
+                        chunk.dts = chunk.dts.map(|dts| av_sync.correct_audio_pts(dts));
+                        audio_sender.send(PipelineEvent::Data(chunk))
+                    }),
+                };
+            }
+        }
+    };
     loop {
-        let Ok(mut buffer) = receiver.recv() else {
-            debug!("Closing RTP depayloader thread.");
-            break;
+        let buffer = match receiver.recv_timeout(JITTER_BUFFER_TICK) {
+            Ok(buffer) => Some(buffer),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => {
+                debug!("Closing RTP depayloader thread.");
+                break;
+            }
         };
+        let now = Instant::now();
+
+        if let Some(mut buffer) = buffer {
+            match rtp::packet::Packet::unmarshal(&mut buffer.clone()) {
+                // https://datatracker.ietf.org/doc/html/rfc5761#section-4
+                //
+                // Given these constraints, it is RECOMMENDED to follow the guidelines
+                // in the RTP/AVP profile [7] for the choice of RTP payload type values,
+                // with the additional restriction that payload type values in the range
+                // 64-95 MUST NOT be used.
+                Ok(packet)
+                    if packet.header.payload_type < 64 || packet.header.payload_type > 95 =>
+                {
+                    let is_rtx = Some(packet.header.payload_type) == opts.video_rtx_payload_type;
+                    let packet = if is_rtx {
+                        // retransmissions are only expected after the original stream started
+                        let Some(ssrc) = video_ssrc else {
+                            continue;
+                        };
+                        match unwrap_rtx_packet(packet, VIDEO_PAYLOAD_TYPE, ssrc) {
+                            Some(packet) => packet,
+                            None => {
+                                warn!("Received malformed RTX packet. Dropping.");
+                                continue;
+                            }
+                        }
+                    } else {
+                        packet
+                    };
 
-        match rtp::packet::Packet::unmarshal(&mut buffer.clone()) {
-            // https://datatracker.ietf.org/doc/html/rfc5761#section-4
-            //
-            // Given these constraints, it is RECOMMENDED to follow the guidelines
-            // in the RTP/AVP profile [7] for the choice of RTP payload type values,
-            // with the additional restriction that payload type values in the range
-            // 64-95 MUST NOT be used.
-            Ok(packet) if packet.header.payload_type < 64 || packet.header.payload_type > 95 => {
-                if packet.header.payload_type == 96 && video_ssrc.is_none() {
-                    video_ssrc = Some(packet.header.ssrc);
-                }
-                if packet.header.payload_type == 97 && audio_ssrc.is_none() {
-                    audio_ssrc = Some(packet.header.ssrc);
-                }
+                    if packet.header.payload_type == VIDEO_PAYLOAD_TYPE && video_ssrc.is_none() {
+                        video_ssrc = Some(packet.header.ssrc);
+                    }
+                    if packet.header.payload_type == AUDIO_PAYLOAD_TYPE && audio_ssrc.is_none() {
+                        audio_ssrc = Some(packet.header.ssrc);
+                    }
 
-                match depayloader.depayload(packet) {
-                    Ok(chunks) => {
-                        for chunk in chunks {
-                            match &chunk.kind {
-                                EncodedChunkKind::Video(_) => {
-                                    video_sender.as_ref().map(|video_sender| {
-                                        video_sender.send(PipelineEvent::Data(chunk))
-                                    })
-                                }
-                                EncodedChunkKind::Audio(_) => {
-                                    audio_sender.as_ref().map(|audio_sender| {
-                                        audio_sender.send(PipelineEvent::Data(chunk))
-                                    })
-                                }
-                            };
+                    // retransmissions would distort jitter and loss statistics
+                    let clock_rate = match packet.header.payload_type {
+                        VIDEO_PAYLOAD_TYPE => video_clock_rate,
+                        AUDIO_PAYLOAD_TYPE => audio_clock_rate,
+                        _ => None,
+                    };
+                    if let (false, Some(clock_rate)) = (is_rtx, clock_rate) {
+                        match reception_stats.get_mut(&packet.header.ssrc) {
+                            Some(stats) => stats.on_packet(&packet.header, now),
+                            None => {
+                                reception_stats.insert(
+                                    packet.header.ssrc,
+                                    ReceptionStats::new(clock_rate, &packet.header, now),
+                                );
+                            }
                         }
+                        update_reception_metrics(&opts.metrics, &reception_stats);
                     }
-                    Err(err) => {
-                        warn!("RTP depayloading error: {}", err);
-                        continue;
-                    }
+
+                    jitter_buffers
+                        .entry(packet.header.ssrc)
+                        .or_insert_with(|| RtpJitterBuffer::new(opts.jitter_buffer))
+                        .push(packet, now);
                 }
-            }
-            Ok(_) | Err(_) => {
-                match rtcp::packet::unmarshal(&mut buffer) {
+                Ok(_) | Err(_) => match rtcp::packet::unmarshal(&mut buffer) {
                     Ok(rtcp_packets) => {
                         for rtcp_packet in rtcp_packets {
-                            if let PacketType::Goodbye = rtcp_packet.header().packet_type {
+                            if let Some(report) =
+                                rtcp_packet.as_any().downcast_ref::<SenderReport>()
+                            {
+                                if let Some(stats) = reception_stats.get_mut(&report.ssrc) {
+                                    stats.on_sender_report(report.ntp_time, now);
+                                }
+                                let track = if Some(report.ssrc) == video_ssrc {
+                                    video_clock_rate.map(|rate| (TrackKind::Video, rate))
+                                } else if Some(report.ssrc) == audio_ssrc {
+                                    audio_clock_rate.map(|rate| (TrackKind::Audio, rate))
+                                } else {
+                                    None
+                                };
+                                if let Some((kind, clock_rate)) = track {
+                                    av_sync.on_sender_report(
+                                        kind,
+                                        report.ntp_time,
+                                        report.rtp_time,
+                                        clock_rate,
+                                    );
+                                }
+                            } else if let PacketType::Goodbye = rtcp_packet.header().packet_type {
                                 for ssrc in rtcp_packet.destination_ssrc() {
+                                    if let Some(jitter_buffer) = jitter_buffers.get_mut(&ssrc) {
+                                        depayload(jitter_buffer.flush(), &mut av_sync);
+                                    }
                                     if Some(ssrc) == audio_ssrc {
                                         maybe_send_audio_eos()
                                     }
@@ -269,15 +408,39 @@ fn run_depayloader_thread(
                     Err(err) => {
                         warn!(%err, "Received an unexpected packet, which is not recognized either as RTP or RTCP. Dropping.");
                     }
-                }
-                continue;
+                },
             }
-        };
+        }
+
+        for jitter_buffer in jitter_buffers.values_mut() {
+            depayload(jitter_buffer.pop_packets(now), &mut av_sync);
+        }
+        rtcp_feedback.send(&mut jitter_buffers, video_ssrc, now);
+        if opts.jitter_buffer.rtcp_feedback {
+            rtcp_feedback.send_receiver_report(&mut reception_stats, now);
+        }
     }
     maybe_send_audio_eos();
     maybe_send_video_eos();
 }
 
+fn update_reception_metrics(
+    metrics: &InputMetrics,
+    reception_stats: &HashMap<u32, ReceptionStats>,
+) {
+    let packets_lost = reception_stats
+        .values()
+        .map(ReceptionStats::total_lost)
+        .sum();
+    let jitter = reception_stats
+        .values()
+        .map(ReceptionStats::jitter)
+        .max()
+        .unwrap_or_default();
+    metrics.packets_lost.set(packets_lost);
+    metrics.jitter_micros.set(jitter.as_micros() as u64);
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum DepayloadingError {
     #[error("Bad payload type {0}")]
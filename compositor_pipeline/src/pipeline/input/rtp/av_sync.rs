@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use tracing::debug;
+
+use crate::pipeline::rtp::ntp_timestamp_to_secs;
+
+/// Corrections smaller than that are ignored, so small inaccuracies of sender
+/// reports do not cause constant discontinuities of the audio stream.
+const MIN_CORRECTION_CHANGE: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TrackKind {
+    Video,
+    Audio,
+}
+
+/// Synchronizes audio and video tracks of an RTP input based on RTCP sender reports.
+///
+/// The queue aligns the first chunks of both tracks, so without sender reports
+/// audio and video are synchronized by the arrival of the first packet. After
+/// sender reports for both tracks are received, the mapping of RTP timestamps to
+/// the sender's wall clock is known, and audio PTS is shifted so that samples
+/// captured at the same time as a video frame are played together with that frame.
+#[derive(Debug, Default)]
+pub(super) struct AvSync {
+    video: TrackSync,
+    audio: TrackSync,
+    /// Offset in seconds added to PTS of audio chunks.
+    audio_correction: f64,
+}
+
+#[derive(Debug, Default)]
+struct TrackSync {
+    first_pts: Option<Duration>,
+    /// RTP timestamp and PTS of the last depayloaded chunk.
+    last_chunk: Option<(u32, Duration)>,
+    /// NTP time (in seconds) from the last sender report and PTS corresponding
+    /// to its RTP timestamp.
+    sender_report: Option<(f64, Duration)>,
+}
+
+impl AvSync {
+    /// Registers a chunk before the correction is applied.
+    pub(super) fn on_chunk(&mut self, kind: TrackKind, rtp_timestamp: u32, pts: Duration) {
+        let track = self.track_mut(kind);
+        track.first_pts.get_or_insert(pts);
+        track.last_chunk = Some((rtp_timestamp, pts));
+    }
+
+    pub(super) fn on_sender_report(
+        &mut self,
+        kind: TrackKind,
+        ntp_time: u64,
+        rtp_time: u32,
+        clock_rate: u32,
+    ) {
+        let track = self.track_mut(kind);
+        // RTP timestamps are extended to PTS based on the last chunk, sender report
+        // is always close to the media stream.
+        let Some((last_timestamp, last_pts)) = track.last_chunk else {
+            return;
+        };
+        let offset = rtp_time.wrapping_sub(last_timestamp) as i32 as f64 / clock_rate as f64;
+        let report_pts = Duration::from_secs_f64((last_pts.as_secs_f64() + offset).max(0.0));
+        track.sender_report = Some((ntp_timestamp_to_secs(ntp_time), report_pts));
+
+        self.update_correction();
+    }
+
+    pub(super) fn correct_audio_pts(&self, pts: Duration) -> Duration {
+        match self.audio_correction >= 0.0 {
+            true => pts + Duration::from_secs_f64(self.audio_correction),
+            false => pts.saturating_sub(Duration::from_secs_f64(-self.audio_correction)),
+        }
+    }
+
+    fn update_correction(&mut self) {
+        let (
+            Some(video_first_pts),
+            Some((video_ntp, video_report_pts)),
+            Some(audio_first_pts),
+            Some((audio_ntp, audio_report_pts)),
+        ) = (
+            self.video.first_pts,
+            self.video.sender_report,
+            self.audio.first_pts,
+            self.audio.sender_report,
+        )
+        else {
+            return;
+        };
+
+        let correction = (audio_first_pts.as_secs_f64() - video_first_pts.as_secs_f64())
+            + (audio_ntp - video_ntp)
+            - (audio_report_pts.as_secs_f64() - video_report_pts.as_secs_f64());
+        if (correction - self.audio_correction).abs() >= MIN_CORRECTION_CHANGE {
+            debug!(correction, "Updated audio-video synchronization.");
+            self.audio_correction = correction;
+        }
+    }
+
+    fn track_mut(&mut self, kind: TrackKind) -> &mut TrackSync {
+        match kind {
+            TrackKind::Video => &mut self.video,
+            TrackKind::Audio => &mut self.audio,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use crate::pipeline::rtp::ntp_timestamp;
+
+    use super::*;
+
+    fn ntp(secs: f64) -> u64 {
+        ntp_timestamp(UNIX_EPOCH + Duration::from_secs_f64(secs))
+    }
+
+    #[test]
+    fn aligns_audio_to_video() {
+        let mut sync = AvSync::default();
+        sync.on_chunk(TrackKind::Video, 900_000, Duration::from_secs(10));
+        sync.on_chunk(TrackKind::Audio, 240_000, Duration::from_secs(5));
+        assert_eq!(
+            sync.correct_audio_pts(Duration::from_secs(6)),
+            Duration::from_secs(6)
+        );
+
+        // the first audio chunk was captured 200ms after the first video frame,
+        // so it needs to be played 200ms later
+        sync.on_sender_report(TrackKind::Video, ntp(100.0), 900_000, 90_000);
+        sync.on_sender_report(TrackKind::Audio, ntp(100.2), 240_000, 48_000);
+
+        let corrected = sync.correct_audio_pts(Duration::from_secs(6));
+        assert!(
+            corrected.abs_diff(Duration::from_millis(6200)) < Duration::from_millis(1),
+            "{corrected:?}"
+        );
+    }
+
+    #[test]
+    fn ignores_small_changes() {
+        let mut sync = AvSync::default();
+        sync.on_chunk(TrackKind::Video, 0, Duration::ZERO);
+        sync.on_chunk(TrackKind::Audio, 0, Duration::ZERO);
+        sync.on_sender_report(TrackKind::Video, ntp(100.0), 0, 90_000);
+        sync.on_sender_report(TrackKind::Audio, ntp(100.005), 0, 48_000);
+
+        assert_eq!(
+            sync.correct_audio_pts(Duration::from_secs(1)),
+            Duration::from_secs(1)
+        );
+    }
+}
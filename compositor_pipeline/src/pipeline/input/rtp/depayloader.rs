@@ -70,6 +70,15 @@ impl Depayloader {
             other => Err(DepayloadingError::BadPayloadType(other)),
         }
     }
+
+    /// Clock rate of RTP timestamps for the payload type.
+    pub fn clock_rate(&self, payload_type: u8) -> Option<u32> {
+        match payload_type {
+            VIDEO_PAYLOAD_TYPE => self.video.as_ref().map(VideoDepayloader::clock_rate),
+            AUDIO_PAYLOAD_TYPE => self.audio.as_ref().map(AudioDepayloader::clock_rate),
+            _ => None,
+        }
+    }
 }
 
 pub enum VideoDepayloader {
@@ -98,6 +107,12 @@ impl VideoDepayloader {
         }
     }
 
+    fn clock_rate(&self) -> u32 {
+        match self {
+            VideoDepayloader::H264 { .. } => 90000,
+        }
+    }
+
     fn depayload(
         &mut self,
         packet: rtp::packet::Packet,
@@ -174,6 +189,13 @@ impl AudioDepayloader {
         }
     }
 
+    fn clock_rate(&self) -> u32 {
+        match self {
+            AudioDepayloader::Opus { .. } => 48000,
+            AudioDepayloader::Aac(aac) => aac.clock_rate(),
+        }
+    }
+
     fn depayload(
         &mut self,
         packet: rtp::packet::Packet,
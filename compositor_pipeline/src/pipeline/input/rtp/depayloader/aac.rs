@@ -48,6 +48,12 @@ pub struct AacDepayloader {
     rollover_state: RolloverState,
 }
 
+impl AacDepayloader {
+    pub(super) fn clock_rate(&self) -> u32 {
+        self.asc.frequency
+    }
+}
+
 /// MPEG-4 part 3, 1.6.3.4
 fn freq_id_to_freq(id: u8) -> Result<u32, AudioSpecificConfigParseError> {
     match id {
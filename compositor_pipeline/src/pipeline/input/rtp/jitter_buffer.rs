@@ -0,0 +1,401 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    mem,
+    time::{Duration, Instant},
+};
+
+use bytes::Buf;
+use tracing::{debug, trace};
+
+/// Sequence number gaps larger than this are treated as a discontinuity of the
+/// stream (e.g. sender restart) and not as a packet loss.
+const MAX_SEQUENCE_NUMBER_GAP: u64 = 1000;
+
+/// Minimal interval between NACKs sent for the same packet.
+const NACK_RETRY_INTERVAL: Duration = Duration::from_millis(30);
+
+const MAX_NACKS_PER_PACKET: u32 = 3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RtpJitterBufferOptions {
+    /// Maximal duration for which packets are held while waiting for a missing
+    /// packet. Packets that arrive in order are never delayed.
+    pub latency: Duration,
+    /// Send RTCP receiver reports, NACKs for missing packets and PLI/FIR after
+    /// a loss that can't be recovered.
+    pub rtcp_feedback: bool,
+}
+
+impl Default for RtpJitterBufferOptions {
+    fn default() -> Self {
+        Self {
+            latency: Duration::from_millis(100),
+            rtcp_feedback: true,
+        }
+    }
+}
+
+/// Reorders RTP packets of a single SSRC based on sequence numbers and tracks
+/// missing packets.
+#[derive(Debug)]
+pub(super) struct RtpJitterBuffer {
+    opts: RtpJitterBufferOptions,
+
+    /// Packets waiting for a missing packet, keyed by extended sequence number.
+    packets: BTreeMap<u64, rtp::packet::Packet>,
+    missing: BTreeMap<u64, MissingPacket>,
+    /// Packets that can be returned without waiting, e.g. after discontinuity.
+    ready: VecDeque<rtp::packet::Packet>,
+
+    /// Extended sequence number of the next packet that should be returned.
+    next_sequence_number: Option<u64>,
+    highest_sequence_number: Option<u64>,
+
+    keyframe_needed: bool,
+}
+
+#[derive(Debug)]
+struct MissingPacket {
+    detected_at: Instant,
+    last_nack: Option<Instant>,
+    nack_count: u32,
+}
+
+impl RtpJitterBuffer {
+    pub(super) fn new(opts: RtpJitterBufferOptions) -> Self {
+        Self {
+            opts,
+            packets: BTreeMap::new(),
+            missing: BTreeMap::new(),
+            ready: VecDeque::new(),
+            next_sequence_number: None,
+            highest_sequence_number: None,
+            keyframe_needed: false,
+        }
+    }
+
+    pub(super) fn push(&mut self, packet: rtp::packet::Packet, now: Instant) {
+        let sequence_number = self.extended_sequence_number(packet.header.sequence_number);
+        let (Some(next), Some(highest)) = (self.next_sequence_number, self.highest_sequence_number)
+        else {
+            self.reset(sequence_number, packet);
+            return;
+        };
+
+        if sequence_number + MAX_SEQUENCE_NUMBER_GAP < next
+            || sequence_number > highest + MAX_SEQUENCE_NUMBER_GAP
+        {
+            debug!("Discontinuity in RTP sequence numbers, resetting jitter buffer.");
+            self.ready
+                .extend(mem::take(&mut self.packets).into_values());
+            self.reset(sequence_number, packet);
+            return;
+        }
+
+        if sequence_number < next {
+            trace!(
+                sequence_number = packet.header.sequence_number,
+                "Dropping duplicated or late RTP packet."
+            );
+            return;
+        }
+
+        if sequence_number > highest {
+            for missing in (highest + 1)..sequence_number {
+                self.missing.insert(
+                    missing,
+                    MissingPacket {
+                        detected_at: now,
+                        last_nack: None,
+                        nack_count: 0,
+                    },
+                );
+            }
+            self.highest_sequence_number = Some(sequence_number);
+        }
+        self.missing.remove(&sequence_number);
+        self.packets.entry(sequence_number).or_insert(packet);
+    }
+
+    /// Returns packets in order. Missing packets are skipped after waiting
+    /// for them for `latency`.
+    pub(super) fn pop_packets(&mut self, now: Instant) -> Vec<rtp::packet::Packet> {
+        let mut packets: Vec<_> = self.ready.drain(..).collect();
+        while let Some(next) = self.next_sequence_number {
+            if let Some(packet) = self.packets.remove(&next) {
+                packets.push(packet);
+                self.next_sequence_number = Some(next + 1);
+                continue;
+            }
+
+            let Some(&first_buffered) = self.packets.keys().next() else {
+                break;
+            };
+            let waiting_since = self
+                .missing
+                .get(&next)
+                .map(|missing| missing.detected_at)
+                .unwrap_or(now);
+            if now.saturating_duration_since(waiting_since) < self.opts.latency {
+                break;
+            }
+
+            debug!(
+                lost_packets = first_buffered - next,
+                "Missing RTP packets were not recovered."
+            );
+            self.missing = self.missing.split_off(&first_buffered);
+            self.next_sequence_number = Some(first_buffered);
+            self.keyframe_needed = true;
+        }
+        packets
+    }
+
+    /// Returns all buffered packets without waiting for the missing ones.
+    pub(super) fn flush(&mut self) -> Vec<rtp::packet::Packet> {
+        let mut packets: Vec<_> = self.ready.drain(..).collect();
+        packets.extend(mem::take(&mut self.packets).into_values());
+        self.missing.clear();
+        self.next_sequence_number = self.highest_sequence_number.map(|highest| highest + 1);
+        packets
+    }
+
+    /// Sequence numbers of missing packets that should be requested with NACK.
+    pub(super) fn nacks(&mut self, now: Instant) -> Vec<u16> {
+        if !self.opts.rtcp_feedback {
+            return Vec::new();
+        }
+        self.missing
+            .iter_mut()
+            .filter(|(_, missing)| {
+                missing.nack_count < MAX_NACKS_PER_PACKET
+                    && missing.last_nack.map_or(true, |last_nack| {
+                        now.saturating_duration_since(last_nack) >= NACK_RETRY_INTERVAL
+                    })
+            })
+            .map(|(sequence_number, missing)| {
+                missing.last_nack = Some(now);
+                missing.nack_count += 1;
+                *sequence_number as u16
+            })
+            .collect()
+    }
+
+    /// Returns true if some packets were skipped since the last call, so
+    /// the decoder needs a new keyframe.
+    pub(super) fn take_keyframe_request(&mut self) -> bool {
+        mem::take(&mut self.keyframe_needed) && self.opts.rtcp_feedback
+    }
+
+    fn reset(&mut self, sequence_number: u64, packet: rtp::packet::Packet) {
+        self.missing.clear();
+        self.next_sequence_number = Some(sequence_number);
+        self.highest_sequence_number = Some(sequence_number);
+        self.packets.insert(sequence_number, packet);
+    }
+
+    /// Maps 16-bit sequence number to a value that does not roll over, choosing
+    /// the one closest to the highest sequence number received so far.
+    fn extended_sequence_number(&self, sequence_number: u16) -> u64 {
+        match self.highest_sequence_number {
+            Some(highest) => {
+                let diff = sequence_number.wrapping_sub(highest as u16) as i16;
+                highest.saturating_add_signed(diff as i64)
+            }
+            // start from the second cycle, so packets received out of order
+            // before the first one do not underflow
+            None => (1 << 16) + sequence_number as u64,
+        }
+    }
+}
+
+/// Converts a retransmission packet ([RFC 4588](https://datatracker.ietf.org/doc/html/rfc4588#section-4))
+/// into the original packet. Returns `None` if the packet is malformed.
+pub(super) fn unwrap_rtx_packet(
+    mut packet: rtp::packet::Packet,
+    payload_type: u8,
+    ssrc: u32,
+) -> Option<rtp::packet::Packet> {
+    if packet.payload.len() < 2 {
+        return None;
+    }
+    packet.header.sequence_number = packet.payload.get_u16();
+    packet.header.payload_type = payload_type;
+    packet.header.ssrc = ssrc;
+    Some(packet)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::{BufMut, Bytes, BytesMut};
+
+    use super::*;
+
+    const LATENCY: Duration = Duration::from_millis(100);
+
+    fn jitter_buffer() -> RtpJitterBuffer {
+        RtpJitterBuffer::new(RtpJitterBufferOptions {
+            latency: LATENCY,
+            rtcp_feedback: true,
+        })
+    }
+
+    fn packet(sequence_number: u16) -> rtp::packet::Packet {
+        rtp::packet::Packet {
+            header: rtp::header::Header {
+                sequence_number,
+                payload_type: 96,
+                ssrc: 1,
+                ..Default::default()
+            },
+            payload: Bytes::from_static(&[0, 1, 2]),
+        }
+    }
+
+    fn sequence_numbers(packets: &[rtp::packet::Packet]) -> Vec<u16> {
+        packets.iter().map(|p| p.header.sequence_number).collect()
+    }
+
+    /// Pushes packets in the provided order and returns packets released by the jitter buffer.
+    fn run(jitter_buffer: &mut RtpJitterBuffer, order: &[u16], now: Instant) -> Vec<u16> {
+        let mut output = vec![];
+        for sequence_number in order {
+            jitter_buffer.push(packet(*sequence_number), now);
+            output.extend(sequence_numbers(&jitter_buffer.pop_packets(now)));
+        }
+        output
+    }
+
+    #[test]
+    fn in_order() {
+        let mut jitter_buffer = jitter_buffer();
+        let now = Instant::now();
+
+        assert_eq!(
+            run(&mut jitter_buffer, &[10, 11, 12, 13], now),
+            [10, 11, 12, 13]
+        );
+        assert!(jitter_buffer.nacks(now).is_empty());
+        assert!(!jitter_buffer.take_keyframe_request());
+    }
+
+    #[test]
+    fn reorder() {
+        let mut jitter_buffer = jitter_buffer();
+        let now = Instant::now();
+
+        assert_eq!(run(&mut jitter_buffer, &[1, 3, 4], now), [1]);
+        assert_eq!(jitter_buffer.nacks(now), [2]);
+        assert_eq!(run(&mut jitter_buffer, &[2, 5], now), [2, 3, 4, 5]);
+        assert!(jitter_buffer.nacks(now + LATENCY).is_empty());
+        assert!(!jitter_buffer.take_keyframe_request());
+    }
+
+    #[test]
+    fn sequence_number_rollover() {
+        let mut jitter_buffer = jitter_buffer();
+        let now = Instant::now();
+
+        assert_eq!(
+            run(&mut jitter_buffer, &[65534, 0, 65535, 1], now),
+            [65534, 65535, 0, 1]
+        );
+    }
+
+    #[test]
+    fn duplicated_and_late_packets() {
+        let mut jitter_buffer = jitter_buffer();
+        let now = Instant::now();
+
+        assert_eq!(run(&mut jitter_buffer, &[5, 6, 6, 4, 7], now), [5, 6, 7]);
+    }
+
+    #[test]
+    fn nack_retries() {
+        let mut jitter_buffer = jitter_buffer();
+        let start = Instant::now();
+
+        run(&mut jitter_buffer, &[1, 4], start);
+        assert_eq!(jitter_buffer.nacks(start), [2, 3]);
+        assert!(jitter_buffer
+            .nacks(start + Duration::from_millis(10))
+            .is_empty());
+        assert_eq!(jitter_buffer.nacks(start + NACK_RETRY_INTERVAL), [2, 3]);
+        assert_eq!(jitter_buffer.nacks(start + 2 * NACK_RETRY_INTERVAL), [2, 3]);
+        assert!(jitter_buffer
+            .nacks(start + 3 * NACK_RETRY_INTERVAL)
+            .is_empty());
+    }
+
+    #[test]
+    fn unrecoverable_loss() {
+        let mut jitter_buffer = jitter_buffer();
+        let start = Instant::now();
+
+        assert_eq!(run(&mut jitter_buffer, &[1, 2, 5, 6], start), [1, 2]);
+        assert!(jitter_buffer
+            .pop_packets(start + Duration::from_millis(50))
+            .is_empty());
+
+        let packets = jitter_buffer.pop_packets(start + LATENCY);
+        assert_eq!(sequence_numbers(&packets), [5, 6]);
+        assert!(jitter_buffer.take_keyframe_request());
+        assert!(!jitter_buffer.take_keyframe_request());
+        assert!(jitter_buffer.nacks(start + LATENCY).is_empty());
+
+        // packet arrived after it was skipped
+        assert!(run(&mut jitter_buffer, &[3], start + LATENCY).is_empty());
+    }
+
+    #[test]
+    fn discontinuity() {
+        let mut jitter_buffer = jitter_buffer();
+        let now = Instant::now();
+
+        assert_eq!(run(&mut jitter_buffer, &[100, 102], now), [100]);
+        assert_eq!(
+            run(&mut jitter_buffer, &[20000, 20001], now),
+            [102, 20000, 20001]
+        );
+        assert!(jitter_buffer.nacks(now).is_empty());
+    }
+
+    #[test]
+    fn flush() {
+        let mut jitter_buffer = jitter_buffer();
+        let now = Instant::now();
+
+        assert_eq!(run(&mut jitter_buffer, &[1, 3, 5], now), [1]);
+        assert_eq!(sequence_numbers(&jitter_buffer.flush()), [3, 5]);
+        assert_eq!(run(&mut jitter_buffer, &[6], now), [6]);
+    }
+
+    #[test]
+    fn retransmission() {
+        let mut jitter_buffer = jitter_buffer();
+        let now = Instant::now();
+
+        assert_eq!(run(&mut jitter_buffer, &[1, 3], now), [1]);
+
+        let mut payload = BytesMut::new();
+        payload.put_u16(2);
+        payload.put_slice(&[0, 1, 2]);
+        let rtx_packet = rtp::packet::Packet {
+            header: rtp::header::Header {
+                sequence_number: 500,
+                payload_type: 98,
+                ssrc: 2,
+                ..Default::default()
+            },
+            payload: payload.freeze(),
+        };
+        let packet = unwrap_rtx_packet(rtx_packet, 96, 1).unwrap();
+        assert_eq!(packet.header.payload_type, 96);
+        assert_eq!(packet.header.ssrc, 1);
+        assert_eq!(packet.payload, Bytes::from_static(&[0, 1, 2]));
+
+        jitter_buffer.push(packet, now);
+        assert_eq!(sequence_numbers(&jitter_buffer.pop_packets(now)), [2, 3]);
+        assert!(!jitter_buffer.take_keyframe_request());
+    }
+}
@@ -0,0 +1,210 @@
+use std::time::{Duration, Instant};
+
+use rtcp::reception_report::ReceptionReport;
+
+/// Cycle count added to the first sequence number, it allows packets from before
+/// the first received one without going below zero.
+const SEQUENCE_NUMBER_BASE: u64 = 1 << 16;
+
+/// Maximal value of cumulative number of packets lost (24-bit signed field).
+const MAX_TOTAL_LOST: u32 = 0x7F_FFFF;
+
+/// Reception statistics of a single SSRC used to generate RTCP reception reports
+/// ([RFC 3550, section 6.4.1](https://datatracker.ietf.org/doc/html/rfc3550#section-6.4.1)).
+#[derive(Debug)]
+pub(super) struct ReceptionStats {
+    clock_rate: u32,
+    /// Reference for arrival times expressed in RTP timestamp units.
+    start: Instant,
+
+    base_sequence_number: u64,
+    highest_sequence_number: u64,
+    received: u64,
+    expected_prior: u64,
+    received_prior: u64,
+
+    /// Interarrival jitter in RTP timestamp units.
+    jitter: f64,
+    last_transit: Option<u32>,
+
+    /// Middle 32 bits of NTP timestamp from the last sender report and the time
+    /// it was received.
+    last_sender_report: Option<(u32, Instant)>,
+}
+
+impl ReceptionStats {
+    pub(super) fn new(clock_rate: u32, header: &rtp::header::Header, now: Instant) -> Self {
+        let sequence_number = SEQUENCE_NUMBER_BASE + header.sequence_number as u64;
+        let mut stats = Self {
+            clock_rate,
+            start: now,
+            base_sequence_number: sequence_number,
+            highest_sequence_number: sequence_number,
+            received: 0,
+            expected_prior: 0,
+            received_prior: 0,
+            jitter: 0.0,
+            last_transit: None,
+            last_sender_report: None,
+        };
+        stats.on_packet_with_sequence_number(sequence_number, header.timestamp, now);
+        stats
+    }
+
+    pub(super) fn on_packet(&mut self, header: &rtp::header::Header, now: Instant) {
+        let offset = header
+            .sequence_number
+            .wrapping_sub(self.highest_sequence_number as u16) as i16;
+        let sequence_number = self
+            .highest_sequence_number
+            .saturating_add_signed(offset as i64);
+        self.on_packet_with_sequence_number(sequence_number, header.timestamp, now);
+    }
+
+    fn on_packet_with_sequence_number(
+        &mut self,
+        sequence_number: u64,
+        timestamp: u32,
+        now: Instant,
+    ) {
+        self.received += 1;
+        self.highest_sequence_number = u64::max(self.highest_sequence_number, sequence_number);
+        self.base_sequence_number = u64::min(self.base_sequence_number, sequence_number);
+
+        let arrival =
+            (now.duration_since(self.start).as_secs_f64() * self.clock_rate as f64) as u64;
+        let transit = (arrival as u32).wrapping_sub(timestamp);
+        if let Some(last_transit) = self.last_transit {
+            let diff = (transit.wrapping_sub(last_transit) as i32).unsigned_abs() as f64;
+            self.jitter += (diff - self.jitter) / 16.0;
+        }
+        self.last_transit = Some(transit);
+    }
+
+    pub(super) fn on_sender_report(&mut self, ntp_time: u64, now: Instant) {
+        self.last_sender_report = Some(((ntp_time >> 16) as u32, now));
+    }
+
+    fn expected(&self) -> u64 {
+        self.highest_sequence_number - self.base_sequence_number + 1
+    }
+
+    /// Number of packets lost since the beginning of reception.
+    pub(super) fn total_lost(&self) -> u64 {
+        self.expected().saturating_sub(self.received)
+    }
+
+    pub(super) fn jitter(&self) -> Duration {
+        Duration::from_secs_f64(self.jitter / self.clock_rate as f64)
+    }
+
+    /// Returns reception report block for the interval since the previous call.
+    pub(super) fn report(&mut self, ssrc: u32, now: Instant) -> ReceptionReport {
+        let expected = self.expected();
+        let total_lost = self.total_lost();
+
+        let expected_interval = expected - self.expected_prior;
+        let received_interval = self.received - self.received_prior;
+        self.expected_prior = expected;
+        self.received_prior = self.received;
+        let lost_interval = expected_interval.saturating_sub(received_interval);
+        let fraction_lost = match expected_interval {
+            0 => 0,
+            _ => ((lost_interval << 8) / expected_interval).min(u8::MAX as u64) as u8,
+        };
+
+        let (last_sender_report, delay) = match self.last_sender_report {
+            Some((ntp_middle_bits, received_at)) => {
+                let delay = now.saturating_duration_since(received_at).as_secs_f64() * 65536.0;
+                (ntp_middle_bits, delay as u32)
+            }
+            None => (0, 0),
+        };
+
+        ReceptionReport {
+            ssrc,
+            fraction_lost,
+            total_lost: u32::min(total_lost as u32, MAX_TOTAL_LOST),
+            last_sequence_number: (self.highest_sequence_number - SEQUENCE_NUMBER_BASE) as u32,
+            jitter: self.jitter as u32,
+            last_sender_report,
+            delay,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn header(sequence_number: u16, timestamp: u32) -> rtp::header::Header {
+        rtp::header::Header {
+            sequence_number,
+            timestamp,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn counts_lost_packets() {
+        let start = Instant::now();
+        let mut stats = ReceptionStats::new(90000, &header(65530, 0), start);
+        for sequence_number in [65531, 65533, 65534, 0, 1, 5] {
+            stats.on_packet(&header(sequence_number, 0), start);
+        }
+
+        assert_eq!(stats.total_lost(), 5);
+        let report = stats.report(1, start);
+        // 65530..=5 is 12 packets, 7 received
+        assert_eq!(report.total_lost, 5);
+        assert_eq!(report.fraction_lost, (5 << 8) / 12);
+        assert_eq!(report.last_sequence_number, (1 << 16) + 5);
+
+        stats.on_packet(&header(6, 0), start);
+        let report = stats.report(1, start);
+        assert_eq!(report.total_lost, 5);
+        assert_eq!(report.fraction_lost, 0);
+    }
+
+    #[test]
+    fn reordered_packets_are_not_lost() {
+        let start = Instant::now();
+        let mut stats = ReceptionStats::new(90000, &header(10, 0), start);
+        for sequence_number in [12, 11, 9, 13] {
+            stats.on_packet(&header(sequence_number, 0), start);
+        }
+
+        let report = stats.report(1, start);
+        assert_eq!(report.total_lost, 0);
+        assert_eq!(report.last_sequence_number, 13);
+    }
+
+    #[test]
+    fn jitter() {
+        let start = Instant::now();
+        let mut stats = ReceptionStats::new(90000, &header(0, 0), start);
+        // packets are sent every 20ms, but every other packet arrives 20ms late
+        for i in 1..200u16 {
+            let offset = if i % 2 == 0 { 0 } else { 20 };
+            let arrival = start + Duration::from_millis(20 * i as u64 + offset);
+            stats.on_packet(&header(i, 1800 * i as u32), arrival);
+        }
+
+        // transit difference is 20ms (1800 in 90kHz clock)
+        let jitter = stats.report(1, start).jitter;
+        assert!((1750..=1800).contains(&jitter), "{jitter}");
+    }
+
+    #[test]
+    fn last_sender_report_delay() {
+        let start = Instant::now();
+        let mut stats = ReceptionStats::new(48000, &header(0, 0), start);
+        stats.on_sender_report(0x0001_2345_6789_0000, start);
+
+        let report = stats.report(1, start + Duration::from_millis(500));
+        assert_eq!(report.last_sender_report, 0x2345_6789);
+        assert_eq!(report.delay, 32768);
+    }
+}
@@ -0,0 +1,307 @@
+use std::{
+    collections::HashMap,
+    io::Write,
+    net::{SocketAddr, TcpStream, UdpSocket},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use compositor_render::InputId;
+use crossbeam_channel::{bounded, Receiver, Sender, TrySendError};
+use rand::Rng;
+use rtcp::{
+    payload_feedbacks::{
+        full_intra_request::{FirEntry, FullIntraRequest},
+        picture_loss_indication::PictureLossIndication,
+    },
+    receiver_report::ReceiverReport,
+    transport_feedbacks::transport_layer_nack::{
+        nack_pairs_from_sequence_numbers, TransportLayerNack,
+    },
+};
+use tracing::{debug, warn};
+
+use super::{jitter_buffer::RtpJitterBuffer, reception_stats::ReceptionStats};
+
+/// Minimal interval between keyframe requests, the sender needs some time
+/// to produce a new keyframe.
+const KEYFRAME_REQUEST_INTERVAL: Duration = Duration::from_millis(500);
+
+const RECEIVER_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Max number of RTCP packets waiting to be written to the TCP connection.
+/// When the client does not read them, new packets are dropped.
+const TCP_FEEDBACK_QUEUE_SIZE: usize = 32;
+
+/// Write timeout of the TCP connection used to send RTCP feedback, it only
+/// bounds how long the writer thread can be stuck on a client that does not read.
+pub(super) const TCP_FEEDBACK_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Sends RTCP packets back to the sender of the RTP stream.
+#[derive(Debug, Clone)]
+pub(super) enum RtcpFeedbackSender {
+    Udp {
+        socket: Arc<UdpSocket>,
+        /// Address from which the last packet was received.
+        peer: Arc<Mutex<Option<SocketAddr>>>,
+    },
+    Tcp {
+        /// Framed packets written to the connected client on a separate thread,
+        /// so a client that does not read them can't block the receiver.
+        packets: Sender<Bytes>,
+    },
+}
+
+impl RtcpFeedbackSender {
+    /// `stream` is the currently connected client, it is set when a client connects.
+    pub(super) fn new_tcp(input_id: &InputId, stream: Arc<Mutex<Option<TcpStream>>>) -> Self {
+        let (packets_tx, packets_rx) = bounded(TCP_FEEDBACK_QUEUE_SIZE);
+        thread::Builder::new()
+            .name(format!("RTCP TCP feedback sender {}", input_id))
+            .spawn(move || {
+                run_tcp_feedback_thread(packets_rx, stream);
+                debug!("Closing RTCP feedback thread (TCP server).");
+            })
+            .unwrap();
+        Self::Tcp {
+            packets: packets_tx,
+        }
+    }
+
+    fn send(&self, packets: &[Box<dyn rtcp::packet::Packet + Send + Sync>]) {
+        let data = match rtcp::packet::marshal(packets) {
+            Ok(data) => data,
+            Err(err) => {
+                warn!(%err, "Failed to serialize RTCP feedback packets.");
+                return;
+            }
+        };
+
+        match self {
+            RtcpFeedbackSender::Udp { socket, peer } => {
+                let Some(peer) = *peer.lock().unwrap() else {
+                    return;
+                };
+                if let Err(err) = socket.send_to(&data, peer) {
+                    debug!(%err, "Failed to send RTCP feedback.");
+                }
+            }
+            RtcpFeedbackSender::Tcp { packets } => {
+                // RFC 4571 framing, the same as for received packets
+                let mut framed = BytesMut::with_capacity(data.len() + 2);
+                framed.put_u16(data.len() as u16);
+                framed.put(data);
+                match packets.try_send(framed.freeze()) {
+                    Ok(()) | Err(TrySendError::Disconnected(_)) => {}
+                    Err(TrySendError::Full(_)) => {
+                        debug!("Client is not reading RTCP feedback, dropping packet.")
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn run_tcp_feedback_thread(packets: Receiver<Bytes>, stream: Arc<Mutex<Option<TcpStream>>>) {
+    for packet in packets {
+        let mut stream = stream.lock().unwrap();
+        let Some(socket) = stream.as_mut() else {
+            continue;
+        };
+        if let Err(err) = socket.write_all(&packet) {
+            // Packet might be partially written, so the framing of the following
+            // packets would be broken. Feedback is disabled for this connection.
+            debug!(%err, "Failed to send RTCP feedback.");
+            *stream = None;
+        }
+    }
+}
+
+/// Generates NACKs and keyframe requests (PLI and FIR) based on the state
+/// of jitter buffers and periodic receiver reports.
+pub(super) struct RtcpFeedback {
+    sender: RtcpFeedbackSender,
+    sender_ssrc: u32,
+    /// Keyframe is needed, but request was not sent yet.
+    keyframe_needed: bool,
+    last_keyframe_request: Option<Instant>,
+    fir_sequence_number: u8,
+    last_receiver_report: Option<Instant>,
+}
+
+impl RtcpFeedback {
+    pub(super) fn new(sender: RtcpFeedbackSender) -> Self {
+        Self {
+            sender,
+            sender_ssrc: rand::thread_rng().gen::<u32>(),
+            keyframe_needed: false,
+            last_keyframe_request: None,
+            fir_sequence_number: 0,
+            last_receiver_report: None,
+        }
+    }
+
+    pub(super) fn send(
+        &mut self,
+        jitter_buffers: &mut HashMap<u32, RtpJitterBuffer>,
+        video_ssrc: Option<u32>,
+        now: Instant,
+    ) {
+        let mut packets: Vec<Box<dyn rtcp::packet::Packet + Send + Sync>> = Vec::new();
+
+        for (ssrc, jitter_buffer) in jitter_buffers.iter_mut() {
+            let nacks = jitter_buffer.nacks(now);
+            if !nacks.is_empty() {
+                packets.push(Box::new(TransportLayerNack {
+                    sender_ssrc: self.sender_ssrc,
+                    media_ssrc: *ssrc,
+                    nacks: nack_pairs_from_sequence_numbers(&nacks),
+                }));
+            }
+            // keyframes are only meaningful for video
+            if jitter_buffer.take_keyframe_request() && Some(*ssrc) == video_ssrc {
+                self.keyframe_needed = true;
+            }
+        }
+
+        let can_request_keyframe = self.last_keyframe_request.map_or(true, |last| {
+            now.saturating_duration_since(last) >= KEYFRAME_REQUEST_INTERVAL
+        });
+        if let (true, true, Some(video_ssrc)) =
+            (self.keyframe_needed, can_request_keyframe, video_ssrc)
+        {
+            debug!(ssrc = video_ssrc, "Requesting keyframe.");
+            self.keyframe_needed = false;
+            self.last_keyframe_request = Some(now);
+            self.fir_sequence_number = self.fir_sequence_number.wrapping_add(1);
+            packets.push(Box::new(PictureLossIndication {
+                sender_ssrc: self.sender_ssrc,
+                media_ssrc: video_ssrc,
+            }));
+            // Some senders support only one of those, so both are sent.
+            packets.push(Box::new(FullIntraRequest {
+                sender_ssrc: self.sender_ssrc,
+                media_ssrc: video_ssrc,
+                fir: vec![FirEntry {
+                    ssrc: video_ssrc,
+                    sequence_number: self.fir_sequence_number,
+                }],
+            }));
+        }
+
+        if !packets.is_empty() {
+            self.sender.send(&packets);
+        }
+    }
+
+    pub(super) fn send_receiver_report(
+        &mut self,
+        reception_stats: &mut HashMap<u32, ReceptionStats>,
+        now: Instant,
+    ) {
+        let is_report_due = self.last_receiver_report.map_or(true, |last| {
+            now.saturating_duration_since(last) >= RECEIVER_REPORT_INTERVAL
+        });
+        if !is_report_due || reception_stats.is_empty() {
+            return;
+        }
+        self.last_receiver_report = Some(now);
+
+        let reports = reception_stats
+            .iter_mut()
+            .map(|(ssrc, stats)| stats.report(*ssrc, now))
+            .collect();
+        self.sender.send(&[Box::new(ReceiverReport {
+            ssrc: self.sender_ssrc,
+            reports,
+            profile_extensions: Bytes::new(),
+        })]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Read,
+        net::{Ipv4Addr, TcpListener},
+    };
+
+    use socket2::SockRef;
+
+    use super::*;
+
+    fn header() -> rtp::header::Header {
+        rtp::header::Header {
+            sequence_number: 1,
+            ssrc: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Returns connected (server, client) pair with small socket buffers, so they
+    /// fill up quickly when the client does not read.
+    fn connected_streams() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        SockRef::from(&server).set_send_buffer_size(4096).unwrap();
+        SockRef::from(&client).set_recv_buffer_size(4096).unwrap();
+        server
+            .set_write_timeout(Some(TCP_FEEDBACK_WRITE_TIMEOUT))
+            .unwrap();
+        (server, client)
+    }
+
+    #[test]
+    fn receiver_reports_are_framed() {
+        let (server, mut client) = connected_streams();
+        let sender = RtcpFeedbackSender::new_tcp(
+            &InputId("input".into()),
+            Arc::new(Mutex::new(Some(server))),
+        );
+        let mut feedback = RtcpFeedback::new(sender);
+
+        let now = Instant::now();
+        let mut stats = HashMap::from([(1, ReceptionStats::new(90000, &header(), now))]);
+        feedback.send_receiver_report(&mut stats, now);
+
+        let mut len = [0; 2];
+        client.read_exact(&mut len).unwrap();
+        let mut packet = vec![0; u16::from_be_bytes(len) as usize];
+        client.read_exact(&mut packet).unwrap();
+        let packets = rtcp::packet::unmarshal(&mut Bytes::from(packet)).unwrap();
+        let report = packets[0]
+            .as_any()
+            .downcast_ref::<ReceiverReport>()
+            .unwrap();
+        assert_eq!(report.reports.len(), 1);
+        assert_eq!(report.reports[0].ssrc, 1);
+    }
+
+    #[test]
+    fn client_that_does_not_read_does_not_block() {
+        let (server, _client) = connected_streams();
+        let sender = RtcpFeedbackSender::new_tcp(
+            &InputId("input".into()),
+            Arc::new(Mutex::new(Some(server))),
+        );
+
+        let (done_tx, done_rx) = bounded(1);
+        thread::spawn(move || {
+            let mut feedback = RtcpFeedback::new(sender);
+            let start = Instant::now();
+            let mut stats = HashMap::from([(1, ReceptionStats::new(90000, &header(), start))]);
+            // Much more data than fits into the socket buffers.
+            for i in 0..20_000 {
+                feedback.send_receiver_report(&mut stats, start + RECEIVER_REPORT_INTERVAL * i);
+            }
+            done_tx.send(()).unwrap();
+        });
+
+        done_rx
+            .recv_timeout(Duration::from_secs(5))
+            .expect("Sending RTCP feedback was blocked by the client.");
+    }
+}
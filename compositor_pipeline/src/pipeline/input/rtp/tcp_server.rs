@@ -2,7 +2,7 @@ use std::{
     collections::VecDeque,
     io::Read,
     net::TcpStream,
-    sync::{atomic::AtomicBool, Arc},
+    sync::{atomic::AtomicBool, Arc, Mutex},
     thread,
     time::Duration,
 };
@@ -13,15 +13,22 @@ use crossbeam_channel::{bounded, Receiver, Sender};
 use log::error;
 use tracing::{debug, info, span, trace, Level};
 
-use crate::pipeline::{rtp::bind_to_requested_port, Port};
+use crate::{
+    event::{Event, EventEmitter},
+    pipeline::{rtp::bind_to_requested_port, Port},
+};
 
-use super::{RtpReceiverError, RtpReceiverOptions};
+use super::{
+    rtcp_feedback::{RtcpFeedbackSender, TCP_FEEDBACK_WRITE_TIMEOUT},
+    RtpReceiverError, RtpReceiverOptions,
+};
 
 pub(super) fn start_tcp_server_thread(
     input_id: &InputId,
     opts: &RtpReceiverOptions,
     should_close: Arc<AtomicBool>,
-) -> Result<(Port, Receiver<bytes::Bytes>), RtpReceiverError> {
+    event_emitter: Arc<EventEmitter>,
+) -> Result<(Port, Receiver<bytes::Bytes>, RtcpFeedbackSender), RtpReceiverError> {
     let (packets_tx, packets_rx) = bounded(1000);
     let input_id = input_id.clone();
     info!(?input_id, "Starting tcp socket");
@@ -38,6 +45,8 @@ pub(super) fn start_tcp_server_thread(
     socket.listen(1).map_err(RtpReceiverError::SocketBind)?;
 
     let socket = std::net::TcpListener::from(socket);
+    let connected_stream = Arc::new(Mutex::new(None));
+    let feedback_sender = RtcpFeedbackSender::new_tcp(&input_id, connected_stream.clone());
 
     thread::Builder::new()
         .name(format!("RTP TCP server receiver {}", input_id))
@@ -48,18 +57,28 @@ pub(super) fn start_tcp_server_thread(
                 input_id = input_id.to_string()
             )
             .entered();
-            run_tcp_server_thread(socket, packets_tx, should_close);
+            run_tcp_server_thread(
+                &input_id,
+                socket,
+                packets_tx,
+                connected_stream,
+                should_close,
+                &event_emitter,
+            );
             debug!("Closing RTP receiver thread (TCP server).");
         })
         .unwrap();
 
-    Ok((port, packets_rx))
+    Ok((port, packets_rx, feedback_sender))
 }
 
 fn run_tcp_server_thread(
+    input_id: &InputId,
     socket: std::net::TcpListener,
     packets_tx: Sender<bytes::Bytes>,
+    connected_stream: Arc<Mutex<Option<TcpStream>>>,
     should_close: Arc<AtomicBool>,
+    event_emitter: &EventEmitter,
 ) {
     // make accept non blocking so we have a chance to handle should_close value
     socket
@@ -77,7 +96,17 @@ fn run_tcp_server_thread(
     }
 
     let socket = match connected_socket {
-        Some(socket) => TcpReadPacketStream::new(socket, should_close.clone()),
+        Some(socket) => {
+            *connected_stream.lock().unwrap() = socket
+                .try_clone()
+                .and_then(|stream| {
+                    stream.set_write_timeout(Some(TCP_FEEDBACK_WRITE_TIMEOUT))?;
+                    Ok(stream)
+                })
+                .ok();
+            event_emitter.emit(Event::InputConnected(input_id.clone()));
+            TcpReadPacketStream::new(socket, should_close.clone())
+        }
         None => {
             return;
         }
@@ -90,6 +119,9 @@ fn run_tcp_server_thread(
             return;
         }
     }
+    if !should_close.load(std::sync::atomic::Ordering::Relaxed) {
+        event_emitter.emit(Event::InputDisconnected(input_id.clone()));
+    }
 }
 
 struct TcpReadPacketStream {
@@ -1,5 +1,6 @@
 use std::{
-    sync::{atomic::AtomicBool, Arc},
+    net::SocketAddr,
+    sync::{atomic::AtomicBool, Arc, Mutex},
     thread,
 };
 
@@ -10,13 +11,13 @@ use tracing::{debug, span, warn, Level};
 
 use crate::pipeline::{rtp::bind_to_requested_port, Port};
 
-use super::{RtpReceiverError, RtpReceiverOptions};
+use super::{rtcp_feedback::RtcpFeedbackSender, RtpReceiverError, RtpReceiverOptions};
 
 pub(super) fn start_udp_reader_thread(
     input_id: &InputId,
     opts: &RtpReceiverOptions,
     should_close: Arc<AtomicBool>,
-) -> Result<(Port, Receiver<bytes::Bytes>), RtpReceiverError> {
+) -> Result<(Port, Receiver<bytes::Bytes>, RtcpFeedbackSender), RtpReceiverError> {
     let (packets_tx, packets_rx) = unbounded();
 
     let socket = socket2::Socket::new(
@@ -43,6 +44,15 @@ pub(super) fn start_udp_reader_thread(
         .map_err(RtpReceiverError::SocketOptions)?;
 
     let socket = std::net::UdpSocket::from(socket);
+    let peer = Arc::new(Mutex::new(None));
+    let feedback_sender = RtcpFeedbackSender::Udp {
+        socket: Arc::new(
+            socket
+                .try_clone()
+                .map_err(RtpReceiverError::SocketOptions)?,
+        ),
+        peer: peer.clone(),
+    };
 
     let input_id = input_id.clone();
     thread::Builder::new()
@@ -54,17 +64,18 @@ pub(super) fn start_udp_reader_thread(
                 input_id = input_id.to_string()
             )
             .entered();
-            run_udp_receiver_thread(socket, packets_tx, should_close);
+            run_udp_receiver_thread(socket, packets_tx, peer, should_close);
             debug!("Closing RTP receiver thread (UDP).");
         })
         .unwrap();
 
-    Ok((port, packets_rx))
+    Ok((port, packets_rx, feedback_sender))
 }
 
 fn run_udp_receiver_thread(
     socket: std::net::UdpSocket,
     packets_tx: Sender<Bytes>,
+    peer: Arc<Mutex<Option<SocketAddr>>>,
     should_close: Arc<AtomicBool>,
 ) {
     let mut buffer = BytesMut::zeroed(65536);
@@ -75,7 +86,7 @@ fn run_udp_receiver_thread(
         }
 
         // This can be faster if we batched sending the packets through the channel
-        let (received_bytes, _) = match socket.recv_from(&mut buffer) {
+        let (received_bytes, address) = match socket.recv_from(&mut buffer) {
             Ok(n) => n,
             Err(e) => match e.kind() {
                 std::io::ErrorKind::WouldBlock => continue,
@@ -85,6 +96,8 @@ fn run_udp_receiver_thread(
                 }
             },
         };
+        // RTCP feedback is sent to the address of the last received packet
+        *peer.lock().unwrap() = Some(address);
 
         if packets_tx
             .send(Bytes::copy_from_slice(&buffer[..received_bytes]))
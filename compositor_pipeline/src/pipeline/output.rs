@@ -107,8 +107,13 @@ impl OutputOptionsExt<Option<Port>> for OutputOptions {
             audio: self.audio.clone(),
         };
 
-        let (encoder, packets) = Encoder::new(output_id, encoder_opts, ctx.mixing_sample_rate)
-            .map_err(|e| RegisterOutputError::EncoderError(output_id.clone(), e))?;
+        let (encoder, packets) = Encoder::new(
+            output_id,
+            encoder_opts,
+            ctx.mixing_sample_rate,
+            ctx.metrics.output(output_id),
+        )
+        .map_err(|e| RegisterOutputError::EncoderError(output_id.clone(), e))?;
 
         match &self.output_protocol {
             OutputProtocolOptions::Rtp(rtp_options) => {
@@ -151,8 +156,13 @@ impl OutputOptionsExt<Receiver<EncoderOutputEvent>> for EncodedDataOutputOptions
             audio: self.audio.clone(),
         };
 
-        let (encoder, packets) = Encoder::new(output_id, encoder_opts, ctx.mixing_sample_rate)
-            .map_err(|e| RegisterOutputError::EncoderError(output_id.clone(), e))?;
+        let (encoder, packets) = Encoder::new(
+            output_id,
+            encoder_opts,
+            ctx.mixing_sample_rate,
+            ctx.metrics.output(output_id),
+        )
+        .map_err(|e| RegisterOutputError::EncoderError(output_id.clone(), e))?;
 
         Ok((Output::EncodedData { encoder }, packets))
     }
@@ -223,6 +233,17 @@ impl Output {
         }
     }
 
+    /// `None` for outputs that do not encode the stream.
+    pub fn encoder_options(&self) -> Option<&EncoderOptions> {
+        match &self {
+            Output::Rtp { encoder, .. } => Some(encoder.options()),
+            Output::Mp4 { encoder, .. } => Some(encoder.options()),
+            Output::Whip { encoder, .. } => Some(encoder.options()),
+            Output::EncodedData { encoder } => Some(encoder.options()),
+            Output::RawData { .. } => None,
+        }
+    }
+
     pub fn request_keyframe(&self, output_id: OutputId) -> Result<(), RequestKeyframeError> {
         let encoder = match &self {
             Output::Rtp { encoder, .. } => encoder,
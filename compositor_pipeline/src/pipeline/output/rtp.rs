@@ -21,6 +21,8 @@ mod udp;
 #[derive(Debug)]
 pub struct RtpSender {
     pub connection_options: RtpConnectionOptions,
+    /// Port that was actually used, e.g. selected from the requested range.
+    pub port: Port,
 
     /// should_close will be set after output is unregistered,
     /// but the primary way of controlling the shutdown is a channel
@@ -78,9 +80,13 @@ impl RtpSender {
                     RtpConnectionOptions::Udp { .. } => {
                         udp::run_udp_sender_thread(socket, packet_stream)
                     }
-                    RtpConnectionOptions::TcpServer { .. } => {
-                        tcp_server::run_tcp_sender_thread(socket, should_close2, packet_stream)
-                    }
+                    RtpConnectionOptions::TcpServer { .. } => tcp_server::run_tcp_sender_thread(
+                        &output_id,
+                        socket,
+                        should_close2,
+                        packet_stream,
+                        &event_emitter,
+                    ),
                 }
                 event_emitter.emit(Event::OutputDone(output_id));
                 debug!("Closing RTP sender thread.")
@@ -90,6 +96,7 @@ impl RtpSender {
         Ok((
             Self {
                 connection_options: options.connection_options,
+                port,
                 should_close,
             },
             port,
@@ -1,5 +1,9 @@
 use bytes::Bytes;
-use std::{collections::VecDeque, fmt::Debug};
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    time::{Duration, Instant, SystemTime},
+};
 use tracing::error;
 use webrtc_util::Marshal;
 
@@ -7,7 +11,7 @@ use rand::Rng;
 use rtp::codecs::{h264::H264Payloader, opus::OpusPayloader};
 
 use crate::pipeline::{
-    rtp::{AUDIO_PAYLOAD_TYPE, VIDEO_PAYLOAD_TYPE},
+    rtp::{ntp_timestamp, AUDIO_PAYLOAD_TYPE, VIDEO_PAYLOAD_TYPE},
     types::{EncodedChunk, EncodedChunkKind},
     AudioCodec, VideoCodec,
 };
@@ -15,10 +19,15 @@ use crate::pipeline::{
 const H264_CLOCK_RATE: u32 = 90000;
 const OPUS_CLOCK_RATE: u32 = 48000;
 
+const SENDER_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
 struct RtpStreamContext {
     ssrc: u32,
     next_sequence_number: u16,
     received_eos: bool,
+    packet_count: u32,
+    octet_count: u32,
+    last_sender_report: Option<Instant>,
 }
 
 impl RtpStreamContext {
@@ -31,8 +40,40 @@ impl RtpStreamContext {
             ssrc,
             next_sequence_number,
             received_eos: false,
+            packet_count: 0,
+            octet_count: 0,
+            last_sender_report: None,
         }
     }
+
+    /// Returns sender report if it's time to send the next one. `wall_clock_time` is
+    /// the time when the chunk with `pts` is sent.
+    fn sender_report(
+        &mut self,
+        pts: Duration,
+        wall_clock_time: SystemTime,
+        clock_rate: u32,
+    ) -> Result<Option<Bytes>, PayloadingError> {
+        let now = Instant::now();
+        let is_report_due = self.last_sender_report.map_or(true, |last| {
+            now.duration_since(last) >= SENDER_REPORT_INTERVAL
+        });
+        if !is_report_due {
+            return Ok(None);
+        }
+        self.last_sender_report = Some(now);
+
+        let report = rtcp::sender_report::SenderReport {
+            ssrc: self.ssrc,
+            ntp_time: ntp_timestamp(wall_clock_time),
+            rtp_time: rtp_timestamp(pts, clock_rate),
+            packet_count: self.packet_count,
+            octet_count: self.octet_count,
+            reports: vec![],
+            profile_extensions: Bytes::new(),
+        };
+        Ok(Some(report.marshal()?))
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -79,6 +120,9 @@ pub enum PayloadingError {
 pub struct Payloader {
     video: Option<VideoPayloader>,
     audio: Option<AudioPayloader>,
+    /// PTS of the first payloaded chunk and the time it was sent. The same mapping
+    /// is used in sender reports of both streams, so the receiver can synchronize them.
+    wall_clock_reference: Option<(Duration, SystemTime)>,
 }
 
 enum VideoPayloader {
@@ -100,6 +144,7 @@ impl Payloader {
         Self {
             video: video.map(VideoPayloader::new),
             audio: audio.map(AudioPayloader::new),
+            wall_clock_reference: None,
         }
     }
 
@@ -108,6 +153,15 @@ impl Payloader {
         mtu: usize,
         data: EncodedChunk,
     ) -> Result<VecDeque<Bytes>, PayloadingError> {
+        let pts = data.pts;
+        let (reference_pts, reference_time) = *self
+            .wall_clock_reference
+            .get_or_insert_with(|| (pts, SystemTime::now()));
+        let wall_clock_time = match pts >= reference_pts {
+            true => reference_time + (pts - reference_pts),
+            false => reference_time - (reference_pts - pts),
+        };
+
         match data.kind {
             EncodedChunkKind::Video(chunk_codec) => {
                 let Some(ref mut video_payloader) = self.video else {
@@ -121,7 +175,15 @@ impl Payloader {
                     });
                 }
 
-                video_payloader.payload(mtu, data)
+                let mut packets = video_payloader.payload(mtu, data)?;
+                let clock_rate = video_payloader.clock_rate();
+                let report = video_payloader.context_mut().sender_report(
+                    pts,
+                    wall_clock_time,
+                    clock_rate,
+                )?;
+                packets.extend(report);
+                Ok(packets)
             }
             EncodedChunkKind::Audio(chunk_codec) => {
                 let Some(ref mut audio_payloader) = self.audio else {
@@ -135,7 +197,15 @@ impl Payloader {
                     });
                 }
 
-                audio_payloader.payload(mtu, data)
+                let mut packets = audio_payloader.payload(mtu, data)?;
+                let clock_rate = audio_payloader.clock_rate();
+                let report = audio_payloader.context_mut().sender_report(
+                    pts,
+                    wall_clock_time,
+                    clock_rate,
+                )?;
+                packets.extend(report);
+                Ok(packets)
             }
         }
     }
@@ -220,6 +290,12 @@ impl VideoPayloader {
             VideoPayloader::H264 { context, .. } => context,
         }
     }
+
+    fn clock_rate(&self) -> u32 {
+        match self {
+            VideoPayloader::H264 { .. } => H264_CLOCK_RATE,
+        }
+    }
 }
 
 impl AudioPayloader {
@@ -264,6 +340,12 @@ impl AudioPayloader {
             AudioPayloader::Opus { context, .. } => context,
         }
     }
+
+    fn clock_rate(&self) -> u32 {
+        match self {
+            AudioPayloader::Opus { .. } => OPUS_CLOCK_RATE,
+        }
+    }
 }
 
 fn payload<T: rtp::packetizer::Payloader>(
@@ -288,13 +370,19 @@ fn payload<T: rtp::packetizer::Payloader>(
                 marker: i == packets_amount - 1, // marker needs to be set on the last packet of each frame
                 payload_type,
                 sequence_number: context.next_sequence_number,
-                timestamp: (chunk.pts.as_secs_f64() * clock_rate as f64) as u32,
+                timestamp: rtp_timestamp(chunk.pts, clock_rate),
                 ssrc: context.ssrc,
                 ..Default::default()
             };
             context.next_sequence_number = context.next_sequence_number.wrapping_add(1);
+            context.packet_count = context.packet_count.wrapping_add(1);
+            context.octet_count = context.octet_count.wrapping_add(payload.len() as u32);
 
             Ok(rtp::packet::Packet { header, payload }.marshal()?)
         })
         .collect()
 }
+
+fn rtp_timestamp(pts: Duration, clock_rate: u32) -> u32 {
+    (pts.as_secs_f64() * clock_rate as f64) as u32
+}
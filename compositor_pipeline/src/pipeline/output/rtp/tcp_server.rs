@@ -5,10 +5,12 @@ use std::{
     time::Duration,
 };
 
+use compositor_render::OutputId;
 use tracing::{debug, error, trace, warn};
 
 use crate::{
     error::OutputInitError,
+    event::{Event, EventEmitter, OutputConnectionState},
     pipeline::{
         rtp::{bind_to_requested_port, BindToPortError, RequestedPort},
         Port,
@@ -31,9 +33,11 @@ pub(super) fn tcp_socket(port: RequestedPort) -> Result<(socket2::Socket, Port),
     Ok((socket, port))
 }
 pub(super) fn run_tcp_sender_thread(
+    output_id: &OutputId,
     socket: socket2::Socket,
     should_close: Arc<AtomicBool>,
     mut packet_stream: PacketStream,
+    event_emitter: &EventEmitter,
 ) {
     // make accept non blocking so we have a chance to handle should_close value
     socket
@@ -54,6 +58,14 @@ pub(super) fn run_tcp_sender_thread(
         Some(socket) => TcpWritePacketStream::new(socket, should_close.clone()),
         None => return,
     };
+    let emit_state_change = |state| {
+        event_emitter.emit(Event::OutputConnectionStateChanged {
+            output_id: output_id.clone(),
+            state,
+        })
+    };
+    emit_state_change(OutputConnectionState::Connected);
+    let mut connected = true;
 
     loop {
         let chunk = match packet_stream.next() {
@@ -76,6 +88,10 @@ pub(super) fn run_tcp_sender_thread(
                 return;
             }
             debug!("Failed to send RTP packet: {err}");
+            if connected {
+                connected = false;
+                emit_state_change(OutputConnectionState::Disconnected);
+            }
             continue;
         }
     }
@@ -1,4 +1,5 @@
 use super::{WhipCtx, WhipError};
+use crate::event::{Event, OutputConnectionState};
 use compositor_render::error::ErrorStack;
 use reqwest::{
     header::{HeaderMap, HeaderValue},
@@ -25,16 +26,31 @@ pub async fn connect(
     whip_ctx: &WhipCtx,
 ) -> Result<Url, WhipError> {
     let whip_ctx = whip_ctx.clone();
+    let event_emitter = whip_ctx.pipeline_ctx.event_emitter.clone();
+    let output_id = whip_ctx.output_id.clone();
     peer_connection.on_ice_connection_state_change(Box::new(
         move |connection_state: RTCIceConnectionState| {
             debug!("Connection State has changed {connection_state}.");
-            if connection_state == RTCIceConnectionState::Connected {
-                debug!("Ice connected.");
-            } else if connection_state == RTCIceConnectionState::Failed {
-                debug!("Ice connection failed.");
-                whip_ctx
-                    .should_close
-                    .store(true, std::sync::atomic::Ordering::Relaxed);
+            let state = match connection_state {
+                RTCIceConnectionState::Connected => {
+                    debug!("Ice connected.");
+                    Some(OutputConnectionState::Connected)
+                }
+                RTCIceConnectionState::Disconnected => Some(OutputConnectionState::Disconnected),
+                RTCIceConnectionState::Failed => {
+                    debug!("Ice connection failed.");
+                    whip_ctx
+                        .should_close
+                        .store(true, std::sync::atomic::Ordering::Relaxed);
+                    Some(OutputConnectionState::Failed)
+                }
+                _ => None,
+            };
+            if let Some(state) = state {
+                event_emitter.emit(Event::OutputConnectionStateChanged {
+                    output_id: output_id.clone(),
+                    state,
+                });
             }
             Box::pin(async {})
         },
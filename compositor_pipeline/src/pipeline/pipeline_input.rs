@@ -4,7 +4,10 @@ use compositor_render::InputId;
 
 use crate::{error::RegisterInputError, queue::QueueInputOptions, Pipeline};
 
-use super::input::{self, InputOptionsExt};
+use super::{
+    input::{self, InputOptionsExt},
+    AudioCodec, VideoDecoder,
+};
 
 pub struct PipelineInput {
     pub input: input::Input,
@@ -15,6 +18,11 @@ pub struct PipelineInput {
     /// Some(received) - Whether EOS was received from queue on video stream for that input.
     /// None - No video configured for that input.
     pub(super) video_eos_received: Option<bool>,
+
+    /// `None` if the input does not have video or produces raw frames.
+    pub(super) video_decoder: Option<VideoDecoder>,
+    /// `None` if the input does not have audio or produces raw samples.
+    pub(super) audio_codec: Option<AudioCodec>,
 }
 
 /// This method doesn't take pipeline lock for the whole scope,
@@ -31,7 +39,9 @@ pub(super) fn register_pipeline_input<NewInputResult>(
 
     let pipeline_ctx = pipeline.lock().unwrap().ctx.clone();
 
-    let (input, receiver, input_result) = input_options.new_input(&input_id, &pipeline_ctx)?;
+    let (input, receiver, input_result) = input_options
+        .new_input(&input_id, &pipeline_ctx)
+        .inspect_err(|_| pipeline_ctx.metrics.remove_input(&input_id))?;
 
     let (audio_eos_received, video_eos_received) = (
         receiver.audio.as_ref().map(|_| false),
@@ -42,6 +52,8 @@ pub(super) fn register_pipeline_input<NewInputResult>(
         input,
         audio_eos_received,
         video_eos_received,
+        video_decoder: receiver.video_decoder,
+        audio_codec: receiver.audio_codec,
     };
 
     let mut guard = pipeline.lock().unwrap();
@@ -78,6 +90,6 @@ impl PipelineInput {
         self.audio_eos_received = self.audio_eos_received.map(|_| true);
     }
     pub(super) fn on_video_eos(&mut self) {
-        self.audio_eos_received = self.audio_eos_received.map(|_| true);
+        self.video_eos_received = self.video_eos_received.map(|_| true);
     }
 }
@@ -52,7 +52,10 @@ pub(super) fn register_pipeline_output<NewOutputResult>(
 
     let pipeline_ctx = Arc::new(pipeline.lock().unwrap().ctx.clone());
 
-    let (output, output_result) = output_options.new_output(&output_id, pipeline_ctx)?;
+    let metrics = pipeline_ctx.metrics.clone();
+    let (output, output_result) = output_options
+        .new_output(&output_id, pipeline_ctx)
+        .inspect_err(|_| metrics.remove_output(&output_id))?;
 
     let mut guard = pipeline.lock().unwrap();
 
@@ -84,6 +87,9 @@ pub(super) fn register_pipeline_output<NewOutputResult>(
             guard.renderer.unregister_output(&output_id);
             return Err(RegisterOutputError::SceneError(output_id.clone(), err));
         }
+
+        let framerate = video_opts.framerate.unwrap_or(guard.ctx.output_framerate);
+        guard.queue.add_output(&output_id, framerate);
     };
 
     if let Some(audio_opts) = audio.clone() {
@@ -238,6 +244,10 @@ impl PipelineOutputEndConditionState {
         self.did_end
     }
 
+    pub(super) fn did_send_eos(&self) -> bool {
+        self.did_send_eos
+    }
+
     pub(super) fn on_input_registered(&mut self, input_id: &InputId) {
         self.on_event(StateChange::AddInput(input_id))
     }
@@ -1,10 +1,30 @@
-use std::net;
+use std::{
+    net,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use super::Port;
 
 pub(crate) const VIDEO_PAYLOAD_TYPE: u8 = 96;
 pub(crate) const AUDIO_PAYLOAD_TYPE: u8 = 97;
 
+/// Seconds between the NTP epoch (1900) and the UNIX epoch (1970).
+const NTP_UNIX_EPOCH_OFFSET: u64 = 2_208_988_800;
+
+/// Converts system time to a 64-bit NTP timestamp used in RTCP sender reports
+/// ([RFC 3550, section 4](https://datatracker.ietf.org/doc/html/rfc3550#section-4)).
+pub(crate) fn ntp_timestamp(time: SystemTime) -> u64 {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs() + NTP_UNIX_EPOCH_OFFSET;
+    let fraction = ((since_epoch.subsec_nanos() as u64) << 32) / 1_000_000_000;
+    (seconds << 32) | fraction
+}
+
+/// Converts 64-bit NTP timestamp to seconds since the NTP epoch.
+pub(crate) fn ntp_timestamp_to_secs(ntp_timestamp: u64) -> f64 {
+    (ntp_timestamp >> 32) as f64 + (ntp_timestamp & 0xFFFF_FFFF) as f64 / (1u64 << 32) as f64
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TransportProtocol {
     Udp,
@@ -74,6 +74,16 @@ pub enum VideoDecoder {
     VulkanVideoH264,
 }
 
+impl VideoDecoder {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            VideoDecoder::FFmpegH264 => "ffmpeg_h264",
+            #[cfg(feature = "vk-video")]
+            VideoDecoder::VulkanVideoH264 => "vulkan_video",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VideoCodec {
     H264,
@@ -1,4 +1,6 @@
-use crate::{error::InitPipelineError, pipeline::input::whip::depayloader::Depayloader};
+use crate::{
+    error::InitPipelineError, event::EventEmitter, pipeline::input::whip::depayloader::Depayloader,
+};
 use axum::{
     routing::{delete, get, patch, post},
     Router,
@@ -115,13 +117,15 @@ impl WhipInputConnectionOptions {
 pub struct WhipWhepState {
     pub input_connections: Arc<Mutex<HashMap<InputId, WhipInputConnectionOptions>>>,
     pub stun_servers: Arc<Vec<String>>,
+    pub event_emitter: Arc<EventEmitter>,
 }
 
 impl WhipWhepState {
-    pub fn new(stun_servers: Arc<Vec<String>>) -> Arc<Self> {
+    pub fn new(stun_servers: Arc<Vec<String>>, event_emitter: Arc<EventEmitter>) -> Arc<Self> {
         Arc::new(WhipWhepState {
             input_connections: Arc::from(Mutex::new(HashMap::new())),
             stun_servers,
+            event_emitter,
         })
     }
 
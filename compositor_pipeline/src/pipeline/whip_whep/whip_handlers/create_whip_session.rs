@@ -1,7 +1,11 @@
-use crate::pipeline::{
-    input::whip::process_track_stream,
-    whip_whep::{
-        bearer_token::validate_token, error::WhipServerError, init_peer_connection, WhipWhepState,
+use crate::{
+    event::Event,
+    pipeline::{
+        input::whip::process_track_stream,
+        whip_whep::{
+            bearer_token::validate_token, error::WhipServerError, init_peer_connection,
+            WhipWhepState,
+        },
     },
 };
 use axum::{
@@ -16,7 +20,9 @@ use tokio::{sync::watch, time::timeout};
 use tracing::{debug, info};
 use urlencoding::encode;
 use webrtc::{
-    ice_transport::ice_gatherer_state::RTCIceGathererState,
+    ice_transport::{
+        ice_connection_state::RTCIceConnectionState, ice_gatherer_state::RTCIceGathererState,
+    },
     peer_connection::{sdp::session_description::RTCSessionDescription, RTCPeerConnection},
     rtp_transceiver::rtp_codec::RTPCodecType,
 };
@@ -55,6 +61,22 @@ pub async fn handle_create_whip_session(
         .update_peer_connection(input_id.clone(), peer_connection.clone())
         .await?;
 
+    let event_emitter = state.event_emitter.clone();
+    let connection_input_id = input_id.clone();
+    peer_connection.on_ice_connection_state_change(Box::new(move |connection_state| {
+        info!("ICE connection state changed: {connection_state:?}");
+        match connection_state {
+            RTCIceConnectionState::Connected => {
+                event_emitter.emit(Event::InputConnected(connection_input_id.clone()))
+            }
+            RTCIceConnectionState::Disconnected | RTCIceConnectionState::Failed => {
+                event_emitter.emit(Event::InputDisconnected(connection_input_id.clone()))
+            }
+            _ => (),
+        }
+        Box::pin(async {})
+    }));
+
     peer_connection.on_track(Box::new(move |track, _, _| {
         let track_kind = track.kind();
         let state_clone = state.clone();
@@ -86,11 +108,6 @@ pub async fn handle_create_whip_session(
         })
     }));
 
-    peer_connection.on_ice_connection_state_change(Box::new(move |state| {
-        info!("ICE connection state changed: {state:?}");
-        Box::pin(async {})
-    }));
-
     let description = RTCSessionDescription::offer(offer)?;
 
     peer_connection.set_remote_description(description).await?;
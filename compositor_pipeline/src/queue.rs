@@ -1,34 +1,41 @@
 mod audio_queue;
+mod clock_drift;
+mod input_buffer;
 mod queue_thread;
 mod utils;
 mod video_queue;
 
 use std::{
-    collections::HashMap,
-    fmt::Debug,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc, Mutex,
     },
     time::{Duration, Instant},
 };
 
-use compositor_render::{Frame, FrameSet, Framerate, InputId};
-use crossbeam_channel::{bounded, Sender};
+use compositor_render::{BlendedFrame, Frame, FrameSet, Framerate, InputId, OutputId};
+use crossbeam_channel::{bounded, unbounded, Sender};
 
 use crate::{
     audio_mixer::{InputSamples, InputSamplesSet},
+    error::CancelScheduledEventError,
     event::EventEmitter,
+    metrics::PipelineMetrics,
     pipeline::decoder::DecodedDataReceiver,
 };
 
 use self::{
     audio_queue::AudioQueue,
+    input_buffer::InputBuffer,
     queue_thread::{QueueStartEvent, QueueThread},
     utils::Clock,
     video_queue::VideoQueue,
 };
 
+pub use clock_drift::ClockDrift;
+
 pub const DEFAULT_BUFFER_DURATION: Duration = Duration::from_millis(16 * 5); // about 5 frames at 60 fps
 const DEFAULT_AUDIO_CHUNK_DURATION: Duration = Duration::from_millis(20); // typical audio packet size
 
@@ -44,7 +51,11 @@ pub struct Queue {
     video_queue: Mutex<VideoQueue>,
     audio_queue: Mutex<AudioQueue>,
 
+    /// Framerate used when there are no outputs registered.
     output_framerate: Framerate,
+    /// Framerates of the registered video outputs. Queue produces frame sets
+    /// for PTS values from all of those cadences.
+    output_framerates: Mutex<HashMap<OutputId, Framerate>>,
 
     /// Duration of queue output samples set.
     audio_chunk_duration: Duration,
@@ -64,9 +75,19 @@ pub struct Queue {
 
     start_sender: Mutex<Option<Sender<QueueStartEvent>>>,
     scheduled_event_sender: Sender<ScheduledEvent>,
+    /// Events that were scheduled, but not executed or cancelled yet. Callbacks are
+    /// stored here, so they are dropped as soon as the event is cancelled.
+    pending_scheduled_events: Mutex<BTreeMap<ScheduledEventId, PendingScheduledEvent>>,
+    next_scheduled_event_id: AtomicU64,
+    /// PTS of the last video frame set produced by the queue.
+    current_pts: Mutex<Option<Duration>>,
 
     clock: Clock,
 
+    clock_drifts: Mutex<HashMap<InputId, ClockDrift>>,
+
+    metrics: Arc<PipelineMetrics>,
+
     should_close: AtomicBool,
 }
 
@@ -74,6 +95,10 @@ pub struct Queue {
 pub(super) struct QueueVideoOutput {
     pub(super) pts: Duration,
     pub(super) frames: HashMap<InputId, PipelineEvent<Frame>>,
+    /// Frames that should be mixed with the frames from `frames`.
+    pub(super) blended_frames: HashMap<InputId, BlendedFrame>,
+    /// Outputs that should be rendered for this PTS.
+    pub(super) outputs: HashSet<OutputId>,
 }
 
 impl From<QueueVideoOutput> for FrameSet<InputId> {
@@ -87,6 +112,7 @@ impl From<QueueVideoOutput> for FrameSet<InputId> {
                     PipelineEvent::EOS => None,
                 })
                 .collect(),
+            blended_frames: value.blended_frames,
             pts: value.pts,
         }
     }
@@ -132,13 +158,72 @@ pub struct QueueInputOptions {
     ///
     /// By default DEFAULT_BUFFER_DURATION will be used.
     pub buffer_duration: Option<Duration>,
+
+    /// Adjust buffer duration based on measured jitter of the input stream. `buffer_duration`
+    /// is used as an initial value. Ignored if `offset` is set.
+    pub adaptive_buffer: Option<AdaptiveBufferOptions>,
+
+    /// Defines how frames are selected if the input framerate does not match
+    /// the output framerate.
+    pub frame_rate_conversion: FrameRateConversion,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FrameRateConversion {
+    /// Use a frame closest to the PTS of the output frame.
+    #[default]
+    Nearest,
+    /// Mix two neighbouring frames, weighted by their distance to the PTS of the output frame.
+    /// Blending is done by the renderer.
+    Blend,
 }
 
 #[derive(Debug, Clone, Copy)]
+pub struct AdaptiveBufferOptions {
+    pub min_duration: Duration,
+    pub max_duration: Duration,
+}
+
+#[derive(Debug, Clone)]
 struct InputOptions {
     required: bool,
     offset: Option<Duration>,
-    buffer_duration: Duration,
+    /// Shared between audio and video of the same input.
+    buffer: InputBuffer,
+    /// Clock drift estimated and compensated by this track. Only one track
+    /// of the input measures the drift.
+    clock_drift: Option<ClockDrift>,
+    frame_rate_conversion: FrameRateConversion,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct QueueInputStats {
+    /// `None` if the input does not have a video track.
+    pub video_state: Option<QueueInputState>,
+    /// `None` if the input does not have an audio track.
+    pub audio_state: Option<QueueInputState>,
+    /// Duration of the video frames waiting in the queue, `None` if the input
+    /// does not have a video track.
+    pub video_buffered: Option<Duration>,
+    /// Duration of the audio samples waiting in the queue, `None` if the input
+    /// does not have an audio track.
+    pub audio_buffered: Option<Duration>,
+    /// Estimated difference between the clock of the input source and the local
+    /// clock in ppm. Positive value means that the source clock runs faster.
+    /// `None` if clock drift is not compensated for this input.
+    pub clock_drift_ppm: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueInputState {
+    /// No data was received yet.
+    WaitingForStart,
+    /// Data is received, but the initial buffer is not filled yet.
+    Buffering,
+    /// Input is producing frames/samples.
+    Ready,
+    /// EOS was received.
+    Done,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -150,11 +235,36 @@ pub struct QueueOptions {
     pub never_drop_output_frames: bool,
 }
 
+#[derive(Debug)]
 pub struct ScheduledEvent {
+    id: ScheduledEventId,
     pts: Duration,
+}
+
+struct PendingScheduledEvent {
+    info: ScheduledEventInfo,
     callback: Box<dyn FnOnce() + Send>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ScheduledEventId(pub u64);
+
+/// Describes what a scheduled event does, so it can be listed.
+#[derive(Debug, Clone)]
+pub struct ScheduledEventDescription {
+    /// Type of the scheduled request, e.g. `update_output`.
+    pub kind: &'static str,
+    /// Input, output or renderer affected by the event.
+    pub entity_id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScheduledEventInfo {
+    pub id: ScheduledEventId,
+    pub pts: Duration,
+    pub description: ScheduledEventDescription,
+}
+
 #[derive(Debug)]
 pub enum PipelineEvent<T> {
     Data(T),
@@ -171,17 +281,25 @@ impl<T: Clone> Clone for PipelineEvent<T> {
 }
 
 impl Queue {
-    pub(crate) fn new(opts: QueueOptions, event_emitter: &Arc<EventEmitter>) -> Arc<Self> {
+    pub(crate) fn new(
+        opts: QueueOptions,
+        event_emitter: &Arc<EventEmitter>,
+        metrics: &Arc<PipelineMetrics>,
+    ) -> Arc<Self> {
         let (queue_start_sender, queue_start_receiver) = bounded(0);
-        let (scheduled_event_sender, scheduled_event_receiver) = bounded(0);
+        let (scheduled_event_sender, scheduled_event_receiver) = unbounded();
         let queue = Arc::new(Queue {
             video_queue: Mutex::new(VideoQueue::new(event_emitter.clone())),
             output_framerate: opts.output_framerate,
+            output_framerates: Mutex::new(HashMap::new()),
 
             audio_queue: Mutex::new(AudioQueue::new(event_emitter.clone())),
             audio_chunk_duration: DEFAULT_AUDIO_CHUNK_DURATION,
 
             scheduled_event_sender,
+            pending_scheduled_events: Mutex::new(BTreeMap::new()),
+            next_scheduled_event_id: AtomicU64::new(0),
+            current_pts: Mutex::new(None),
             start_sender: Mutex::new(Some(queue_start_sender)),
             ahead_of_time_processing: opts.ahead_of_time_processing,
             never_drop_output_frames: opts.never_drop_output_frames,
@@ -189,6 +307,8 @@ impl Queue {
             default_buffer_duration: opts.default_buffer_duration,
 
             clock: Clock::new(),
+            clock_drifts: Mutex::new(HashMap::new()),
+            metrics: metrics.clone(),
             should_close: AtomicBool::new(false),
         });
 
@@ -215,22 +335,42 @@ impl Queue {
         let input_options = InputOptions {
             required: opts.required,
             offset: opts.offset,
-            buffer_duration: opts.buffer_duration.unwrap_or(self.default_buffer_duration),
+            buffer: InputBuffer::new(
+                opts.buffer_duration.unwrap_or(self.default_buffer_duration),
+                opts.adaptive_buffer.filter(|_| opts.offset.is_none()),
+            ),
+            clock_drift: None,
+            frame_rate_conversion: opts.frame_rate_conversion,
         };
+        if let Some(ref clock_drift) = receiver.clock_drift {
+            self.clock_drifts
+                .lock()
+                .unwrap()
+                .insert(input_id.clone(), clock_drift.clone());
+        }
 
-        if let Some(receiver) = receiver.video {
+        // drift is measured on video if it is available
+        let has_video = receiver.video.is_some();
+        if let Some(video_receiver) = receiver.video {
             self.video_queue.lock().unwrap().add_input(
                 input_id,
-                receiver,
-                input_options,
+                video_receiver,
+                InputOptions {
+                    clock_drift: receiver.clock_drift.clone(),
+                    ..input_options.clone()
+                },
                 self.clock.clone(),
+                self.metrics.input(input_id),
             );
         };
-        if let Some(receiver) = receiver.audio {
+        if let Some(audio_receiver) = receiver.audio {
             self.audio_queue.lock().unwrap().add_input(
                 input_id,
-                receiver,
-                input_options,
+                audio_receiver,
+                InputOptions {
+                    clock_drift: receiver.clock_drift.filter(|_| !has_video),
+                    ..input_options
+                },
                 self.clock.clone(),
             );
         }
@@ -239,6 +379,62 @@ impl Queue {
     pub fn remove_input(&self, input_id: &InputId) {
         self.video_queue.lock().unwrap().remove_input(input_id);
         self.audio_queue.lock().unwrap().remove_input(input_id);
+        self.clock_drifts.lock().unwrap().remove(input_id);
+    }
+
+    /// Registers framerate of a video output. Frame sets will be produced at PTS values
+    /// matching this framerate in addition to the framerates of other outputs.
+    pub fn add_output(&self, output_id: &OutputId, framerate: Framerate) {
+        self.output_framerates
+            .lock()
+            .unwrap()
+            .insert(output_id.clone(), framerate);
+    }
+
+    pub fn remove_output(&self, output_id: &OutputId) {
+        self.output_framerates.lock().unwrap().remove(output_id);
+    }
+
+    /// `None` if the output does not have a video stream.
+    pub fn output_framerate(&self, output_id: &OutputId) -> Option<Framerate> {
+        self.output_framerates
+            .lock()
+            .unwrap()
+            .get(output_id)
+            .copied()
+    }
+
+    pub fn input_stats(&self, input_id: &InputId) -> Option<QueueInputStats> {
+        let (video_state, video_buffered) = {
+            let video_queue = self.video_queue.lock().unwrap();
+            (
+                video_queue.input_state(input_id),
+                video_queue.buffered_duration(input_id),
+            )
+        };
+        let (audio_state, audio_buffered) = {
+            let audio_queue = self.audio_queue.lock().unwrap();
+            (
+                audio_queue.input_state(input_id),
+                audio_queue.buffered_duration(input_id),
+            )
+        };
+        if video_state.is_none() && audio_state.is_none() {
+            return None;
+        }
+        let clock_drift_ppm = self
+            .clock_drifts
+            .lock()
+            .unwrap()
+            .get(input_id)
+            .map(ClockDrift::drift_ppm);
+        Some(QueueInputStats {
+            video_state,
+            audio_state,
+            video_buffered,
+            audio_buffered,
+            clock_drift_ppm,
+        })
     }
 
     pub(super) fn start(
@@ -257,18 +453,77 @@ impl Queue {
         }
     }
 
-    pub fn schedule_event(&self, pts: Duration, callback: Box<dyn FnOnce() + Send>) {
+    /// Schedules `callback` to be executed before the frames for `pts` are produced.
+    /// Returned id can be used to cancel the event. It does not block, so it can be
+    /// called while holding locks that the callback takes.
+    pub fn schedule_event(
+        &self,
+        pts: Duration,
+        description: ScheduledEventDescription,
+        callback: Box<dyn FnOnce() + Send>,
+    ) -> ScheduledEventId {
+        let id = ScheduledEventId(self.next_scheduled_event_id.fetch_add(1, Ordering::Relaxed));
+        self.pending_scheduled_events.lock().unwrap().insert(
+            id,
+            PendingScheduledEvent {
+                info: ScheduledEventInfo {
+                    id,
+                    pts,
+                    description,
+                },
+                callback,
+            },
+        );
         self.scheduled_event_sender
-            .send(ScheduledEvent { pts, callback })
+            .send(ScheduledEvent { id, pts })
             .unwrap();
+        id
+    }
+
+    /// PTS of the last video frame set produced by the queue. `None` if the queue
+    /// was not started or nothing was produced yet.
+    pub fn current_pts(&self) -> Option<Duration> {
+        *self.current_pts.lock().unwrap()
+    }
+
+    /// Events that were scheduled, but not executed or cancelled yet, ordered by PTS.
+    pub fn scheduled_events(&self) -> Vec<ScheduledEventInfo> {
+        let mut events: Vec<_> = self
+            .pending_scheduled_events
+            .lock()
+            .unwrap()
+            .values()
+            .map(|event| event.info.clone())
+            .collect();
+        events.sort_by_key(|event| (event.pts, event.id));
+        events
+    }
+
+    /// Cancels the event, so its callback is never executed. Fails if the event
+    /// was already executed, cancelled or discarded.
+    ///
+    /// The callback is dropped before this function returns, so the caller should
+    /// not hold locks that are taken when the callback is dropped.
+    pub fn cancel_scheduled_event(
+        &self,
+        id: ScheduledEventId,
+    ) -> Result<(), CancelScheduledEventError> {
+        match self.take_scheduled_event(id) {
+            Some(_callback) => Ok(()),
+            None => Err(CancelScheduledEventError::NotFound(id)),
+        }
+    }
+
+    /// Removes the event from the pending events and returns its callback. Returns
+    /// `None` if it was already removed, e.g. the event was cancelled.
+    fn take_scheduled_event(&self, id: ScheduledEventId) -> Option<Box<dyn FnOnce() + Send>> {
+        let event = self.pending_scheduled_events.lock().unwrap().remove(&id);
+        event.map(|event| event.callback)
     }
 }
 
-impl Debug for ScheduledEvent {
+impl Display for ScheduledEventId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("ScheduledEvent")
-            .field("pts", &self.pts)
-            .field("callback", &"<callback>".to_string())
-            .finish()
+        write!(f, "{}", self.0)
     }
 }
@@ -12,7 +12,7 @@ use crate::{
 
 use super::{
     utils::{Clock, InputProcessor},
-    InputOptions, PipelineEvent, QueueAudioOutput,
+    InputOptions, PipelineEvent, QueueAudioOutput, QueueInputState,
 };
 use compositor_render::InputId;
 use crossbeam_channel::{Receiver, TryRecvError};
@@ -45,7 +45,8 @@ impl AudioQueue {
                 queue: VecDeque::new(),
                 receiver,
                 input_samples_processor: InputProcessor::new(
-                    opts.buffer_duration,
+                    opts.buffer,
+                    opts.clock_drift,
                     clock,
                     input_id.clone(),
                     self.event_emitter.clone(),
@@ -63,6 +64,26 @@ impl AudioQueue {
         self.inputs.remove(input_id);
     }
 
+    pub fn has_input(&self, input_id: &InputId) -> bool {
+        self.inputs.contains_key(input_id)
+    }
+
+    pub(super) fn input_state(&self, input_id: &InputId) -> Option<QueueInputState> {
+        self.inputs
+            .get(input_id)
+            .map(|input| input.input_samples_processor.state())
+    }
+
+    /// Duration of the samples in the queue.
+    pub(super) fn buffered_duration(&self, input_id: &InputId) -> Option<Duration> {
+        let input = self.inputs.get(input_id)?;
+        let duration = match (input.queue.front(), input.queue.back()) {
+            (Some(first), Some(last)) => last.end_pts.saturating_sub(first.start_pts),
+            _ => Duration::ZERO,
+        };
+        Some(duration)
+    }
+
     /// Checks if all inputs are ready to produce frames for specific PTS value (if all inputs have
     /// frames closest to buffer_pts).
     pub(super) fn check_all_inputs_ready_for_pts(
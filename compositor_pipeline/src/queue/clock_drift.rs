@@ -0,0 +1,184 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tracing::debug;
+
+/// Minimal transit time (arrival time - PTS) is calculated for each window. Minimum
+/// filters out network and decoding jitter that would dominate a drift of a few ppm.
+const WINDOW_DURATION: Duration = Duration::from_secs(10);
+/// Drift is estimated with linear regression over that many windows.
+const MAX_WINDOWS: usize = 30;
+const MIN_WINDOWS: usize = 6;
+/// Smaller drift is ignored, it is below the precision of the estimate.
+const MIN_DRIFT: f64 = 5e-6;
+/// Larger difference is not caused by clock drift, e.g. input is paused or
+/// timestamps are broken, so it's not compensated.
+const MAX_DRIFT: f64 = 1e-3;
+
+/// Difference between the clock of an input source and the local clock. It is shared
+/// between the queue, which estimates it based on arrival times, and the audio
+/// resampler, which compensates it by adjusting resampling ratio. Video is compensated
+/// in the queue by scaling PTS, which makes the queue drop or repeat frames.
+#[derive(Debug, Clone, Default)]
+pub struct ClockDrift(Arc<Mutex<ClockDriftState>>);
+
+#[derive(Debug, Default)]
+struct ClockDriftState {
+    /// Compensated relative difference of clock rates. Positive value means that
+    /// the source clock runs faster than the local one.
+    correction: f64,
+    /// Drift remaining after compensation.
+    residual: f64,
+
+    /// Reference point for window timestamps.
+    start: Option<Instant>,
+    window: Option<TransitWindow>,
+    /// Start of the window (seconds since `start`) and minimal transit time in that window.
+    windows: VecDeque<(f64, f64)>,
+}
+
+#[derive(Debug)]
+struct TransitWindow {
+    start: Instant,
+    min_transit: f64,
+}
+
+impl ClockDrift {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compensated relative difference of clock rates (source / local - 1).
+    pub(crate) fn correction(&self) -> f64 {
+        self.0.lock().unwrap().correction
+    }
+
+    /// Current estimate of clock drift in ppm. Positive value means that the source
+    /// clock runs faster than the local one.
+    pub(crate) fn drift_ppm(&self) -> f64 {
+        let state = self.0.lock().unwrap();
+        (state.correction + state.residual) * 1_000_000.0
+    }
+
+    /// Registers transit time (local arrival time - compensated PTS) of a chunk.
+    pub(super) fn on_transit(&self, transit: f64, now: Instant) {
+        let mut state = self.0.lock().unwrap();
+        let start = *state.start.get_or_insert(now);
+
+        let window = state.window.get_or_insert(TransitWindow {
+            start: now,
+            min_transit: transit,
+        });
+        window.min_transit = f64::min(window.min_transit, transit);
+        if now.saturating_duration_since(window.start) < WINDOW_DURATION {
+            return;
+        }
+
+        let window_start = window.start.saturating_duration_since(start).as_secs_f64();
+        let min_transit = window.min_transit;
+        state.window = None;
+        state.windows.push_back((window_start, min_transit));
+        if state.windows.len() > MAX_WINDOWS {
+            state.windows.pop_front();
+        }
+        if state.windows.len() < MIN_WINDOWS {
+            return;
+        }
+
+        // Growing transit time means that PTS progresses slower than the local clock.
+        state.residual = -regression_slope(&state.windows);
+        if state.residual.abs() < MIN_DRIFT {
+            return;
+        }
+        let correction = state.correction + state.residual;
+        if correction.abs() > MAX_DRIFT {
+            return;
+        }
+        debug!(
+            drift_ppm = correction * 1_000_000.0,
+            "Compensating input clock drift."
+        );
+        state.correction = correction;
+        state.residual = 0.0;
+        // Old windows were measured with a different correction.
+        state.windows.clear();
+    }
+}
+
+fn regression_slope(points: &VecDeque<(f64, f64)>) -> f64 {
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+    let (covariance, variance) = points.iter().fold((0.0, 0.0), |(cov, var), (x, y)| {
+        (
+            cov + (x - mean_x) * (y - mean_y),
+            var + (x - mean_x) * (x - mean_x),
+        )
+    });
+    match variance > 0.0 {
+        true => covariance / variance,
+        false => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Simulates source that sends 20ms chunks with clock running `drift` faster,
+    /// with jitter of up to 15ms.
+    fn simulate(clock_drift: &ClockDrift, drift: f64, duration: Duration) {
+        let start = Instant::now();
+        let chunks = (duration.as_secs_f64() / 0.02) as u64;
+        let mut pts_scale_anchor = (0.0, 0.0, 0.0);
+        for i in 0..chunks {
+            let arrival = i as f64 * 0.02;
+            let pts = arrival * (1.0 + drift);
+            let jitter = ((i * 7919) % 16) as f64 / 1000.0;
+
+            // compensation of PTS, the same as in the queue for video
+            let correction = clock_drift.correction();
+            let (anchor_pts, anchor_compensated, anchor_correction) = pts_scale_anchor;
+            if correction != anchor_correction {
+                let compensated =
+                    anchor_compensated + (pts - anchor_pts) / (1.0 + anchor_correction);
+                pts_scale_anchor = (pts, compensated, correction);
+            }
+            let (anchor_pts, anchor_compensated, _) = pts_scale_anchor;
+            let compensated = anchor_compensated + (pts - anchor_pts) / (1.0 + correction);
+
+            let now = start + Duration::from_secs_f64(arrival + jitter);
+            clock_drift.on_transit(arrival + jitter - compensated, now);
+        }
+    }
+
+    #[test]
+    fn compensates_fast_source() {
+        let clock_drift = ClockDrift::new();
+        simulate(&clock_drift, 50e-6, Duration::from_secs(600));
+
+        let drift_ppm = clock_drift.drift_ppm();
+        assert!((45.0..55.0).contains(&drift_ppm), "{drift_ppm}");
+        assert!(clock_drift.correction() > 40e-6);
+    }
+
+    #[test]
+    fn compensates_slow_source() {
+        let clock_drift = ClockDrift::new();
+        simulate(&clock_drift, -100e-6, Duration::from_secs(600));
+
+        let drift_ppm = clock_drift.drift_ppm();
+        assert!((-110.0..-90.0).contains(&drift_ppm), "{drift_ppm}");
+    }
+
+    #[test]
+    fn ignores_jitter_without_drift() {
+        let clock_drift = ClockDrift::new();
+        simulate(&clock_drift, 0.0, Duration::from_secs(600));
+
+        assert_eq!(clock_drift.correction(), 0.0);
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use super::AdaptiveBufferOptions;
+
+/// Buffer duration is set to this multiple of the measured jitter.
+const JITTER_MULTIPLIER: f64 = 4.0;
+/// Relative difference between the current and the target duration that
+/// triggers a change.
+const CHANGE_THRESHOLD: f64 = 0.2;
+/// Smaller changes are ignored, they would only cause frequent glitches.
+const MIN_CHANGE: Duration = Duration::from_millis(10);
+/// Growing the buffer prevents stalls, so it can happen more often than shrinking.
+const GROW_INTERVAL: Duration = Duration::from_secs(1);
+const SHRINK_INTERVAL: Duration = Duration::from_secs(10);
+/// Number of samples needed before the buffer is adjusted for the first time. Jitter
+/// is smoothed over ~16 samples, so earlier estimates are mostly based on the initial 0.
+const WARM_UP_SAMPLES: u32 = 64;
+
+/// Buffer duration of a single input. It is shared between audio and video
+/// processors of that input, so when adaptive mode changes the duration both
+/// tracks are shifted by the same amount and stay in sync.
+#[derive(Debug, Clone)]
+pub(super) struct InputBuffer(Arc<Mutex<InputBufferState>>);
+
+#[derive(Debug)]
+struct InputBufferState {
+    /// Duration buffered before the input starts.
+    initial: Duration,
+    current: Duration,
+    adaptive: Option<AdaptiveState>,
+}
+
+#[derive(Debug)]
+struct AdaptiveState {
+    opts: AdaptiveBufferOptions,
+    /// Smoothed interarrival jitter in seconds, calculated like in RFC 3550 (section 6.4.1).
+    jitter: f64,
+    samples: u32,
+    last_change: Option<Instant>,
+}
+
+impl InputBuffer {
+    pub(super) fn new(initial: Duration, adaptive: Option<AdaptiveBufferOptions>) -> Self {
+        let initial = match adaptive {
+            Some(opts) => initial.clamp(opts.min_duration, opts.max_duration),
+            None => initial,
+        };
+        Self(Arc::new(Mutex::new(InputBufferState {
+            initial,
+            current: initial,
+            adaptive: adaptive.map(|opts| AdaptiveState {
+                opts,
+                jitter: 0.0,
+                samples: 0,
+                last_change: None,
+            }),
+        })))
+    }
+
+    pub(super) fn initial_duration(&self) -> Duration {
+        self.0.lock().unwrap().initial
+    }
+
+    pub(super) fn is_adaptive(&self) -> bool {
+        self.0.lock().unwrap().adaptive.is_some()
+    }
+
+    /// Moves start time of an input by the difference between the current
+    /// and the initial buffer duration.
+    pub(super) fn shift_start_time(&self, start_time: Instant) -> Instant {
+        let state = self.0.lock().unwrap();
+        match state.current >= state.initial {
+            true => start_time + (state.current - state.initial),
+            false => start_time - (state.initial - state.current),
+        }
+    }
+
+    /// Updates jitter estimate with a difference between transit times of two
+    /// consecutive chunks. Returns new buffer duration if it changed.
+    pub(super) fn on_transit_diff(&self, transit_diff: f64, now: Instant) -> Option<Duration> {
+        let mut state = self.0.lock().unwrap();
+        let current = state.current;
+        let adaptive = state.adaptive.as_mut()?;
+
+        adaptive.jitter += (transit_diff.abs() - adaptive.jitter) / 16.0;
+        adaptive.samples = adaptive.samples.saturating_add(1);
+        if adaptive.samples < WARM_UP_SAMPLES {
+            return None;
+        }
+
+        let target = Duration::from_secs_f64(adaptive.jitter * JITTER_MULTIPLIER)
+            .clamp(adaptive.opts.min_duration, adaptive.opts.max_duration);
+        let diff = target.abs_diff(current);
+        if diff < MIN_CHANGE || diff.as_secs_f64() < current.as_secs_f64() * CHANGE_THRESHOLD {
+            return None;
+        }
+
+        let interval = match target > current {
+            true => GROW_INTERVAL,
+            false => SHRINK_INTERVAL,
+        };
+        if let Some(last_change) = adaptive.last_change {
+            if now.saturating_duration_since(last_change) < interval {
+                return None;
+            }
+        }
+
+        let target = Duration::from_millis(target.as_millis() as u64);
+        adaptive.last_change = Some(now);
+        state.current = target;
+        Some(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adaptive_buffer(initial_ms: u64) -> InputBuffer {
+        InputBuffer::new(
+            Duration::from_millis(initial_ms),
+            Some(AdaptiveBufferOptions {
+                min_duration: Duration::from_millis(20),
+                max_duration: Duration::from_millis(500),
+            }),
+        )
+    }
+
+    #[test]
+    fn grows_on_jitter() {
+        let buffer = adaptive_buffer(20);
+        let start = Instant::now();
+
+        let mut changes = vec![];
+        for i in 0..200 {
+            let transit_diff = if i % 2 == 0 { 0.05 } else { -0.05 };
+            let now = start + Duration::from_millis(20 * i);
+            changes.extend(buffer.on_transit_diff(transit_diff, now));
+        }
+
+        let last = *changes.last().unwrap();
+        assert!(last > Duration::from_millis(150), "{last:?}");
+        assert!(last <= Duration::from_millis(200), "{last:?}");
+        assert!(buffer.shift_start_time(start) > start);
+    }
+
+    #[test]
+    fn clamps_to_bounds_after_warm_up() {
+        let buffer = adaptive_buffer(1000);
+        assert_eq!(buffer.initial_duration(), Duration::from_millis(500));
+
+        let start = Instant::now();
+        let sample_time = |i: u32| start + Duration::from_millis(20) * i;
+        for i in 0..WARM_UP_SAMPLES - 1 {
+            assert_eq!(buffer.on_transit_diff(0.0, sample_time(i)), None);
+        }
+        assert_eq!(buffer.shift_start_time(start), start);
+
+        assert_eq!(
+            buffer.on_transit_diff(0.0, sample_time(WARM_UP_SAMPLES)),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(
+            buffer.on_transit_diff(0.0, sample_time(WARM_UP_SAMPLES + 50)),
+            None
+        );
+        assert!(buffer.shift_start_time(start) < start);
+    }
+
+    #[test]
+    fn ignores_jitter_during_warm_up() {
+        let buffer = adaptive_buffer(100);
+        let start = Instant::now();
+
+        // Single large spike at the beginning of the stream.
+        assert_eq!(buffer.on_transit_diff(2.0, start), None);
+        for i in 1..WARM_UP_SAMPLES - 1 {
+            let now = start + Duration::from_millis(20) * i;
+            assert_eq!(buffer.on_transit_diff(0.0, now), None);
+        }
+        assert_eq!(buffer.shift_start_time(start), start);
+    }
+
+    #[test]
+    fn fixed_buffer() {
+        let buffer = InputBuffer::new(Duration::from_millis(80), None);
+        let start = Instant::now();
+
+        assert_eq!(buffer.on_transit_diff(1.0, start), None);
+        assert_eq!(buffer.shift_start_time(start), start);
+    }
+}
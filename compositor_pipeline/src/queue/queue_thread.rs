@@ -1,24 +1,29 @@
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashSet},
     ops::Add,
     sync::{atomic::Ordering, Arc, MutexGuard},
     thread::{self, JoinHandle},
     time::{Duration, Instant},
 };
 
+use compositor_render::{Framerate, OutputId};
 use crossbeam_channel::{select, tick, Receiver, Sender};
 use tracing::{debug, info, info_span, trace, warn};
 
 use super::{
     audio_queue::AudioQueue, video_queue::VideoQueue, Queue, QueueAudioOutput, QueueVideoOutput,
-    ScheduledEvent,
+    ScheduledEvent, ScheduledEventId,
 };
 
+/// Ids of the scheduled events grouped by PTS. Callbacks are stored in the queue,
+/// so cancelled events can be dropped right away.
+type ScheduledCallbacks = BTreeMap<Duration, Vec<ScheduledEventId>>;
+
 pub(super) struct QueueThread {
     queue: Arc<Queue>,
     start_receiver: Receiver<QueueStartEvent>,
     scheduled_event_receiver: Receiver<ScheduledEvent>,
-    scheduled_events: BTreeMap<Duration, Vec<Box<dyn FnOnce() + Send>>>,
+    scheduled_events: ScheduledCallbacks,
 }
 
 pub(super) struct QueueStartEvent {
@@ -58,14 +63,10 @@ impl QueueThread {
                 },
                 recv(self.scheduled_event_receiver) -> event => {
                     let event = event.unwrap();
-                    match self.scheduled_events.get_mut(&event.pts) {
-                        Some(events) => {
-                            events.push(event.callback);
-                        }
-                        None => {
-                            self.scheduled_events.insert(event.pts, vec![event.callback]);
-                        }
-                    }
+                    self.scheduled_events
+                        .entry(event.pts)
+                        .or_default()
+                        .push(event.id);
                 }
                 recv(self.start_receiver) -> start_event => {
                     QueueThreadAfterStart::new(self, start_event.unwrap()).run();
@@ -96,7 +97,7 @@ struct QueueThreadAfterStart {
     audio_processor: AudioQueueProcessor,
     video_processor: VideoQueueProcessor,
     scheduled_event_receiver: Receiver<ScheduledEvent>,
-    scheduled_events: BTreeMap<Duration, Vec<Box<dyn FnOnce() + Send>>>,
+    scheduled_events: ScheduledCallbacks,
 }
 
 impl QueueThreadAfterStart {
@@ -113,7 +114,7 @@ impl QueueThreadAfterStart {
             video_processor: VideoQueueProcessor {
                 queue: queue_thread.queue,
                 sender: start_event.video_sender,
-                sent_batches_counter: 0,
+                last_pts: None,
                 queue_start_time: start_event.start_time,
             },
             scheduled_event_receiver: queue_thread.scheduled_event_receiver,
@@ -146,9 +147,12 @@ impl QueueThreadAfterStart {
                 .map(|event_pts: Duration| event_pts < video_pts && event_pts < audio_pts_range.0)
             {
                 info!("Handle scheduled event for PTS={:?}", event_pts);
-                if let Some((_, callbacks)) = self.scheduled_events.pop_first() {
-                    for callback in callbacks {
-                        callback()
+                if let Some((_, ids)) = self.scheduled_events.pop_first() {
+                    for id in ids {
+                        // Cancelled events are already removed from the queue.
+                        if let Some(callback) = self.queue.take_scheduled_event(id) {
+                            callback()
+                        }
                     }
                 }
             } else if video_pts > audio_pts_range.0 {
@@ -180,39 +184,54 @@ impl QueueThreadAfterStart {
     fn on_enqueue_event(&mut self, scheduled_event: ScheduledEvent) {
         let audio_pts_range = self.audio_processor.next_buffer_pts_range();
         let video_pts = self.video_processor.next_buffer_pts();
-        let event_pts = self.scheduled_events.first_key_value().map(|(pts, _)| *pts);
 
-        let is_future_event = scheduled_event.pts >= video_pts
-            && scheduled_event.pts >= audio_pts_range.0
-            && scheduled_event.pts >= event_pts.unwrap_or(Duration::ZERO);
+        let is_future_event =
+            scheduled_event.pts >= video_pts && scheduled_event.pts >= audio_pts_range.0;
 
         if self.queue.run_late_scheduled_events || is_future_event {
-            match self.scheduled_events.get_mut(&scheduled_event.pts) {
-                Some(events) => {
-                    events.push(scheduled_event.callback);
-                }
-                None => {
-                    self.scheduled_events
-                        .insert(scheduled_event.pts, vec![scheduled_event.callback]);
-                }
-            }
+            self.scheduled_events
+                .entry(scheduled_event.pts)
+                .or_default()
+                .push(scheduled_event.id);
+        } else {
+            warn!(
+                "Discarding event scheduled for PTS={:?}, it is too late.",
+                scheduled_event.pts
+            );
+            self.queue.take_scheduled_event(scheduled_event.id);
         }
     }
 }
 
 struct VideoQueueProcessor {
     queue: Arc<Queue>,
-    sent_batches_counter: u32,
+    /// PTS of the last frame set that was sent (or dropped).
+    last_pts: Option<Duration>,
     queue_start_time: Instant,
     sender: Sender<QueueVideoOutput>,
 }
 
 impl VideoQueueProcessor {
+    /// The earliest PTS after the last frame set from framerates of all outputs.
     fn next_buffer_pts(&self) -> Duration {
-        Duration::from_secs_f64(
-            self.sent_batches_counter as f64 * self.queue.output_framerate.den as f64
-                / self.queue.output_framerate.num as f64,
-        )
+        let output_framerates = self.queue.output_framerates.lock().unwrap();
+        output_framerates
+            .values()
+            .map(|framerate| next_frame_pts(*framerate, self.last_pts))
+            .min()
+            .unwrap_or_else(|| next_frame_pts(self.queue.output_framerate, self.last_pts))
+    }
+
+    /// Outputs that have a frame at that PTS.
+    fn outputs_for_pts(&self, pts: Duration) -> HashSet<OutputId> {
+        self.queue
+            .output_framerates
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, framerate)| is_frame_pts(**framerate, pts))
+            .map(|(output_id, _)| output_id.clone())
+            .collect()
     }
 
     fn should_push_for_pts(&self, pts: Duration, queue: &mut MutexGuard<VideoQueue>) -> bool {
@@ -235,21 +254,27 @@ impl VideoQueueProcessor {
     fn send_output_frames(&mut self, frames_batch: QueueVideoOutput, is_required: bool) {
         let pts = frames_batch.pts;
         debug!(?pts, "Pushing video frames.");
-        if is_required {
-            if self.sender.send(frames_batch).is_err() {
-                warn!(?pts, "Dropping video frame on queue output.");
-            }
+        let metrics = &self.queue.metrics;
+        let send_time = self.queue_start_time.add(pts);
+        metrics
+            .queue_lateness
+            .observe(Instant::now().saturating_duration_since(send_time));
+        let outputs = frames_batch.outputs.clone();
+        let result = if is_required {
+            self.sender.send(frames_batch).map_err(|_| ())
         } else {
-            let send_deadline = self.queue_start_time.add(frames_batch.pts);
-            if self
-                .sender
-                .send_deadline(frames_batch, send_deadline)
-                .is_err()
-            {
-                warn!(?pts, "Dropping video frame on queue output.");
+            self.sender
+                .send_deadline(frames_batch, send_time)
+                .map_err(|_| ())
+        };
+        if result.is_err() {
+            warn!(?pts, "Dropping video frame on queue output.");
+            for output_id in outputs.iter() {
+                metrics.on_output_frame_dropped(output_id);
             }
         }
-        self.sent_batches_counter += 1
+        self.last_pts = Some(pts);
+        *self.queue.current_pts.lock().unwrap() = Some(pts);
     }
 
     /// Some(()) - Successfully pushed new frame (or dropped it).
@@ -262,7 +287,9 @@ impl VideoQueueProcessor {
             return None;
         }
 
-        let frames_batch = internal_queue.get_frames_batch(next_buffer_pts, self.queue_start_time);
+        let outputs = self.outputs_for_pts(next_buffer_pts);
+        let frames_batch =
+            internal_queue.get_frames_batch(next_buffer_pts, self.queue_start_time, outputs);
 
         let is_required = self.queue.never_drop_output_frames
             || internal_queue.has_required_inputs_for_pts(next_buffer_pts, self.queue_start_time);
@@ -352,3 +379,85 @@ impl AudioQueueProcessor {
         self.chunks_counter += 1;
     }
 }
+
+fn frame_pts(framerate: Framerate, index: u64) -> Duration {
+    Duration::from_secs_f64(index as f64 * framerate.den as f64 / framerate.num as f64)
+}
+
+/// PTS of the first frame of the framerate after `pts`, or PTS=0 if `pts` is `None`.
+///
+/// Division is correctly rounded, so equal points in time from different framerates
+/// (e.g. every second frame of 60 fps and every frame of 30 fps) have the same PTS.
+fn next_frame_pts(framerate: Framerate, pts: Option<Duration>) -> Duration {
+    let Some(pts) = pts else {
+        return Duration::ZERO;
+    };
+    let index = (pts.as_secs_f64() * framerate.num as f64 / framerate.den as f64) as u64;
+    // rounding errors can put frame with `index` on either side of `pts`
+    (index.saturating_sub(1)..)
+        .map(|index| frame_pts(framerate, index))
+        .find(|frame_pts| *frame_pts > pts)
+        .unwrap()
+}
+
+fn is_frame_pts(framerate: Framerate, pts: Duration) -> bool {
+    let index = (pts.as_secs_f64() * framerate.num as f64 / framerate.den as f64).round() as u64;
+    frame_pts(framerate, index) == pts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cadence(framerates: &[Framerate], count: usize) -> Vec<Duration> {
+        let mut last_pts = None;
+        (0..count)
+            .map(|_| {
+                let pts = framerates
+                    .iter()
+                    .map(|framerate| next_frame_pts(*framerate, last_pts))
+                    .min()
+                    .unwrap();
+                last_pts = Some(pts);
+                pts
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_framerate() {
+        let framerate = Framerate {
+            num: 30000,
+            den: 1001,
+        };
+        let cadence = cadence(&[framerate], 1000);
+        for (index, pts) in cadence.into_iter().enumerate() {
+            assert_eq!(pts, frame_pts(framerate, index as u64));
+            assert!(is_frame_pts(framerate, pts));
+        }
+    }
+
+    #[test]
+    fn multiple_framerates() {
+        let fps_60 = Framerate { num: 60, den: 1 };
+        let fps_25 = Framerate { num: 25, den: 1 };
+        let fps_1 = Framerate { num: 1, den: 1 };
+        let cadence = cadence(&[fps_60, fps_25, fps_1], 10_000);
+
+        // 60 + 25 frames per second, and 5 of them are shared
+        let first_second: Vec<_> = cadence
+            .iter()
+            .filter(|pts| **pts < Duration::from_secs(1))
+            .collect();
+        assert_eq!(first_second.len(), 80);
+
+        assert!(cadence.windows(2).all(|pts| pts[0] < pts[1]));
+        for framerate in [fps_60, fps_25, fps_1] {
+            for index in 0..100 {
+                assert!(cadence.contains(&frame_pts(framerate, index)));
+            }
+        }
+        assert!(!is_frame_pts(fps_25, frame_pts(fps_60, 1)));
+        assert!(is_frame_pts(fps_25, frame_pts(fps_60, 12)));
+    }
+}
@@ -14,7 +14,7 @@ use tracing::warn;
 use crate::event::Event;
 use crate::{audio_mixer::InputSamples, event::EventEmitter};
 
-use super::PipelineEvent;
+use super::{clock_drift::ClockDrift, input_buffer::InputBuffer, PipelineEvent, QueueInputState};
 
 /// InputProcessor handles initial processing for frames/samples that are being
 /// queued. For each received frame/sample batch, the `process_new_chunk`
@@ -34,11 +34,22 @@ use super::PipelineEvent;
 pub(super) struct InputProcessor<Payload: InputProcessorMediaExt> {
     input_id: InputId,
 
-    buffer_duration: Duration,
+    buffer: InputBuffer,
 
     /// Moment where input transitioned to a ready state
     start_time: Option<Instant>,
 
+    /// Difference between arrival time and PTS of the last chunk, used
+    /// to measure jitter when adaptive buffer is enabled.
+    last_transit: Option<f64>,
+
+    /// Clock drift estimated by this processor. Video is also compensated here,
+    /// audio is compensated earlier by the resampler.
+    clock_drift: Option<ClockDrift>,
+    /// Input PTS, compensated PTS and correction used since that point. PTS are
+    /// scaled piecewise, so changes of the correction do not cause jumps.
+    drift_anchor: Option<(Duration, Duration, f64)>,
+
     state: InputState<Payload>,
 
     clock: Clock,
@@ -62,14 +73,18 @@ pub(super) enum InputState<Payload: InputProcessorMediaExt> {
 
 impl<Payload: InputProcessorMediaExt> InputProcessor<Payload> {
     pub(super) fn new(
-        buffer_duration: Duration,
+        buffer: InputBuffer,
+        clock_drift: Option<ClockDrift>,
         clock: Clock,
         input_id: InputId,
         event_emitter: Arc<EventEmitter>,
     ) -> Self {
         Self {
-            buffer_duration,
+            buffer,
             start_time: None,
+            last_transit: None,
+            clock_drift,
+            drift_anchor: None,
             state: InputState::WaitingForStart,
             clock,
             input_id,
@@ -77,14 +92,26 @@ impl<Payload: InputProcessorMediaExt> InputProcessor<Payload> {
         }
     }
 
+    /// Moment where input transitioned to a ready state, adjusted by changes
+    /// of the buffer duration made after that moment.
     pub(super) fn start_time(&self) -> Option<Instant> {
         self.start_time
+            .map(|start_time| self.buffer.shift_start_time(start_time))
     }
 
     pub(super) fn did_receive_eos(&self) -> bool {
         matches!(self.state, InputState::Done)
     }
 
+    pub(super) fn state(&self) -> QueueInputState {
+        match self.state {
+            InputState::WaitingForStart => QueueInputState::WaitingForStart,
+            InputState::Buffering { .. } => QueueInputState::Buffering,
+            InputState::Ready { .. } => QueueInputState::Ready,
+            InputState::Done => QueueInputState::Done,
+        }
+    }
+
     pub(super) fn process_new_chunk(
         &mut self,
         payload: PipelineEvent<Payload>,
@@ -140,7 +167,7 @@ impl<Payload: InputProcessorMediaExt> InputProcessor<Payload> {
                 let last_pts = buffer.last().map(|(_, p)| *p).unwrap_or(Duration::ZERO);
                 let buffer_duration = last_pts.saturating_sub(first_pts);
 
-                if buffer_duration < self.buffer_duration {
+                if buffer_duration < self.buffer.initial_duration() {
                     VecDeque::new()
                 } else {
                     let offset = first_pts;
@@ -159,7 +186,9 @@ impl<Payload: InputProcessorMediaExt> InputProcessor<Payload> {
                 }
             }
             InputState::Ready { offset, .. } => {
+                self.measure_jitter(pts);
                 payload.apply_offset(offset);
+                self.compensate_drift(&mut payload);
                 VecDeque::from([payload])
             }
             InputState::Done => {
@@ -169,6 +198,57 @@ impl<Payload: InputProcessorMediaExt> InputProcessor<Payload> {
         }
     }
 
+    fn measure_jitter(&mut self, pts: Duration) {
+        if !self.buffer.is_adaptive() {
+            return;
+        }
+        let now = Instant::now();
+        let Some(start_time) = self.start_time else {
+            return;
+        };
+        let transit = now.saturating_duration_since(start_time).as_secs_f64() - pts.as_secs_f64();
+        let Some(last_transit) = self.last_transit.replace(transit) else {
+            return;
+        };
+        if let Some(buffer_duration) = self.buffer.on_transit_diff(transit - last_transit, now) {
+            self.event_emitter.emit(Event::InputBufferDurationChanged {
+                input_id: self.input_id.clone(),
+                buffer_duration,
+            });
+        }
+    }
+
+    fn compensate_drift(&mut self, payload: &mut Payload) {
+        let Some(ref clock_drift) = self.clock_drift else {
+            return;
+        };
+        let pts = payload.pts();
+        if let MediaType::Video = Payload::media_type() {
+            let correction = clock_drift.correction();
+            let (anchor_pts, anchor_compensated_pts, anchor_correction) =
+                *self.drift_anchor.get_or_insert((pts, pts, correction));
+            if anchor_correction != correction {
+                let compensated_pts =
+                    scale_pts(pts, anchor_pts, anchor_compensated_pts, anchor_correction);
+                self.drift_anchor = Some((pts, compensated_pts, correction));
+            }
+            let (anchor_pts, anchor_compensated_pts, _) = self.drift_anchor.unwrap();
+            payload.set_pts(scale_pts(
+                pts,
+                anchor_pts,
+                anchor_compensated_pts,
+                correction,
+            ));
+        }
+
+        if let Some(start_time) = self.start_time {
+            let now = Instant::now();
+            let transit = now.saturating_duration_since(start_time).as_secs_f64()
+                - payload.pts().as_secs_f64();
+            clock_drift.on_transit(transit, now);
+        }
+    }
+
     fn on_ready(&self) {
         match Payload::media_type() {
             MediaType::Audio => self
@@ -186,8 +266,21 @@ pub(super) enum MediaType {
     Video,
 }
 
+/// Maps PTS from the source clock to the local clock, starting from an anchor point.
+fn scale_pts(
+    pts: Duration,
+    anchor_pts: Duration,
+    anchor_scaled_pts: Duration,
+    correction: f64,
+) -> Duration {
+    let elapsed = pts.as_secs_f64() - anchor_pts.as_secs_f64();
+    let scaled = anchor_scaled_pts.as_secs_f64() + elapsed / (1.0 + correction);
+    Duration::from_secs_f64(scaled.max(0.0))
+}
+
 pub(super) trait InputProcessorMediaExt {
     fn apply_offset(&mut self, offset: Duration);
+    fn set_pts(&mut self, pts: Duration);
     fn pts(&self) -> Duration;
     fn media_type() -> MediaType;
 }
@@ -197,6 +290,10 @@ impl InputProcessorMediaExt for Frame {
         self.pts = self.pts.saturating_sub(offset)
     }
 
+    fn set_pts(&mut self, pts: Duration) {
+        self.pts = pts
+    }
+
     fn pts(&self) -> Duration {
         self.pts
     }
@@ -212,6 +309,11 @@ impl InputProcessorMediaExt for InputSamples {
         self.end_pts = self.end_pts.saturating_sub(offset);
     }
 
+    fn set_pts(&mut self, pts: Duration) {
+        self.end_pts = pts + self.end_pts.saturating_sub(self.start_pts);
+        self.start_pts = pts;
+    }
+
     fn pts(&self) -> Duration {
         self.start_pts
     }
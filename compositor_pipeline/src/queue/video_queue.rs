@@ -1,9 +1,13 @@
+use compositor_render::BlendedFrame;
 use compositor_render::Frame;
 use compositor_render::InputId;
+use compositor_render::OutputId;
+use compositor_render::Resolution;
 use crossbeam_channel::Receiver;
 use crossbeam_channel::TryRecvError;
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::VecDeque;
 use std::sync::Arc;
 use std::time::Duration;
@@ -11,11 +15,14 @@ use std::time::Instant;
 
 use crate::event::Event;
 use crate::event::EventEmitter;
+use crate::metrics::InputMetrics;
 
 use super::utils::Clock;
 use super::utils::InputProcessor;
+use super::FrameRateConversion;
 use super::InputOptions;
 use super::PipelineEvent;
+use super::QueueInputState;
 use super::QueueVideoOutput;
 
 pub struct VideoQueue {
@@ -37,6 +44,7 @@ impl VideoQueue {
         receiver: Receiver<PipelineEvent<Frame>>,
         opts: InputOptions,
         clock: Clock,
+        metrics: Arc<InputMetrics>,
     ) {
         self.inputs.insert(
             input_id.clone(),
@@ -45,16 +53,21 @@ impl VideoQueue {
                 queue: VecDeque::new(),
                 receiver,
                 input_frames_processor: InputProcessor::new(
-                    opts.buffer_duration,
+                    opts.buffer,
+                    opts.clock_drift,
                     clock,
                     input_id.clone(),
                     self.event_emitter.clone(),
                 ),
                 required: opts.required,
                 offset: opts.offset,
+                frame_rate_conversion: opts.frame_rate_conversion,
                 eos_sent: false,
                 first_frame_sent: false,
+                last_input_pts: None,
+                last_resolution: None,
                 event_emitter: self.event_emitter.clone(),
+                metrics,
             },
         );
     }
@@ -63,26 +76,51 @@ impl VideoQueue {
         self.inputs.remove(input_id);
     }
 
+    pub fn has_input(&self, input_id: &InputId) -> bool {
+        self.inputs.contains_key(input_id)
+    }
+
+    pub(super) fn input_state(&self, input_id: &InputId) -> Option<QueueInputState> {
+        self.inputs
+            .get(input_id)
+            .map(|input| input.input_frames_processor.state())
+    }
+
+    /// Difference between PTS of the newest and the oldest frame in the queue.
+    pub(super) fn buffered_duration(&self, input_id: &InputId) -> Option<Duration> {
+        let input = self.inputs.get(input_id)?;
+        let duration = match (input.queue.front(), input.queue.back()) {
+            (Some(first), Some(last)) => last.pts.saturating_sub(first.pts),
+            _ => Duration::ZERO,
+        };
+        Some(duration)
+    }
+
     /// Gets frames closest to buffer pts. It does not check whether input is ready
     /// or not. It should not be called before pipeline start.
     pub(super) fn get_frames_batch(
         &mut self,
         buffer_pts: Duration,
         queue_start: Instant,
+        outputs: HashSet<OutputId>,
     ) -> QueueVideoOutput {
-        let frames = self
-            .inputs
-            .iter_mut()
-            .filter_map(|(input_id, input)| {
-                input
-                    .get_frame(buffer_pts, queue_start)
-                    .map(|frame| (input_id.clone(), frame))
-            })
-            .collect();
+        let mut frames = HashMap::new();
+        let mut blended_frames = HashMap::new();
+        for (input_id, input) in self.inputs.iter_mut() {
+            let Some((frame, blended_frame)) = input.get_frame(buffer_pts, queue_start) else {
+                continue;
+            };
+            frames.insert(input_id.clone(), frame);
+            if let Some(blended_frame) = blended_frame {
+                blended_frames.insert(input_id.clone(), blended_frame);
+            }
+        }
 
         QueueVideoOutput {
             frames,
+            blended_frames,
             pts: buffer_pts,
+            outputs,
         }
     }
 
@@ -150,38 +188,58 @@ pub struct VideoQueueInput {
     /// Offset of the stream relative to the start. If set to `None`
     /// offset will be resolved automatically on the stream start.
     offset: Option<Duration>,
+    frame_rate_conversion: FrameRateConversion,
 
     eos_sent: bool,
     first_frame_sent: bool,
+    /// Input PTS of the last frame batch. Frames older than that
+    /// arrived too late to be rendered.
+    last_input_pts: Option<Duration>,
+    /// Resolution of the last frame received from the decoder.
+    last_resolution: Option<Resolution>,
 
     event_emitter: Arc<EventEmitter>,
+    metrics: Arc<InputMetrics>,
 }
 
 impl VideoQueueInput {
     /// Return frame for PTS and drop all the older frames. This function does not check
     /// whether stream is required or not.
+    ///
+    /// If frames are blended, the next frame with its weight is also returned.
     fn get_frame(
         &mut self,
         buffer_pts: Duration,
         queue_start: Instant,
-    ) -> Option<PipelineEvent<Frame>> {
+    ) -> Option<(PipelineEvent<Frame>, Option<BlendedFrame>)> {
         // ignore result, we only need to ensure frames are enqueued
         self.check_ready_for_pts(buffer_pts, queue_start);
 
         self.drop_old_frames(buffer_pts, queue_start);
+        if let Some(input_pts) = self.input_pts_from_queue_pts(buffer_pts, queue_start) {
+            self.last_input_pts = Some(input_pts);
+        }
         let input_start_time = self.input_start_time()?;
+        let offset = self.offset;
+        let into_queue_pts = |mut frame: Frame| {
+            frame.pts = match offset {
+                Some(offset) => frame.pts + offset,
+                None => (input_start_time + frame.pts).duration_since(queue_start),
+            };
+            frame
+        };
         let frame = match self.offset {
             // if stream should not start yet, do not send any frames
             Some(offset) if offset > buffer_pts => None,
             // if stream is started then take the frames
-            Some(offset) => self.queue.front().cloned().map(|mut frame| {
-                frame.pts += offset;
-                frame
-            }),
-            None => self.queue.front().cloned().map(|mut frame| {
-                frame.pts = (input_start_time + frame.pts).duration_since(queue_start);
-                frame
-            }),
+            _ => self.queue.front().cloned().map(into_queue_pts),
+        };
+        let blended_frame = match (self.frame_rate_conversion, &frame) {
+            (FrameRateConversion::Blend, Some(frame)) => {
+                let next_frame = self.queue.get(1).cloned().map(into_queue_pts);
+                next_frame.and_then(|next_frame| blended_frame(frame, next_frame, buffer_pts))
+            }
+            _ => None,
         };
         // Handle a case where we have last frame and received EOS.
         // "drop_old_frames" is ensuring that there will only be one frame at
@@ -194,14 +252,14 @@ impl VideoQueueInput {
             self.eos_sent = true;
             self.event_emitter
                 .emit(Event::VideoInputStreamEos(self.input_id.clone()));
-            Some(PipelineEvent::EOS)
+            Some((PipelineEvent::EOS, None))
         } else {
             if !self.first_frame_sent && frame.is_some() {
                 self.event_emitter
                     .emit(Event::VideoInputStreamPlaying(self.input_id.clone()));
                 self.first_frame_sent = true
             }
-            frame.map(PipelineEvent::Data)
+            frame.map(|frame| (PipelineEvent::Data(frame), blended_frame))
         }
     }
 
@@ -255,7 +313,8 @@ impl VideoQueueInput {
     ///
     /// Finds frame that is closest to the next_buffer_pts and removes everything older.
     /// Frames in queue have monotonically increasing pts, so we can just drop all the frames
-    /// before the "closest" one.
+    /// before the "closest" one. If frames are blended, the last frame before the
+    /// next_buffer_pts is kept instead, so it can be mixed with the following one.
     /// If dropping frames removes everything from the queue try to enqueue some new frames
     /// and repeat the process.
     fn drop_old_frames(&mut self, next_buffer_pts: Duration, queue_start: Instant) {
@@ -268,16 +327,22 @@ impl VideoQueueInput {
         let next_output_buffer_nanos = next_buffer_pts.as_nanos();
 
         loop {
-            let closest_diff_frame_index = self
-                .queue
-                .iter()
-                .enumerate()
-                .min_by_key(|(_index, frame)| {
-                    frame.pts.as_nanos().abs_diff(next_output_buffer_nanos)
-                })
-                .map(|(index, _frame)| index);
+            let first_used_frame_index = match self.frame_rate_conversion {
+                FrameRateConversion::Nearest => self
+                    .queue
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_index, frame)| {
+                        frame.pts.as_nanos().abs_diff(next_output_buffer_nanos)
+                    })
+                    .map(|(index, _frame)| index),
+                FrameRateConversion::Blend => self
+                    .queue
+                    .iter()
+                    .rposition(|frame| frame.pts <= next_buffer_pts),
+            };
 
-            if let Some(index) = closest_diff_frame_index {
+            if let Some(index) = first_used_frame_index {
                 self.queue.drain(0..index);
             }
 
@@ -359,9 +424,73 @@ impl VideoQueueInput {
 
     fn try_enqueue_frame(&mut self) -> Result<(), TryRecvError> {
         let frame = self.receiver.try_recv()?;
+        if let PipelineEvent::Data(frame) = &frame {
+            if self.last_resolution != Some(frame.resolution) {
+                self.last_resolution = Some(frame.resolution);
+                self.event_emitter.emit(Event::InputResolutionChanged {
+                    input_id: self.input_id.clone(),
+                    resolution: frame.resolution,
+                });
+            }
+        }
         let mut frames = self.input_frames_processor.process_new_chunk(frame);
+        if let Some(last_input_pts) = self.last_input_pts {
+            let late_frames = frames
+                .iter()
+                .filter(|frame| frame.pts < last_input_pts)
+                .count();
+            self.metrics.late_frames.add(late_frames as u64);
+        }
         self.queue.append(&mut frames);
 
         Ok(())
     }
 }
+
+/// Returns `next_frame` with its weight if `pts` is between PTS values of both frames.
+fn blended_frame(frame: &Frame, next_frame: Frame, pts: Duration) -> Option<BlendedFrame> {
+    if frame.pts >= pts || next_frame.pts <= pts {
+        return None;
+    }
+    let weight = (pts - frame.pts).as_secs_f64() / (next_frame.pts - frame.pts).as_secs_f64();
+    Some(BlendedFrame {
+        frame: next_frame,
+        weight: weight as f32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use compositor_render::{FrameData, Resolution, YuvPlanes};
+
+    use super::*;
+
+    fn frame(pts_ms: u64) -> Frame {
+        Frame {
+            data: FrameData::PlanarYuv420(YuvPlanes {
+                y_plane: bytes::Bytes::new(),
+                u_plane: bytes::Bytes::new(),
+                v_plane: bytes::Bytes::new(),
+            }),
+            resolution: Resolution {
+                width: 0,
+                height: 0,
+            },
+            pts: Duration::from_millis(pts_ms),
+        }
+    }
+
+    #[test]
+    fn blends_frames_around_pts() {
+        // 24 fps input in 60 fps output
+        let blended = blended_frame(&frame(0), frame(41), Duration::from_micros(16_400)).unwrap();
+        assert_eq!(blended.frame.pts, Duration::from_millis(41));
+        assert!((blended.weight - 0.4).abs() < 1e-6, "{}", blended.weight);
+    }
+
+    #[test]
+    fn does_not_blend_exact_frame() {
+        assert!(blended_frame(&frame(40), frame(80), Duration::from_millis(40)).is_none());
+        assert!(blended_frame(&frame(40), frame(80), Duration::from_millis(80)).is_none());
+    }
+}
@@ -5,10 +5,11 @@ use crate::{
     registry,
     scene::SceneError,
     transformations::{
-        image_renderer::ImageError, web_renderer::chromium_context::WebRendererContextError,
+        image_renderer::ImageError, lottie_renderer::LottieError,
+        web_renderer::chromium_context::WebRendererContextError,
     },
 };
-use crate::{OutputId, RendererId};
+use crate::{InputId, OutputId, RendererId, Resolution};
 
 pub use crate::registry::RegisterError;
 pub use crate::wgpu::WgpuError;
@@ -33,9 +34,15 @@ pub enum RegisterRendererError {
     #[error("Failed to register shader \"{1}\".")]
     Shader(#[source] CreateShaderError, RendererId),
 
+    #[error("Failed to register compute shader \"{1}\".")]
+    ComputeShader(#[source] CreateShaderError, RendererId),
+
     #[error("Failed to register image \"{1}\".")]
     Image(#[source] ImageError, RendererId),
 
+    #[error("Failed to register Lottie animation \"{1}\".")]
+    Lottie(#[source] LottieError, RendererId),
+
     #[error("Failed to register web renderer \"{1}\".")]
     Web(#[source] CreateWebRendererError, RendererId),
 }
@@ -52,6 +59,24 @@ pub enum RenderSceneError {
     WgpuError(#[from] WgpuError),
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum RenderPreviewError {
+    #[error("Failed to build preview scene.")]
+    UpdateScene(#[from] UpdateSceneError),
+
+    #[error("Failed to render preview.")]
+    Render(#[from] RenderSceneError),
+
+    #[error("Failed to download preview from the GPU.")]
+    Download(#[source] wgpu::BufferAsyncError),
+
+    #[error("Preview resolution {}x{} exceeds the maximal texture size {max}x{max} supported by the GPU.", .0.width, .0.height, max = .1)]
+    ResolutionTooLarge(Resolution, usize),
+
+    #[error("Image of input \"{0}\" ({width}x{height}) exceeds the maximal texture size {max}x{max} supported by the GPU.", width = .1.width, height = .1.height, max = .2)]
+    InputTooLarge(InputId, Resolution, usize),
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum UpdateSceneError {
     #[error(transparent)]
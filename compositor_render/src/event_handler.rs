@@ -10,7 +10,7 @@ use log::trace;
 #[derive(Debug, Clone)]
 pub struct Event {
     pub kind: String,
-    pub properties: Vec<(String, String)>,
+    pub properties: Vec<(String, serde_json::Value)>,
 }
 
 pub fn emit_event<T: Into<Event>>(event: T) {
@@ -18,16 +18,30 @@ pub use registry::RegistryType;
 pub use state::Renderer;
 pub use state::RendererOptions;
 pub use state::RendererSpec;
+pub use state::Snapshot;
+pub use state::SnapshotDownload;
 
 pub use wgpu::WgpuFeatures;
 pub use wgpu::{create_wgpu_ctx, required_wgpu_features, set_required_wgpu_limits, WgpuComponents};
 
+pub mod compute_shader {
+    pub use crate::transformations::compute_shader::{
+        ComputeShaderSpec, COMPUTE_SHADER_MAX_STORAGE_BUFFERS,
+    };
+}
+
 pub mod image {
     pub use crate::transformations::image_renderer::{ImageSource, ImageSpec, ImageType};
 }
 
+pub mod lottie {
+    pub use crate::transformations::lottie_renderer::{LottieSource, LottieSpec};
+}
+
 pub mod shader {
-    pub use crate::transformations::shader::ShaderSpec;
+    pub use crate::transformations::shader::{
+        ShaderPassInput, ShaderPassResolution, ShaderPassSpec, ShaderSpec,
+    };
 }
 
 pub mod web_renderer {
@@ -18,19 +18,23 @@ pub struct UnregisterError {
     renderer_id: RendererId,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RegistryType {
     Shader,
+    ComputeShader,
     WebRenderer,
     Image,
+    Lottie,
 }
 
 impl RegistryType {
     fn registry_item_name(&self) -> &'static str {
         match self {
             RegistryType::Shader => "shader",
+            RegistryType::ComputeShader => "compute shader",
             RegistryType::WebRenderer => "web renderer instance",
             RegistryType::Image => "image",
+            RegistryType::Lottie => "Lottie animation",
         }
     }
 }
@@ -52,6 +56,10 @@ impl<T: Clone> RendererRegistry<T> {
         self.registry.get(key).cloned()
     }
 
+    pub(crate) fn ids(&self) -> Vec<RendererId> {
+        self.registry.keys().cloned().collect()
+    }
+
     pub(crate) fn register(&mut self, id: RendererId, renderer: T) -> Result<(), RegisterError> {
         if self.registry.contains_key(&id) {
             return Err(RegisterError::KeyTaken {
@@ -68,10 +76,22 @@ impl<T: Clone> RendererRegistry<T> {
     pub(crate) fn unregister(&mut self, id: &RendererId) -> Result<(), UnregisterError> {
         match self.registry.remove(id) {
             Some(_) => Ok(()),
-            None => Err(UnregisterError {
-                item_type: self.registry_type.registry_item_name(),
-                renderer_id: id.clone(),
-            }),
+            None => Err(self.not_found_error(id)),
+        }
+    }
+
+    /// Returns the same error as `unregister`, but does not remove anything.
+    pub(crate) fn check_unregister(&self, id: &RendererId) -> Result<(), UnregisterError> {
+        match self.registry.contains_key(id) {
+            true => Ok(()),
+            false => Err(self.not_found_error(id)),
+        }
+    }
+
+    fn not_found_error(&self, id: &RendererId) -> UnregisterError {
+        UnregisterError {
+            item_type: self.registry_type.registry_item_name(),
+            renderer_id: id.clone(),
         }
     }
 }
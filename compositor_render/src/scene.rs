@@ -1,16 +1,20 @@
 use std::sync::Arc;
 use std::time::Duration;
 
+use crate::transformations::compute_shader::ComputeShader;
 use crate::transformations::image_renderer::Image;
+use crate::transformations::lottie_renderer::{Lottie, LottieRenderParams};
 use crate::transformations::shader::validation::error::ParametersValidationError;
 use crate::transformations::shader::Shader;
 use crate::transformations::text_renderer::TextRenderParams;
 use crate::transformations::web_renderer::WebRenderer;
 use crate::{InputId, OutputId, RendererId, Resolution};
 
+use self::compute_shader_component::StatefulComputeShaderComponent;
 use self::image_component::StatefulImageComponent;
 use self::input_stream_component::StatefulInputStreamComponent;
 use self::layout::StatefulLayoutComponent;
+use self::lottie_component::StatefulLottieComponent;
 use self::scene_state::{BuildStateTreeCtx, IntermediateNode};
 use self::shader_component::StatefulShaderComponent;
 use self::text_component::StatefulTextComponent;
@@ -19,14 +23,18 @@ use self::web_view_component::StatefulWebViewComponent;
 pub(crate) use layout::LayoutNode;
 pub(crate) use scene_state::{OutputNode, SceneState};
 pub(crate) use shader_component::ShaderComponentParams;
+pub(crate) use transition::cubic_bezier_easing;
 
 pub use components::*;
 pub use types::*;
+pub use validation::SceneValidationError;
 
 mod components;
+mod compute_shader_component;
 mod image_component;
 mod input_stream_component;
 mod layout;
+mod lottie_component;
 mod rescaler_component;
 mod scene_state;
 mod shader_component;
@@ -49,8 +57,10 @@ pub struct OutputScene {
 pub enum Component {
     InputStream(InputStreamComponent),
     Shader(ShaderComponent),
+    ComputeShader(ComputeShaderComponent),
     WebView(WebViewComponent),
     Image(ImageComponent),
+    Lottie(LottieComponent),
     Text(TextComponent),
     View(ViewComponent),
     Tiles(TilesComponent),
@@ -65,8 +75,10 @@ pub enum Component {
 enum StatefulComponent {
     InputStream(StatefulInputStreamComponent),
     Shader(StatefulShaderComponent),
+    ComputeShader(StatefulComputeShaderComponent),
     WebView(StatefulWebViewComponent),
     Image(StatefulImageComponent),
+    Lottie(StatefulLottieComponent),
     Text(StatefulTextComponent),
     Layout(StatefulLayoutComponent),
 }
@@ -91,8 +103,10 @@ pub(crate) struct Node {
 pub(crate) enum NodeParams {
     InputStream(InputId),
     Shader(ShaderComponentParams, Arc<Shader>),
+    ComputeShader(ShaderComponentParams, Arc<ComputeShader>),
     Web(Vec<ComponentId>, Arc<WebRenderer>),
     Image(Image),
+    Lottie(LottieRenderParams, Lottie),
     Text(TextRenderParams),
     Layout(LayoutNode),
 }
@@ -102,8 +116,10 @@ impl StatefulComponent {
         match self {
             StatefulComponent::InputStream(input) => Some(input.size.width),
             StatefulComponent::Shader(shader) => Some(shader.component.size.width),
+            StatefulComponent::ComputeShader(shader) => Some(shader.component.size.width),
             StatefulComponent::WebView(web) => Some(web.size().width),
             StatefulComponent::Image(image) => Some(image.size().width),
+            StatefulComponent::Lottie(lottie) => Some(lottie.size().width),
             StatefulComponent::Text(text) => Some(text.width()),
             StatefulComponent::Layout(layout) => match layout.position(pts) {
                 Position::Static { width, .. } => width,
@@ -116,8 +132,10 @@ impl StatefulComponent {
         match self {
             StatefulComponent::InputStream(input) => Some(input.size.height),
             StatefulComponent::Shader(shader) => Some(shader.component.size.height),
+            StatefulComponent::ComputeShader(shader) => Some(shader.component.size.height),
             StatefulComponent::WebView(web) => Some(web.size().height),
             StatefulComponent::Image(image) => Some(image.size().height),
+            StatefulComponent::Lottie(lottie) => Some(lottie.size().height),
             StatefulComponent::Text(text) => Some(text.height()),
             StatefulComponent::Layout(layout) => match layout.position(pts) {
                 Position::Static { height, .. } => height,
@@ -130,8 +148,10 @@ impl StatefulComponent {
         match self {
             StatefulComponent::InputStream(input) => input.intermediate_node(),
             StatefulComponent::Shader(shader) => shader.intermediate_node(),
+            StatefulComponent::ComputeShader(shader) => shader.intermediate_node(),
             StatefulComponent::WebView(web) => web.intermediate_node(),
             StatefulComponent::Image(image) => image.intermediate_node(),
+            StatefulComponent::Lottie(lottie) => lottie.intermediate_node(),
             StatefulComponent::Text(text) => text.intermediate_node(),
             StatefulComponent::Layout(layout) => match layout {
                 StatefulLayoutComponent::View(view) => view.intermediate_node(),
@@ -145,8 +165,10 @@ impl StatefulComponent {
         match self {
             StatefulComponent::InputStream(_) => vec![],
             StatefulComponent::Shader(shader) => shader.children.iter_mut().collect(),
+            StatefulComponent::ComputeShader(shader) => shader.children.iter_mut().collect(),
             StatefulComponent::WebView(web) => web.children.iter_mut().collect(),
             StatefulComponent::Image(_) => vec![],
+            StatefulComponent::Lottie(_) => vec![],
             StatefulComponent::Text(_) => vec![],
             StatefulComponent::Layout(layout) => layout.children_mut(),
         }
@@ -156,8 +178,10 @@ impl StatefulComponent {
         match self {
             StatefulComponent::InputStream(input) => input.component_id(),
             StatefulComponent::Shader(shader) => shader.component_id(),
+            StatefulComponent::ComputeShader(shader) => shader.component_id(),
             StatefulComponent::WebView(web) => web.component_id(),
             StatefulComponent::Image(image) => image.component_id(),
+            StatefulComponent::Lottie(lottie) => lottie.component_id(),
             StatefulComponent::Text(text) => text.component_id(),
             StatefulComponent::Layout(layout) => layout.component_id(),
         }
@@ -174,8 +198,10 @@ impl Component {
         match self {
             Component::InputStream(input) => input.stateful_component(ctx),
             Component::Shader(shader) => shader.stateful_component(ctx),
+            Component::ComputeShader(shader) => shader.stateful_component(ctx),
             Component::WebView(web_view) => web_view.stateful_component(ctx),
             Component::Image(image) => image.stateful_component(ctx),
+            Component::Lottie(lottie) => lottie.stateful_component(ctx),
             Component::Text(text) => text.stateful_component(ctx),
             Component::View(view) => view.stateful_component(ctx),
             Component::Tiles(tiles) => tiles.stateful_component(ctx),
@@ -195,9 +221,15 @@ pub enum SceneError {
     #[error("Image \"{0}\" does not exist. You have to register it first before using it in the scene definition.")]
     ImageNotFound(RendererId),
 
+    #[error("Lottie animation \"{0}\" does not exist. You have to register it first before using it in the scene definition.")]
+    LottieNotFound(RendererId),
+
     #[error("Shader \"{0}\" does not exist. You have to register it first before using it in the scene definition.")]
     ShaderNotFound(RendererId),
 
+    #[error("Compute shader \"{0}\" does not exist. You have to register it first before using it in the scene definition.")]
+    ComputeShaderNotFound(RendererId),
+
     #[error("Instance of web renderer \"{0}\" does not exist. You have to register it first before using it in the scene definition.")]
     WebRendererNotFound(RendererId),
 
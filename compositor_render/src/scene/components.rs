@@ -36,11 +36,29 @@ pub struct ShaderComponent {
     pub size: Size,
 }
 
+#[derive(Debug, Clone)]
+pub struct ComputeShaderComponent {
+    pub id: Option<ComponentId>,
+    pub children: Vec<Component>,
+
+    pub compute_shader_id: RendererId,
+    pub shader_param: Option<ShaderParam>,
+
+    pub size: Size,
+}
+
 #[derive(Debug, Clone)]
 pub enum ShaderParam {
     F32(f32),
     U32(u32),
     I32(i32),
+    /// Passed to the shader as `u32` equal to 0 or 1.
+    Bool(bool),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+    /// List of columns of `f32` matrix.
+    Matrix(Vec<Vec<f32>>),
     List(Vec<ShaderParam>),
     Struct(Vec<ShaderParamStructField>),
 }
@@ -65,6 +83,31 @@ pub struct ImageComponent {
     pub image_id: RendererId,
 }
 
+#[derive(Debug, Clone)]
+pub struct LottieComponent {
+    pub id: Option<ComponentId>,
+    pub lottie_id: RendererId,
+    /// Restart animation after the last frame, otherwise the last frame is displayed.
+    pub repeat: bool,
+    pub text_overrides: Vec<LottieTextOverride>,
+    pub color_overrides: Vec<LottieColorOverride>,
+}
+
+/// Replaces content of a text layer with the matching name.
+#[derive(Debug, Clone)]
+pub struct LottieTextOverride {
+    pub layer_name: Arc<str>,
+    pub text: Arc<str>,
+}
+
+/// Replaces color of fills and strokes inside a layer, shape group or of
+/// a fill/stroke with the matching name.
+#[derive(Debug, Clone)]
+pub struct LottieColorOverride {
+    pub name: Arc<str>,
+    pub color: RGBAColor,
+}
+
 #[derive(Debug, Clone)]
 pub struct TextComponent {
     pub id: Option<ComponentId>,
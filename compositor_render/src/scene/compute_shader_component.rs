@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use crate::transformations::compute_shader::ComputeShader;
+
+use super::{
+    scene_state::BuildStateTreeCtx, shader_component::ShaderComponentParams, Component,
+    ComponentId, ComputeShaderComponent, IntermediateNode, SceneError, StatefulComponent,
+};
+
+#[derive(Debug, Clone)]
+pub(super) struct StatefulComputeShaderComponent {
+    pub(super) component: ShaderComponentParams,
+    pub(super) children: Vec<StatefulComponent>,
+    pub(super) shader: Arc<ComputeShader>,
+}
+
+impl StatefulComputeShaderComponent {
+    pub(super) fn component_id(&self) -> Option<&ComponentId> {
+        self.component.id.as_ref()
+    }
+
+    pub(super) fn intermediate_node(&self) -> IntermediateNode {
+        let children = self
+            .children
+            .iter()
+            .map(StatefulComponent::intermediate_node)
+            .collect();
+
+        IntermediateNode::ComputeShader {
+            shader: self.clone(),
+            children,
+        }
+    }
+}
+
+impl ComputeShaderComponent {
+    pub(super) fn stateful_component(
+        self,
+        ctx: &BuildStateTreeCtx,
+    ) -> Result<StatefulComponent, SceneError> {
+        let shader = ctx
+            .renderers
+            .compute_shaders
+            .get(&self.compute_shader_id)
+            .ok_or_else(|| SceneError::ComputeShaderNotFound(self.compute_shader_id.clone()))?;
+        if let Some(params) = &self.shader_param {
+            shader.validate_params(params).map_err(|err| {
+                SceneError::ShaderNodeParametersValidationError(err, self.compute_shader_id.clone())
+            })?
+        }
+
+        let children = self
+            .children
+            .into_iter()
+            .map(|c| Component::stateful_component(c, ctx))
+            .collect::<Result<_, _>>()?;
+        Ok(StatefulComponent::ComputeShader(
+            StatefulComputeShaderComponent {
+                component: ShaderComponentParams {
+                    id: self.id,
+                    shader_param: self.shader_param,
+                    size: self.size,
+                },
+                shader,
+                children,
+            },
+        ))
+    }
+}
@@ -115,7 +115,9 @@ impl StatefulLayoutComponent {
                     child_index_offset += 1;
                 }
                 StatefulComponent::Shader(_)
+                | StatefulComponent::ComputeShader(_)
                 | StatefulComponent::Image(_)
+                | StatefulComponent::Lottie(_)
                 | StatefulComponent::Text(_)
                 | StatefulComponent::WebView(_) => {
                     child_index_offset += 1; // no state
@@ -142,6 +144,10 @@ impl StatefulLayoutComponent {
                 index,
                 size: shader.component.size,
             },
+            StatefulComponent::ComputeShader(shader) => LayoutContent::ChildNode {
+                index,
+                size: shader.component.size,
+            },
             StatefulComponent::WebView(web) => LayoutContent::ChildNode {
                 index,
                 size: web.size(),
@@ -150,6 +156,10 @@ impl StatefulLayoutComponent {
                 index,
                 size: image.size(),
             },
+            StatefulComponent::Lottie(lottie) => LayoutContent::ChildNode {
+                index,
+                size: lottie.size(),
+            },
             StatefulComponent::Text(text) => LayoutContent::ChildNode {
                 index,
                 size: text.size(),
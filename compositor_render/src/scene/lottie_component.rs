@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use crate::transformations::lottie_renderer::{Lottie, LottieRenderParams};
+
+use super::{
+    scene_state::BuildStateTreeCtx, ComponentId, IntermediateNode, LottieComponent, SceneError,
+    Size, StatefulComponent,
+};
+
+#[derive(Debug, Clone)]
+pub(super) struct StatefulLottieComponent {
+    pub(super) component: LottieComponent,
+    pub(super) lottie: Lottie,
+    /// PTS of the first frame of the animation. Preserved between scene
+    /// updates as long as component keeps the same id and animation.
+    start_pts: Duration,
+}
+
+impl StatefulLottieComponent {
+    pub(super) fn component_id(&self) -> Option<&ComponentId> {
+        self.component.id.as_ref()
+    }
+
+    pub(super) fn size(&self) -> Size {
+        self.lottie.resolution().into()
+    }
+
+    pub(super) fn intermediate_node(&self) -> IntermediateNode {
+        IntermediateNode::Lottie(self.clone())
+    }
+
+    pub(super) fn render_params(&self) -> LottieRenderParams {
+        LottieRenderParams {
+            start_pts: self.start_pts,
+            repeat: self.component.repeat,
+            text_overrides: self.component.text_overrides.clone(),
+            color_overrides: self.component.color_overrides.clone(),
+        }
+    }
+}
+
+impl LottieComponent {
+    pub(super) fn stateful_component(
+        self,
+        ctx: &BuildStateTreeCtx,
+    ) -> Result<StatefulComponent, SceneError> {
+        let lottie = ctx
+            .renderers
+            .lotties
+            .get(&self.lottie_id)
+            .ok_or_else(|| SceneError::LottieNotFound(self.lottie_id.clone()))?;
+
+        let previous_start_pts = self
+            .id
+            .as_ref()
+            .and_then(|id| ctx.prev_state.get(id))
+            .and_then(|state| match state {
+                StatefulComponent::Lottie(prev) if prev.component.lottie_id == self.lottie_id => {
+                    Some(prev.start_pts)
+                }
+                _ => None,
+            });
+
+        Ok(StatefulComponent::Lottie(StatefulLottieComponent {
+            start_pts: previous_start_pts.unwrap_or(ctx.last_render_pts),
+            component: self,
+            lottie,
+        }))
+    }
+}
@@ -8,12 +8,14 @@ use crate::{
 };
 
 use super::{
+    compute_shader_component::StatefulComputeShaderComponent,
     image_component::StatefulImageComponent,
     input_stream_component::StatefulInputStreamComponent,
     layout::{LayoutNode, SizedLayoutComponent, StatefulLayoutComponent},
+    lottie_component::StatefulLottieComponent,
     shader_component::StatefulShaderComponent,
     text_component::StatefulTextComponent,
-    validation::validate_scene_update,
+    validation::{scene_update_errors, validate_scene_update, SceneValidationError},
     web_view_component::StatefulWebViewComponent,
     ComponentId, Node, NodeParams, OutputScene, Position, SceneError, Size, StatefulComponent,
 };
@@ -71,14 +73,80 @@ impl SceneState {
         self.output_states.remove(output_id);
     }
 
+    /// Builds the scene the same way as [`SceneState::update_scenes`], but does not
+    /// store the result, so the current scene of the output is not affected.
+    pub(crate) fn build_scene_preview(
+        &self,
+        output_scene: &OutputScene,
+        renderers: &Renderers,
+        text_renderer_ctx: &TextRendererCtx,
+    ) -> Result<OutputNode, SceneError> {
+        validate_scene_update(&self.output_scenes, std::slice::from_ref(output_scene))?;
+        let (_, node) = self.build_output_scene(output_scene, renderers, text_renderer_ctx)?;
+        Ok(node)
+    }
+
+    /// Checks the scene the same way as [`SceneState::update_scenes`], but each component
+    /// is checked on its own, so errors of all components are returned together with
+    /// their paths.
+    pub(crate) fn validate_scene(
+        &self,
+        output_scene: &OutputScene,
+        renderers: &Renderers,
+        text_renderer_ctx: &TextRendererCtx,
+    ) -> Vec<SceneValidationError> {
+        let mut errors =
+            scene_update_errors(&self.output_scenes, std::slice::from_ref(output_scene));
+        let ctx = self.build_state_tree_ctx(&output_scene.output_id, renderers, text_renderer_ctx);
+
+        // Size of the root component is defined by the output resolution, for other
+        // components it is required if they are a separate node of the tree.
+        let mut components = vec![("root".to_string(), output_scene.scene_root.clone(), false)];
+        while let Some((path, mut component, size_required)) = components.pop() {
+            let children = component.take_children();
+            let children_size_required = !component.is_layout();
+            let result = component.stateful_component(&ctx).and_then(|state| {
+                if size_required {
+                    state.intermediate_node().node_size(self.last_pts)?;
+                }
+                Ok(())
+            });
+            if let Err(error) = result {
+                errors.push(SceneValidationError {
+                    path: path.clone(),
+                    error,
+                });
+            }
+            // reversed, so errors are reported in the order of the components in the request
+            let children = children
+                .into_iter()
+                .rev()
+                .map(|(suffix, child)| (format!("{path}{suffix}"), child, children_size_required));
+            components.extend(children);
+        }
+        errors
+    }
+
     pub(crate) fn update_scene(
         &mut self,
         output_scene: OutputScene,
         renderers: &Renderers,
         text_renderer_ctx: &TextRendererCtx,
     ) -> Result<OutputNode, SceneError> {
-        let output_id = output_scene.output_id.clone();
-        validate_scene_update(&self.output_scenes, &output_scene)?;
+        let mut output_nodes =
+            self.update_scenes(vec![output_scene], renderers, text_renderer_ctx)?;
+        Ok(output_nodes.remove(0))
+    }
+
+    /// Updates scenes of multiple outputs. If any of the scenes is invalid, none
+    /// of them is applied.
+    pub(crate) fn update_scenes(
+        &mut self,
+        output_scenes: Vec<OutputScene>,
+        renderers: &Renderers,
+        text_renderer_ctx: &TextRendererCtx,
+    ) -> Result<Vec<OutputNode>, SceneError> {
+        validate_scene_update(&self.output_scenes, &output_scenes)?;
 
         for (_, output) in self.output_states.iter_mut() {
             recalculate_layout(
@@ -89,21 +157,36 @@ impl SceneState {
             )
         }
 
-        let ctx = BuildStateTreeCtx {
-            prev_state: self
-                .output_states
-                .get(&output_id)
-                .map(|o| {
-                    let mut components = HashMap::new();
-                    gather_components_with_id(&o.root, &mut components);
-                    components
-                })
-                .unwrap_or_default(),
-            last_render_pts: self.last_pts,
-            input_resolutions: &self.input_resolutions,
-            text_renderer_ctx,
-            renderers,
-        };
+        let built_scenes = output_scenes
+            .into_iter()
+            .map(|output_scene| {
+                let (state, node) =
+                    self.build_output_scene(&output_scene, renderers, text_renderer_ctx)?;
+                Ok((output_scene, state, node))
+            })
+            .collect::<Result<Vec<_>, SceneError>>()?;
+
+        let output_nodes = built_scenes
+            .into_iter()
+            .map(|(output_scene, state, node)| {
+                let output_id = output_scene.output_id.clone();
+                self.output_scenes.insert(output_id.clone(), output_scene);
+                self.output_states.insert(output_id, state);
+                node
+            })
+            .collect();
+
+        Ok(output_nodes)
+    }
+
+    fn build_output_scene(
+        &self,
+        output_scene: &OutputScene,
+        renderers: &Renderers,
+        text_renderer_ctx: &TextRendererCtx,
+    ) -> Result<(OutputSceneState, OutputNode), SceneError> {
+        let output_id = &output_scene.output_id;
+        let ctx = self.build_state_tree_ctx(output_id, renderers, text_renderer_ctx);
 
         let output_state_tree = OutputSceneState {
             root: output_scene.scene_root.clone().stateful_component(&ctx)?,
@@ -119,10 +202,30 @@ impl SceneState {
             resolution: output_scene.resolution,
         };
 
-        self.output_scenes.insert(output_id.clone(), output_scene);
-        self.output_states.insert(output_id, output_state_tree);
+        Ok((output_state_tree, output_node_tree))
+    }
 
-        Ok(output_node_tree)
+    fn build_state_tree_ctx<'a>(
+        &'a self,
+        output_id: &OutputId,
+        renderers: &'a Renderers,
+        text_renderer_ctx: &'a TextRendererCtx,
+    ) -> BuildStateTreeCtx<'a> {
+        BuildStateTreeCtx {
+            prev_state: self
+                .output_states
+                .get(output_id)
+                .map(|o| {
+                    let mut components = HashMap::new();
+                    gather_components_with_id(&o.root, &mut components);
+                    components
+                })
+                .unwrap_or_default(),
+            last_render_pts: self.last_pts,
+            input_resolutions: &self.input_resolutions,
+            text_renderer_ctx,
+            renderers,
+        }
     }
 }
 
@@ -133,11 +236,16 @@ pub(super) enum IntermediateNode {
         shader: StatefulShaderComponent,
         children: Vec<IntermediateNode>,
     },
+    ComputeShader {
+        shader: StatefulComputeShaderComponent,
+        children: Vec<IntermediateNode>,
+    },
     WebView {
         web: StatefulWebViewComponent,
         children: Vec<IntermediateNode>,
     },
     Image(StatefulImageComponent),
+    Lottie(StatefulLottieComponent),
     Text(StatefulTextComponent),
     Layout {
         root: StatefulLayoutComponent,
@@ -168,6 +276,13 @@ impl IntermediateNode {
                     .map(|node| node.build_tree(None, pts))
                     .collect::<Result<_, _>>()?,
             }),
+            IntermediateNode::ComputeShader { shader, children } => Ok(Node {
+                params: NodeParams::ComputeShader(shader.component, shader.shader),
+                children: children
+                    .into_iter()
+                    .map(|node| node.build_tree(None, pts))
+                    .collect::<Result<_, _>>()?,
+            }),
             IntermediateNode::WebView { web, children } => Ok(Node {
                 params: NodeParams::Web(web.children_ids, web.instance), // TODO: enforce resolution
                 children: children
@@ -188,6 +303,10 @@ impl IntermediateNode {
                 params: NodeParams::Image(image.image),
                 children: vec![],
             }),
+            IntermediateNode::Lottie(lottie) => Ok(Node {
+                params: NodeParams::Lottie(lottie.render_params(), lottie.lottie),
+                children: vec![],
+            }),
             IntermediateNode::Text(text) => Ok(Node {
                 params: NodeParams::Text(text.params),
                 children: vec![],
@@ -202,8 +321,13 @@ impl IntermediateNode {
                 shader,
                 children: _,
             } => Ok(shader.component.size),
+            IntermediateNode::ComputeShader {
+                shader,
+                children: _,
+            } => Ok(shader.component.size),
             IntermediateNode::WebView { web, children: _ } => Ok(web.size()),
             IntermediateNode::Image(image) => Ok(image.size()),
+            IntermediateNode::Lottie(lottie) => Ok(lottie.size()),
             IntermediateNode::Text(text) => Ok(text.size()),
             IntermediateNode::Layout { root, children: _ } => {
                 let (width, height) = match root.position(pts) {
@@ -279,6 +403,14 @@ fn gather_components_with_id<'a>(
                 gather_components_with_id(child, components);
             }
         }
+        StatefulComponent::ComputeShader(shader) => {
+            if let Some(id) = shader.component_id() {
+                components.insert(id.clone(), component);
+            }
+            for child in shader.children.iter() {
+                gather_components_with_id(child, components);
+            }
+        }
         StatefulComponent::WebView(web) => {
             if let Some(id) = web.component_id() {
                 components.insert(id.clone(), component);
@@ -292,6 +424,11 @@ fn gather_components_with_id<'a>(
                 components.insert(id.clone(), component);
             }
         }
+        StatefulComponent::Lottie(lottie) => {
+            if let Some(id) = lottie.component_id() {
+                components.insert(id.clone(), component);
+            }
+        }
         StatefulComponent::Text(image) => {
             if let Some(id) = image.component_id() {
                 components.insert(id.clone(), component);
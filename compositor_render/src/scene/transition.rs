@@ -1,6 +1,8 @@
 use std::time::Duration;
 
-use self::{bounce::bounce_easing, cubic_bezier::cubic_bezier_easing};
+use self::bounce::bounce_easing;
+
+pub(crate) use self::cubic_bezier::cubic_bezier_easing;
 
 use super::{types::interpolation::InterpolationState, InterpolationKind};
 
@@ -1,16 +1,29 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
-use crate::{OutputId, RendererId};
+use crate::{InputId, OutputId, RendererId};
 
-use super::{Component, ComponentId, OutputScene, SceneError};
+use super::{Component, ComponentId, InputStreamComponent, OutputScene, SceneError};
+
+/// Error found while validating a scene, together with a path to the component
+/// that caused it, e.g. `root.children[1].child`.
+#[derive(Debug)]
+pub struct SceneValidationError {
+    pub path: String,
+    pub error: SceneError,
+}
 
 impl Component {
     fn component_id(&self) -> Option<&ComponentId> {
         match self {
             Component::InputStream(input) => input.id.as_ref(),
             Component::Shader(shader) => shader.id.as_ref(),
+            Component::ComputeShader(shader) => shader.id.as_ref(),
             Component::WebView(web) => web.id.as_ref(),
             Component::Image(image) => image.id.as_ref(),
+            Component::Lottie(lottie) => lottie.id.as_ref(),
             Component::Text(text) => text.id.as_ref(),
             Component::View(view) => view.id.as_ref(),
             Component::Tiles(tiles) => tiles.id.as_ref(),
@@ -18,84 +31,142 @@ impl Component {
         }
     }
 
-    fn children(&self) -> Vec<&Component> {
-        match self {
-            Component::InputStream(_input) => vec![],
-            Component::Shader(shader) => shader.children.iter().collect(),
-            Component::WebView(view) => view.children.iter().collect(),
-            Component::Image(_image) => vec![],
-            Component::Text(_text) => vec![],
-            Component::View(view) => view.children.iter().collect(),
-            Component::Tiles(tiles) => tiles.children.iter().collect(),
-            Component::Rescaler(rescaler) => vec![rescaler.child.as_ref()],
+    /// Children paired with their path relative to the parent.
+    fn children(&self) -> Vec<(String, &Component)> {
+        let children = match self {
+            Component::Shader(shader) => &shader.children,
+            Component::ComputeShader(shader) => &shader.children,
+            Component::WebView(view) => &view.children,
+            Component::View(view) => &view.children,
+            Component::Tiles(tiles) => &tiles.children,
+            Component::Rescaler(rescaler) => {
+                return vec![(".child".to_string(), rescaler.child.as_ref())]
+            }
+            Component::InputStream(_)
+            | Component::Image(_)
+            | Component::Lottie(_)
+            | Component::Text(_) => return Vec::new(),
+        };
+        children
+            .iter()
+            .enumerate()
+            .map(|(index, child)| (format!(".children[{index}]"), child))
+            .collect()
+    }
+
+    /// Replaces children with placeholders that keep their ids, so the component
+    /// can be checked on its own. Returned children are paired with their path
+    /// relative to the parent.
+    pub(super) fn take_children(&mut self) -> Vec<(String, Component)> {
+        fn placeholder(child: &Component) -> Component {
+            Component::InputStream(InputStreamComponent {
+                id: child.component_id().cloned(),
+                input_id: InputId(Arc::from("")),
+            })
         }
+
+        let children = match self {
+            Component::Shader(shader) => &mut shader.children,
+            Component::ComputeShader(shader) => &mut shader.children,
+            Component::WebView(view) => &mut view.children,
+            Component::View(view) => &mut view.children,
+            Component::Tiles(tiles) => &mut tiles.children,
+            Component::Rescaler(rescaler) => {
+                let child = placeholder(&rescaler.child);
+                let child = std::mem::replace(rescaler.child.as_mut(), child);
+                return vec![(".child".to_string(), child)];
+            }
+            Component::InputStream(_)
+            | Component::Image(_)
+            | Component::Lottie(_)
+            | Component::Text(_) => return Vec::new(),
+        };
+        let placeholders = children.iter().map(placeholder).collect();
+        std::mem::replace(children, placeholders)
+            .into_iter()
+            .enumerate()
+            .map(|(index, child)| (format!(".children[{index}]"), child))
+            .collect()
+    }
+
+    /// Layout components of the same tree are rendered together, so only the
+    /// top-level one needs a known size.
+    pub(super) fn is_layout(&self) -> bool {
+        matches!(
+            self,
+            Component::View(_) | Component::Tiles(_) | Component::Rescaler(_)
+        )
     }
 }
 
+/// Checks constraints that span all outputs. All `updated_outputs` are validated
+/// together, so e.g. a component can be moved from one output to another.
 pub(super) fn validate_scene_update(
     old_outputs: &HashMap<OutputId, OutputScene>,
-    updated_output: &OutputScene,
+    updated_outputs: &[OutputScene],
 ) -> Result<(), SceneError> {
-    let mut updated_outputs: HashMap<&OutputId, &OutputScene> = old_outputs.iter().collect();
-    updated_outputs.insert(&updated_output.output_id, updated_output);
-    let updated_outputs: Vec<&OutputScene> = updated_outputs.values().copied().collect();
-
-    validate_component_ids_uniqueness(&updated_outputs)?;
-    validate_web_renderer_ids_uniqueness(&updated_outputs)?;
-    Ok(())
+    match scene_update_errors(old_outputs, updated_outputs)
+        .into_iter()
+        .next()
+    {
+        Some(err) => Err(err.error),
+        None => Ok(()),
+    }
 }
 
-fn validate_component_ids_uniqueness(outputs: &[&OutputScene]) -> Result<(), SceneError> {
-    let mut ids: HashSet<&ComponentId> = HashSet::new();
-
-    fn visit<'a>(
-        component: &'a Component,
-        ids: &mut HashSet<&'a ComponentId>,
-    ) -> Result<(), SceneError> {
-        let id = component.component_id();
-        if let Some(id) = id {
-            if ids.contains(id) {
-                return Err(SceneError::DuplicateComponentId(id.clone()));
-            }
-
-            ids.insert(id);
-        }
+/// Returns all errors of the constraints checked by [`validate_scene_update`]. Outputs
+/// that are not updated are visited first, so conflicts are reported on components
+/// of the updated outputs.
+pub(super) fn scene_update_errors(
+    old_outputs: &HashMap<OutputId, OutputScene>,
+    updated_outputs: &[OutputScene],
+) -> Vec<SceneValidationError> {
+    let mut outputs: Vec<&OutputScene> = old_outputs
+        .values()
+        .filter(|old_output| {
+            !updated_outputs
+                .iter()
+                .any(|output| output.output_id == old_output.output_id)
+        })
+        .collect();
+    outputs.extend(updated_outputs);
 
-        component
-            .children()
-            .into_iter()
-            .try_for_each(|c| visit(c, ids))
+    let mut validator = UniquenessValidator::default();
+    for output in outputs {
+        validator.visit(&output.scene_root, "root".to_string());
     }
-
-    outputs
-        .iter()
-        .try_for_each(|output| visit(&output.scene_root, &mut ids))
+    validator.errors
 }
 
-fn validate_web_renderer_ids_uniqueness(outputs: &[&OutputScene]) -> Result<(), SceneError> {
-    let mut web_renderer_ids: HashSet<&RendererId> = HashSet::new();
+#[derive(Default)]
+struct UniquenessValidator<'a> {
+    component_ids: HashSet<&'a ComponentId>,
+    web_renderer_ids: HashSet<&'a RendererId>,
+    errors: Vec<SceneValidationError>,
+}
 
-    fn visit<'a>(
-        component: &'a Component,
-        ids: &mut HashSet<&'a RendererId>,
-    ) -> Result<(), SceneError> {
+impl<'a> UniquenessValidator<'a> {
+    fn visit(&mut self, component: &'a Component, path: String) {
+        if let Some(id) = component.component_id() {
+            if !self.component_ids.insert(id) {
+                self.errors.push(SceneValidationError {
+                    path: path.clone(),
+                    error: SceneError::DuplicateComponentId(id.clone()),
+                });
+            }
+        }
         if let Component::WebView(web_view) = component {
             let instance_id = &web_view.instance_id;
-            if ids.contains(instance_id) {
-                return Err(SceneError::WebRendererUsageNotExclusive(
-                    instance_id.clone(),
-                ));
+            if !self.web_renderer_ids.insert(instance_id) {
+                self.errors.push(SceneValidationError {
+                    path: path.clone(),
+                    error: SceneError::WebRendererUsageNotExclusive(instance_id.clone()),
+                });
             }
-            ids.insert(instance_id);
         }
 
-        component
-            .children()
-            .into_iter()
-            .try_for_each(|c| visit(c, ids))
+        for (suffix, child) in component.children() {
+            self.visit(child, format!("{path}{suffix}"));
+        }
     }
-
-    outputs
-        .iter()
-        .try_for_each(|output| visit(&output.scene_root, &mut web_renderer_ids))
 }
@@ -1,26 +1,34 @@
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use bytes::Bytes;
 use glyphon::fontdb;
+use wgpu::BufferAsyncError;
 
 use crate::error::{RegisterRendererError, UnregisterRendererError};
 
-use crate::scene::{Component, OutputScene};
+use crate::scene::{Component, OutputScene, SceneValidationError};
+use crate::transformations::compute_shader::ComputeShader;
 use crate::transformations::image_renderer::Image;
+use crate::transformations::lottie_renderer::Lottie;
 use crate::transformations::shader::Shader;
 use crate::transformations::web_renderer::{self, WebRenderer};
+use crate::{compute_shader, image, lottie, OutputFrameFormat, Resolution};
 use crate::{
-    error::{InitRendererEngineError, RenderSceneError, UpdateSceneError},
+    error::{InitRendererEngineError, RenderPreviewError, RenderSceneError, UpdateSceneError},
     transformations::{
         text_renderer::TextRendererCtx, web_renderer::chromium_context::ChromiumContext,
     },
     types::Framerate,
-    EventLoop, FrameSet, InputId, OutputId,
+    EventLoop, Frame, FrameData, FrameSet, InputId, OutputId,
 };
-use crate::{image, OutputFrameFormat, Resolution};
 use crate::{
     scene::SceneState,
-    wgpu::{WgpuCtx, WgpuErrorScope},
+    wgpu::{
+        texture::{NodeTexture, RGBATexture},
+        WgpuCtx, WgpuErrorScope,
+    },
 };
 use crate::{shader, RegistryType, RendererId};
 
@@ -48,6 +56,25 @@ pub struct RendererOptions {
 #[derive(Clone)]
 pub struct Renderer(Arc<Mutex<InnerRenderer>>);
 
+/// RGBA frame read back from or uploaded to the renderer's textures.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub resolution: Resolution,
+    /// RGBA 8-bit per channel, sRGB, without row padding.
+    pub data: Bytes,
+}
+
+/// Snapshot copy that was already scheduled on the GPU. Waiting for it does not
+/// require any locks, so it should happen after they are released.
+pub struct SnapshotDownload(Box<dyn FnOnce() -> Result<Snapshot, BufferAsyncError> + Send>);
+
+impl SnapshotDownload {
+    /// Blocks until the copy is finished.
+    pub fn wait(self) -> Result<Snapshot, BufferAsyncError> {
+        (self.0)()
+    }
+}
+
 struct InnerRenderer {
     wgpu_ctx: Arc<WgpuCtx>,
     text_renderer_ctx: Arc<TextRendererCtx>,
@@ -71,6 +98,7 @@ pub(crate) struct RenderCtx<'a> {
 pub(crate) struct RegisterCtx {
     pub(crate) wgpu_ctx: Arc<WgpuCtx>,
     pub(crate) chromium: Arc<ChromiumContext>,
+    pub(crate) text_renderer_ctx: Arc<TextRendererCtx>,
 }
 
 /// RendererSpec provides configuration necessary to construct Renderer. Renderers
@@ -79,8 +107,10 @@ pub(crate) struct RegisterCtx {
 #[derive(Debug, Clone)]
 pub enum RendererSpec {
     Shader(shader::ShaderSpec),
+    ComputeShader(compute_shader::ComputeShaderSpec),
     WebRenderer(web_renderer::WebRendererSpec),
     Image(image::ImageSpec),
+    Lottie(lottie::LottieSpec),
 }
 
 impl Renderer {
@@ -128,6 +158,16 @@ impl Renderer {
                 let mut guard = self.0.lock().unwrap();
                 Ok(guard.renderers.shaders.register(id, Arc::new(shader))?)
             }
+            RendererSpec::ComputeShader(spec) => {
+                let shader = ComputeShader::new(&ctx.wgpu_ctx, spec)
+                    .map_err(|err| RegisterRendererError::ComputeShader(err, id.clone()))?;
+
+                let mut guard = self.0.lock().unwrap();
+                Ok(guard
+                    .renderers
+                    .compute_shaders
+                    .register(id, Arc::new(shader))?)
+            }
             RendererSpec::WebRenderer(params) => {
                 let web = WebRenderer::new(&ctx, &id, params)
                     .map_err(|err| RegisterRendererError::Web(err, id.clone()))?;
@@ -142,6 +182,13 @@ impl Renderer {
                 let mut guard = self.0.lock().unwrap();
                 Ok(guard.renderers.images.register(id, asset)?)
             }
+            RendererSpec::Lottie(spec) => {
+                let lottie = Lottie::new(&ctx, spec)
+                    .map_err(|err| RegisterRendererError::Lottie(err, id.clone()))?;
+
+                let mut guard = self.0.lock().unwrap();
+                Ok(guard.renderers.lotties.register(id, lottie)?)
+            }
         }
     }
 
@@ -153,8 +200,35 @@ impl Renderer {
         let mut guard = self.0.lock().unwrap();
         match registry_type {
             RegistryType::Shader => guard.renderers.shaders.unregister(renderer_id)?,
+            RegistryType::ComputeShader => {
+                guard.renderers.compute_shaders.unregister(renderer_id)?
+            }
             RegistryType::WebRenderer => guard.renderers.web_renderers.unregister(renderer_id)?,
             RegistryType::Image => guard.renderers.images.unregister(renderer_id)?,
+            RegistryType::Lottie => guard.renderers.lotties.unregister(renderer_id)?,
+        }
+        Ok(())
+    }
+
+    /// Checks whether `unregister_renderer` with the same arguments would succeed.
+    pub fn check_unregister_renderer(
+        &self,
+        renderer_id: &RendererId,
+        registry_type: RegistryType,
+    ) -> Result<(), UnregisterRendererError> {
+        let guard = self.0.lock().unwrap();
+        match registry_type {
+            RegistryType::Shader => guard.renderers.shaders.check_unregister(renderer_id)?,
+            RegistryType::ComputeShader => guard
+                .renderers
+                .compute_shaders
+                .check_unregister(renderer_id)?,
+            RegistryType::WebRenderer => guard
+                .renderers
+                .web_renderers
+                .check_unregister(renderer_id)?,
+            RegistryType::Image => guard.renderers.images.check_unregister(renderer_id)?,
+            RegistryType::Lottie => guard.renderers.lotties.check_unregister(renderer_id)?,
         }
         Ok(())
     }
@@ -164,8 +238,71 @@ impl Renderer {
         ctx.add_font(font_source);
     }
 
+    pub fn registered_renderers(&self, registry_type: RegistryType) -> Vec<RendererId> {
+        let guard = self.0.lock().unwrap();
+        match registry_type {
+            RegistryType::Shader => guard.renderers.shaders.ids(),
+            RegistryType::ComputeShader => guard.renderers.compute_shaders.ids(),
+            RegistryType::WebRenderer => guard.renderers.web_renderers.ids(),
+            RegistryType::Image => guard.renderers.images.ids(),
+            RegistryType::Lottie => guard.renderers.lotties.ids(),
+        }
+    }
+
+    pub fn font_families(&self) -> Vec<String> {
+        let ctx = self.0.lock().unwrap().text_renderer_ctx.clone();
+        ctx.font_families()
+    }
+
+    /// Resolution of the last frame received from the input, `None` if the input
+    /// is not registered or no frame was rendered yet.
+    pub fn input_resolution(&self, input_id: &InputId) -> Option<Resolution> {
+        self.0
+            .lock()
+            .unwrap()
+            .render_graph
+            .inputs
+            .get(input_id)
+            .and_then(|(node_texture, _)| node_texture.resolution())
+    }
+
+    /// Starts reading back the last frame received from the input, `None` if the
+    /// input is not registered or no frame was rendered yet.
+    pub fn start_input_snapshot(&self, input_id: &InputId) -> Option<SnapshotDownload> {
+        let guard = self.0.lock().unwrap();
+        let node_texture = guard
+            .render_graph
+            .inputs
+            .get(input_id)
+            .map(|(node_texture, _)| node_texture);
+        start_snapshot_download(&guard.wgpu_ctx, node_texture)
+    }
+
+    /// Starts reading back the last frame rendered for the output, before it was
+    /// converted to the output format. `None` if the output is not registered or
+    /// no frame was rendered yet.
+    pub fn start_output_snapshot(&self, output_id: &OutputId) -> Option<SnapshotDownload> {
+        let guard = self.0.lock().unwrap();
+        let node_texture = guard
+            .render_graph
+            .outputs
+            .get(output_id)
+            .map(|output| output.root.output_texture(&guard.render_graph.inputs));
+        start_snapshot_download(&guard.wgpu_ctx, node_texture)
+    }
+
     pub fn render(&self, input: FrameSet<InputId>) -> Result<FrameSet<OutputId>, RenderSceneError> {
-        self.0.lock().unwrap().render(input)
+        self.0.lock().unwrap().render(input, None)
+    }
+
+    /// Renders only the specified outputs. Other outputs are not updated, e.g. when
+    /// their framerate does not have a frame at this PTS.
+    pub fn render_outputs(
+        &self,
+        input: FrameSet<InputId>,
+        outputs: &HashSet<OutputId>,
+    ) -> Result<FrameSet<OutputId>, RenderSceneError> {
+        self.0.lock().unwrap().render(input, Some(outputs))
     }
 
     pub fn update_scene(
@@ -175,10 +312,61 @@ impl Renderer {
         output_format: OutputFrameFormat,
         scene_root: Component,
     ) -> Result<(), UpdateSceneError> {
+        let output_scene = OutputScene {
+            output_id,
+            scene_root,
+            resolution,
+        };
+        self.0
+            .lock()
+            .unwrap()
+            .update_scenes(vec![(output_scene, output_format)])
+    }
+
+    /// Updates scenes of multiple outputs at once. Scenes are validated together
+    /// and either all of them are applied or none of them is, so frames rendered
+    /// afterwards never mix old and new scenes.
+    pub fn update_scenes(
+        &mut self,
+        output_scenes: Vec<(OutputScene, OutputFrameFormat)>,
+    ) -> Result<(), UpdateSceneError> {
+        self.0.lock().unwrap().update_scenes(output_scenes)
+    }
+
+    /// Runs all steps of the scene update against the current state of the renderer,
+    /// but the result is discarded instead of being applied. Errors of individual
+    /// components are returned together with their paths, errors that are not related
+    /// to a single component are returned as `Err`.
+    pub fn validate_scene(
+        &self,
+        output_scene: OutputScene,
+        output_format: OutputFrameFormat,
+    ) -> Result<Vec<SceneValidationError>, UpdateSceneError> {
+        self.0
+            .lock()
+            .unwrap()
+            .validate_scene(output_scene, output_format)
+    }
+
+    /// Renders a single frame of the scene without registering an output. Registered
+    /// renderers (images, shaders, ...) are available, but inputs are limited to
+    /// the RGBA frames passed in `inputs`.
+    pub fn render_preview(
+        &self,
+        resolution: Resolution,
+        scene_root: Component,
+        pts: Duration,
+        inputs: HashMap<InputId, Snapshot>,
+    ) -> Result<Snapshot, RenderPreviewError> {
         self.0
             .lock()
             .unwrap()
-            .update_scene(output_id, resolution, scene_root, output_format)
+            .render_preview(resolution, scene_root, pts, inputs)
+    }
+
+    /// Maximal width and height of a texture supported by the GPU.
+    pub fn max_texture_dimension(&self) -> usize {
+        self.0.lock().unwrap().max_texture_dimension()
     }
 
     pub fn wgpu_ctx(&self) -> (Arc<wgpu::Device>, Arc<wgpu::Queue>) {
@@ -187,6 +375,25 @@ impl Renderer {
     }
 }
 
+/// Copy is scheduled while the renderer is locked, but waiting for the GPU happens
+/// in [`SnapshotDownload::wait`], so rendering is not blocked in the meantime.
+fn start_snapshot_download(
+    ctx: &WgpuCtx,
+    node_texture: Option<&NodeTexture>,
+) -> Option<SnapshotDownload> {
+    let node_texture = node_texture?;
+    let resolution = node_texture.resolution()?;
+    let download = node_texture.start_download(ctx)?;
+    let device = ctx.device.clone();
+    Some(SnapshotDownload(Box::new(move || {
+        device.poll(wgpu::MaintainBase::Wait);
+        Ok(Snapshot {
+            resolution,
+            data: download()?,
+        })
+    })))
+}
+
 impl InnerRenderer {
     pub fn new(opts: RendererOptions) -> Result<Self, InitRendererEngineError> {
         let wgpu_ctx = WgpuCtx::new(opts.force_gpu, opts.wgpu_features, opts.wgpu_ctx)?;
@@ -205,16 +412,22 @@ impl InnerRenderer {
         })
     }
 
+    fn max_texture_dimension(&self) -> usize {
+        self.wgpu_ctx.device.limits().max_texture_dimension_2d as usize
+    }
+
     pub(super) fn register_ctx(&self) -> RegisterCtx {
         RegisterCtx {
             wgpu_ctx: self.wgpu_ctx.clone(),
             chromium: self.chromium_context.clone(),
+            text_renderer_ctx: self.text_renderer_ctx.clone(),
         }
     }
 
     pub fn render(
         &mut self,
         inputs: FrameSet<InputId>,
+        outputs: Option<&HashSet<OutputId>>,
     ) -> Result<FrameSet<OutputId>, RenderSceneError> {
         let ctx = &mut RenderCtx {
             wgpu_ctx: &self.wgpu_ctx,
@@ -235,29 +448,26 @@ impl InnerRenderer {
 
         let pts = inputs.pts;
         populate_inputs(ctx, &mut self.render_graph, inputs);
-        run_transforms(ctx, &mut self.render_graph, pts);
-        let frames = read_outputs(ctx, &mut self.render_graph, pts);
+        run_transforms(ctx, &mut self.render_graph, pts, outputs);
+        let frames = read_outputs(ctx, &mut self.render_graph, pts, outputs);
 
         scope.pop(&ctx.wgpu_ctx.device)?;
 
-        Ok(FrameSet { frames, pts })
+        Ok(FrameSet {
+            frames,
+            blended_frames: HashMap::new(),
+            pts,
+        })
     }
 
-    pub fn update_scene(
+    pub fn update_scenes(
         &mut self,
-        output_id: OutputId,
-        resolution: Resolution,
-        scene_root: Component,
-        output_format: OutputFrameFormat,
+        output_scenes: Vec<(OutputScene, OutputFrameFormat)>,
     ) -> Result<(), UpdateSceneError> {
-        let output = OutputScene {
-            output_id: output_id.clone(),
-            scene_root,
-            resolution,
-        };
-        let output_node =
+        let (output_scenes, output_formats): (Vec<_>, Vec<_>) = output_scenes.into_iter().unzip();
+        let output_nodes =
             self.scene
-                .update_scene(output, &self.renderers, &self.text_renderer_ctx)?;
+                .update_scenes(output_scenes, &self.renderers, &self.text_renderer_ctx)?;
         self.render_graph.update(
             &RenderCtx {
                 wgpu_ctx: &self.wgpu_ctx,
@@ -265,9 +475,145 @@ impl InnerRenderer {
                 renderers: &self.renderers,
                 stream_fallback_timeout: self.stream_fallback_timeout,
             },
-            output_node,
-            output_format,
+            output_nodes.into_iter().zip(output_formats).collect(),
         )?;
         Ok(())
     }
+
+    /// Render graph is created just for this call and dropped afterwards, so state
+    /// of the registered outputs is not affected.
+    pub fn validate_scene(
+        &self,
+        output_scene: OutputScene,
+        output_format: OutputFrameFormat,
+    ) -> Result<Vec<SceneValidationError>, UpdateSceneError> {
+        let errors =
+            self.scene
+                .validate_scene(&output_scene, &self.renderers, &self.text_renderer_ctx);
+        if !errors.is_empty() {
+            return Ok(errors);
+        }
+
+        let output_node = self.scene.build_scene_preview(
+            &output_scene,
+            &self.renderers,
+            &self.text_renderer_ctx,
+        )?;
+        let mut render_graph = RenderGraph::empty();
+        for input_id in self.render_graph.inputs.keys() {
+            render_graph.register_input(input_id.clone());
+        }
+        render_graph.update(
+            &RenderCtx {
+                wgpu_ctx: &self.wgpu_ctx,
+                text_renderer_ctx: &self.text_renderer_ctx,
+                renderers: &self.renderers,
+                stream_fallback_timeout: self.stream_fallback_timeout,
+            },
+            vec![(output_node, output_format)],
+        )?;
+        Ok(Vec::new())
+    }
+
+    /// Scene and render graph are created just for this call, so state of the
+    /// registered outputs is not affected. Frames are uploaded and read back as RGBA,
+    /// so the result is not affected by a conversion to YUV.
+    pub fn render_preview(
+        &mut self,
+        resolution: Resolution,
+        scene_root: Component,
+        pts: Duration,
+        inputs: HashMap<InputId, Snapshot>,
+    ) -> Result<Snapshot, RenderPreviewError> {
+        // Larger textures would be invalid, and the fallback frame below is allocated
+        // on the CPU, so the resolution has to be checked upfront.
+        let max_dimension = self.max_texture_dimension();
+        let exceeds_max = |resolution: Resolution| {
+            resolution.width > max_dimension || resolution.height > max_dimension
+        };
+        if exceeds_max(resolution) {
+            return Err(RenderPreviewError::ResolutionTooLarge(
+                resolution,
+                max_dimension,
+            ));
+        }
+        if let Some((input_id, input)) = inputs
+            .iter()
+            .find(|(_, input)| exceeds_max(input.resolution))
+        {
+            return Err(RenderPreviewError::InputTooLarge(
+                input_id.clone(),
+                input.resolution,
+                max_dimension,
+            ));
+        }
+
+        let ctx = &mut RenderCtx {
+            wgpu_ctx: &self.wgpu_ctx,
+            text_renderer_ctx: &self.text_renderer_ctx,
+            renderers: &self.renderers,
+            stream_fallback_timeout: self.stream_fallback_timeout,
+        };
+        let output_id = OutputId("__preview".into());
+
+        let mut scene = SceneState::new();
+        let mut render_graph = RenderGraph::empty();
+        let input_resolutions = inputs
+            .iter()
+            .map(|(input_id, input)| (input_id.clone(), input.resolution))
+            .collect();
+        scene.register_render_event(pts, input_resolutions);
+
+        let mut frame_set = FrameSet::new(pts);
+        for (input_id, input) in inputs {
+            let texture = RGBATexture::new(ctx.wgpu_ctx, input.resolution);
+            texture.upload(ctx.wgpu_ctx, &input.data);
+            render_graph.register_input(input_id.clone());
+            frame_set.frames.insert(
+                input_id,
+                Frame {
+                    data: FrameData::Rgba8UnormWgpuTexture(Arc::new(
+                        texture.texture_owned().texture,
+                    )),
+                    resolution: input.resolution,
+                    pts,
+                },
+            );
+        }
+
+        let output = OutputScene {
+            output_id: output_id.clone(),
+            scene_root,
+            resolution,
+        };
+        let output_node = scene
+            .update_scene(output, &self.renderers, &self.text_renderer_ctx)
+            .map_err(UpdateSceneError::from)?;
+        render_graph.update(ctx, vec![(output_node, OutputFrameFormat::RgbaWgpuTexture)])?;
+
+        let scope = WgpuErrorScope::push(&ctx.wgpu_ctx.device);
+        populate_inputs(ctx, &mut render_graph, frame_set);
+        run_transforms(ctx, &mut render_graph, pts, None);
+        // render graph has only one output
+        let output = render_graph.outputs.get(&output_id).unwrap();
+        let download = start_snapshot_download(
+            ctx.wgpu_ctx,
+            Some(output.root.output_texture(&render_graph.inputs)),
+        );
+        scope
+            .pop(&ctx.wgpu_ctx.device)
+            .map_err(RenderSceneError::from)?;
+
+        match download {
+            Some(download) => download.wait().map_err(RenderPreviewError::Download),
+            // Nothing was rendered, e.g. the scene contains only inputs that
+            // were not provided. Registered outputs produce black frames then.
+            None => Ok(Snapshot {
+                resolution,
+                data: [0, 0, 0, 255]
+                    .repeat(resolution.width * resolution.height)
+                    .into(),
+            }),
+        }
+    }
 }
@@ -4,8 +4,11 @@ use std::time::Duration;
 use std::vec;
 
 use crate::scene::{self, ComponentId, ShaderComponentParams};
+use crate::transformations::compute_shader::node::ComputeShaderNode;
+use crate::transformations::compute_shader::ComputeShader;
 use crate::transformations::image_renderer::Image;
 use crate::transformations::layout::LayoutNode;
+use crate::transformations::lottie_renderer::{Lottie, LottieNode, LottieRenderParams};
 use crate::transformations::shader::node::ShaderNode;
 use crate::transformations::shader::Shader;
 use crate::InputId;
@@ -21,9 +24,11 @@ use super::RenderCtx;
 
 pub(super) enum InnerRenderNode {
     Shader(ShaderNode),
+    ComputeShader(ComputeShaderNode),
     Web(WebRendererNode),
     Text(TextRendererNode),
     Image(ImageNode),
+    Lottie(LottieNode),
     Layout(LayoutNode),
     InputStreamRef(InputId),
 }
@@ -40,11 +45,15 @@ impl InnerRenderNode {
             InnerRenderNode::Shader(ref shader) => {
                 shader.render(ctx.wgpu_ctx, sources, target, pts);
             }
+            InnerRenderNode::ComputeShader(ref shader) => {
+                shader.render(ctx.wgpu_ctx, sources, target, pts);
+            }
             InnerRenderNode::Web(renderer) => renderer.render(ctx, sources, target),
             InnerRenderNode::Text(renderer) => {
                 renderer.render(ctx, target);
             }
             InnerRenderNode::Image(ref node) => node.render(ctx, target, pts),
+            InnerRenderNode::Lottie(node) => node.render(ctx, target, pts),
             InnerRenderNode::InputStreamRef(_) => {
                 // Nothing to do, textures on input nodes should be populated
                 // at the start of render loop
@@ -75,10 +84,14 @@ impl RenderNode {
             scene::NodeParams::Shader(shader_params, shader) => {
                 Self::new_shader_node(ctx, children, shader_params, shader)
             }
+            scene::NodeParams::ComputeShader(shader_params, shader) => {
+                Self::new_compute_shader_node(ctx, children, shader_params, shader)
+            }
             scene::NodeParams::Web(children_ids, web_renderer) => {
                 Self::new_web_renderer_node(ctx, children, children_ids, web_renderer)
             }
             scene::NodeParams::Image(image) => Self::new_image_node(image),
+            scene::NodeParams::Lottie(params, lottie) => Self::new_lottie_node(params, lottie),
             scene::NodeParams::Text(text_params) => Self::new_text_node(text_params),
             scene::NodeParams::Layout(layout_provider) => {
                 Self::new_layout_node(ctx, children, layout_provider)
@@ -113,6 +126,29 @@ impl RenderNode {
             shader,
             &shader_params.shader_param,
             &shader_params.size.into(),
+            children.len(),
+        ));
+        let mut output = NodeTexture::new();
+        output.ensure_size(ctx.wgpu_ctx, shader_params.size.into());
+
+        Self {
+            renderer: node,
+            output,
+            children,
+        }
+    }
+
+    fn new_compute_shader_node(
+        ctx: &RenderCtx,
+        children: Vec<RenderNode>,
+        shader_params: ShaderComponentParams,
+        shader: Arc<ComputeShader>,
+    ) -> Self {
+        let node = InnerRenderNode::ComputeShader(ComputeShaderNode::new(
+            ctx,
+            shader,
+            &shader_params.shader_param,
+            &shader_params.size.into(),
         ));
         let mut output = NodeTexture::new();
         output.ensure_size(ctx.wgpu_ctx, shader_params.size.into());
@@ -153,6 +189,17 @@ impl RenderNode {
         }
     }
 
+    pub(super) fn new_lottie_node(params: LottieRenderParams, lottie: Lottie) -> Self {
+        let node = InnerRenderNode::Lottie(LottieNode::new(lottie, params));
+        let output = NodeTexture::new();
+
+        Self {
+            renderer: node,
+            output,
+            children: vec![],
+        }
+    }
+
     pub(super) fn new_text_node(params: TextRenderParams) -> Self {
         let node = InnerRenderNode::Text(TextRendererNode::new(params));
         let output = NodeTexture::new();
@@ -42,22 +42,29 @@ impl RenderGraph {
     pub(super) fn update(
         &mut self,
         ctx: &RenderCtx,
-        output: OutputNode,
-        output_format: OutputFrameFormat,
+        outputs: Vec<(OutputNode, OutputFrameFormat)>,
     ) -> Result<(), UpdateSceneError> {
         // TODO: If we want nodes to be stateful we could try reusing nodes instead
         //       of recreating them on every scene update
         let scope = WgpuErrorScope::push(&ctx.wgpu_ctx.device);
 
-        let output_tree = OutputRenderTree {
-            root: Self::create_node(ctx, output.node)?,
-            output_texture: OutputTexture::new(ctx.wgpu_ctx, output.resolution),
-            output_format,
-        };
+        let output_trees = outputs
+            .into_iter()
+            .map(|(output, output_format)| {
+                let output_tree = OutputRenderTree {
+                    root: Self::create_node(ctx, output.node)?,
+                    output_texture: OutputTexture::new(ctx.wgpu_ctx, output.resolution),
+                    output_format,
+                };
+                Ok((output.output_id, output_tree))
+            })
+            .collect::<Result<Vec<_>, UpdateSceneError>>()?;
 
         scope.pop(&ctx.wgpu_ctx.device)?;
 
-        self.outputs.insert(output.output_id, output_tree);
+        // Trees are replaced only after all of them were created, so the outputs
+        // are either all updated or none of them is.
+        self.outputs.extend(output_trees);
 
         Ok(())
     }
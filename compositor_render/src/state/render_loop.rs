@@ -1,4 +1,8 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 
 use tracing::error;
 
@@ -25,6 +29,9 @@ pub(super) fn populate_inputs(
         }
 
         input_textures.upload(ctx.wgpu_ctx, frame);
+        if let Some(next_frame) = frame_set.blended_frames.remove(input_id) {
+            input_textures.upload_next_frame(ctx.wgpu_ctx, next_frame.frame, next_frame.weight);
+        }
     }
 
     ctx.wgpu_ctx.queue.submit([]);
@@ -53,9 +60,14 @@ pub(super) fn read_outputs(
     ctx: &RenderCtx,
     scene: &mut RenderGraph,
     pts: Duration,
+    outputs: Option<&HashSet<OutputId>>,
 ) -> HashMap<OutputId, Frame> {
     let mut partial_textures = Vec::with_capacity(scene.outputs.len());
-    for (output_id, output) in &scene.outputs {
+    let scene_outputs = scene
+        .outputs
+        .iter()
+        .filter(|(output_id, _)| outputs.map_or(true, |outputs| outputs.contains(output_id)));
+    for (output_id, output) in scene_outputs {
         match output.root.output_texture(&scene.inputs).state() {
             Some(node) => match output.output_format {
                 OutputFrameFormat::PlanarYuv420Bytes => {
@@ -168,9 +180,16 @@ pub(super) fn read_outputs(
     result
 }
 
-pub(super) fn run_transforms(ctx: &mut RenderCtx, scene: &mut RenderGraph, pts: Duration) {
-    for output in scene.outputs.values_mut() {
-        render_node(ctx, &scene.inputs, pts, &mut output.root);
+pub(super) fn run_transforms(
+    ctx: &mut RenderCtx,
+    scene: &mut RenderGraph,
+    pts: Duration,
+    outputs: Option<&HashSet<OutputId>>,
+) {
+    for (output_id, output) in scene.outputs.iter_mut() {
+        if outputs.map_or(true, |outputs| outputs.contains(output_id)) {
+            render_node(ctx, &scene.inputs, pts, &mut output.root);
+        }
     }
 }
 
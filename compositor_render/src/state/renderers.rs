@@ -4,7 +4,8 @@ use crate::{
     error::InitRendererEngineError,
     registry::{RegistryType, RendererRegistry},
     transformations::{
-        image_renderer::Image, layout::LayoutRenderer, shader::Shader, web_renderer::WebRenderer,
+        compute_shader::ComputeShader, image_renderer::Image, layout::LayoutRenderer,
+        lottie_renderer::Lottie, shader::Shader, web_renderer::WebRenderer,
     },
 };
 
@@ -12,8 +13,10 @@ use super::WgpuCtx;
 
 pub(crate) struct Renderers {
     pub(crate) shaders: RendererRegistry<Arc<Shader>>,
+    pub(crate) compute_shaders: RendererRegistry<Arc<ComputeShader>>,
     pub(crate) web_renderers: RendererRegistry<Arc<WebRenderer>>,
     pub(crate) images: RendererRegistry<Image>,
+    pub(crate) lotties: RendererRegistry<Lottie>,
     pub(crate) layout: LayoutRenderer,
 }
 
@@ -21,8 +24,10 @@ impl Renderers {
     pub fn new(wgpu_ctx: Arc<WgpuCtx>) -> Result<Self, InitRendererEngineError> {
         Ok(Self {
             shaders: RendererRegistry::new(RegistryType::Shader),
+            compute_shaders: RendererRegistry::new(RegistryType::ComputeShader),
             web_renderers: RendererRegistry::new(RegistryType::WebRenderer),
             images: RendererRegistry::new(RegistryType::Image),
+            lotties: RendererRegistry::new(RegistryType::Lottie),
             layout: LayoutRenderer::new(&wgpu_ctx)
                 .map_err(InitRendererEngineError::LayoutTransformationsInitError)?,
         })
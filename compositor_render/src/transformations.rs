@@ -1,5 +1,7 @@
+pub mod compute_shader;
 pub mod image_renderer;
 pub mod layout;
+pub mod lottie_renderer;
 pub mod shader;
 pub mod text_renderer;
 pub mod web_renderer;
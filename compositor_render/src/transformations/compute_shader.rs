@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use crate::{
+    scene::ShaderParam,
+    wgpu::{common_pipeline::CreateShaderError, WgpuCtx},
+};
+
+use self::pipeline::ComputeShaderPipeline;
+
+use super::shader::validation::error::ParametersValidationError;
+
+pub mod node;
+mod pipeline;
+
+/// Maximal number of storage buffers that can be bound to a compute shader.
+pub const COMPUTE_SHADER_MAX_STORAGE_BUFFERS: u32 = 8;
+
+pub(crate) const STORAGE_BUFFERS_GROUP: u32 = 3;
+
+#[derive(Debug, Clone)]
+pub struct ComputeShaderSpec {
+    pub source: Arc<str>,
+    /// Sizes (in bytes) of storage buffers bound in `@group(3)`, buffer with index `i`
+    /// is bound to `@binding(i)`. Buffers are zero-initialized when a component is
+    /// created and preserve their content between frames.
+    pub storage_buffers: Vec<u64>,
+}
+
+#[derive(Debug)]
+pub struct ComputeShader {
+    pipeline: ComputeShaderPipeline,
+    storage_buffers: Vec<u64>,
+}
+
+impl ComputeShader {
+    pub fn new(
+        wgpu_ctx: &Arc<WgpuCtx>,
+        spec: ComputeShaderSpec,
+    ) -> Result<Self, CreateShaderError> {
+        if spec.storage_buffers.len() > COMPUTE_SHADER_MAX_STORAGE_BUFFERS as usize {
+            return Err(CreateShaderError::TooManyStorageBuffers(
+                spec.storage_buffers.len(),
+                COMPUTE_SHADER_MAX_STORAGE_BUFFERS,
+            ));
+        }
+
+        let max_size = wgpu_ctx.device.limits().max_storage_buffer_binding_size as u64;
+        for (index, size) in spec.storage_buffers.iter().enumerate() {
+            if *size == 0 || *size % 4 != 0 || *size > max_size {
+                return Err(CreateShaderError::InvalidStorageBufferSize(
+                    index, *size, max_size,
+                ));
+            }
+        }
+
+        let pipeline = ComputeShaderPipeline::new(wgpu_ctx, spec.source, &spec.storage_buffers)?;
+
+        Ok(Self {
+            pipeline,
+            storage_buffers: spec.storage_buffers,
+        })
+    }
+
+    pub(crate) fn validate_params(
+        &self,
+        params: &ShaderParam,
+    ) -> Result<(), ParametersValidationError> {
+        self.pipeline.validate_params(params)
+    }
+}
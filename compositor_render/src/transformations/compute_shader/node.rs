@@ -0,0 +1,177 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    scene::ShaderParam,
+    state::RenderCtx,
+    transformations::shader::new_params_buffer,
+    wgpu::{
+        texture::{NodeTexture, Texture},
+        WgpuCtx,
+    },
+    Resolution,
+};
+
+use super::{
+    pipeline::{ComputeShaderPipeline, OUTPUT_TEXTURE_FORMAT},
+    ComputeShader,
+};
+
+pub struct ComputeShaderNode {
+    shader: Arc<ComputeShader>,
+    params_bind_group: wgpu::BindGroup,
+    _params_buffer: wgpu::Buffer,
+    /// Storage buffers live as long as the node, so their content is preserved
+    /// between frames until the next scene update.
+    storage_buffers: Option<(wgpu::BindGroup, Vec<wgpu::Buffer>)>,
+    output: ComputeShaderOutput,
+    resolution: Resolution,
+}
+
+/// Storage texture written by the compute shader.
+struct ComputeShaderOutput {
+    _texture: Texture,
+    /// Bind group with the storage texture used by the compute pass.
+    storage_bind_group: wgpu::BindGroup,
+    /// Bind group with the same texture used to blit it into the node texture.
+    blit_bind_group: wgpu::BindGroup,
+}
+
+impl ComputeShaderNode {
+    pub fn new(
+        ctx: &RenderCtx,
+        shader: Arc<ComputeShader>,
+        shader_params: &Option<ShaderParam>,
+        resolution: &Resolution,
+    ) -> Self {
+        let wgpu_ctx = ctx.wgpu_ctx;
+        let params = shader_params
+            .as_ref()
+            .and_then(|params| shader.pipeline.encode_params(params));
+        let params_buffer = new_params_buffer(wgpu_ctx, params);
+        let params_bind_group = wgpu_ctx
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("compute shader node params bind group"),
+                layout: shader.pipeline.params_bind_group_layout(),
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params_buffer.as_entire_binding(),
+                }],
+            });
+        let storage_buffers = shader
+            .pipeline
+            .storage_buffers_bind_group_layout()
+            .map(|layout| new_storage_buffers(wgpu_ctx, layout, &shader.storage_buffers));
+        let output = ComputeShaderOutput::new(wgpu_ctx, &shader.pipeline, *resolution);
+
+        Self {
+            shader,
+            params_bind_group,
+            _params_buffer: params_buffer,
+            storage_buffers,
+            output,
+            resolution: *resolution,
+        }
+    }
+
+    pub fn render(
+        &self,
+        wgpu_ctx: &Arc<WgpuCtx>,
+        sources: &[&NodeTexture],
+        target: &mut NodeTexture,
+        pts: Duration,
+    ) {
+        let target = target.ensure_size(wgpu_ctx, self.resolution);
+        let mut encoder = wgpu_ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("compute shader node encoder"),
+            });
+
+        self.shader.pipeline.dispatch(
+            wgpu_ctx,
+            &mut encoder,
+            &self.params_bind_group,
+            &self.output.storage_bind_group,
+            self.storage_buffers
+                .as_ref()
+                .map(|(bind_group, _)| bind_group),
+            sources,
+            self.resolution,
+            pts,
+        );
+        wgpu_ctx.utils.texture_blit.blit(
+            wgpu_ctx,
+            &mut encoder,
+            Some(&self.output.blit_bind_group),
+            &target.rgba_texture().texture().view,
+        );
+
+        wgpu_ctx.queue.submit(Some(encoder.finish()));
+    }
+}
+
+impl ComputeShaderOutput {
+    fn new(ctx: &WgpuCtx, pipeline: &ComputeShaderPipeline, resolution: Resolution) -> Self {
+        let texture = Texture::new(
+            &ctx.device,
+            Some("compute shader output texture"),
+            wgpu::Extent3d {
+                width: resolution.width as u32,
+                height: resolution.height as u32,
+                depth_or_array_layers: 1,
+            },
+            OUTPUT_TEXTURE_FORMAT,
+            wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+        );
+        let storage_bind_group = pipeline.output_bind_group(ctx, &texture.view);
+        let blit_bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("compute shader output blit bind group"),
+            layout: ctx.format.rgba_layout(),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&texture.view),
+            }],
+        });
+
+        Self {
+            _texture: texture,
+            storage_bind_group,
+            blit_bind_group,
+        }
+    }
+}
+
+fn new_storage_buffers(
+    ctx: &WgpuCtx,
+    layout: &wgpu::BindGroupLayout,
+    sizes: &[u64],
+) -> (wgpu::BindGroup, Vec<wgpu::Buffer>) {
+    // wgpu zero-initializes buffers created without `mapped_at_creation`
+    let buffers: Vec<_> = sizes
+        .iter()
+        .map(|size| {
+            ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("compute shader storage buffer"),
+                size: *size,
+                usage: wgpu::BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            })
+        })
+        .collect();
+    let entries: Vec<_> = buffers
+        .iter()
+        .enumerate()
+        .map(|(binding, buffer)| wgpu::BindGroupEntry {
+            binding: binding as u32,
+            resource: buffer.as_entire_binding(),
+        })
+        .collect();
+    let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("compute shader storage buffers bind group"),
+        layout,
+        entries: &entries,
+    });
+
+    (bind_group, buffers)
+}
@@ -0,0 +1,329 @@
+use std::{borrow::Cow, num::NonZeroU32, sync::Arc, time::Duration};
+
+use naga::{Handle, Type};
+
+use crate::{
+    scene::ShaderParam,
+    transformations::shader::{
+        encode_params,
+        validation::{
+            error::{ParametersValidationError, ShaderParseError},
+            validate_compute_shader, validate_params, validate_storage_buffers,
+        },
+        BaseShaderParameters, SHADER_INPUT_TEXTURES_AMOUNT, USER_DEFINED_BUFFER_BINDING,
+        USER_DEFINED_BUFFER_GROUP,
+    },
+    wgpu::{
+        common_pipeline::{CreateShaderError, COMPUTE_ENTRYPOINT_NAME},
+        texture::{NodeTexture, NodeTextureState, RGBATexture},
+        WgpuCtx, WgpuErrorScope,
+    },
+    Resolution,
+};
+
+use super::STORAGE_BUFFERS_GROUP;
+
+const OUTPUT_GROUP: u32 = 2;
+const SAMPLER_BINDING: u32 = 0;
+const OUTPUT_TEXTURE_BINDING: u32 = 1;
+
+/// Format of the texture that compute shader writes to. sRGB formats can't be used
+/// as storage textures, so output is blitted into the node texture after dispatch.
+pub(super) const OUTPUT_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+#[derive(Debug)]
+pub(super) struct ComputeShaderPipeline {
+    pipeline: wgpu::ComputePipeline,
+    sampler: wgpu::Sampler,
+    textures_bgl: wgpu::BindGroupLayout,
+    params_bgl: wgpu::BindGroupLayout,
+    output_bgl: wgpu::BindGroupLayout,
+    /// Only present if storage buffers were registered with the shader.
+    storage_buffers_bgl: Option<wgpu::BindGroupLayout>,
+    workgroup_size: [u32; 3],
+    module: naga::Module,
+}
+
+impl ComputeShaderPipeline {
+    pub fn new(
+        wgpu_ctx: &Arc<WgpuCtx>,
+        shader_src: Arc<str>,
+        storage_buffers: &[u64],
+    ) -> Result<Self, CreateShaderError> {
+        let scope = WgpuErrorScope::push(&wgpu_ctx.device);
+
+        let module = naga::front::wgsl::parse_str(&shader_src)
+            .map_err(|err| CreateShaderError::ParseError(ShaderParseError::new(err, shader_src)))?;
+
+        validate_compute_shader(&wgpu_ctx.compute_shader_header, &module)?;
+        validate_storage_buffers(&module, storage_buffers)?;
+
+        let workgroup_size = module
+            .entry_points
+            .iter()
+            .find(|entry_point| entry_point.name == COMPUTE_ENTRYPOINT_NAME)
+            .map(|entry_point| entry_point.workgroup_size)
+            .unwrap_or([1, 1, 1]);
+
+        let sampler = wgpu_ctx.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("compute shader sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            min_filter: wgpu::FilterMode::Linear,
+            mag_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let textures_bgl =
+            wgpu_ctx
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("compute shader textures bgl"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        count: NonZeroU32::new(SHADER_INPUT_TEXTURES_AMOUNT),
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                        },
+                    }],
+                });
+        let params_bgl =
+            wgpu_ctx
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("compute shader params bgl"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: USER_DEFINED_BUFFER_BINDING,
+                        count: None,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                    }],
+                });
+        let output_bgl =
+            wgpu_ctx
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("compute shader output bgl"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: SAMPLER_BINDING,
+                            count: None,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: OUTPUT_TEXTURE_BINDING,
+                            count: None,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::StorageTexture {
+                                access: wgpu::StorageTextureAccess::WriteOnly,
+                                format: OUTPUT_TEXTURE_FORMAT,
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                            },
+                        },
+                    ],
+                });
+        let storage_buffers_bgl = (!storage_buffers.is_empty()).then(|| {
+            let entries: Vec<_> = (0..storage_buffers.len() as u32)
+                .map(|binding| wgpu::BindGroupLayoutEntry {
+                    binding,
+                    count: None,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                })
+                .collect();
+            wgpu_ctx
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("compute shader storage buffers bgl"),
+                    entries: &entries,
+                })
+        });
+
+        let mut bind_group_layouts = vec![&textures_bgl, &params_bgl, &output_bgl];
+        bind_group_layouts.extend(storage_buffers_bgl.as_ref());
+        let pipeline_layout =
+            wgpu_ctx
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("compute shader pipeline layout"),
+                    bind_group_layouts: &bind_group_layouts,
+                    push_constant_ranges: &[wgpu::PushConstantRange {
+                        stages: wgpu::ShaderStages::COMPUTE,
+                        range: 0..BaseShaderParameters::push_constant_size(),
+                    }],
+                });
+        let shader_module = wgpu_ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Naga(Cow::Owned(module.clone())),
+            });
+        let pipeline = wgpu_ctx
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("compute shader pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &shader_module,
+                entry_point: COMPUTE_ENTRYPOINT_NAME,
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                cache: None,
+            });
+
+        scope.pop(&wgpu_ctx.device)?;
+
+        Ok(Self {
+            pipeline,
+            sampler,
+            textures_bgl,
+            params_bgl,
+            output_bgl,
+            storage_buffers_bgl,
+            workgroup_size,
+            module,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn dispatch(
+        &self,
+        wgpu_ctx: &Arc<WgpuCtx>,
+        encoder: &mut wgpu::CommandEncoder,
+        params: &wgpu::BindGroup,
+        output: &wgpu::BindGroup,
+        storage_buffers: Option<&wgpu::BindGroup>,
+        sources: &[&NodeTexture],
+        resolution: Resolution,
+        pts: Duration,
+    ) {
+        let input_textures_bg = self.input_textures_bg(wgpu_ctx, sources);
+        // All inputs are available in a single dispatch, so there is no plane to render.
+        let base_params = BaseShaderParameters::new(-1, pts, sources.len() as u32, resolution);
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("compute shader pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.pipeline);
+        compute_pass.set_push_constants(0, base_params.push_constant());
+        compute_pass.set_bind_group(0, &input_textures_bg, &[]);
+        compute_pass.set_bind_group(USER_DEFINED_BUFFER_GROUP, params, &[]);
+        compute_pass.set_bind_group(OUTPUT_GROUP, output, &[]);
+        if let Some(storage_buffers) = storage_buffers {
+            compute_pass.set_bind_group(STORAGE_BUFFERS_GROUP, storage_buffers, &[]);
+        }
+
+        let [x, y, _] = self.workgroup_size;
+        compute_pass.dispatch_workgroups(
+            (resolution.width as u32).div_ceil(x),
+            (resolution.height as u32).div_ceil(y),
+            1,
+        );
+    }
+
+    pub fn validate_params(&self, params: &ShaderParam) -> Result<(), ParametersValidationError> {
+        let ty = self
+            .user_params_type()
+            .ok_or(ParametersValidationError::NoBindingInShader)?;
+
+        validate_params(params, ty, &self.module)
+    }
+
+    /// Returns `None` if the shader does not declare user-defined binding.
+    pub fn encode_params(&self, params: &ShaderParam) -> Option<bytes::Bytes> {
+        let ty = self.user_params_type()?;
+        Some(encode_params(params, ty, &self.module))
+    }
+
+    pub fn params_bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.params_bgl
+    }
+
+    pub fn storage_buffers_bind_group_layout(&self) -> Option<&wgpu::BindGroupLayout> {
+        self.storage_buffers_bgl.as_ref()
+    }
+
+    pub fn output_bind_group(
+        &self,
+        wgpu_ctx: &WgpuCtx,
+        output_view: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        wgpu_ctx
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("compute shader output bind group"),
+                layout: &self.output_bgl,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: SAMPLER_BINDING,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: OUTPUT_TEXTURE_BINDING,
+                        resource: wgpu::BindingResource::TextureView(output_view),
+                    },
+                ],
+            })
+    }
+
+    fn user_params_type(&self) -> Option<Handle<Type>> {
+        self.module
+            .global_variables
+            .iter()
+            .find(|(_, global)| match global.binding.as_ref() {
+                Some(binding) => {
+                    (binding.group, binding.binding)
+                        == (USER_DEFINED_BUFFER_GROUP, USER_DEFINED_BUFFER_BINDING)
+                }
+
+                None => false,
+            })
+            .map(|(_, handle)| handle.ty)
+    }
+
+    fn input_textures_bg(
+        &self,
+        wgpu_ctx: &Arc<WgpuCtx>,
+        sources: &[&NodeTexture],
+    ) -> wgpu::BindGroup {
+        let mut texture_views: Vec<&wgpu::TextureView> = sources
+            .iter()
+            .map(|texture| {
+                texture
+                    .state()
+                    .map(NodeTextureState::rgba_texture)
+                    .map(RGBATexture::texture)
+                    .map_or(&wgpu_ctx.empty_texture.view, |texture| &texture.view)
+            })
+            .collect();
+
+        texture_views.extend(
+            (sources.len()..SHADER_INPUT_TEXTURES_AMOUNT as usize)
+                .map(|_| &wgpu_ctx.empty_texture.view),
+        );
+
+        wgpu_ctx
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.textures_bgl,
+                label: None,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureViewArray(&texture_views),
+                }],
+            })
+    }
+}
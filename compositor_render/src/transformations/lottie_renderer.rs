@@ -0,0 +1,268 @@
+use std::{fmt, fs, io, sync::Arc, time::Duration};
+
+use bytes::Bytes;
+use log::error;
+use resvg::{
+    tiny_skia,
+    usvg::{self, TreeParsing, TreeTextToPath},
+};
+
+use crate::{
+    scene::{LottieColorOverride, LottieTextOverride},
+    state::{RegisterCtx, RenderCtx},
+    wgpu::texture::NodeTexture,
+    Resolution,
+};
+
+use self::{animation::Animation, svg::frame_to_svg};
+
+mod animation;
+mod svg;
+
+#[derive(Debug, Clone)]
+pub struct LottieSpec {
+    pub src: LottieSource,
+    /// Resolution of the rendered animation. Defaults to the size defined
+    /// in the animation file. Aspect ratio is preserved.
+    pub resolution: Option<Resolution>,
+}
+
+#[derive(Debug, Clone)]
+pub enum LottieSource {
+    Url { url: String },
+    LocalPath { path: String },
+    Bytes { bytes: Bytes },
+}
+
+#[derive(Clone)]
+pub struct Lottie(Arc<LottieAsset>);
+
+struct LottieAsset {
+    animation: Animation,
+    resolution: Resolution,
+    /// Fonts of the text renderer available at registration. Only loaded if
+    /// animation has text layers.
+    fontdb: Option<usvg::fontdb::Database>,
+}
+
+impl Lottie {
+    pub(crate) fn new(ctx: &RegisterCtx, spec: LottieSpec) -> Result<Self, LottieError> {
+        let file = Self::download_file(&spec.src)?;
+        let json: serde_json::Value = serde_json::from_slice(&file)?;
+        let animation = Animation::parse(&json)?;
+
+        let resolution = spec.resolution.unwrap_or(Resolution {
+            width: animation.width.round() as usize,
+            height: animation.height.round() as usize,
+        });
+        if resolution.width == 0 || resolution.height == 0 {
+            return Err(LottieError::ZeroResolution);
+        }
+
+        let fontdb = animation.has_text_layers().then(|| {
+            let mut fontdb = usvg::fontdb::Database::new();
+            for source in ctx.text_renderer_ctx.font_sources() {
+                fontdb.load_font_source(usvg_font_source(source));
+            }
+            fontdb
+        });
+
+        let asset = LottieAsset {
+            animation,
+            resolution,
+            fontdb,
+        };
+        // Render the first frame to detect unsupported content on registration.
+        let first_frame = frame_to_svg(
+            &asset.animation,
+            &LottieRenderParams::default(),
+            asset.animation.in_point,
+        );
+        asset.rasterize(&first_frame)?;
+
+        Ok(Self(Arc::new(asset)))
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        self.0.resolution
+    }
+
+    fn download_file(src: &LottieSource) -> Result<Bytes, LottieError> {
+        match src {
+            LottieSource::Url { url } => {
+                #[cfg(target_arch = "wasm32")]
+                return Err(LottieError::LottieSourceUrlNotSupported);
+
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    let response = reqwest::blocking::get(url)?;
+                    let response = response.error_for_status()?;
+                    Ok(response.bytes()?)
+                }
+            }
+            LottieSource::LocalPath { path } => {
+                let file = fs::read(path)?;
+                Ok(Bytes::from(file))
+            }
+            LottieSource::Bytes { bytes } => Ok(bytes.clone()),
+        }
+    }
+}
+
+/// `usvg` depends on a different version of `fontdb` than the text renderer.
+fn usvg_font_source(source: glyphon::fontdb::Source) -> usvg::fontdb::Source {
+    match source {
+        glyphon::fontdb::Source::Binary(data) => usvg::fontdb::Source::Binary(data),
+        glyphon::fontdb::Source::File(path) => usvg::fontdb::Source::File(path),
+        glyphon::fontdb::Source::SharedFile(path, data) => {
+            usvg::fontdb::Source::SharedFile(path, data)
+        }
+    }
+}
+
+impl fmt::Debug for Lottie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lottie")
+            .field("resolution", &self.0.resolution)
+            .field("frame_rate", &self.0.animation.frame_rate)
+            .field("in_point", &self.0.animation.in_point)
+            .field("out_point", &self.0.animation.out_point)
+            .finish()
+    }
+}
+
+impl LottieAsset {
+    fn rasterize(&self, svg: &str) -> Result<tiny_skia::Pixmap, LottieRenderError> {
+        let mut tree = usvg::Tree::from_str(svg, &usvg::Options::default())?;
+        if let Some(fontdb) = &self.fontdb {
+            tree.convert_text(fontdb);
+        }
+        let tree = resvg::Tree::from_usvg(&tree);
+
+        let mut pixmap =
+            tiny_skia::Pixmap::new(self.resolution.width as u32, self.resolution.height as u32)
+                .ok_or(LottieRenderError::PixmapAllocationFailed)?;
+        let scale_multiplier = f64::min(
+            self.resolution.width as f64 / self.animation.width,
+            self.resolution.height as f64 / self.animation.height,
+        ) as f32;
+        tree.render(
+            tiny_skia::Transform::from_scale(scale_multiplier, scale_multiplier),
+            &mut pixmap.as_mut(),
+        );
+        Ok(pixmap)
+    }
+
+    /// Number of the frame (counted from the in point of the animation)
+    /// that should be displayed `elapsed` time after the animation started.
+    fn frame_index(&self, elapsed: Duration, repeat: bool) -> u64 {
+        let frame_count =
+            ((self.animation.out_point - self.animation.in_point).ceil() as u64).max(1);
+        let frame_index = (elapsed.as_secs_f64() * self.animation.frame_rate).floor() as u64;
+        match repeat {
+            true => frame_index % frame_count,
+            false => u64::min(frame_index, frame_count - 1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LottieRenderParams {
+    /// PTS of the first frame of the animation.
+    pub(crate) start_pts: Duration,
+    pub(crate) repeat: bool,
+    pub(crate) text_overrides: Vec<LottieTextOverride>,
+    pub(crate) color_overrides: Vec<LottieColorOverride>,
+}
+
+pub(crate) struct LottieNode {
+    lottie: Lottie,
+    params: LottieRenderParams,
+    last_rendered_frame: Option<u64>,
+}
+
+impl LottieNode {
+    pub(crate) fn new(lottie: Lottie, params: LottieRenderParams) -> Self {
+        Self {
+            lottie,
+            params,
+            last_rendered_frame: None,
+        }
+    }
+
+    pub(crate) fn render(&mut self, ctx: &mut RenderCtx, target: &mut NodeTexture, pts: Duration) {
+        let asset = &self.lottie.0;
+        let elapsed = pts.saturating_sub(self.params.start_pts);
+        let frame_index = asset.frame_index(elapsed, self.params.repeat);
+        if self.last_rendered_frame == Some(frame_index) {
+            return;
+        }
+
+        let svg = frame_to_svg(
+            &asset.animation,
+            &self.params,
+            asset.animation.in_point + frame_index as f64,
+        );
+        match asset.rasterize(&svg) {
+            Ok(pixmap) => {
+                let target = target.ensure_size(ctx.wgpu_ctx, asset.resolution);
+                target.rgba_texture().upload(ctx.wgpu_ctx, pixmap.data());
+            }
+            Err(err) => error!("Failed to render Lottie animation frame: {err}"),
+        }
+        self.last_rendered_frame = Some(frame_index);
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LottieError {
+    #[error("Failed to download asset: {0}")]
+    AssetDownload(#[from] reqwest::Error),
+
+    #[error("Failed to read Lottie animation from disk: {0}")]
+    AssetDiskReadError(#[from] io::Error),
+
+    #[error("Lottie animation is not a valid JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    ParsingLottieFailed(#[from] LottieParseError),
+
+    #[error(transparent)]
+    RenderingLottieFailed(#[from] LottieRenderError),
+
+    #[error("Lottie animation resolution can't be zero.")]
+    ZeroResolution,
+
+    #[error("Providing URL as Lottie animation source is not supported on wasm platform")]
+    LottieSourceUrlNotSupported,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LottieParseError {
+    #[error("Lottie animation is missing required field \"{0}\".")]
+    MissingField(&'static str),
+
+    #[error("Lottie animation has invalid frame rate: {0}.")]
+    InvalidFrameRate(f64),
+
+    #[error(
+        "Lottie animation does not contain any frames, out point has to be larger than in point."
+    )]
+    EmptyAnimation,
+
+    #[error("Lottie animation has a cycle of precompositions referencing \"{0}\".")]
+    PrecompCycle(String),
+
+    #[error("Lottie animation has precompositions nested deeper than {0} levels.")]
+    PrecompTooDeep(usize),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LottieRenderError {
+    #[error("Failed to rasterize Lottie animation frame: {0}")]
+    RasterizationFailed(#[from] usvg::Error),
+
+    #[error("Failed to allocate a buffer for Lottie animation frame.")]
+    PixmapAllocationFailed,
+}
@@ -0,0 +1,846 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::scene::cubic_bezier_easing;
+
+use super::LottieParseError;
+
+/// Max nesting depth of precompositions. Layers are rendered recursively, so
+/// nesting has to be bounded.
+const MAX_PRECOMP_DEPTH: usize = 32;
+
+/// Parsed representation of a Lottie (Bodymovin) animation. Only a subset of
+/// the format is supported: shape, solid, text, null and precomposition layers
+/// with their transforms. Masks, mattes, effects, gradients and image layers
+/// are ignored.
+#[derive(Debug)]
+pub(super) struct Animation {
+    pub frame_rate: f64,
+    pub in_point: f64,
+    pub out_point: f64,
+    pub width: f64,
+    pub height: f64,
+    pub layers: Vec<Layer>,
+    pub precomps: HashMap<String, Vec<Layer>>,
+    /// Maps font name used by text layers to a font family.
+    pub fonts: HashMap<String, String>,
+}
+
+#[derive(Debug)]
+pub(super) struct Layer {
+    pub name: Option<String>,
+    pub index: Option<i64>,
+    pub parent: Option<i64>,
+    pub in_point: f64,
+    pub out_point: f64,
+    pub start_time: f64,
+    pub hidden: bool,
+    pub transform: Transform,
+    pub content: LayerContent,
+}
+
+#[derive(Debug)]
+pub(super) enum LayerContent {
+    Null,
+    Shapes(Vec<Shape>),
+    Solid {
+        color: [f64; 3],
+        width: f64,
+        height: f64,
+    },
+    Text(Vec<TextDocumentKeyframe>),
+    Precomp {
+        ref_id: String,
+        width: f64,
+        height: f64,
+    },
+}
+
+#[derive(Debug)]
+pub(super) struct Transform {
+    pub anchor: Property,
+    pub position: Position,
+    pub scale: Property,
+    pub rotation: Property,
+    pub opacity: Property,
+}
+
+#[derive(Debug)]
+pub(super) enum Position {
+    Combined(Property),
+    Split { x: Property, y: Property },
+}
+
+#[derive(Debug)]
+pub(super) enum Shape {
+    Group {
+        name: Option<String>,
+        hidden: bool,
+        items: Vec<Shape>,
+    },
+    Rect {
+        position: Property,
+        size: Property,
+        roundness: Property,
+    },
+    Ellipse {
+        position: Property,
+        size: Property,
+    },
+    Path {
+        closed: bool,
+        path: Property,
+    },
+    Fill {
+        name: Option<String>,
+        color: Property,
+        opacity: Property,
+        even_odd: bool,
+    },
+    Stroke {
+        name: Option<String>,
+        color: Property,
+        opacity: Property,
+        width: Property,
+        line_cap: &'static str,
+        line_join: &'static str,
+    },
+    Transform(Transform),
+}
+
+#[derive(Debug)]
+pub(super) struct TextDocumentKeyframe {
+    pub time: f64,
+    pub text: String,
+    pub font_size: f64,
+    pub font_name: String,
+    pub line_height: Option<f64>,
+    pub color: [f64; 3],
+    pub justify: TextJustify,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) enum TextJustify {
+    Left,
+    Right,
+    Center,
+}
+
+/// Animatable property. All values are represented as a flat list of numbers,
+/// bezier paths are flattened into `[vx, vy, ix, iy, ox, oy]` for every vertex.
+#[derive(Debug)]
+pub(super) enum Property {
+    Static(Vec<f64>),
+    Animated(Vec<Keyframe>),
+}
+
+#[derive(Debug)]
+pub(super) struct Keyframe {
+    time: f64,
+    start: Vec<f64>,
+    end: Option<Vec<f64>>,
+    hold: bool,
+    /// (out_x, out_y, in_x, in_y)
+    easing: Option<(f64, f64, f64, f64)>,
+}
+
+impl Animation {
+    pub fn parse(json: &Value) -> Result<Self, LottieParseError> {
+        let frame_rate = required_f64(json, "fr")?;
+        if frame_rate <= 0.0 {
+            return Err(LottieParseError::InvalidFrameRate(frame_rate));
+        }
+        let in_point = required_f64(json, "ip")?;
+        let out_point = required_f64(json, "op")?;
+        if out_point <= in_point {
+            return Err(LottieParseError::EmptyAnimation);
+        }
+
+        let precomps = json
+            .get("assets")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|asset| {
+                let id = asset.get("id").and_then(Value::as_str)?;
+                let layers = asset.get("layers").and_then(Value::as_array)?;
+                Some((id.to_string(), parse_layers(layers)))
+            })
+            .collect();
+
+        let fonts = json
+            .get("fonts")
+            .and_then(|fonts| fonts.get("list"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|font| {
+                let name = font.get("fName").and_then(Value::as_str)?;
+                let family = font.get("fFamily").and_then(Value::as_str)?;
+                Some((name.to_string(), family.to_string()))
+            })
+            .collect();
+
+        let layers = json
+            .get("layers")
+            .and_then(Value::as_array)
+            .ok_or(LottieParseError::MissingField("layers"))?;
+        let layers = parse_layers(layers);
+
+        let mut depths = HashMap::new();
+        for ref_id in precomp_refs(&layers) {
+            precomp_depth(ref_id, &precomps, &mut Vec::new(), &mut depths)?;
+        }
+
+        Ok(Self {
+            frame_rate,
+            in_point,
+            out_point,
+            width: required_f64(json, "w")?,
+            height: required_f64(json, "h")?,
+            layers,
+            precomps,
+            fonts,
+        })
+    }
+
+    pub fn has_text_layers(&self) -> bool {
+        self.layers
+            .iter()
+            .chain(self.precomps.values().flatten())
+            .any(|layer| matches!(layer.content, LayerContent::Text(_)))
+    }
+}
+
+fn required_f64(json: &Value, field: &'static str) -> Result<f64, LottieParseError> {
+    json.get(field)
+        .and_then(Value::as_f64)
+        .ok_or(LottieParseError::MissingField(field))
+}
+
+fn precomp_refs(layers: &[Layer]) -> impl Iterator<Item = &str> {
+    layers.iter().filter_map(|layer| match &layer.content {
+        LayerContent::Precomp { ref_id, .. } => Some(ref_id.as_str()),
+        _ => None,
+    })
+}
+
+/// Returns nesting depth of the precomposition. `path` contains precompositions
+/// that are currently being checked, so references back to them are cycles.
+/// Depths of already checked precompositions are memoized in `depths`.
+fn precomp_depth<'a>(
+    ref_id: &'a str,
+    precomps: &'a HashMap<String, Vec<Layer>>,
+    path: &mut Vec<&'a str>,
+    depths: &mut HashMap<&'a str, usize>,
+) -> Result<usize, LottieParseError> {
+    if path.contains(&ref_id) {
+        return Err(LottieParseError::PrecompCycle(ref_id.to_string()));
+    }
+    if let Some(depth) = depths.get(ref_id) {
+        if path.len() + depth >= MAX_PRECOMP_DEPTH {
+            return Err(LottieParseError::PrecompTooDeep(MAX_PRECOMP_DEPTH));
+        }
+        return Ok(*depth);
+    }
+    if path.len() >= MAX_PRECOMP_DEPTH {
+        return Err(LottieParseError::PrecompTooDeep(MAX_PRECOMP_DEPTH));
+    }
+    let Some(layers) = precomps.get(ref_id) else {
+        return Ok(0);
+    };
+
+    path.push(ref_id);
+    let mut depth = 0;
+    for child_ref_id in precomp_refs(layers) {
+        depth = depth.max(precomp_depth(child_ref_id, precomps, path, depths)? + 1);
+    }
+    path.pop();
+    depths.insert(ref_id, depth);
+    Ok(depth)
+}
+
+fn parse_layers(layers: &[Value]) -> Vec<Layer> {
+    layers.iter().filter_map(parse_layer).collect()
+}
+
+fn parse_layer(json: &Value) -> Option<Layer> {
+    let f64_field = |name: &str| json.get(name).and_then(Value::as_f64);
+    let content = match json.get("ty").and_then(Value::as_i64)? {
+        0 => LayerContent::Precomp {
+            ref_id: json.get("refId").and_then(Value::as_str)?.to_string(),
+            width: f64_field("w").unwrap_or(0.0),
+            height: f64_field("h").unwrap_or(0.0),
+        },
+        1 => LayerContent::Solid {
+            color: json
+                .get("sc")
+                .and_then(Value::as_str)
+                .and_then(parse_hex_color)
+                .unwrap_or([0.0, 0.0, 0.0]),
+            width: f64_field("sw").unwrap_or(0.0),
+            height: f64_field("sh").unwrap_or(0.0),
+        },
+        3 => LayerContent::Null,
+        4 => LayerContent::Shapes(parse_shapes(json.get("shapes"))),
+        5 => LayerContent::Text(parse_text_document(json.get("t")?)),
+        // Image, audio, camera and other unsupported layers
+        _ => return None,
+    };
+
+    Some(Layer {
+        name: json.get("nm").and_then(Value::as_str).map(str::to_string),
+        index: json.get("ind").and_then(Value::as_i64),
+        parent: json.get("parent").and_then(Value::as_i64),
+        in_point: f64_field("ip").unwrap_or(f64::MIN),
+        out_point: f64_field("op").unwrap_or(f64::MAX),
+        start_time: f64_field("st").unwrap_or(0.0),
+        hidden: json.get("hd").and_then(Value::as_bool).unwrap_or(false),
+        transform: parse_transform(json.get("ks")),
+        content,
+    })
+}
+
+fn parse_transform(json: Option<&Value>) -> Transform {
+    let property = |name: &str, default: &[f64]| {
+        Property::parse(json.and_then(|json| json.get(name)), default)
+    };
+
+    let position = match json.and_then(|json| json.get("p")) {
+        Some(p) if p.get("s").and_then(Value::as_bool).unwrap_or(false) => Position::Split {
+            x: Property::parse(p.get("x"), &[0.0]),
+            y: Property::parse(p.get("y"), &[0.0]),
+        },
+        p => Position::Combined(Property::parse(p, &[0.0, 0.0])),
+    };
+
+    Transform {
+        anchor: property("a", &[0.0, 0.0]),
+        position,
+        scale: property("s", &[100.0, 100.0]),
+        rotation: property("r", &[0.0]),
+        opacity: property("o", &[100.0]),
+    }
+}
+
+fn parse_shapes(json: Option<&Value>) -> Vec<Shape> {
+    json.and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(parse_shape)
+        .collect()
+}
+
+fn parse_shape(json: &Value) -> Option<Shape> {
+    let name = json.get("nm").and_then(Value::as_str).map(str::to_string);
+    let property = |field: &str, default: &[f64]| Property::parse(json.get(field), default);
+    let shape = match json.get("ty").and_then(Value::as_str)? {
+        "gr" => Shape::Group {
+            name,
+            hidden: json.get("hd").and_then(Value::as_bool).unwrap_or(false),
+            items: parse_shapes(json.get("it")),
+        },
+        "rc" => Shape::Rect {
+            position: property("p", &[0.0, 0.0]),
+            size: property("s", &[0.0, 0.0]),
+            roundness: property("r", &[0.0]),
+        },
+        "el" => Shape::Ellipse {
+            position: property("p", &[0.0, 0.0]),
+            size: property("s", &[0.0, 0.0]),
+        },
+        "sh" => {
+            let path = json.get("ks")?;
+            Shape::Path {
+                closed: is_path_closed(path),
+                path: Property::parse(Some(path), &[]),
+            }
+        }
+        "fl" => Shape::Fill {
+            name,
+            color: property("c", &[0.0, 0.0, 0.0, 1.0]),
+            opacity: property("o", &[100.0]),
+            even_odd: json.get("r").and_then(Value::as_i64) == Some(2),
+        },
+        "st" => Shape::Stroke {
+            name,
+            color: property("c", &[0.0, 0.0, 0.0, 1.0]),
+            opacity: property("o", &[100.0]),
+            width: property("w", &[1.0]),
+            line_cap: match json.get("lc").and_then(Value::as_i64) {
+                Some(2) => "round",
+                Some(3) => "square",
+                _ => "butt",
+            },
+            line_join: match json.get("lj").and_then(Value::as_i64) {
+                Some(2) => "round",
+                Some(3) => "bevel",
+                _ => "miter",
+            },
+        },
+        "tr" => Shape::Transform(parse_transform(Some(json))),
+        _ => return None,
+    };
+    Some(shape)
+}
+
+fn is_path_closed(path: &Value) -> bool {
+    let shape = match path.get("k") {
+        Some(Value::Array(keyframes)) => keyframes
+            .first()
+            .and_then(|keyframe| keyframe.get("s"))
+            .and_then(|s| s.as_array().and_then(|s| s.first()).or(Some(s))),
+        shape => shape,
+    };
+    shape
+        .and_then(|shape| shape.get("c"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+fn parse_text_document(json: &Value) -> Vec<TextDocumentKeyframe> {
+    json.get("d")
+        .and_then(|d| d.get("k"))
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|keyframe| {
+            let doc = keyframe.get("s")?;
+            Some(TextDocumentKeyframe {
+                time: keyframe.get("t").and_then(Value::as_f64).unwrap_or(0.0),
+                text: doc
+                    .get("t")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                font_size: doc.get("s").and_then(Value::as_f64).unwrap_or(16.0),
+                font_name: doc
+                    .get("f")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                line_height: doc.get("lh").and_then(Value::as_f64),
+                color: doc
+                    .get("fc")
+                    .and_then(parse_numbers)
+                    .map(|c| normalize_color(&c))
+                    .unwrap_or([0.0, 0.0, 0.0]),
+                justify: match doc.get("j").and_then(Value::as_i64) {
+                    Some(1) => TextJustify::Right,
+                    Some(2) => TextJustify::Center,
+                    _ => TextJustify::Left,
+                },
+            })
+        })
+        .collect()
+}
+
+fn parse_hex_color(color: &str) -> Option<[f64; 3]> {
+    let color = color.strip_prefix('#')?;
+    if color.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(&color[i..i + 2], 16)
+            .ok()
+            .map(|v| v as f64 / 255.0)
+    };
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Older versions of the format used 0-255 range for colors instead of 0-1.
+pub(super) fn normalize_color(color: &[f64]) -> [f64; 3] {
+    let channel = |i: usize| color.get(i).copied().unwrap_or(0.0);
+    let scale = match color.iter().take(3).any(|c| *c > 1.0) {
+        true => 255.0,
+        false => 1.0,
+    };
+    [channel(0) / scale, channel(1) / scale, channel(2) / scale]
+}
+
+/// Parses a keyframe or static value. Numbers are returned as a single element
+/// list and bezier shapes are flattened.
+fn parse_numbers(value: &Value) -> Option<Vec<f64>> {
+    match value {
+        Value::Number(n) => Some(vec![n.as_f64()?]),
+        Value::Array(values) => match values.first() {
+            Some(Value::Object(_)) => parse_numbers(&values[0]),
+            _ => values.iter().map(Value::as_f64).collect(),
+        },
+        Value::Object(shape) => {
+            let points = |name: &str| -> Option<Vec<[f64; 2]>> {
+                shape
+                    .get(name)?
+                    .as_array()?
+                    .iter()
+                    .map(|point| Some([point.get(0)?.as_f64()?, point.get(1)?.as_f64()?]))
+                    .collect()
+            };
+            let (vertices, in_tangents, out_tangents) = (points("v")?, points("i")?, points("o")?);
+            Some(
+                vertices
+                    .iter()
+                    .zip(in_tangents.iter())
+                    .zip(out_tangents.iter())
+                    .flat_map(|((v, i), o)| [v[0], v[1], i[0], i[1], o[0], o[1]])
+                    .collect(),
+            )
+        }
+        _ => None,
+    }
+}
+
+fn parse_easing(keyframe: &Value) -> Option<(f64, f64, f64, f64)> {
+    let (out_tangent, in_tangent) = (keyframe.get("o")?, keyframe.get("i")?);
+    Some((
+        parse_easing_component(out_tangent.get("x"))?,
+        parse_easing_component(out_tangent.get("y"))?,
+        parse_easing_component(in_tangent.get("x"))?,
+        parse_easing_component(in_tangent.get("y"))?,
+    ))
+}
+
+fn parse_easing_component(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(n) => n.as_f64(),
+        Value::Array(values) => values.first()?.as_f64(),
+        _ => None,
+    }
+}
+
+impl Property {
+    fn parse(json: Option<&Value>, default: &[f64]) -> Self {
+        let Some(json) = json else {
+            return Property::Static(default.to_vec());
+        };
+        let value = json.get("k");
+        let is_animated = json.get("a").and_then(Value::as_i64) == Some(1)
+            || matches!(
+                value.and_then(|value| value.get(0)),
+                Some(first) if first.get("t").is_some()
+            );
+
+        if !is_animated {
+            return Property::Static(
+                value
+                    .and_then(parse_numbers)
+                    .unwrap_or_else(|| default.to_vec()),
+            );
+        }
+
+        let mut keyframes: Vec<Keyframe> = vec![];
+        for keyframe in value.and_then(Value::as_array).into_iter().flatten() {
+            let Some(time) = keyframe.get("t").and_then(Value::as_f64) else {
+                continue;
+            };
+            // Last keyframe in older versions of the format does not have a start value,
+            // in that case end value of the previous keyframe is used.
+            let start = keyframe.get("s").and_then(parse_numbers).or_else(|| {
+                keyframes
+                    .last()
+                    .map(|prev| prev.end.clone().unwrap_or_else(|| prev.start.clone()))
+            });
+            let Some(start) = start else {
+                continue;
+            };
+            keyframes.push(Keyframe {
+                time,
+                start,
+                end: keyframe.get("e").and_then(parse_numbers),
+                hold: keyframe.get("h").and_then(Value::as_i64) == Some(1),
+                easing: parse_easing(keyframe),
+            });
+        }
+
+        match keyframes.is_empty() {
+            true => Property::Static(default.to_vec()),
+            false => Property::Animated(keyframes),
+        }
+    }
+
+    pub fn value(&self, frame: f64) -> Vec<f64> {
+        let keyframes = match self {
+            Property::Static(value) => return value.clone(),
+            Property::Animated(keyframes) => keyframes,
+        };
+
+        let first = &keyframes[0];
+        if frame <= first.time {
+            return first.start.clone();
+        }
+        for pair in keyframes.windows(2) {
+            let (current, next) = (&pair[0], &pair[1]);
+            if frame >= next.time {
+                continue;
+            }
+            if current.hold {
+                return current.start.clone();
+            }
+            let end = current.end.as_ref().unwrap_or(&next.start);
+            let duration = next.time - current.time;
+            let progress = match duration > 0.0 {
+                true => (frame - current.time) / duration,
+                false => 1.0,
+            };
+            let progress = match current.easing {
+                Some((x1, y1, x2, y2)) => cubic_bezier_easing(progress, x1, y1, x2, y2),
+                None => progress,
+            };
+            return lerp(&current.start, end, progress);
+        }
+
+        let last = &keyframes[keyframes.len() - 1];
+        last.end.clone().unwrap_or_else(|| last.start.clone())
+    }
+
+    pub fn scalar(&self, frame: f64) -> f64 {
+        self.value(frame).first().copied().unwrap_or(0.0)
+    }
+
+    pub fn vec2(&self, frame: f64) -> [f64; 2] {
+        let value = self.value(frame);
+        let x = value.first().copied().unwrap_or(0.0);
+        [x, value.get(1).copied().unwrap_or(x)]
+    }
+}
+
+impl Position {
+    pub fn vec2(&self, frame: f64) -> [f64; 2] {
+        match self {
+            Position::Combined(position) => position.vec2(frame),
+            Position::Split { x, y } => [x.scalar(frame), y.scalar(frame)],
+        }
+    }
+}
+
+fn lerp(start: &[f64], end: &[f64], progress: f64) -> Vec<f64> {
+    if start.len() != end.len() {
+        return start.to_vec();
+    }
+    start
+        .iter()
+        .zip(end.iter())
+        .map(|(start, end)| start + (end - start) * progress)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn animation(layers: Value, assets: Value) -> Result<Animation, LottieParseError> {
+        Animation::parse(&json!({
+            "fr": 30,
+            "ip": 0,
+            "op": 60,
+            "w": 100,
+            "h": 100,
+            "layers": layers,
+            "assets": assets,
+        }))
+    }
+
+    fn precomp_layer(ref_id: &str) -> Value {
+        json!({ "ty": 0, "refId": ref_id, "w": 100, "h": 100 })
+    }
+
+    fn is_close(a: &[f64], b: &[f64]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-3)
+    }
+
+    #[test]
+    fn test_required_fields() {
+        assert!(matches!(
+            Animation::parse(&json!({ "fr": 30, "ip": 0, "op": 60, "w": 100, "h": 100 })),
+            Err(LottieParseError::MissingField("layers"))
+        ));
+        assert!(matches!(
+            Animation::parse(
+                &json!({ "fr": 0, "ip": 0, "op": 60, "w": 100, "h": 100, "layers": [] })
+            ),
+            Err(LottieParseError::InvalidFrameRate(_))
+        ));
+        assert!(matches!(
+            Animation::parse(
+                &json!({ "fr": 30, "ip": 10, "op": 10, "w": 100, "h": 100, "layers": [] })
+            ),
+            Err(LottieParseError::EmptyAnimation)
+        ));
+    }
+
+    #[test]
+    fn test_linear_keyframes() {
+        let property = Property::parse(
+            Some(&json!({
+                "a": 1,
+                "k": [
+                    { "t": 0, "s": [0, 0] },
+                    { "t": 10, "s": [100, 50] },
+                    { "t": 20 },
+                ]
+            })),
+            &[0.0, 0.0],
+        );
+        assert!(is_close(&property.value(-5.0), &[0.0, 0.0]));
+        assert!(is_close(&property.value(5.0), &[50.0, 25.0]));
+        // Older format without end value and without start value in the last keyframe
+        assert!(is_close(&property.value(15.0), &[100.0, 50.0]));
+        assert!(is_close(&property.value(30.0), &[100.0, 50.0]));
+    }
+
+    #[test]
+    fn test_hold_and_end_value_keyframes() {
+        let property = Property::parse(
+            Some(&json!({
+                "k": [
+                    { "t": 0, "s": [10], "h": 1 },
+                    { "t": 10, "s": [20], "e": [40] },
+                    { "t": 20, "s": [0] },
+                ]
+            })),
+            &[0.0],
+        );
+        assert!(is_close(&property.value(9.0), &[10.0]));
+        assert!(is_close(&property.value(15.0), &[30.0]));
+    }
+
+    #[test]
+    fn test_eased_keyframes() {
+        let property = Property::parse(
+            Some(&json!({
+                "a": 1,
+                "k": [
+                    {
+                        "t": 0,
+                        "s": [0],
+                        "o": { "x": [0.42], "y": [0] },
+                        "i": { "x": [0.58], "y": [1] },
+                    },
+                    { "t": 10, "s": [100] },
+                ]
+            })),
+            &[0.0],
+        );
+        assert!(is_close(&property.value(5.0), &[50.0]));
+        assert!(property.scalar(2.5) < 25.0);
+        assert!(property.scalar(7.5) > 75.0);
+    }
+
+    #[test]
+    fn test_static_property() {
+        let property = Property::parse(Some(&json!({ "a": 0, "k": [1, 2] })), &[0.0, 0.0]);
+        assert!(is_close(&property.value(100.0), &[1.0, 2.0]));
+        assert_eq!(property.vec2(0.0), [1.0, 2.0]);
+
+        let property = Property::parse(None, &[100.0, 100.0]);
+        assert!(is_close(&property.value(0.0), &[100.0, 100.0]));
+
+        let property = Property::parse(Some(&json!({ "k": 5 })), &[0.0]);
+        assert_eq!(property.vec2(0.0), [5.0, 5.0]);
+    }
+
+    #[test]
+    fn test_shapes() {
+        let animation = animation(
+            json!([{
+                "ty": 4,
+                "nm": "shape_layer",
+                "shapes": [{
+                    "ty": "gr",
+                    "nm": "group",
+                    "it": [
+                        { "ty": "rc", "p": { "k": [50, 50] }, "s": { "k": [20, 10] }, "r": { "k": 0 } },
+                        {
+                            "ty": "sh",
+                            "ks": { "k": { "c": true, "v": [[0, 0], [10, 0]], "i": [[0, 0], [0, 0]], "o": [[0, 0], [0, 0]] } }
+                        },
+                        { "ty": "fl", "c": { "k": [1, 0, 0, 1] }, "o": { "k": 100 }, "r": 2 },
+                        { "ty": "st", "c": { "k": [0, 0, 1, 1] }, "o": { "k": 100 }, "w": { "k": 2 }, "lc": 2, "lj": 3 },
+                        { "ty": "tr" },
+                        { "ty": "unsupported" },
+                    ]
+                }]
+            }]),
+            json!([]),
+        )
+        .unwrap();
+
+        let LayerContent::Shapes(shapes) = &animation.layers[0].content else {
+            panic!("Expected shape layer");
+        };
+        let Shape::Group { name, items, .. } = &shapes[0] else {
+            panic!("Expected group");
+        };
+        assert_eq!(name.as_deref(), Some("group"));
+        assert_eq!(items.len(), 5);
+        assert!(matches!(items[0], Shape::Rect { .. }));
+        let Shape::Path { closed, path } = &items[1] else {
+            panic!("Expected path");
+        };
+        assert!(*closed);
+        assert_eq!(path.value(0.0).len(), 12);
+        assert!(matches!(items[2], Shape::Fill { even_odd: true, .. }));
+        assert!(matches!(
+            items[3],
+            Shape::Stroke {
+                line_cap: "round",
+                line_join: "bevel",
+                ..
+            }
+        ));
+        assert!(matches!(items[4], Shape::Transform(_)));
+    }
+
+    #[test]
+    fn test_precomp() {
+        let animation = animation(
+            json!([precomp_layer("comp_1")]),
+            json!([
+                { "id": "comp_1", "layers": [precomp_layer("comp_2"), precomp_layer("comp_2")] },
+                { "id": "comp_2", "layers": [{ "ty": 1, "sc": "#ff0000", "sw": 10, "sh": 10 }] },
+            ]),
+        )
+        .unwrap();
+        assert_eq!(animation.precomps.len(), 2);
+        assert!(matches!(
+            animation.precomps["comp_2"][0].content,
+            LayerContent::Solid { color, .. } if is_close(&color, &[1.0, 0.0, 0.0])
+        ));
+    }
+
+    #[test]
+    fn test_precomp_cycle() {
+        let result = animation(
+            json!([precomp_layer("comp_1")]),
+            json!([{ "id": "comp_1", "layers": [precomp_layer("comp_1")] }]),
+        );
+        assert!(matches!(result, Err(LottieParseError::PrecompCycle(id)) if id == "comp_1"));
+
+        let result = animation(
+            json!([precomp_layer("comp_1")]),
+            json!([
+                { "id": "comp_1", "layers": [precomp_layer("comp_2")] },
+                { "id": "comp_2", "layers": [precomp_layer("comp_3")] },
+                { "id": "comp_3", "layers": [precomp_layer("comp_1")] },
+            ]),
+        );
+        assert!(matches!(result, Err(LottieParseError::PrecompCycle(_))));
+    }
+
+    #[test]
+    fn test_precomp_too_deep() {
+        let assets: Vec<Value> = (0..MAX_PRECOMP_DEPTH + 1)
+            .map(|i| {
+                json!({
+                    "id": format!("comp_{i}"),
+                    "layers": [precomp_layer(&format!("comp_{}", i + 1))],
+                })
+            })
+            .collect();
+        let result = animation(json!([precomp_layer("comp_0")]), json!(assets));
+        assert!(matches!(result, Err(LottieParseError::PrecompTooDeep(_))));
+    }
+}
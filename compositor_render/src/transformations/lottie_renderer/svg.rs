@@ -0,0 +1,503 @@
+use std::fmt::{self, Write};
+
+use crate::scene::RGBAColor;
+
+use super::{
+    animation::{
+        normalize_color, Animation, Layer, LayerContent, Shape, TextDocumentKeyframe, TextJustify,
+        Transform,
+    },
+    LottieRenderParams,
+};
+
+/// Max depth of parenting chain, protects against cycles in malformed files.
+const MAX_PARENT_DEPTH: usize = 32;
+
+/// Converts a single frame of the animation into an SVG document that can be
+/// rasterized with `resvg`.
+pub(super) fn frame_to_svg(
+    animation: &Animation,
+    params: &LottieRenderParams,
+    frame: f64,
+) -> String {
+    let mut writer = SvgWriter {
+        out: String::new(),
+        animation,
+        params,
+        clip_path_count: 0,
+    };
+    let (width, height) = (num(animation.width), num(animation.height));
+    let _ = write!(
+        writer.out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    );
+    writer.write_layers(&animation.layers, frame);
+    writer.out.push_str("</svg>");
+    writer.out
+}
+
+struct SvgWriter<'a> {
+    out: String,
+    animation: &'a Animation,
+    params: &'a LottieRenderParams,
+    clip_path_count: usize,
+}
+
+impl<'a> SvgWriter<'a> {
+    fn write_layers(&mut self, layers: &'a [Layer], frame: f64) {
+        // Layers earlier in the list are drawn on top.
+        for layer in layers.iter().rev() {
+            let is_visible = frame >= layer.in_point && frame < layer.out_point;
+            if layer.hidden || !is_visible || matches!(layer.content, LayerContent::Null) {
+                continue;
+            }
+
+            let matrix = layer_matrix(layers, layer, frame, 0);
+            let opacity = num(layer.transform.opacity.scalar(frame) / 100.0);
+            let color_override = self.color_override(layer.name.as_deref());
+            let _ = write!(self.out, r#"<g transform="{matrix}" opacity="{opacity}">"#);
+            match &layer.content {
+                LayerContent::Null => {}
+                LayerContent::Solid {
+                    color,
+                    width,
+                    height,
+                } => {
+                    let (fill, alpha) = resolve_color(*color, 1.0, color_override);
+                    let _ = write!(
+                        self.out,
+                        r#"<rect width="{}" height="{}" fill="{fill}" fill-opacity="{alpha}"/>"#,
+                        num(*width),
+                        num(*height)
+                    );
+                }
+                LayerContent::Shapes(shapes) => {
+                    self.write_shapes(shapes, frame, &[], color_override);
+                }
+                LayerContent::Text(documents) => {
+                    self.write_text(layer, documents, frame, color_override);
+                }
+                LayerContent::Precomp {
+                    ref_id,
+                    width,
+                    height,
+                } => {
+                    if let Some(precomp_layers) = self.animation.precomps.get(ref_id) {
+                        self.clip_path_count += 1;
+                        let clip_id = format!("precomp-clip-{}", self.clip_path_count);
+                        let _ = write!(
+                            self.out,
+                            r#"<defs><clipPath id="{clip_id}"><rect width="{}" height="{}"/></clipPath></defs><g clip-path="url(#{clip_id})">"#,
+                            num(*width),
+                            num(*height)
+                        );
+                        // Nesting depth is bounded, cycles are rejected in `Animation::parse`.
+                        self.write_layers(precomp_layers, frame - layer.start_time);
+                        self.out.push_str("</g>");
+                    }
+                }
+            }
+            self.out.push_str("</g>");
+        }
+    }
+
+    fn write_shapes(
+        &mut self,
+        shapes: &'a [Shape],
+        frame: f64,
+        inherited_styles: &[&'a Shape],
+        color_override: Option<&'a RGBAColor>,
+    ) {
+        let geometry = shapes
+            .iter()
+            .filter_map(|shape| shape_path_data(shape, frame))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let styles: Vec<&Shape> = shapes
+            .iter()
+            .filter(|shape| matches!(shape, Shape::Fill { .. } | Shape::Stroke { .. }))
+            .collect();
+
+        // Groups without their own fill or stroke are painted with styles of the
+        // parent group.
+        if styles.is_empty() && !geometry.is_empty() {
+            for style in inherited_styles.iter().rev() {
+                self.write_styled_path(&geometry, style, frame, color_override);
+            }
+        }
+        let child_styles = match styles.is_empty() {
+            true => inherited_styles,
+            false => &styles,
+        };
+
+        // Items earlier in the list are drawn on top.
+        for shape in shapes.iter().rev() {
+            match shape {
+                Shape::Group {
+                    name,
+                    hidden: false,
+                    items,
+                } => {
+                    let color_override = self.color_override(name.as_deref()).or(color_override);
+                    match items.iter().find_map(|item| match item {
+                        Shape::Transform(transform) => Some(transform),
+                        _ => None,
+                    }) {
+                        Some(transform) => {
+                            let _ = write!(
+                                self.out,
+                                r#"<g transform="{}" opacity="{}">"#,
+                                transform_matrix(transform, frame),
+                                num(transform.opacity.scalar(frame) / 100.0)
+                            );
+                        }
+                        None => self.out.push_str("<g>"),
+                    }
+                    self.write_shapes(items, frame, child_styles, color_override);
+                    self.out.push_str("</g>");
+                }
+                Shape::Fill { .. } | Shape::Stroke { .. } if !geometry.is_empty() => {
+                    self.write_styled_path(&geometry, shape, frame, color_override);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn write_styled_path(
+        &mut self,
+        path_data: &str,
+        style: &Shape,
+        frame: f64,
+        color_override: Option<&RGBAColor>,
+    ) {
+        match style {
+            Shape::Fill {
+                name,
+                color,
+                opacity,
+                even_odd,
+            } => {
+                let color_override = self.color_override(name.as_deref()).or(color_override);
+                let (fill, alpha) = resolve_color(
+                    normalize_color(&color.value(frame)),
+                    opacity.scalar(frame) / 100.0,
+                    color_override,
+                );
+                let fill_rule = match even_odd {
+                    true => "evenodd",
+                    false => "nonzero",
+                };
+                let _ = write!(
+                    self.out,
+                    r#"<path d="{path_data}" fill="{fill}" fill-opacity="{alpha}" fill-rule="{fill_rule}"/>"#
+                );
+            }
+            Shape::Stroke {
+                name,
+                color,
+                opacity,
+                width,
+                line_cap,
+                line_join,
+            } => {
+                let color_override = self.color_override(name.as_deref()).or(color_override);
+                let (stroke, alpha) = resolve_color(
+                    normalize_color(&color.value(frame)),
+                    opacity.scalar(frame) / 100.0,
+                    color_override,
+                );
+                let _ = write!(
+                    self.out,
+                    r#"<path d="{path_data}" fill="none" stroke="{stroke}" stroke-opacity="{alpha}" stroke-width="{}" stroke-linecap="{line_cap}" stroke-linejoin="{line_join}"/>"#,
+                    num(width.scalar(frame))
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn write_text(
+        &mut self,
+        layer: &Layer,
+        documents: &[TextDocumentKeyframe],
+        frame: f64,
+        color_override: Option<&RGBAColor>,
+    ) {
+        let Some(document) = documents
+            .iter()
+            .rev()
+            .find(|document| document.time <= frame)
+            .or(documents.first())
+        else {
+            return;
+        };
+
+        let text = layer
+            .name
+            .as_deref()
+            .and_then(|name| {
+                self.params
+                    .text_overrides
+                    .iter()
+                    .find(|text_override| text_override.layer_name.as_ref() == name)
+            })
+            .map(|text_override| text_override.text.as_ref())
+            .unwrap_or(&document.text);
+        let font_family = self
+            .animation
+            .fonts
+            .get(&document.font_name)
+            .unwrap_or(&document.font_name);
+        let (fill, alpha) = resolve_color(document.color, 1.0, color_override);
+        let text_anchor = match document.justify {
+            TextJustify::Left => "start",
+            TextJustify::Right => "end",
+            TextJustify::Center => "middle",
+        };
+        let line_height = document.line_height.unwrap_or(document.font_size * 1.2);
+
+        let _ = write!(
+            self.out,
+            r#"<text font-family="{}" font-size="{}" fill="{fill}" fill-opacity="{alpha}" text-anchor="{text_anchor}">"#,
+            escape_xml(font_family),
+            num(document.font_size),
+        );
+        for (index, line) in text.split(['\r', '\n', '\u{3}']).enumerate() {
+            let _ = write!(
+                self.out,
+                r#"<tspan x="0" y="{}">{}</tspan>"#,
+                num(index as f64 * line_height),
+                escape_xml(line)
+            );
+        }
+        self.out.push_str("</text>");
+    }
+
+    fn color_override(&self, name: Option<&str>) -> Option<&'a RGBAColor> {
+        let name = name?;
+        self.params
+            .color_overrides
+            .iter()
+            .find(|color_override| color_override.name.as_ref() == name)
+            .map(|color_override| &color_override.color)
+    }
+}
+
+fn layer_matrix(layers: &[Layer], layer: &Layer, frame: f64, depth: usize) -> Matrix {
+    let local = transform_matrix(&layer.transform, frame);
+    let parent = layer
+        .parent
+        .filter(|_| depth < MAX_PARENT_DEPTH)
+        .and_then(|parent| layers.iter().find(|l| l.index == Some(parent)));
+    match parent {
+        Some(parent) => layer_matrix(layers, parent, frame, depth + 1).multiply(local),
+        None => local,
+    }
+}
+
+fn transform_matrix(transform: &Transform, frame: f64) -> Matrix {
+    let [position_x, position_y] = transform.position.vec2(frame);
+    let [anchor_x, anchor_y] = transform.anchor.vec2(frame);
+    let [scale_x, scale_y] = transform.scale.vec2(frame);
+    Matrix::translate(position_x, position_y)
+        .multiply(Matrix::rotate(transform.rotation.scalar(frame)))
+        .multiply(Matrix::scale(scale_x / 100.0, scale_y / 100.0))
+        .multiply(Matrix::translate(-anchor_x, -anchor_y))
+}
+
+fn shape_path_data(shape: &Shape, frame: f64) -> Option<String> {
+    match shape {
+        Shape::Rect {
+            position,
+            size,
+            roundness,
+        } => {
+            let [center_x, center_y] = position.vec2(frame);
+            let [width, height] = size.vec2(frame);
+            let (x, y) = (num(center_x - width / 2.0), num(center_y - height / 2.0));
+            let (x2, y2) = (num(x + width), num(y + height));
+            let r = num(roundness.scalar(frame).min(width / 2.0).min(height / 2.0));
+            if r <= 0.0 {
+                return Some(format!("M{x},{y} H{x2} V{y2} H{x} Z"));
+            }
+            Some(format!(
+                "M{},{y} H{} A{r},{r} 0 0 1 {x2},{} V{} A{r},{r} 0 0 1 {},{y2} H{} A{r},{r} 0 0 1 {x},{} V{} A{r},{r} 0 0 1 {},{y} Z",
+                x + r,
+                x2 - r,
+                y + r,
+                y2 - r,
+                x2 - r,
+                x + r,
+                y2 - r,
+                y + r,
+                x + r,
+            ))
+        }
+        Shape::Ellipse { position, size } => {
+            let [center_x, center_y] = position.vec2(frame);
+            let [width, height] = size.vec2(frame);
+            let (rx, ry) = (num(width / 2.0), num(height / 2.0));
+            let (left, right, center_y) = (num(center_x - rx), num(center_x + rx), num(center_y));
+            Some(format!(
+                "M{left},{center_y} A{rx},{ry} 0 1 0 {right},{center_y} A{rx},{ry} 0 1 0 {left},{center_y} Z"
+            ))
+        }
+        Shape::Path { closed, path } => {
+            let values = path.value(frame);
+            let vertices: Vec<&[f64]> = values.chunks_exact(6).collect();
+            let first = vertices.first()?;
+
+            let mut data = format!("M{},{}", num(first[0]), num(first[1]));
+            let segments = vertices.windows(2).map(|pair| (pair[0], pair[1]));
+            let closing_segment = match (closed, vertices.last()) {
+                (true, Some(last)) => Some((*last, *first)),
+                _ => None,
+            };
+            for (from, to) in segments.chain(closing_segment) {
+                let _ = write!(
+                    data,
+                    " C{},{} {},{} {},{}",
+                    num(from[0] + from[4]),
+                    num(from[1] + from[5]),
+                    num(to[0] + to[2]),
+                    num(to[1] + to[3]),
+                    num(to[0]),
+                    num(to[1]),
+                );
+            }
+            if *closed {
+                data.push_str(" Z");
+            }
+            Some(data)
+        }
+        _ => None,
+    }
+}
+
+/// Returns color in a hex format and an opacity. Color override replaces
+/// the color from the animation and its alpha channel is multiplied with
+/// the original opacity.
+fn resolve_color(
+    color: [f64; 3],
+    opacity: f64,
+    color_override: Option<&RGBAColor>,
+) -> (String, f64) {
+    let to_u8 = |channel: f64| (num(channel).clamp(0.0, 1.0) * 255.0).round() as u8;
+    let (r, g, b, opacity) = match color_override {
+        Some(RGBAColor(r, g, b, a)) => (*r, *g, *b, opacity * (*a as f64 / 255.0)),
+        None => (to_u8(color[0]), to_u8(color[1]), to_u8(color[2]), opacity),
+    };
+    (
+        format!("#{r:02x}{g:02x}{b:02x}"),
+        num(opacity).clamp(0.0, 1.0),
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Guards against NaN and infinity values in the generated document.
+fn num(value: f64) -> f64 {
+    match value.is_finite() {
+        true => value,
+        false => 0.0,
+    }
+}
+
+/// 2D affine transformation matrix in the SVG `matrix(a b c d e f)` order.
+#[derive(Debug, Clone, Copy)]
+struct Matrix([f64; 6]);
+
+impl Matrix {
+    fn translate(x: f64, y: f64) -> Self {
+        Self([1.0, 0.0, 0.0, 1.0, x, y])
+    }
+
+    fn scale(x: f64, y: f64) -> Self {
+        Self([x, 0.0, 0.0, y, 0.0, 0.0])
+    }
+
+    fn rotate(degrees: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self([cos, sin, -sin, cos, 0.0, 0.0])
+    }
+
+    /// Returns a matrix that applies `other` first and then `self`.
+    fn multiply(self, other: Matrix) -> Self {
+        let [a1, b1, c1, d1, e1, f1] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = other.0;
+        Self([
+            a1 * a2 + c1 * b2,
+            b1 * a2 + d1 * b2,
+            a1 * c2 + c1 * d2,
+            b1 * c2 + d1 * d2,
+            a1 * e2 + c1 * f2 + e1,
+            b1 * e2 + d1 * f2 + f1,
+        ])
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d, e, f_] = self.0.map(num);
+        write!(f, "matrix({a} {b} {c} {d} {e} {f_})")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_frame_to_svg() {
+        let animation = Animation::parse(&json!({
+            "fr": 30,
+            "ip": 0,
+            "op": 60,
+            "w": 200,
+            "h": 100,
+            "layers": [
+                { "ty": 1, "nm": "hidden", "hd": true, "sc": "#00ff00", "sw": 10, "sh": 10 },
+                { "ty": 1, "nm": "late", "ip": 30, "sc": "#0000ff", "sw": 10, "sh": 10 },
+                { "ty": 0, "refId": "comp", "w": 50, "h": 40 },
+            ],
+            "assets": [
+                {
+                    "id": "comp",
+                    "layers": [{
+                        "ty": 1,
+                        "sc": "#ff0000",
+                        "sw": 20,
+                        "sh": 10,
+                        "ks": { "o": { "k": 50 } },
+                    }],
+                },
+            ],
+        }))
+        .unwrap();
+
+        let svg = frame_to_svg(&animation, &LottieRenderParams::default(), 0.0);
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100" viewBox="0 0 200 100">"#
+        ));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains(
+            r#"<defs><clipPath id="precomp-clip-1"><rect width="50" height="40"/></clipPath></defs>"#
+        ));
+        assert!(svg.contains(
+            r##"opacity="0.5"><rect width="20" height="10" fill="#ff0000" fill-opacity="1"/>"##
+        ));
+        assert!(!svg.contains("#00ff00"));
+        assert!(!svg.contains("#0000ff"));
+
+        let svg = frame_to_svg(&animation, &LottieRenderParams::default(), 30.0);
+        assert!(svg.contains(r##"fill="#0000ff""##));
+    }
+}
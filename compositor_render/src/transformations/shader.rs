@@ -1,45 +1,265 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use crate::{
     scene::ShaderParam,
     wgpu::{common_pipeline::CreateShaderError, WgpuCtx},
+    Resolution,
 };
 
 use self::{pipeline::ShaderPipeline, validation::error::ParametersValidationError};
 
+pub(crate) use base_params::BaseShaderParameters;
+pub(crate) use params_encoding::{encode_params, new_params_buffer};
+pub(crate) use pipeline::{USER_DEFINED_BUFFER_BINDING, USER_DEFINED_BUFFER_GROUP};
+
 mod base_params;
+mod history;
 pub mod node;
+mod params_encoding;
 mod pipeline;
 pub mod validation;
 
-const SHADER_INPUT_TEXTURES_AMOUNT: u32 = 16;
+pub(crate) const SHADER_INPUT_TEXTURES_AMOUNT: u32 = 16;
+
+/// Maximal number of past frames of each input that can be bound to a shader. Every
+/// frame of the history is a texture with the resolution of the shader output, so
+/// a shader with all inputs used keeps up to `1 + 16 * 4` frames in GPU memory.
+pub const SHADER_MAX_INPUT_HISTORY_LEN: u32 = 4;
+
+/// Maximal factor of `ShaderPassResolution::Scale`. Scaled resolution is additionally
+/// limited to the maximal texture size supported by the GPU.
+pub const SHADER_MAX_PASS_SCALE: f32 = 4.0;
 
 #[derive(Debug)]
 pub struct Shader {
     pipeline: ShaderPipeline,
+    /// Textures bound to the main pass, `None` means all component inputs.
+    inputs: Option<Vec<PassSource>>,
+    /// Passes rendered before the main pass.
+    passes: Vec<ShaderPass>,
     clear_color: Option<wgpu::Color>,
+    previous_frame: bool,
+    input_history_len: u32,
+}
+
+#[derive(Debug)]
+struct ShaderPass {
+    name: Arc<str>,
+    pipeline: ShaderPipeline,
+    resolution: ShaderPassResolution,
+    inputs: Option<Vec<PassSource>>,
+    /// Maximal width and height of a texture supported by the GPU.
+    max_dimension: usize,
+}
+
+/// Texture bound to a shader pass, with pass names resolved to indexes.
+#[derive(Debug, Clone, Copy)]
+enum PassSource {
+    Input(usize),
+    Pass(usize),
 }
 
 #[derive(Debug, Clone)]
 pub struct ShaderSpec {
+    /// Source of the main pass that renders the output of the shader.
     pub source: Arc<str>,
+    /// Bind output of the previous frame rendered by the shader.
+    pub previous_frame: bool,
+    /// Number of past frames of each input that are bound to the shader.
+    pub input_history_len: u32,
+    /// Textures bound to the main pass. Defaults to all inputs of the component.
+    pub inputs: Option<Vec<ShaderPassInput>>,
+    /// Intermediate passes rendered in order before the main pass.
+    pub passes: Vec<ShaderPassSpec>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShaderPassSpec {
+    pub name: Arc<str>,
+    pub source: Arc<str>,
+    pub resolution: ShaderPassResolution,
+    /// Textures bound to the pass. Defaults to all inputs of the component.
+    pub inputs: Option<Vec<ShaderPassInput>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ShaderPassResolution {
+    /// Resolution of the shader output multiplied by the factor.
+    Scale(f32),
+    Fixed(Resolution),
+}
+
+#[derive(Debug, Clone)]
+pub enum ShaderPassInput {
+    /// Input of the component with the specified index.
+    Input(usize),
+    /// Output of an earlier pass with the specified name.
+    Pass(Arc<str>),
+}
+
+impl ShaderSpec {
+    fn history_enabled(&self) -> bool {
+        self.previous_frame || self.input_history_len > 0
+    }
 }
 
 impl Shader {
     pub fn new(wgpu_ctx: &Arc<WgpuCtx>, spec: ShaderSpec) -> Result<Self, CreateShaderError> {
+        if spec.input_history_len > SHADER_MAX_INPUT_HISTORY_LEN {
+            return Err(CreateShaderError::InputHistoryTooLong(
+                spec.input_history_len,
+                SHADER_MAX_INPUT_HISTORY_LEN,
+            ));
+        }
+        // History of all inputs is stored in a single texture array.
+        let max_history_layers = 1 + SHADER_INPUT_TEXTURES_AMOUNT * spec.input_history_len;
+        let max_texture_array_layers = wgpu_ctx.device.limits().max_texture_array_layers;
+        if max_history_layers > max_texture_array_layers {
+            return Err(CreateShaderError::HistoryTooLarge(
+                max_history_layers,
+                max_texture_array_layers,
+            ));
+        }
+
+        let mut pass_indexes: HashMap<Arc<str>, usize> = HashMap::new();
+        let mut passes = Vec::with_capacity(spec.passes.len());
+        for pass in spec.passes.iter() {
+            let pass = ShaderPass::new(wgpu_ctx, pass, &pass_indexes)
+                .map_err(|err| CreateShaderError::PassError(pass.name.clone(), Box::new(err)))?;
+            if pass_indexes
+                .insert(pass.name.clone(), passes.len())
+                .is_some()
+            {
+                return Err(CreateShaderError::DuplicatePassName(pass.name));
+            }
+            passes.push(pass);
+        }
+
         let clear_color = None;
-        let pipeline = ShaderPipeline::new(wgpu_ctx, spec.source)?;
+        let pipeline = ShaderPipeline::new(wgpu_ctx, spec.source.clone(), spec.history_enabled())?;
+        let inputs = resolve_pass_inputs(spec.inputs.as_deref(), &pass_indexes)?;
 
         Ok(Self {
             pipeline,
+            inputs,
+            passes,
             clear_color,
+            previous_frame: spec.previous_frame,
+            input_history_len: spec.input_history_len,
         })
     }
 
+    /// Parameters are validated against every pass that declares the user-defined binding.
     pub(crate) fn validate_params(
         &self,
         params: &ShaderParam,
     ) -> Result<(), ParametersValidationError> {
-        self.pipeline.validate_params(params)
+        let mut has_binding = false;
+        for pass in self.passes.iter() {
+            match pass.pipeline.validate_params(params) {
+                Ok(()) => has_binding = true,
+                Err(ParametersValidationError::NoBindingInShader) => {}
+                Err(err) => {
+                    return Err(ParametersValidationError::WrongPassParams {
+                        pass_name: pass.name.to_string(),
+                        error: Box::new(err),
+                    })
+                }
+            }
+        }
+
+        match self.pipeline.validate_params(params) {
+            Err(ParametersValidationError::NoBindingInShader) if has_binding => Ok(()),
+            result => result,
+        }
+    }
+}
+
+impl ShaderPass {
+    fn new(
+        wgpu_ctx: &Arc<WgpuCtx>,
+        spec: &ShaderPassSpec,
+        earlier_passes: &HashMap<Arc<str>, usize>,
+    ) -> Result<Self, CreateShaderError> {
+        let max_dimension = wgpu_ctx.device.limits().max_texture_dimension_2d as usize;
+        match spec.resolution {
+            ShaderPassResolution::Scale(factor) if !(factor.is_finite() && factor > 0.0) => {
+                return Err(CreateShaderError::InvalidPassResolution);
+            }
+            ShaderPassResolution::Scale(factor) if factor > SHADER_MAX_PASS_SCALE => {
+                return Err(CreateShaderError::PassScaleTooLarge(
+                    factor,
+                    SHADER_MAX_PASS_SCALE,
+                ));
+            }
+            ShaderPassResolution::Fixed(resolution)
+                if resolution.width == 0 || resolution.height == 0 =>
+            {
+                return Err(CreateShaderError::InvalidPassResolution);
+            }
+            ShaderPassResolution::Fixed(resolution)
+                if resolution.width > max_dimension || resolution.height > max_dimension =>
+            {
+                return Err(CreateShaderError::PassResolutionTooLarge(
+                    resolution,
+                    max_dimension,
+                ));
+            }
+            _ => {}
+        }
+
+        Ok(Self {
+            name: spec.name.clone(),
+            pipeline: ShaderPipeline::new(wgpu_ctx, spec.source.clone(), false)?,
+            resolution: spec.resolution,
+            inputs: resolve_pass_inputs(spec.inputs.as_deref(), earlier_passes)?,
+            max_dimension,
+        })
     }
+
+    fn resolution(&self, output_resolution: Resolution) -> Resolution {
+        let scale = |size: usize, factor: f32| {
+            ((size as f32 * factor).round() as usize).clamp(1, self.max_dimension)
+        };
+        match self.resolution {
+            ShaderPassResolution::Scale(factor) => Resolution {
+                width: scale(output_resolution.width, factor),
+                height: scale(output_resolution.height, factor),
+            },
+            ShaderPassResolution::Fixed(resolution) => resolution,
+        }
+    }
+}
+
+fn resolve_pass_inputs(
+    inputs: Option<&[ShaderPassInput]>,
+    earlier_passes: &HashMap<Arc<str>, usize>,
+) -> Result<Option<Vec<PassSource>>, CreateShaderError> {
+    let Some(inputs) = inputs else {
+        return Ok(None);
+    };
+    if inputs.len() > SHADER_INPUT_TEXTURES_AMOUNT as usize {
+        return Err(CreateShaderError::TooManyPassInputs(
+            inputs.len(),
+            SHADER_INPUT_TEXTURES_AMOUNT,
+        ));
+    }
+
+    let sources = inputs
+        .iter()
+        .map(|input| match input {
+            ShaderPassInput::Input(index) if *index < SHADER_INPUT_TEXTURES_AMOUNT as usize => {
+                Ok(PassSource::Input(*index))
+            }
+            ShaderPassInput::Input(index) => Err(CreateShaderError::PassInputIndexOutOfRange(
+                *index,
+                SHADER_INPUT_TEXTURES_AMOUNT,
+            )),
+            ShaderPassInput::Pass(name) => earlier_passes
+                .get(name)
+                .map(|index| PassSource::Pass(*index))
+                .ok_or_else(|| CreateShaderError::UnknownPassInput(name.clone())),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Some(sources))
 }
@@ -0,0 +1,156 @@
+use crate::{
+    wgpu::{
+        texture::{NodeTexture, NodeTextureState},
+        WgpuCtx,
+    },
+    Resolution,
+};
+
+use super::pipeline::HISTORY_BINDING;
+
+/// Past frames bound to a shader as a single `texture_2d_array<f32>` with a
+/// resolution of the shader output:
+/// - layer `0` contains previous frame rendered by the shader,
+/// - layer `1 + input_index * input_history_len + (n - 1)` contains frame of the
+///   input `input_index` from `n` frames ago.
+///
+/// History is created together with the shader node, so it starts empty
+/// (transparent) after each scene update.
+pub(super) struct ShaderHistory {
+    texture: wgpu::Texture,
+    /// Views of layers where the most recent input frames are written.
+    latest_input_views: Vec<wgpu::TextureView>,
+    bind_group: wgpu::BindGroup,
+    previous_frame: bool,
+    input_history_len: u32,
+}
+
+impl ShaderHistory {
+    pub fn new(
+        ctx: &WgpuCtx,
+        layout: &wgpu::BindGroupLayout,
+        resolution: Resolution,
+        inputs_count: usize,
+        previous_frame: bool,
+        input_history_len: u32,
+    ) -> Self {
+        let layers_count = 1 + inputs_count as u32 * input_history_len;
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("shader history texture"),
+            size: wgpu::Extent3d {
+                width: resolution.width as u32,
+                height: resolution.height as u32,
+                depth_or_array_layers: layers_count,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[wgpu::TextureFormat::Rgba8UnormSrgb],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+        let latest_input_views = match input_history_len {
+            0 => Vec::new(),
+            _ => (0..inputs_count as u32)
+                .map(|input_index| {
+                    texture.create_view(&wgpu::TextureViewDescriptor {
+                        dimension: Some(wgpu::TextureViewDimension::D2),
+                        base_array_layer: input_layer(input_index, 1, input_history_len),
+                        array_layer_count: Some(1),
+                        ..Default::default()
+                    })
+                })
+                .collect(),
+        };
+
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("shader history bind group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: HISTORY_BINDING,
+                resource: wgpu::BindingResource::TextureView(&view),
+            }],
+        });
+
+        Self {
+            texture,
+            latest_input_views,
+            bind_group,
+            previous_frame,
+            input_history_len,
+        }
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    /// Stores frames used in the current render, so they are available
+    /// to the shader during the next one.
+    pub fn update(&self, ctx: &WgpuCtx, sources: &[&NodeTexture], output: &NodeTextureState) {
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("shader history update encoder"),
+            });
+        let layer_size = wgpu::Extent3d {
+            depth_or_array_layers: 1,
+            ..self.texture.size()
+        };
+
+        if self.previous_frame {
+            encoder.copy_texture_to_texture(
+                output.rgba_texture().texture().texture.as_image_copy(),
+                self.layer_copy(0),
+                layer_size,
+            );
+        }
+
+        for (input_index, latest_view) in self.latest_input_views.iter().enumerate() {
+            let input_index = input_index as u32;
+            // shift frames by one, the oldest one is overwritten
+            for age in (2..=self.input_history_len).rev() {
+                encoder.copy_texture_to_texture(
+                    self.layer_copy(input_layer(input_index, age - 1, self.input_history_len)),
+                    self.layer_copy(input_layer(input_index, age, self.input_history_len)),
+                    layer_size,
+                );
+            }
+
+            let source = sources
+                .get(input_index as usize)
+                .and_then(|source| source.state())
+                .map(NodeTextureState::bind_group);
+            ctx.utils
+                .texture_blit
+                .blit(ctx, &mut encoder, source, latest_view);
+        }
+
+        ctx.queue.submit(Some(encoder.finish()));
+    }
+
+    fn layer_copy(&self, layer: u32) -> wgpu::ImageCopyTexture<'_> {
+        wgpu::ImageCopyTexture {
+            texture: &self.texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d {
+                x: 0,
+                y: 0,
+                z: layer,
+            },
+            aspect: wgpu::TextureAspect::All,
+        }
+    }
+}
+
+fn input_layer(input_index: u32, age: u32, input_history_len: u32) -> u32 {
+    1 + input_index * input_history_len + (age - 1)
+}
@@ -1,7 +1,5 @@
 use std::{sync::Arc, time::Duration};
 
-use wgpu::util::DeviceExt;
-
 use crate::{
     scene::ShaderParam,
     state::RenderCtx,
@@ -9,65 +7,71 @@ use crate::{
     Resolution,
 };
 
-use super::Shader;
+use super::{
+    history::ShaderHistory, new_params_buffer, pipeline::ShaderPipeline, PassSource, Shader,
+    SHADER_INPUT_TEXTURES_AMOUNT,
+};
 
 pub struct ShaderNode {
-    params_bind_group: wgpu::BindGroup,
-    _custom_params_buffer: wgpu::Buffer,
+    params: ShaderNodeParams,
+    passes: Vec<ShaderPassNode>,
     shader: Arc<Shader>,
+    history: Option<ShaderHistory>,
     resolution: Resolution,
 }
 
+/// Intermediate pass of a multi-pass shader.
+struct ShaderPassNode {
+    params: ShaderNodeParams,
+    output: NodeTexture,
+}
+
+struct ShaderNodeParams {
+    bind_group: wgpu::BindGroup,
+    _buffer: wgpu::Buffer,
+}
+
 impl ShaderNode {
     pub fn new(
         ctx: &RenderCtx,
         shader: Arc<Shader>,
         shader_params: &Option<ShaderParam>,
         resolution: &Resolution,
+        inputs_count: usize,
     ) -> Self {
-        let custom_params_buffer = Self::new_params_buffer(ctx.wgpu_ctx, shader_params);
-        let params_bind_group = Self::new_params_bind_group(ctx.wgpu_ctx, &custom_params_buffer);
+        let params = ShaderNodeParams::new(ctx.wgpu_ctx, &shader.pipeline, shader_params);
+        let passes = shader
+            .passes
+            .iter()
+            .map(|pass| {
+                let mut output = NodeTexture::new();
+                output.ensure_size(ctx.wgpu_ctx, pass.resolution(*resolution));
+                ShaderPassNode {
+                    params: ShaderNodeParams::new(ctx.wgpu_ctx, &pass.pipeline, shader_params),
+                    output,
+                }
+            })
+            .collect();
+        let history = shader.pipeline.history_bind_group_layout().map(|layout| {
+            ShaderHistory::new(
+                ctx.wgpu_ctx,
+                layout,
+                *resolution,
+                usize::min(inputs_count, SHADER_INPUT_TEXTURES_AMOUNT as usize),
+                shader.previous_frame,
+                shader.input_history_len,
+            )
+        });
 
         Self {
-            params_bind_group,
-            _custom_params_buffer: custom_params_buffer,
+            params,
+            passes,
             shader,
+            history,
             resolution: *resolution,
         }
     }
 
-    fn new_params_buffer(ctx: &WgpuCtx, shader_params: &Option<ShaderParam>) -> wgpu::Buffer {
-        match shader_params {
-            Some(params) => {
-                let params = params.to_bytes();
-                ctx.device
-                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                        label: Some("shader node custom params buffer"),
-                        usage: wgpu::BufferUsages::UNIFORM,
-                        contents: &params,
-                    })
-            }
-            None => ctx
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("shader node empty custom params buffer"),
-                    contents: &[0],
-                    usage: wgpu::BufferUsages::UNIFORM,
-                }),
-        }
-    }
-
-    fn new_params_bind_group(ctx: &WgpuCtx, buffer: &wgpu::Buffer) -> wgpu::BindGroup {
-        ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("shader node params bind group"),
-            layout: &ctx.uniform_bgl,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: buffer.as_entire_binding(),
-            }],
-        })
-    }
-
     pub fn render(
         &self,
         wgpu_ctx: &Arc<WgpuCtx>,
@@ -75,39 +79,81 @@ impl ShaderNode {
         target: &mut NodeTexture,
         pts: Duration,
     ) {
+        let empty_texture = NodeTexture::new();
+
+        for (pass, pass_node) in self.shader.passes.iter().zip(self.passes.iter()) {
+            let Some(pass_target) = pass_node.output.state() else {
+                continue;
+            };
+            let pass_sources = self.pass_sources(pass.inputs.as_deref(), sources, &empty_texture);
+            pass.pipeline.render(
+                wgpu_ctx,
+                &pass_node.params.bind_group,
+                None,
+                &pass_sources,
+                pass_target,
+                pts,
+                self.shader.clear_color,
+            );
+        }
+
         let target = target.ensure_size(wgpu_ctx, self.resolution);
+        let main_sources =
+            self.pass_sources(self.shader.inputs.as_deref(), sources, &empty_texture);
 
         self.shader.pipeline.render(
             wgpu_ctx,
-            &self.params_bind_group,
-            sources,
+            &self.params.bind_group,
+            self.history.as_ref().map(ShaderHistory::bind_group),
+            &main_sources,
             target,
             pts,
             self.shader.clear_color,
-        )
-    }
-}
+        );
 
-pub trait ShaderParamExt {
-    fn to_bytes(&self) -> bytes::Bytes;
-}
-
-impl ShaderParamExt for ShaderParam {
-    fn to_bytes(&self) -> bytes::Bytes {
-        fn extend_bytes(s: &ShaderParam, bytes: &mut bytes::BytesMut) {
-            match s {
-                ShaderParam::F32(v) => bytes.extend_from_slice(&v.to_le_bytes()),
-                ShaderParam::U32(v) => bytes.extend_from_slice(&v.to_le_bytes()),
-                ShaderParam::I32(v) => bytes.extend_from_slice(&v.to_le_bytes()),
-                ShaderParam::List(v) => v.iter().for_each(|v| extend_bytes(v, bytes)),
-                ShaderParam::Struct(v) => v.iter().for_each(|v| extend_bytes(&v.value, bytes)),
-            }
+        if let Some(history) = &self.history {
+            history.update(wgpu_ctx, sources, target);
         }
+    }
 
-        let mut result = bytes::BytesMut::new();
+    fn pass_sources<'a>(
+        &'a self,
+        inputs: Option<&[PassSource]>,
+        sources: &[&'a NodeTexture],
+        empty_texture: &'a NodeTexture,
+    ) -> Vec<&'a NodeTexture> {
+        match inputs {
+            Some(inputs) => inputs
+                .iter()
+                .map(|input| match input {
+                    PassSource::Input(index) => sources.get(*index).copied(),
+                    PassSource::Pass(index) => self.passes.get(*index).map(|pass| &pass.output),
+                })
+                .map(|texture| texture.unwrap_or(empty_texture))
+                .collect(),
+            None => sources.to_vec(),
+        }
+    }
+}
 
-        extend_bytes(self, &mut result);
+impl ShaderNodeParams {
+    fn new(ctx: &WgpuCtx, pipeline: &ShaderPipeline, shader_params: &Option<ShaderParam>) -> Self {
+        let params = shader_params
+            .as_ref()
+            .and_then(|params| pipeline.encode_params(params));
+        let buffer = new_params_buffer(ctx, params);
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("shader node params bind group"),
+            layout: &ctx.uniform_bgl,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
 
-        result.freeze()
+        Self {
+            bind_group,
+            _buffer: buffer,
+        }
     }
 }
@@ -0,0 +1,360 @@
+use bytes::Bytes;
+use naga::{Handle, Type, VectorSize};
+use wgpu::util::DeviceExt;
+
+use crate::{scene::ShaderParam, wgpu::WgpuCtx};
+
+/// Serializes shader parameters into a uniform buffer using memory layout
+/// of the type declared in the shader (struct member offsets, array strides
+/// and vector/matrix alignment), so users don't have to pad the data manually.
+///
+/// Parameters need to be validated against the type before encoding, values
+/// that do not match the type are skipped.
+pub(crate) fn encode_params(
+    params: &ShaderParam,
+    ty: Handle<Type>,
+    module: &naga::Module,
+) -> Bytes {
+    let size = module.types[ty].inner.size(module.to_ctx()) as usize;
+    let mut buffer = vec![0; size];
+    write_param(params, ty, module, 0, &mut buffer);
+    Bytes::from(buffer)
+}
+
+/// Creates a uniform buffer with encoded parameters. Shaders that don't use custom
+/// parameters get a placeholder buffer, because the binding is always present.
+pub(crate) fn new_params_buffer(ctx: &WgpuCtx, params: Option<Bytes>) -> wgpu::Buffer {
+    match params.filter(|params| !params.is_empty()) {
+        Some(params) => ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("shader custom params buffer"),
+                usage: wgpu::BufferUsages::UNIFORM,
+                contents: &params,
+            }),
+        None => ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("shader empty custom params buffer"),
+                contents: &[0],
+                usage: wgpu::BufferUsages::UNIFORM,
+            }),
+    }
+}
+
+fn write_param(
+    params: &ShaderParam,
+    ty: Handle<Type>,
+    module: &naga::Module,
+    offset: usize,
+    buffer: &mut [u8],
+) {
+    match &module.types[ty].inner {
+        naga::TypeInner::Scalar(_) => write_scalar(params, offset, buffer),
+
+        naga::TypeInner::Vector { .. } => write_vector(params, offset, buffer),
+
+        naga::TypeInner::Matrix { columns, rows, .. } => {
+            let column_stride = matrix_column_stride(*rows);
+            let (columns, rows) = (*columns as usize, *rows as usize);
+            match params {
+                // Elements of a list are rows, so they are transposed into columns.
+                ShaderParam::List(list) => {
+                    for (row, values) in list.iter().take(rows).enumerate() {
+                        let values = vector_values(values);
+                        for (column, value) in values.iter().take(columns).enumerate() {
+                            let value_offset = column * column_stride + row * 4;
+                            write_scalar(value, offset + value_offset, buffer);
+                        }
+                    }
+                }
+                ShaderParam::Matrix(list) => {
+                    for (idx, column) in list.iter().take(columns).enumerate() {
+                        write_f32_slice(column, offset + idx * column_stride, buffer);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        naga::TypeInner::Array { base, stride, .. } => {
+            if let ShaderParam::List(list) = params {
+                for (idx, param) in list.iter().enumerate() {
+                    write_param(
+                        param,
+                        *base,
+                        module,
+                        offset + idx * *stride as usize,
+                        buffer,
+                    );
+                }
+            }
+        }
+
+        naga::TypeInner::Struct { members, .. } => {
+            if let ShaderParam::Struct(fields) = params {
+                for (member, field) in members.iter().zip(fields.iter()) {
+                    write_param(
+                        &field.value,
+                        member.ty,
+                        module,
+                        offset + member.offset as usize,
+                        buffer,
+                    );
+                }
+            }
+        }
+
+        _ => {}
+    }
+}
+
+fn write_vector(params: &ShaderParam, offset: usize, buffer: &mut [u8]) {
+    match params {
+        ShaderParam::List(list) => {
+            for (idx, value) in list.iter().enumerate() {
+                write_scalar(value, offset + idx * 4, buffer);
+            }
+        }
+        ShaderParam::Vec2(values) => write_f32_slice(values, offset, buffer),
+        ShaderParam::Vec3(values) => write_f32_slice(values, offset, buffer),
+        ShaderParam::Vec4(values) => write_f32_slice(values, offset, buffer),
+        _ => {}
+    }
+}
+
+fn vector_values(params: &ShaderParam) -> Vec<ShaderParam> {
+    match params {
+        ShaderParam::List(list) => list.clone(),
+        ShaderParam::Vec2(values) => values.map(ShaderParam::F32).to_vec(),
+        ShaderParam::Vec3(values) => values.map(ShaderParam::F32).to_vec(),
+        ShaderParam::Vec4(values) => values.map(ShaderParam::F32).to_vec(),
+        _ => vec![],
+    }
+}
+
+fn write_scalar(params: &ShaderParam, offset: usize, buffer: &mut [u8]) {
+    let bytes = match params {
+        ShaderParam::F32(v) => v.to_le_bytes(),
+        ShaderParam::U32(v) => v.to_le_bytes(),
+        ShaderParam::I32(v) => v.to_le_bytes(),
+        ShaderParam::Bool(v) => (*v as u32).to_le_bytes(),
+        _ => return,
+    };
+    write_bytes(&bytes, offset, buffer);
+}
+
+fn write_f32_slice(values: &[f32], offset: usize, buffer: &mut [u8]) {
+    for (idx, value) in values.iter().enumerate() {
+        write_bytes(&value.to_le_bytes(), offset + idx * 4, buffer);
+    }
+}
+
+fn write_bytes(bytes: &[u8], offset: usize, buffer: &mut [u8]) {
+    if let Some(target) = buffer.get_mut(offset..offset + bytes.len()) {
+        target.copy_from_slice(bytes);
+    }
+}
+
+/// Columns of a matrix are aligned the same way as vectors, `vec3` and
+/// `vec4` columns are aligned to 16 bytes.
+fn matrix_column_stride(rows: VectorSize) -> usize {
+    match rows {
+        VectorSize::Bi => 8,
+        VectorSize::Tri | VectorSize::Quad => 16,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scene::ShaderParamStructField;
+
+    use super::*;
+
+    fn encode(source: &str, params: ShaderParam) -> Bytes {
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let (ty, _) = module
+            .types
+            .iter()
+            .find(|(_, ty)| ty.name.as_deref() == Some("Params"))
+            .unwrap();
+        encode_params(&params, ty, &module)
+    }
+
+    fn read_f32(buffer: &[u8], offset: usize) -> f32 {
+        f32::from_le_bytes(buffer[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn read_u32(buffer: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(buffer[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn fields(values: Vec<(&str, ShaderParam)>) -> ShaderParam {
+        ShaderParam::Struct(
+            values
+                .into_iter()
+                .map(|(field_name, value)| ShaderParamStructField {
+                    field_name: field_name.to_string(),
+                    value,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_vec3_alignment() {
+        let buffer = encode(
+            r#"
+            struct Params { a: f32, b: vec3<f32>, c: f32 }
+            @group(1) @binding(0) var<uniform> params: Params;
+            "#,
+            fields(vec![
+                ("a", ShaderParam::F32(1.0)),
+                ("b", ShaderParam::Vec3([2.0, 3.0, 4.0])),
+                ("c", ShaderParam::F32(5.0)),
+            ]),
+        );
+
+        assert_eq!(buffer.len(), 32);
+        assert_eq!(read_f32(&buffer, 0), 1.0);
+        // `vec3` is aligned to 16 bytes, bytes 4..16 are padding
+        assert_eq!(&buffer[4..16], &[0; 12]);
+        assert_eq!(read_f32(&buffer, 16), 2.0);
+        assert_eq!(read_f32(&buffer, 20), 3.0);
+        assert_eq!(read_f32(&buffer, 24), 4.0);
+        // `f32` fits into the last 4 bytes of `vec3`
+        assert_eq!(read_f32(&buffer, 28), 5.0);
+    }
+
+    #[test]
+    fn test_matrix_column_stride() {
+        let buffer = encode(
+            r#"
+            struct Params { m3: mat3x3<f32>, m2: mat2x2<f32> }
+            @group(1) @binding(0) var<uniform> params: Params;
+            "#,
+            fields(vec![
+                (
+                    "m3",
+                    ShaderParam::Matrix(vec![
+                        vec![1.0, 2.0, 3.0],
+                        vec![4.0, 5.0, 6.0],
+                        vec![7.0, 8.0, 9.0],
+                    ]),
+                ),
+                (
+                    "m2",
+                    ShaderParam::List(vec![
+                        ShaderParam::Vec2([10.0, 11.0]),
+                        ShaderParam::Vec2([12.0, 13.0]),
+                    ]),
+                ),
+            ]),
+        );
+
+        assert_eq!(buffer.len(), 64);
+        for column in 0..3 {
+            for row in 0..3 {
+                let expected = (column * 3 + row + 1) as f32;
+                assert_eq!(read_f32(&buffer, column * 16 + row * 4), expected);
+            }
+            // Every `vec3` column is padded to 16 bytes
+            assert_eq!(read_f32(&buffer, column * 16 + 12), 0.0);
+        }
+        assert_eq!(read_f32(&buffer, 48), 10.0);
+        assert_eq!(read_f32(&buffer, 52), 11.0);
+        assert_eq!(read_f32(&buffer, 56), 12.0);
+        assert_eq!(read_f32(&buffer, 60), 13.0);
+    }
+
+    #[test]
+    fn test_matrix_list_rows() {
+        // `mat2x3` as a list of 3 rows with 2 values each
+        let buffer = encode(
+            r#"
+            struct Params { m: mat2x3<f32> }
+            @group(1) @binding(0) var<uniform> params: Params;
+            "#,
+            fields(vec![(
+                "m",
+                ShaderParam::List(vec![
+                    ShaderParam::Vec2([1.0, 2.0]),
+                    ShaderParam::Vec2([3.0, 4.0]),
+                    ShaderParam::List(vec![ShaderParam::F32(5.0), ShaderParam::F32(6.0)]),
+                ]),
+            )]),
+        );
+
+        assert_eq!(buffer.len(), 32);
+        assert_eq!(read_f32(&buffer, 0), 1.0);
+        assert_eq!(read_f32(&buffer, 4), 3.0);
+        assert_eq!(read_f32(&buffer, 8), 5.0);
+        // `vec3` column is padded to 16 bytes
+        assert_eq!(read_f32(&buffer, 12), 0.0);
+        assert_eq!(read_f32(&buffer, 16), 2.0);
+        assert_eq!(read_f32(&buffer, 20), 4.0);
+        assert_eq!(read_f32(&buffer, 24), 6.0);
+    }
+
+    #[test]
+    fn test_array_element_stride() {
+        let buffer = encode(
+            r#"
+            struct Params { items: array<vec3<f32>, 3>, count: u32 }
+            @group(1) @binding(0) var<uniform> params: Params;
+            "#,
+            fields(vec![
+                (
+                    "items",
+                    ShaderParam::List(vec![
+                        ShaderParam::Vec3([1.0, 2.0, 3.0]),
+                        ShaderParam::Vec3([4.0, 5.0, 6.0]),
+                        ShaderParam::Vec3([7.0, 8.0, 9.0]),
+                    ]),
+                ),
+                ("count", ShaderParam::U32(3)),
+            ]),
+        );
+
+        assert_eq!(buffer.len(), 64);
+        for element in 0..3 {
+            for component in 0..3 {
+                let expected = (element * 3 + component + 1) as f32;
+                assert_eq!(read_f32(&buffer, element * 16 + component * 4), expected);
+            }
+        }
+        assert_eq!(read_u32(&buffer, 48), 3);
+    }
+
+    #[test]
+    fn test_struct_padding() {
+        let buffer = encode(
+            r#"
+            struct Inner { flag: u32, color: vec4<f32> }
+            struct Params { a: f32, inner: Inner, b: i32, enabled: u32 }
+            @group(1) @binding(0) var<uniform> params: Params;
+            "#,
+            fields(vec![
+                ("a", ShaderParam::F32(1.0)),
+                (
+                    "inner",
+                    fields(vec![
+                        ("flag", ShaderParam::U32(7)),
+                        ("color", ShaderParam::Vec4([0.1, 0.2, 0.3, 0.4])),
+                    ]),
+                ),
+                ("b", ShaderParam::I32(-2)),
+                ("enabled", ShaderParam::Bool(true)),
+            ]),
+        );
+
+        // `Inner` is aligned to 16 bytes because of `vec4`
+        assert_eq!(buffer.len(), 64);
+        assert_eq!(read_f32(&buffer, 0), 1.0);
+        assert_eq!(read_u32(&buffer, 16), 7);
+        assert_eq!(read_f32(&buffer, 32), 0.1);
+        assert_eq!(read_f32(&buffer, 44), 0.4);
+        assert_eq!(i32::from_le_bytes(buffer[48..52].try_into().unwrap()), -2);
+        assert_eq!(read_u32(&buffer, 52), 1);
+    }
+}
@@ -1,5 +1,6 @@
 use std::{borrow::Cow, num::NonZeroU32, sync::Arc, time::Duration};
 
+use naga::{Handle, Type};
 use wgpu::ShaderStages;
 
 use crate::{
@@ -13,31 +14,41 @@ use crate::{
 
 use super::{
     base_params::BaseShaderParameters,
+    params_encoding::encode_params,
     validation::{
         error::{ParametersValidationError, ShaderParseError},
-        validate_contains_header, validate_params,
+        validate_contains_header, validate_history_binding, validate_params,
     },
 };
 
-pub(super) const USER_DEFINED_BUFFER_BINDING: u32 = 0;
-pub(super) const USER_DEFINED_BUFFER_GROUP: u32 = 1;
+pub(crate) const USER_DEFINED_BUFFER_BINDING: u32 = 0;
+pub(crate) const USER_DEFINED_BUFFER_GROUP: u32 = 1;
+pub(super) const HISTORY_BINDING: u32 = 0;
+pub(super) const HISTORY_GROUP: u32 = 3;
 
 #[derive(Debug)]
 pub(super) struct ShaderPipeline {
     pipeline: wgpu::RenderPipeline,
     sampler: Sampler,
     textures_bgl: wgpu::BindGroupLayout,
+    /// Only present if shader has access to frame history.
+    history_bgl: Option<wgpu::BindGroupLayout>,
     module: naga::Module,
 }
 
 impl ShaderPipeline {
-    pub fn new(wgpu_ctx: &Arc<WgpuCtx>, shader_src: Arc<str>) -> Result<Self, CreateShaderError> {
+    pub fn new(
+        wgpu_ctx: &Arc<WgpuCtx>,
+        shader_src: Arc<str>,
+        history_enabled: bool,
+    ) -> Result<Self, CreateShaderError> {
         let scope = WgpuErrorScope::push(&wgpu_ctx.device);
 
         let module = naga::front::wgsl::parse_str(&shader_src)
             .map_err(|err| CreateShaderError::ParseError(ShaderParseError::new(err, shader_src)))?;
 
         validate_contains_header(&wgpu_ctx.shader_header, &module)?;
+        validate_history_binding(&module, history_enabled)?;
 
         let shader_source = wgpu::ShaderSource::Naga(Cow::Owned(module.clone()));
         let sampler = Sampler::new(&wgpu_ctx.device);
@@ -57,6 +68,29 @@ impl ShaderPipeline {
                         },
                     }],
                 });
+        let history_bgl = history_enabled.then(|| {
+            wgpu_ctx
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("shader transformation history bgl"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: HISTORY_BINDING,
+                        count: None,
+                        visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2Array,
+                        },
+                    }],
+                })
+        });
+        let mut bind_group_layouts = vec![
+            &textures_bgl,
+            &wgpu_ctx.uniform_bgl,
+            &sampler.bind_group_layout,
+        ];
+        bind_group_layouts.extend(history_bgl.as_ref());
         let shader_module = wgpu_ctx
             .device
             .create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -68,11 +102,7 @@ impl ShaderPipeline {
                 .device
                 .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                     label: Some("shader transformation pipeline layout"),
-                    bind_group_layouts: &[
-                        &textures_bgl,
-                        &wgpu_ctx.uniform_bgl,
-                        &sampler.bind_group_layout,
-                    ],
+                    bind_group_layouts: &bind_group_layouts,
                     push_constant_ranges: &[wgpu::PushConstantRange {
                         stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
                         range: 0..BaseShaderParameters::push_constant_size(),
@@ -90,6 +120,7 @@ impl ShaderPipeline {
             pipeline,
             sampler,
             textures_bgl,
+            history_bgl,
             module,
         })
     }
@@ -98,6 +129,7 @@ impl ShaderPipeline {
         &self,
         wgpu_ctx: &Arc<WgpuCtx>,
         params: &wgpu::BindGroup,
+        history: Option<&wgpu::BindGroup>,
         sources: &[&NodeTexture],
         target: &NodeTextureState,
         pts: Duration,
@@ -142,6 +174,9 @@ impl ShaderPipeline {
             render_pass.set_bind_group(0, &input_textures_bg, &[]);
             render_pass.set_bind_group(USER_DEFINED_BUFFER_GROUP, params, &[]);
             render_pass.set_bind_group(2, &self.sampler.bind_group, &[]);
+            if let Some(history) = history {
+                render_pass.set_bind_group(HISTORY_GROUP, history, &[]);
+            }
 
             wgpu_ctx.plane.draw(&mut render_pass);
         };
@@ -160,7 +195,24 @@ impl ShaderPipeline {
 
     pub fn validate_params(&self, params: &ShaderParam) -> Result<(), ParametersValidationError> {
         let ty = self
-            .module
+            .user_params_type()
+            .ok_or(ParametersValidationError::NoBindingInShader)?;
+
+        validate_params(params, ty, &self.module)
+    }
+
+    pub fn history_bind_group_layout(&self) -> Option<&wgpu::BindGroupLayout> {
+        self.history_bgl.as_ref()
+    }
+
+    /// Returns `None` if the shader does not declare user-defined binding.
+    pub fn encode_params(&self, params: &ShaderParam) -> Option<bytes::Bytes> {
+        let ty = self.user_params_type()?;
+        Some(encode_params(params, ty, &self.module))
+    }
+
+    fn user_params_type(&self) -> Option<Handle<Type>> {
+        self.module
             .global_variables
             .iter()
             .find(|(_, global)| match global.binding.as_ref() {
@@ -172,9 +224,6 @@ impl ShaderPipeline {
                 None => false,
             })
             .map(|(_, handle)| handle.ty)
-            .ok_or(ParametersValidationError::NoBindingInShader)?;
-
-        validate_params(params, ty, &self.module)
     }
 
     fn input_textures_bg(
@@ -1,6 +1,6 @@
 use naga::{ArraySize, Handle, Module, ScalarKind, ShaderStage, Type, VectorSize};
 
-use crate::scene::ShaderParam;
+use crate::{scene::ShaderParam, transformations::compute_shader::STORAGE_BUFFERS_GROUP};
 
 pub mod error;
 
@@ -14,6 +14,11 @@ pub fn shader_header() -> Module {
         .expect("failed to parse the shader header file")
 }
 
+pub fn compute_shader_header() -> Module {
+    naga::front::wgsl::parse_str(include_str!("./validation/compute_shader_header.wgsl"))
+        .expect("failed to parse the compute shader header file")
+}
+
 pub(super) fn validate_contains_header(
     header: &naga::Module,
     shader: &naga::Module,
@@ -23,6 +28,60 @@ pub(super) fn validate_contains_header(
     Ok(())
 }
 
+/// Compute shaders use their own header (output is a storage texture) and
+/// need a `cs_main` entrypoint instead of vertex and fragment ones.
+pub(crate) fn validate_compute_shader(
+    header: &naga::Module,
+    shader: &naga::Module,
+) -> Result<(), ShaderValidationError> {
+    validate_globals(header, shader)?;
+    shader
+        .entry_points
+        .iter()
+        .find(|entry_point| {
+            entry_point.name == crate::wgpu::common_pipeline::COMPUTE_ENTRYPOINT_NAME
+                && entry_point.stage == ShaderStage::Compute
+        })
+        .ok_or(ShaderValidationError::ComputeShaderNotFound)?;
+    Ok(())
+}
+
+/// Every global declared in the storage buffers group has to be a storage buffer
+/// that fits into the registered buffer with an index equal to its binding.
+pub(crate) fn validate_storage_buffers(
+    shader: &naga::Module,
+    buffer_sizes: &[u64],
+) -> Result<(), ShaderValidationError> {
+    let storage_globals = shader.global_variables.iter().filter_map(|(_, global)| {
+        let binding = global.binding.as_ref()?;
+        (binding.group == STORAGE_BUFFERS_GROUP).then_some((global, binding.binding))
+    });
+
+    for (global, binding) in storage_globals {
+        let name = global.name.unwrap_with("<unknown>");
+        if !matches!(global.space, naga::AddressSpace::Storage { .. }) {
+            return Err(ShaderValidationError::StorageBufferNotStorage(name));
+        }
+        let Some(size) = buffer_sizes.get(binding as usize) else {
+            return Err(ShaderValidationError::StorageBufferNotRegistered {
+                name,
+                binding,
+                registered: buffer_sizes.len(),
+            });
+        };
+        let required = shader.types[global.ty].inner.size(shader.to_ctx()) as u64;
+        if required > *size {
+            return Err(ShaderValidationError::StorageBufferTooSmall {
+                name,
+                required,
+                size: *size,
+            });
+        }
+    }
+
+    Ok(())
+}
+
 fn validate_globals(
     header: &naga::Module,
     shader: &naga::Module,
@@ -66,6 +125,51 @@ fn validate_globals(
     Ok(())
 }
 
+/// Shaders with `previous_frame` or `input_history_len` enabled need to declare
+/// `@group(3) @binding(0) var history: texture_2d_array<f32>;`, other shaders can't
+/// declare any binding in that group.
+pub(super) fn validate_history_binding(
+    shader: &naga::Module,
+    history_enabled: bool,
+) -> Result<(), ShaderValidationError> {
+    let history_global = shader.global_variables.iter().find(|(_, global)| {
+        global
+            .binding
+            .as_ref()
+            .is_some_and(|binding| binding.group == super::pipeline::HISTORY_GROUP)
+    });
+
+    match (history_global, history_enabled) {
+        (None, false) => Ok(()),
+        (None, true) => Err(ShaderValidationError::HistoryBindingNotFound),
+        (Some((_, global)), false) => Err(ShaderValidationError::HistoryNotEnabled(
+            global.name.unwrap_with("<unknown>"),
+        )),
+        (Some((_, global)), true) => {
+            let expected = naga::TypeInner::Image {
+                dim: naga::ImageDimension::D2,
+                arrayed: true,
+                class: naga::ImageClass::Sampled {
+                    kind: ScalarKind::Float,
+                    multi: false,
+                },
+            };
+            let is_history_binding = global
+                .binding
+                .as_ref()
+                .is_some_and(|binding| binding.binding == super::pipeline::HISTORY_BINDING);
+            let provided = &shader.types[global.ty].inner;
+            if !is_history_binding || *provided != expected {
+                return Err(ShaderValidationError::HistoryBindingBadType(
+                    global.name.unwrap_with("<unknown>"),
+                    provided.to_string(shader),
+                ));
+            }
+            Ok(())
+        }
+    }
+}
+
 fn validate_vertex_input(
     header: &naga::Module,
     shader: &naga::Module,
@@ -309,7 +413,7 @@ fn validate_array_size_equivalent(
     Ok(())
 }
 
-pub(super) fn validate_params(
+pub(crate) fn validate_params(
     params: &ShaderParam,
     ty: Handle<Type>,
     module: &naga::Module,
@@ -319,13 +423,13 @@ pub(super) fn validate_params(
     match &ty.inner {
         naga::TypeInner::Scalar(scalar) => validate_scalar(params, *scalar),
 
-        naga::TypeInner::Vector { size, scalar } => validate_vector(params, *size, *scalar, module),
+        naga::TypeInner::Vector { size, scalar } => validate_vector(params, *size, *scalar),
 
         naga::TypeInner::Matrix {
             columns,
             rows,
             scalar,
-        } => validate_matrix(params, *columns, *rows, *scalar, module),
+        } => validate_matrix(params, *columns, *rows, *scalar),
 
         naga::TypeInner::Array { base, size, stride } => {
             validate_array(params, *base, *size, *stride, module)
@@ -383,13 +487,8 @@ fn validate_struct(
                     });
                 }
 
-                validate_params(&param_field.value, shader_member.ty, module).map_err(|err| {
-                    ParametersValidationError::WrongFieldType {
-                        struct_name: struct_name_in_shader.into(),
-                        struct_field: param_field.field_name.clone(),
-                        error: Box::new(err),
-                    }
-                })?
+                validate_params(&param_field.value, shader_member.ty, module)
+                    .map_err(|err| err.in_field(&param_field.field_name))?
             }
 
             Ok(())
@@ -426,12 +525,7 @@ fn validate_array(
             }
 
             for (idx, param) in list.iter().enumerate() {
-                validate_params(param, base, module).map_err(|err| {
-                    ParametersValidationError::WrongArrayElementType {
-                        idx,
-                        error: Box::new(err),
-                    }
-                })?
+                validate_params(param, base, module).map_err(|err| err.in_element(idx))?
             }
 
             Ok(())
@@ -444,29 +538,51 @@ fn validate_array(
     }
 }
 
+/// `List` is a list of rows, e.g. `mat2x3<f32>` is a list of three `vec2<f32>`.
+/// `Matrix` is a list of columns, the same way matrices are constructed in WGSL.
 fn validate_matrix(
     params: &ShaderParam,
     columns: VectorSize,
     rows: VectorSize,
     scalar: naga::Scalar,
-    module: &naga::Module,
 ) -> Result<(), ParametersValidationError> {
+    let expected_type = || matrix_type_name(columns, rows, scalar);
+
     match params {
         ShaderParam::List(rows_list) => {
             if rows_list.len() != rows as usize {
-                return Err(ParametersValidationError::ListTooLong {
+                return Err(ParametersValidationError::WrongListLength {
+                    expected_type: expected_type(),
                     expected: rows as usize,
                     actual: rows_list.len(),
                 });
             }
 
             for (idx, row) in rows_list.iter().enumerate() {
-                validate_vector(row, columns, scalar, module).map_err(|err| {
-                    ParametersValidationError::WrongMatrixRowType {
-                        idx,
-                        error: Box::new(err),
+                validate_vector(row, columns, scalar).map_err(|err| err.in_element(idx))?
+            }
+
+            Ok(())
+        }
+
+        ShaderParam::Matrix(columns_list) if is_f32(scalar) => {
+            if columns_list.len() != columns as usize {
+                return Err(ParametersValidationError::WrongListLength {
+                    expected_type: expected_type(),
+                    expected: columns as usize,
+                    actual: columns_list.len(),
+                });
+            }
+
+            for (idx, column) in columns_list.iter().enumerate() {
+                if column.len() != rows as usize {
+                    return Err(ParametersValidationError::WrongListLength {
+                        expected_type: vector_type_name(rows, scalar),
+                        expected: rows as usize,
+                        actual: column.len(),
                     }
-                })?
+                    .in_element(idx));
+                }
             }
 
             Ok(())
@@ -474,12 +590,7 @@ fn validate_matrix(
 
         _ => Err(ParametersValidationError::WrongType {
             actual: params.to_string(),
-            expected: naga::TypeInner::Matrix {
-                columns,
-                rows,
-                scalar,
-            }
-            .to_string(module),
+            expected: expected_type(),
         }),
     }
 }
@@ -488,32 +599,31 @@ fn validate_vector(
     params: &ShaderParam,
     size: VectorSize,
     scalar: naga::Scalar,
-    module: &naga::Module,
 ) -> Result<(), ParametersValidationError> {
     match params {
         ShaderParam::List(list) => {
             if list.len() != size as usize {
-                return Err(ParametersValidationError::ListTooLong {
+                return Err(ParametersValidationError::WrongListLength {
+                    expected_type: vector_type_name(size, scalar),
                     expected: size as usize,
                     actual: list.len(),
                 });
             }
 
             for (idx, v) in list.iter().enumerate() {
-                validate_scalar(v, scalar).map_err(|err| {
-                    ParametersValidationError::WrongVectorElementType {
-                        idx,
-                        error: Box::new(err),
-                    }
-                })?
+                validate_scalar(v, scalar).map_err(|err| err.in_element(idx))?
             }
 
             Ok(())
         }
 
+        ShaderParam::Vec2(_) if size == VectorSize::Bi && is_f32(scalar) => Ok(()),
+        ShaderParam::Vec3(_) if size == VectorSize::Tri && is_f32(scalar) => Ok(()),
+        ShaderParam::Vec4(_) if size == VectorSize::Quad && is_f32(scalar) => Ok(()),
+
         _ => Err(ParametersValidationError::WrongType {
             actual: params.to_string(),
-            expected: naga::TypeInner::Vector { size, scalar }.to_string(module),
+            expected: vector_type_name(size, scalar),
         }),
     }
 }
@@ -534,11 +644,13 @@ fn validate_scalar(
             }),
         },
 
+        // `bool` can't be used in uniform buffers, so boolean parameters
+        // are passed as `u32` equal to 0 or 1.
         naga::Scalar {
             kind: ScalarKind::Uint,
             width: 4,
         } => match params {
-            ShaderParam::U32(_) => Ok(()),
+            ShaderParam::U32(_) | ShaderParam::Bool(_) => Ok(()),
             _ => Err(ParametersValidationError::WrongType {
                 actual: params.to_string(),
                 expected: scalar.to_string(),
@@ -563,6 +675,23 @@ fn validate_scalar(
     }
 }
 
+fn is_f32(scalar: naga::Scalar) -> bool {
+    scalar == naga::Scalar::F32
+}
+
+fn vector_type_name(size: VectorSize, scalar: naga::Scalar) -> String {
+    format!("vec{}<{}>", size as u8, scalar.to_string())
+}
+
+fn matrix_type_name(columns: VectorSize, rows: VectorSize, scalar: naga::Scalar) -> String {
+    format!(
+        "mat{}x{}<{}>",
+        columns as u8,
+        rows as u8,
+        scalar.to_string()
+    )
+}
+
 trait TypeInnerExt {
     fn type_name(&self) -> &'static str;
     fn to_string(&self, module: &naga::Module) -> String;
@@ -589,14 +718,26 @@ impl TypeInnerExt for naga::TypeInner {
     fn to_string(&self, module: &naga::Module) -> String {
         match self {
             naga::TypeInner::Scalar(scalar) => scalar.to_string(),
-            naga::TypeInner::Vector { size, scalar } => {
-                format!("vec{}<{}>", *size as u8, scalar.to_string())
-            }
-            naga::TypeInner::Matrix { .. } => "matrix".to_string(),
+            naga::TypeInner::Vector { size, scalar } => vector_type_name(*size, *scalar),
+            naga::TypeInner::Matrix {
+                columns,
+                rows,
+                scalar,
+            } => matrix_type_name(*columns, *rows, *scalar),
             naga::TypeInner::Atomic { .. } => "atomic".to_string(),
             naga::TypeInner::Pointer { .. } => "pointer".to_string(),
             naga::TypeInner::ValuePointer { .. } => "value pointer".to_string(),
-            naga::TypeInner::Array { .. } => "array".to_string(),
+            naga::TypeInner::Array { base, size, .. } => {
+                let base: &naga::Type = &module.types[*base];
+                let base = match &base.name {
+                    Some(name) => name.clone(),
+                    None => base.inner.to_string(module),
+                };
+                match size {
+                    ArraySize::Constant(size) => format!("array<{base}, {size}>"),
+                    ArraySize::Dynamic => format!("array<{base}>"),
+                }
+            }
             naga::TypeInner::Struct { .. } => "struct".to_string(),
             naga::TypeInner::Image {
                 dim,
@@ -672,6 +813,15 @@ impl ShaderParamExt for ShaderParam {
             ShaderParam::F32(_) => "f32".to_string(),
             ShaderParam::U32(_) => "u32".to_string(),
             ShaderParam::I32(_) => "i32".to_string(),
+            ShaderParam::Bool(_) => "bool".to_string(),
+            ShaderParam::Vec2(_) => "vec2<f32>".to_string(),
+            ShaderParam::Vec3(_) => "vec3<f32>".to_string(),
+            ShaderParam::Vec4(_) => "vec4<f32>".to_string(),
+            ShaderParam::Matrix(columns) => format!(
+                "mat{}x{}<f32>",
+                columns.len(),
+                columns.first().map(Vec::len).unwrap_or_default()
+            ),
             ShaderParam::List(list) => {
                 let list = list
                     .iter()
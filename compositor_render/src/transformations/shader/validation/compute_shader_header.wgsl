@@ -0,0 +1,12 @@
+struct BaseShaderParameters {
+    plane_id: i32,
+    time: f32,
+    output_resolution: vec2<u32>,
+    texture_count: u32,
+}
+
+@group(0) @binding(0) var textures: binding_array<texture_2d<f32>, 16>;
+@group(2) @binding(0) var sampler_: sampler;
+@group(2) @binding(1) var output_texture: texture_storage_2d<rgba8unorm, write>;
+
+var<push_constant> base_params: BaseShaderParameters;
@@ -1,8 +1,13 @@
 use std::{fmt::Display, sync::Arc};
 
 use crate::{
-    transformations::shader::pipeline::{USER_DEFINED_BUFFER_BINDING, USER_DEFINED_BUFFER_GROUP},
-    wgpu::common_pipeline::VERTEX_ENTRYPOINT_NAME,
+    transformations::{
+        compute_shader::STORAGE_BUFFERS_GROUP,
+        shader::pipeline::{
+            HISTORY_BINDING, HISTORY_GROUP, USER_DEFINED_BUFFER_BINDING, USER_DEFINED_BUFFER_GROUP,
+        },
+    },
+    wgpu::common_pipeline::{COMPUTE_ENTRYPOINT_NAME, VERTEX_ENTRYPOINT_NAME},
 };
 
 const HEADER_DOCS_URL: &str = "https://compositor.live/docs/concept/shaders#header";
@@ -60,6 +65,35 @@ pub enum ShaderValidationError {
 
     #[error("User defined binding (group {USER_DEFINED_BUFFER_GROUP}, binding {USER_DEFINED_BUFFER_BINDING}) is not a uniform buffer. Is it defined as var<uniform>?")]
     UserBindingNotUniform,
+
+    #[error("Shader has frame history enabled, but it does not declare a history binding. Add \"@group({HISTORY_GROUP}) @binding({HISTORY_BINDING}) var history: texture_2d_array<f32>;\" in your shader code.")]
+    HistoryBindingNotFound,
+
+    #[error("A global variable \"{0}\" uses a binding group reserved for frame history. Enable \"previous_frame\" or \"input_history_len\" when registering the shader to use it.")]
+    HistoryNotEnabled(String),
+
+    #[error("Frame history binding \"{0}\" has a wrong type (expected: \"texture_2d_array<f32>\" at @group({HISTORY_GROUP}) @binding({HISTORY_BINDING}), actual: {1}).")]
+    HistoryBindingBadType(String, String),
+
+    #[error("Could not find a compute shader entrypoint. Expected \"@compute @workgroup_size(...) fn {COMPUTE_ENTRYPOINT_NAME}(...)\".")]
+    ComputeShaderNotFound,
+
+    #[error("A global variable \"{0}\" in @group({STORAGE_BUFFERS_GROUP}) is not a storage buffer. Is it defined as var<storage, read_write>?")]
+    StorageBufferNotStorage(String),
+
+    #[error("Storage buffer \"{name}\" uses @binding({binding}), but only {registered} storage buffer(s) were registered with the shader.")]
+    StorageBufferNotRegistered {
+        name: String,
+        binding: u32,
+        registered: usize,
+    },
+
+    #[error("Storage buffer \"{name}\" requires at least {required} bytes, but the registered buffer has {size} bytes.")]
+    StorageBufferTooSmall {
+        name: String,
+        required: u64,
+        size: u64,
+    },
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -148,34 +182,58 @@ pub enum ParametersValidationError {
         actual: String,
     },
 
-    #[error("Error while verifying field \"{struct_field}\" in struct \"{struct_name}\".")]
+    #[error("Shader parameter \"{field_path}\" does not match the type declared in the shader.")]
     WrongFieldType {
-        struct_name: String,
-        struct_field: String,
+        /// Path to the mismatched value, e.g. `lights[2].color`.
+        field_path: String,
         #[source]
         error: Box<ParametersValidationError>,
     },
 
-    #[error("Error while verifying array element at index {idx}.")]
-    WrongArrayElementType {
-        idx: usize,
+    #[error("Parameters do not match the shader pass \"{pass_name}\".")]
+    WrongPassParams {
+        pass_name: String,
         #[source]
         error: Box<ParametersValidationError>,
     },
 
-    #[error("Error while verifying vector element at index {idx}.")]
-    WrongVectorElementType {
-        idx: usize,
-        #[source]
-        error: Box<ParametersValidationError>,
+    #[error("Expected {expected_type} with {expected} element(s), but {actual} were provided via shader parameters.")]
+    WrongListLength {
+        expected_type: String,
+        expected: usize,
+        actual: usize,
     },
+}
 
-    #[error("Error while verifying matrix row {idx}.")]
-    WrongMatrixRowType {
-        idx: usize,
-        #[source]
-        error: Box<ParametersValidationError>,
-    },
+impl ParametersValidationError {
+    /// Prepends struct field name to the path of the mismatched value.
+    pub(crate) fn in_field(self, field_name: &str) -> Self {
+        self.prepend_path(field_name)
+    }
+
+    /// Prepends list element index to the path of the mismatched value.
+    pub(crate) fn in_element(self, idx: usize) -> Self {
+        self.prepend_path(&format!("[{idx}]"))
+    }
+
+    fn prepend_path(self, segment: &str) -> Self {
+        match self {
+            Self::WrongFieldType { field_path, error } => {
+                let separator = match field_path.starts_with('[') {
+                    true => "",
+                    false => ".",
+                };
+                Self::WrongFieldType {
+                    field_path: format!("{segment}{separator}{field_path}"),
+                    error,
+                }
+            }
+            error => Self::WrongFieldType {
+                field_path: segment.to_string(),
+                error: Box::new(error),
+            },
+        }
+    }
 }
 
 pub(crate) trait ShaderGlobalVariableExt {
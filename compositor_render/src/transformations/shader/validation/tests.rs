@@ -203,4 +203,373 @@ mod params_validation {
             Err(ParametersValidationError::WrongFieldName { .. })
         ))
     }
+
+    #[test]
+    fn field_path() {
+        let (module, ty) = parse_and_get_type(
+            r#"
+                    struct Light {
+                        color: vec3<f32>,
+                    }
+
+                    struct MyType {
+                        lights: array<Light, 4>,
+                    }
+                "#,
+            "MyType",
+        );
+
+        let light = |color: ShaderParam| {
+            ShaderParam::Struct(vec![ShaderParamStructField {
+                field_name: "color".into(),
+                value: color,
+            }])
+        };
+        let params = ShaderParam::Struct(vec![ShaderParamStructField {
+            field_name: "lights".into(),
+            value: ShaderParam::List(vec![
+                light(ShaderParam::Vec3([1.0, 0.0, 0.0])),
+                light(ShaderParam::List(vec![
+                    ShaderParam::F32(1.0),
+                    ShaderParam::U32(0),
+                    ShaderParam::F32(0.0),
+                ])),
+            ]),
+        }]);
+
+        match validate_params(&params, ty, &module) {
+            Err(ParametersValidationError::WrongFieldType { field_path, error }) => {
+                assert_eq!(field_path, "lights[1].color[1]");
+                assert!(matches!(
+                    *error,
+                    ParametersValidationError::WrongType { .. }
+                ));
+            }
+            result => panic!("Unexpected result: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn vectors_matrices_and_bools() {
+        let (module, ty) = parse_and_get_type(
+            r#"
+                    struct MyType {
+                        position: vec2<f32>,
+                        color: vec4<f32>,
+                        transform: mat2x3<f32>,
+                        enabled: u32,
+                    }
+                "#,
+            "MyType",
+        );
+
+        let params = ShaderParam::Struct(vec![
+            ShaderParamStructField {
+                field_name: "position".into(),
+                value: ShaderParam::Vec2([0.5, 0.5]),
+            },
+            ShaderParamStructField {
+                field_name: "color".into(),
+                value: ShaderParam::Vec4([1.0, 0.0, 0.0, 1.0]),
+            },
+            ShaderParamStructField {
+                field_name: "transform".into(),
+                value: ShaderParam::Matrix(vec![vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0]]),
+            },
+            ShaderParamStructField {
+                field_name: "enabled".into(),
+                value: ShaderParam::Bool(true),
+            },
+        ]);
+
+        validate_params(&params, ty, &module).unwrap();
+    }
+
+    #[test]
+    fn matrix_column_len() {
+        let (module, ty) = parse_and_get_type(
+            r#"
+                    struct MyType {
+                        transform: mat2x3<f32>,
+                    }
+                "#,
+            "MyType",
+        );
+
+        let params = ShaderParam::Struct(vec![ShaderParamStructField {
+            field_name: "transform".into(),
+            value: ShaderParam::Matrix(vec![vec![1.0, 0.0], vec![0.0, 1.0]]),
+        }]);
+
+        match validate_params(&params, ty, &module) {
+            Err(ParametersValidationError::WrongFieldType { field_path, error }) => {
+                assert_eq!(field_path, "transform[0]");
+                assert!(matches!(
+                    *error,
+                    ParametersValidationError::WrongListLength {
+                        expected: 3,
+                        actual: 2,
+                        ..
+                    }
+                ));
+            }
+            result => panic!("Unexpected result: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn matrix_as_list_of_rows() {
+        let (module, ty) = parse_and_get_type(
+            r#"
+                    struct MyType {
+                        transform: mat2x3<f32>,
+                    }
+                "#,
+            "MyType",
+        );
+
+        let rows = ShaderParam::Struct(vec![ShaderParamStructField {
+            field_name: "transform".into(),
+            value: ShaderParam::List(vec![
+                ShaderParam::Vec2([1.0, 0.0]),
+                ShaderParam::Vec2([0.0, 1.0]),
+                ShaderParam::Vec2([0.0, 0.0]),
+            ]),
+        }]);
+        validate_params(&rows, ty, &module).unwrap();
+
+        let columns = ShaderParam::Struct(vec![ShaderParamStructField {
+            field_name: "transform".into(),
+            value: ShaderParam::List(vec![
+                ShaderParam::Vec3([1.0, 0.0, 0.0]),
+                ShaderParam::Vec3([0.0, 1.0, 0.0]),
+            ]),
+        }]);
+        match validate_params(&columns, ty, &module) {
+            Err(ParametersValidationError::WrongFieldType { field_path, error }) => {
+                assert_eq!(field_path, "transform");
+                assert!(matches!(
+                    *error,
+                    ParametersValidationError::WrongListLength {
+                        expected: 3,
+                        actual: 2,
+                        ..
+                    }
+                ));
+            }
+            result => panic!("Unexpected result: {result:?}"),
+        }
+    }
+}
+
+mod params_encoding {
+    use crate::scene::ShaderParamStructField;
+
+    use super::super::super::params_encoding::encode_params;
+    use super::super::*;
+
+    #[test]
+    fn aligned_layout() {
+        let module = naga::front::wgsl::parse_str(
+            r#"
+                struct Item {
+                    value: f32,
+                }
+
+                struct MyType {
+                    offset: f32,
+                    color: vec3<f32>,
+                    items: array<Item, 2>,
+                    enabled: u32,
+                }
+            "#,
+        )
+        .unwrap();
+        let (ty, _) = module
+            .types
+            .iter()
+            .find(|(_, ty)| ty.name.as_deref() == Some("MyType"))
+            .unwrap();
+
+        let item = |value| {
+            ShaderParam::Struct(vec![ShaderParamStructField {
+                field_name: "value".into(),
+                value: ShaderParam::F32(value),
+            }])
+        };
+        let params = ShaderParam::Struct(vec![
+            ShaderParamStructField {
+                field_name: "offset".into(),
+                value: ShaderParam::F32(1.0),
+            },
+            ShaderParamStructField {
+                field_name: "color".into(),
+                value: ShaderParam::Vec3([2.0, 3.0, 4.0]),
+            },
+            ShaderParamStructField {
+                field_name: "items".into(),
+                value: ShaderParam::List(vec![item(5.0), item(6.0)]),
+            },
+            ShaderParamStructField {
+                field_name: "enabled".into(),
+                value: ShaderParam::Bool(true),
+            },
+        ]);
+
+        let bytes = encode_params(&params, ty, &module);
+        let words = bytes
+            .chunks(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect::<Vec<_>>();
+
+        // offset: 0, color: 16 (vec3 is aligned to 16 bytes), items: 28 (stride 4), enabled: 36
+        assert_eq!(
+            words,
+            vec![
+                1.0f32.to_bits(),
+                0,
+                0,
+                0,
+                2.0f32.to_bits(),
+                3.0f32.to_bits(),
+                4.0f32.to_bits(),
+                5.0f32.to_bits(),
+                6.0f32.to_bits(),
+                1,
+                0,
+                0,
+            ]
+        );
+    }
+}
+
+mod history_binding {
+    use super::super::*;
+
+    #[test]
+    fn missing_binding() {
+        let shader = naga::front::wgsl::parse_str("").unwrap();
+
+        assert!(validate_history_binding(&shader, false).is_ok());
+        assert!(matches!(
+            validate_history_binding(&shader, true),
+            Err(ShaderValidationError::HistoryBindingNotFound)
+        ));
+    }
+
+    #[test]
+    fn binding_type() {
+        let valid = naga::front::wgsl::parse_str(
+            "@group(3) @binding(0) var history: texture_2d_array<f32>;",
+        )
+        .unwrap();
+        let invalid =
+            naga::front::wgsl::parse_str("@group(3) @binding(0) var history: texture_2d<f32>;")
+                .unwrap();
+
+        assert!(validate_history_binding(&valid, true).is_ok());
+        assert!(matches!(
+            validate_history_binding(&valid, false),
+            Err(ShaderValidationError::HistoryNotEnabled(_))
+        ));
+        assert!(matches!(
+            validate_history_binding(&invalid, true),
+            Err(ShaderValidationError::HistoryBindingBadType(_, _))
+        ));
+    }
+}
+
+mod compute_shader {
+    use super::super::*;
+
+    #[test]
+    fn entrypoint() {
+        let header = compute_shader_header();
+        let header_src = include_str!("./compute_shader_header.wgsl");
+
+        let valid = naga::front::wgsl::parse_str(&format!(
+            "{header_src}
+            @compute @workgroup_size(8, 8, 1)
+            fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {{}}"
+        ))
+        .unwrap();
+        let fragment_only = naga::front::wgsl::parse_str(&format!(
+            "{header_src}
+            @fragment
+            fn fs_main() -> @location(0) vec4<f32> {{
+                return vec4(0.0);
+            }}"
+        ))
+        .unwrap();
+
+        assert!(validate_compute_shader(&header, &valid).is_ok());
+        assert!(matches!(
+            validate_compute_shader(&header, &fragment_only),
+            Err(ShaderValidationError::ComputeShaderNotFound)
+        ));
+    }
+
+    #[test]
+    fn output_texture() {
+        let header = compute_shader_header();
+        let shader = naga::front::wgsl::parse_str(
+            "
+            struct BaseShaderParameters {
+                plane_id: i32,
+                time: f32,
+                output_resolution: vec2<u32>,
+                texture_count: u32,
+            }
+
+            @group(0) @binding(0) var textures: binding_array<texture_2d<f32>, 16>;
+            @group(2) @binding(0) var sampler_: sampler;
+            @group(2) @binding(1) var output_texture: texture_storage_2d<rgba8unorm, read_write>;
+
+            var<push_constant> base_params: BaseShaderParameters;
+            ",
+        )
+        .unwrap();
+
+        assert!(matches!(
+            validate_compute_shader(&header, &shader),
+            Err(ShaderValidationError::GlobalBadType(_, _))
+        ));
+    }
+
+    #[test]
+    fn storage_buffers() {
+        let shader = naga::front::wgsl::parse_str(
+            "
+            struct Particle {
+                position: vec2<f32>,
+                velocity: vec2<f32>,
+            }
+
+            @group(3) @binding(0) var<storage, read_write> histogram: array<atomic<u32>, 256>;
+            @group(3) @binding(1) var<storage, read_write> particles: array<Particle>;
+            ",
+        )
+        .unwrap();
+
+        assert!(validate_storage_buffers(&shader, &[1024, 16]).is_ok());
+        assert!(matches!(
+            validate_storage_buffers(&shader, &[1024]),
+            Err(ShaderValidationError::StorageBufferNotRegistered { binding: 1, .. })
+        ));
+        assert!(matches!(
+            validate_storage_buffers(&shader, &[512, 16]),
+            Err(ShaderValidationError::StorageBufferTooSmall {
+                required: 1024,
+                size: 512,
+                ..
+            })
+        ));
+
+        let uniform =
+            naga::front::wgsl::parse_str("@group(3) @binding(0) var<uniform> value: u32;").unwrap();
+        assert!(matches!(
+            validate_storage_buffers(&uniform, &[4]),
+            Err(ShaderValidationError::StorageBufferNotStorage(_))
+        ));
+    }
 }
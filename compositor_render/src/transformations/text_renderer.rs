@@ -1,6 +1,8 @@
 use std::{
     cmp::max,
+    collections::HashSet,
     fmt,
+    path::PathBuf,
     sync::{Arc, Mutex},
 };
 
@@ -280,6 +282,47 @@ impl TextRendererCtx {
         let mut font_system = self.font_system.lock().unwrap();
         font_system.db_mut().load_font_source(source);
     }
+
+    /// Names of all font families available to the text renderer.
+    pub fn font_families(&self) -> Vec<String> {
+        let font_system = self.font_system.lock().unwrap();
+        let mut families: Vec<String> = font_system
+            .db()
+            .faces()
+            .flat_map(|face| face.families.iter().map(|(name, _)| name.clone()))
+            .collect();
+        families.sort();
+        families.dedup();
+        families
+    }
+
+    /// Sources of all fonts available to the text renderer, each listed once even if
+    /// it contains multiple faces.
+    pub(crate) fn font_sources(&self) -> Vec<Source> {
+        let font_system = self.font_system.lock().unwrap();
+        let mut loaded = HashSet::new();
+        font_system
+            .db()
+            .faces()
+            .filter(|face| loaded.insert(FontSourceKey::new(&face.source)))
+            .map(|face| face.source.clone())
+            .collect()
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum FontSourceKey {
+    File(PathBuf),
+    Binary(*const u8),
+}
+
+impl FontSourceKey {
+    fn new(source: &Source) -> Self {
+        match source {
+            Source::Binary(data) => Self::Binary(Arc::as_ptr(data) as *const u8),
+            Source::File(path) | Source::SharedFile(path, _) => Self::File(path.clone()),
+        }
+    }
 }
 
 impl TextRendererCtx {
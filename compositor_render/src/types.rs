@@ -44,6 +44,8 @@ where
     Id: From<Arc<str>>,
 {
     pub frames: HashMap<Id, Frame>,
+    /// Frames that should be blended with the frame of the same id from `frames`.
+    pub blended_frames: HashMap<Id, BlendedFrame>,
     pub pts: Duration,
 }
 
@@ -54,11 +56,21 @@ where
     pub fn new(pts: Duration) -> Self {
         FrameSet {
             frames: HashMap::new(),
+            blended_frames: HashMap::new(),
             pts,
         }
     }
 }
 
+/// Frame mixed with another frame of the same stream to produce an intermediate
+/// frame, e.g. when framerate of an input does not match the output framerate.
+#[derive(Debug, Clone)]
+pub struct BlendedFrame {
+    pub frame: Frame,
+    /// Weight of this frame in range `[0, 1]`. The other frame has weight `1 - weight`.
+    pub weight: f32,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Framerate {
     pub num: u32,
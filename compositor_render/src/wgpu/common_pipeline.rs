@@ -1,5 +1,14 @@
 pub mod plane;
 
+use std::sync::Arc;
+
+use crate::{
+    transformations::shader::validation::error::{ShaderParseError, ShaderValidationError},
+    Resolution,
+};
+
+use super::WgpuError;
+
 pub const PRIMITIVE_STATE: wgpu::PrimitiveState = wgpu::PrimitiveState {
     polygon_mode: wgpu::PolygonMode::Fill,
     topology: wgpu::PrimitiveTopology::TriangleList,
@@ -10,12 +19,9 @@ pub const PRIMITIVE_STATE: wgpu::PrimitiveState = wgpu::PrimitiveState {
     unclipped_depth: false,
 };
 
-use crate::transformations::shader::validation::error::{ShaderParseError, ShaderValidationError};
-
-use super::WgpuError;
-
 pub const VERTEX_ENTRYPOINT_NAME: &str = "vs_main";
 pub const FRAGMENT_ENTRYPOINT_NAME: &str = "fs_main";
+pub const COMPUTE_ENTRYPOINT_NAME: &str = "cs_main";
 
 #[derive(Debug, thiserror::Error)]
 pub enum CreateShaderError {
@@ -27,6 +33,42 @@ pub enum CreateShaderError {
 
     #[error(transparent)]
     ParseError(#[from] ShaderParseError),
+
+    #[error("Input history can't be longer than {1} frames (requested: {0}).")]
+    InputHistoryTooLong(u32, u32),
+
+    #[error("Frame history of the shader can have up to {0} frames, but the GPU supports at most {1} texture array layers. Reduce \"input_history_len\".")]
+    HistoryTooLarge(u32, u32),
+
+    #[error("Failed to create shader pass \"{0}\".")]
+    PassError(Arc<str>, #[source] Box<CreateShaderError>),
+
+    #[error("More than one shader pass is named \"{0}\". Pass names need to be unique.")]
+    DuplicatePassName(Arc<str>),
+
+    #[error("Shader pass \"{0}\" is used as an input before it is rendered. Passes can only read outputs of earlier passes.")]
+    UnknownPassInput(Arc<str>),
+
+    #[error("Shader pass can have at most {1} inputs (provided: {0}).")]
+    TooManyPassInputs(usize, u32),
+
+    #[error("Shader pass input index {0} is out of range, shaders can have at most {1} inputs.")]
+    PassInputIndexOutOfRange(usize, u32),
+
+    #[error("Shader pass resolution has to be positive.")]
+    InvalidPassResolution,
+
+    #[error("Shader pass resolution can't be scaled by more than {1} (provided: {0}).")]
+    PassScaleTooLarge(f32, f32),
+
+    #[error("Shader pass resolution {}x{} exceeds the maximal texture size {max}x{max} supported by the GPU.", .0.width, .0.height, max = .1)]
+    PassResolutionTooLarge(Resolution, usize),
+
+    #[error("Compute shader can have at most {1} storage buffers (provided: {0}).")]
+    TooManyStorageBuffers(usize, u32),
+
+    #[error("Storage buffer {0} has an invalid size {1}. Size has to be a positive multiple of 4 and can't exceed {2} bytes.")]
+    InvalidStorageBufferSize(usize, u64, u64),
 }
 
 #[repr(C)]
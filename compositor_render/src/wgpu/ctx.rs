@@ -13,6 +13,7 @@ pub struct WgpuCtx {
     pub queue: Arc<wgpu::Queue>,
 
     pub shader_header: naga::Module,
+    pub compute_shader_header: naga::Module,
 
     pub format: TextureFormat,
     pub utils: TextureUtils,
@@ -58,11 +59,13 @@ impl WgpuCtx {
         queue: Arc<wgpu::Queue>,
     ) -> Result<Self, CreateWgpuCtxError> {
         let shader_header = crate::transformations::shader::validation::shader_header();
+        let compute_shader_header =
+            crate::transformations::shader::validation::compute_shader_header();
 
         let scope = WgpuErrorScope::push(&device);
 
         let format = TextureFormat::new(&device);
-        let utils = TextureUtils::new(&device);
+        let utils = TextureUtils::new(&device, &format);
 
         let uniform_bgl = uniform_bind_group_layout(&device);
 
@@ -79,6 +82,7 @@ impl WgpuCtx {
             device,
             queue,
             shader_header,
+            compute_shader_header,
             format,
             utils,
             uniform_bgl,
@@ -149,10 +153,24 @@ pub fn create_wgpu_ctx(
         return Err(CreateWgpuCtxError::NoAdapter);
     }
 
+    let required_limits = set_required_wgpu_limits(limits);
+    let required_limits = wgpu::Limits {
+        // Shaders with frame history bind one texture in addition to the input textures.
+        // Request it only if adapter supports it, otherwise history is unavailable.
+        max_sampled_textures_per_shader_stage: u32::max(
+            required_limits.max_sampled_textures_per_shader_stage,
+            u32::min(
+                adapter.limits().max_sampled_textures_per_shader_stage,
+                crate::transformations::shader::SHADER_INPUT_TEXTURES_AMOUNT + 1,
+            ),
+        ),
+        ..required_limits
+    };
+
     let (device, queue) = pollster::block_on(adapter.request_device(
         &wgpu::DeviceDescriptor {
             label: None,
-            required_limits: set_required_wgpu_limits(limits),
+            required_limits,
             required_features,
             memory_hints: wgpu::MemoryHints::default(),
         },
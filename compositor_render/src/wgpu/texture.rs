@@ -60,18 +60,31 @@ impl InputTextureState {
     }
 }
 
-pub struct InputTexture(Option<InputTextureState>);
+pub struct InputTexture {
+    state: Option<InputTextureState>,
+    /// Textures of the next frame that is blended with the current one. They are
+    /// preserved when blending is not used, so they can be reused later.
+    next_frame: Option<Box<(InputTexture, NodeTexture)>>,
+    /// Weight of the next frame, `None` if blending is not used.
+    next_frame_weight: Option<f32>,
+}
 
 impl InputTexture {
     pub fn new() -> Self {
-        Self(None)
+        Self {
+            state: None,
+            next_frame: None,
+            next_frame_weight: None,
+        }
     }
 
     pub fn clear(&mut self) {
-        self.0 = None;
+        self.state = None;
+        self.next_frame_weight = None;
     }
 
     pub fn upload(&mut self, ctx: &WgpuCtx, frame: Frame) {
+        self.next_frame_weight = None;
         match frame.data {
             FrameData::PlanarYuv420(planes) => self.upload_planar_yuv(
                 ctx,
@@ -89,14 +102,24 @@ impl InputTexture {
                 self.upload_interleaved_yuv(ctx, data, frame.resolution)
             }
             FrameData::Rgba8UnormWgpuTexture(texture) => {
-                self.0 = Some(InputTextureState::Rgba8UnormWgpuTexture(texture))
+                self.state = Some(InputTextureState::Rgba8UnormWgpuTexture(texture))
             }
             FrameData::Nv12WgpuTexture(texture) => {
-                self.0 = Some(InputTextureState::Nv12WgpuTexture(texture))
+                self.state = Some(InputTextureState::Nv12WgpuTexture(texture))
             }
         }
     }
 
+    /// Uploads frame that will be blended with the frame from the last `upload` call.
+    /// `weight` is a weight of the next frame in range `[0, 1]`.
+    pub fn upload_next_frame(&mut self, ctx: &WgpuCtx, frame: Frame, weight: f32) {
+        let next_frame = self
+            .next_frame
+            .get_or_insert_with(|| Box::new((InputTexture::new(), NodeTexture::new())));
+        next_frame.0.upload(ctx, frame);
+        self.next_frame_weight = Some(weight);
+    }
+
     fn upload_planar_yuv(
         &mut self,
         ctx: &WgpuCtx,
@@ -104,7 +127,7 @@ impl InputTexture {
         resolution: Resolution,
         variant: planar_yuv::YuvVariant,
     ) {
-        let should_recreate = match &self.0 {
+        let should_recreate = match &self.state {
             Some(state) => {
                 !matches!(state, InputTextureState::PlanarYuvTextures { .. })
                     || resolution != state.resolution()
@@ -115,12 +138,13 @@ impl InputTexture {
         if should_recreate {
             let textures = PlanarYuvTextures::new(ctx, resolution);
             let bind_group = textures.new_bind_group(ctx, ctx.format.planar_yuv_layout());
-            self.0 = Some(InputTextureState::PlanarYuvTextures {
+            self.state = Some(InputTextureState::PlanarYuvTextures {
                 textures,
                 bind_group,
             })
         }
-        let Some(InputTextureState::PlanarYuvTextures { textures, .. }) = self.0.as_mut() else {
+        let Some(InputTextureState::PlanarYuvTextures { textures, .. }) = self.state.as_mut()
+        else {
             error!("Invalid texture format.");
             return;
         };
@@ -133,7 +157,7 @@ impl InputTexture {
         data: bytes::Bytes,
         resolution: Resolution,
     ) {
-        let should_recreate = match &self.0 {
+        let should_recreate = match &self.state {
             Some(state) => {
                 !matches!(state, InputTextureState::InterleavedYuv422Texture { .. })
                     || resolution != state.resolution()
@@ -145,13 +169,13 @@ impl InputTexture {
             let texture = InterleavedYuv422Texture::new(ctx, resolution);
             let bind_group = texture.new_bind_group(ctx, ctx.format.interleaved_yuv_layout());
 
-            self.0 = Some(InputTextureState::InterleavedYuv422Texture {
+            self.state = Some(InputTextureState::InterleavedYuv422Texture {
                 texture,
                 bind_group,
             });
         }
 
-        let Some(InputTextureState::InterleavedYuv422Texture { texture, .. }) = self.0.as_mut()
+        let Some(InputTextureState::InterleavedYuv422Texture { texture, .. }) = self.state.as_mut()
         else {
             error!("Invalid texture format.");
             return;
@@ -159,8 +183,30 @@ impl InputTexture {
         texture.upload(ctx, &data)
     }
 
-    pub fn convert_to_node_texture(&self, ctx: &WgpuCtx, dest: &mut NodeTexture) {
-        match &self.0 {
+    pub fn convert_to_node_texture(&mut self, ctx: &WgpuCtx, dest: &mut NodeTexture) {
+        self.convert_frame(ctx, dest);
+
+        let (Some(weight), Some(next_frame), Some(dest_state)) = (
+            self.next_frame_weight,
+            self.next_frame.as_mut(),
+            dest.state(),
+        ) else {
+            return;
+        };
+        let (next_input, next_node) = &mut **next_frame;
+        next_input.convert_frame(ctx, next_node);
+        if let Some(next_state) = next_node.state() {
+            ctx.utils.texture_blend.blend(
+                ctx,
+                next_state.bind_group(),
+                dest_state.rgba_texture().texture(),
+                weight,
+            );
+        }
+    }
+
+    fn convert_frame(&self, ctx: &WgpuCtx, dest: &mut NodeTexture) {
+        match &self.state {
             Some(input_texture) => {
                 let dest_state = dest.ensure_size(ctx, input_texture.resolution());
                 match &input_texture {
@@ -290,6 +336,47 @@ impl NodeTexture {
     pub fn texture(&self) -> Option<&Texture> {
         self.state().map(|state| state.rgba_texture().texture())
     }
+
+    /// Copies current content of the texture into a new buffer. Returns `None` if
+    /// texture is empty.
+    ///
+    /// `device.poll(wgpu::MaintainBase::Wait)` needs to be called after download
+    /// is started, but before the returned function is called. Data is returned
+    /// as RGBA (sRGB) without row padding.
+    pub fn start_download(
+        &self,
+        ctx: &WgpuCtx,
+    ) -> Option<impl FnOnce() -> Result<Bytes, BufferAsyncError> + 'static> {
+        let texture = self.texture()?;
+        let size = texture.size();
+        let row_size = (texture.block_size().unwrap() * size.width) as usize;
+
+        let source = texture.new_download_buffer(ctx);
+        let mut encoder = ctx.device.create_command_encoder(&Default::default());
+        texture.copy_to_buffer(&mut encoder, &source);
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let (s, r) = bounded(1);
+        source.slice(..).map_async(MapMode::Read, move |result| {
+            if let Err(err) = s.send(result) {
+                error!("channel send error: {err}")
+            }
+        });
+
+        Some(move || {
+            r.recv().unwrap()?;
+            let mut buffer = BytesMut::with_capacity(row_size * size.height as usize);
+            {
+                let range = source.slice(..).get_mapped_range();
+                let chunks = range.chunks(pad_to_256(row_size as u32) as usize);
+                for chunk in chunks {
+                    buffer.extend_from_slice(&chunk[..row_size]);
+                }
+            };
+            source.unmap();
+            Ok(buffer.freeze())
+        })
+    }
 }
 
 impl Default for NodeTexture {
@@ -1,18 +1,26 @@
-use self::r8_fill_with_color::R8FillWithValue;
+use self::{
+    r8_fill_with_color::R8FillWithValue, texture_blend::TextureBlend, texture_blit::TextureBlit,
+};
 
-use super::{texture::Texture, WgpuCtx};
+use super::{format::TextureFormat, texture::Texture, WgpuCtx};
 
 mod r8_fill_with_color;
+mod texture_blend;
+mod texture_blit;
 
 #[derive(Debug)]
 pub struct TextureUtils {
     pub r8_fill_with_value: R8FillWithValue,
+    pub texture_blit: TextureBlit,
+    pub texture_blend: TextureBlend,
 }
 
 impl TextureUtils {
-    pub fn new(device: &wgpu::Device) -> Self {
+    pub fn new(device: &wgpu::Device, format: &TextureFormat) -> Self {
         Self {
             r8_fill_with_value: R8FillWithValue::new(device),
+            texture_blit: TextureBlit::new(device, format.rgba_layout()),
+            texture_blend: TextureBlend::new(device, format.rgba_layout()),
         }
     }
 
@@ -0,0 +1,106 @@
+use crate::wgpu::common_pipeline::{Sampler, Vertex, PRIMITIVE_STATE};
+
+use super::super::{texture::Texture, WgpuCtx};
+
+/// Mixes RGBA texture into a render target with a constant weight.
+#[derive(Debug)]
+pub struct TextureBlend {
+    pipeline: wgpu::RenderPipeline,
+    sampler: Sampler,
+}
+
+impl TextureBlend {
+    pub fn new(device: &wgpu::Device, rgba_layout: &wgpu::BindGroupLayout) -> Self {
+        let shader_module = device.create_shader_module(wgpu::include_wgsl!("texture_blit.wgsl"));
+        let sampler = Sampler::new(device);
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Texture blend render pipeline layout"),
+            bind_group_layouts: &[rgba_layout, &sampler.bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        // result = src * weight + dst * (1 - weight), where weight is a blend constant
+        let blend_component = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Constant,
+            dst_factor: wgpu::BlendFactor::OneMinusConstant,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Texture blend pipeline"),
+            layout: Some(&pipeline_layout),
+            primitive: PRIMITIVE_STATE,
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vs_main",
+                buffers: &[Vertex::LAYOUT],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                    write_mask: wgpu::ColorWrites::all(),
+                    blend: Some(wgpu::BlendState {
+                        color: blend_component,
+                        alpha: blend_component,
+                    }),
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Self { pipeline, sampler }
+    }
+
+    /// Mixes texture bound by `src` (created with `rgba_layout`) into the current
+    /// content of `dst`. `weight` is a weight of `src` in range `[0, 1]`.
+    pub fn blend(&self, ctx: &WgpuCtx, src: &wgpu::BindGroup, dst: &Texture, weight: f32) {
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Texture blend command encoder"),
+            });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Texture blend render pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                    view: &dst.view,
+                    resolve_target: None,
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            let weight = weight.clamp(0.0, 1.0) as f64;
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_blend_constant(wgpu::Color {
+                r: weight,
+                g: weight,
+                b: weight,
+                a: weight,
+            });
+            render_pass.set_bind_group(0, src, &[]);
+            render_pass.set_bind_group(1, &self.sampler.bind_group, &[]);
+            ctx.plane.draw(&mut render_pass);
+        }
+
+        ctx.queue.submit(Some(encoder.finish()));
+    }
+}
@@ -0,0 +1,87 @@
+use crate::wgpu::common_pipeline::{Sampler, Vertex, PRIMITIVE_STATE};
+
+use super::super::WgpuCtx;
+
+/// Copies RGBA texture into a render target of a different size.
+#[derive(Debug)]
+pub struct TextureBlit {
+    pipeline: wgpu::RenderPipeline,
+    sampler: Sampler,
+}
+
+impl TextureBlit {
+    pub fn new(device: &wgpu::Device, rgba_layout: &wgpu::BindGroupLayout) -> Self {
+        let shader_module = device.create_shader_module(wgpu::include_wgsl!("texture_blit.wgsl"));
+        let sampler = Sampler::new(device);
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Texture blit render pipeline layout"),
+            bind_group_layouts: &[rgba_layout, &sampler.bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Texture blit pipeline"),
+            layout: Some(&pipeline_layout),
+            primitive: PRIMITIVE_STATE,
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vs_main",
+                buffers: &[Vertex::LAYOUT],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                    write_mask: wgpu::ColorWrites::all(),
+                    blend: None,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Self { pipeline, sampler }
+    }
+
+    /// Renders texture bound by `src` (created with `rgba_layout`) into `dst`. If `src`
+    /// is `None`, then `dst` is cleared.
+    pub fn blit(
+        &self,
+        ctx: &WgpuCtx,
+        encoder: &mut wgpu::CommandEncoder,
+        src: Option<&wgpu::BindGroup>,
+        dst: &wgpu::TextureView,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Texture blit render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+                view: dst,
+                resolve_target: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        if let Some(src) = src {
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, src, &[]);
+            render_pass.set_bind_group(1, &self.sampler.bind_group, &[]);
+            ctx.plane.draw(&mut render_pass);
+        }
+    }
+}
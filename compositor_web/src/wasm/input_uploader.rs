@@ -43,7 +43,11 @@ impl InputUploader {
             );
         }
 
-        Ok(FrameSet { frames, pts })
+        Ok(FrameSet {
+            frames,
+            blended_frames: HashMap::new(),
+            pts,
+        })
     }
 
     pub fn remove_input(&mut self, input_id: &InputId) {
@@ -2,8 +2,9 @@ use super::docs_config::DocsConfig;
 use super::document::generate;
 use super::markdown::overrides;
 use compositor_api::types::{
-    DeckLink, Image, ImageSpec, InputStream, Mp4Input, Mp4Output, Rescaler, RtpInput, RtpOutput,
-    Shader, ShaderSpec, Text, Tiles, View, WebRendererSpec, WebView,
+    ComputeShader, ComputeShaderSpec, DeckLink, Image, ImageSpec, InputStream, Lottie, LottieSpec,
+    Mp4Input, Mp4Output, Rescaler, RtpInput, RtpOutput, Shader, ShaderSpec, Text, Tiles, View,
+    WebRendererSpec, WebView,
 };
 use std::{fs, path::PathBuf};
 
@@ -23,7 +24,9 @@ pub fn generate_docs() {
 
     let renderer_pages = [
         generate::<ShaderSpec>("Shader", &config),
+        generate::<ComputeShaderSpec>("ComputeShader", &config),
         generate::<ImageSpec>("Image", &config),
+        generate::<LottieSpec>("Lottie", &config),
         generate::<WebRendererSpec>("WebRenderer", &config),
         generate::<RtpInput>("RtpInputStream", &config),
         generate::<Mp4Input>("Mp4Input", &config),
@@ -32,10 +35,12 @@ pub fn generate_docs() {
 
     let component_pages = [
         generate::<Shader>("Shader", &config),
+        generate::<ComputeShader>("ComputeShader", &config),
         generate::<InputStream>("InputStream", &config),
         generate::<View>("View", &config),
         generate::<WebView>("WebView", &config),
         generate::<Image>("Image", &img_component_config),
+        generate::<Lottie>("Lottie", &config),
         generate::<Text>("Text", &config),
         generate::<Tiles>("Tiles", &config),
         generate::<Rescaler>("Rescaler", &config),
@@ -19,9 +19,16 @@ enum ApiTypes {
     RegisterInput(routes::RegisterInput),
     RegisterOutput(routes::RegisterOutput),
     RegisterImage(types::ImageSpec),
+    RegisterLottie(types::LottieSpec),
     RegisterWebRenderer(types::WebRendererSpec),
     RegisterShader(types::ShaderSpec),
+    RegisterComputeShader(types::ComputeShaderSpec),
     UpdateOutput(types::UpdateOutputRequest),
+    RenderPreview(types::RenderPreviewRequest),
+    PatchOutput(types::ScenePatch),
+    Batch(routes::BatchRequest),
+    Timeline(routes::TimelineRequest),
+    PipelineState(routes::PipelineState),
 }
 
 pub fn generate_json_schema(check_flag: bool) {
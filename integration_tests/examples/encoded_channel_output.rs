@@ -15,7 +15,7 @@ use compositor_pipeline::{
         AudioCodec, EncodedChunkKind, EncoderOutputEvent, Pipeline, PipelineOutputEndCondition,
         RegisterInputOptions, RegisterOutputOptions, VideoCodec,
     },
-    queue::QueueInputOptions,
+    queue::{FrameRateConversion, QueueInputOptions},
 };
 use compositor_render::{
     error::ErrorStack,
@@ -76,6 +76,7 @@ fn main() {
                 input_id: input_id.clone(),
             }),
             end_condition: PipelineOutputEndCondition::Never,
+            framerate: None,
         }),
         audio: Some(compositor_pipeline::pipeline::OutputAudioOptions {
             initial: AudioMixingParams {
@@ -100,6 +101,8 @@ fn main() {
             required: true,
             offset: Some(Duration::ZERO),
             buffer_duration: None,
+            adaptive_buffer: None,
+            frame_rate_conversion: FrameRateConversion::Nearest,
         },
     };
 
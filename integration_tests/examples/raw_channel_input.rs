@@ -20,7 +20,7 @@ use compositor_pipeline::{
         GraphicsContext, Options, Pipeline, PipelineOutputEndCondition, RegisterOutputOptions,
         VideoCodec,
     },
-    queue::{PipelineEvent, QueueInputOptions},
+    queue::{FrameRateConversion, PipelineEvent, QueueInputOptions},
 };
 use compositor_render::{
     error::ErrorStack,
@@ -94,6 +94,7 @@ fn main() {
                 input_id: input_id.clone(),
             }),
             end_condition: PipelineOutputEndCondition::Never,
+            framerate: None,
         }),
         audio: None, // TODO: add audio example
     };
@@ -109,6 +110,8 @@ fn main() {
             required: true,
             offset: Some(Duration::ZERO),
             buffer_duration: None,
+            adaptive_buffer: None,
+            frame_rate_conversion: FrameRateConversion::Nearest,
         },
     )
     .unwrap();
@@ -19,7 +19,7 @@ use compositor_pipeline::{
         GraphicsContext, Options, PipelineOutputEndCondition, RawDataReceiver,
         RegisterInputOptions, RegisterOutputOptions,
     },
-    queue::{PipelineEvent, QueueInputOptions},
+    queue::{FrameRateConversion, PipelineEvent, QueueInputOptions},
     Pipeline,
 };
 use compositor_render::{
@@ -100,6 +100,7 @@ fn main() {
                 input_id: input_id.clone(),
             }),
             end_condition: PipelineOutputEndCondition::Never,
+            framerate: None,
         }),
         audio: Some(compositor_pipeline::pipeline::OutputAudioOptions {
             initial: AudioMixingParams {
@@ -124,6 +125,8 @@ fn main() {
             required: true,
             offset: Some(Duration::ZERO),
             buffer_duration: None,
+            adaptive_buffer: None,
+            frame_rate_conversion: FrameRateConversion::Nearest,
         },
     };
 
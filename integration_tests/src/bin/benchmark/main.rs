@@ -250,6 +250,8 @@ fn run_single_test(ctx: GraphicsContext, bench_config: SingleBenchConfig) -> boo
                     offset: Some(Duration::ZERO),
                     required: true,
                     buffer_duration: None,
+                    adaptive_buffer: None,
+                    frame_rate_conversion: queue::FrameRateConversion::Nearest,
                 },
             },
         );
@@ -266,6 +268,7 @@ fn run_single_test(ctx: GraphicsContext, bench_config: SingleBenchConfig) -> boo
         RegisterOutputOptions {
             video: Some(OutputVideoOptions {
                 end_condition: PipelineOutputEndCondition::AnyInput,
+                framerate: None,
                 initial: Component::Tiles(TilesComponent {
                     id: None,
                     width: Some(bench_config.output_width as f32),
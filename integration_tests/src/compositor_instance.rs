@@ -63,6 +63,16 @@ impl CompositorInstance {
     }
 
     pub fn send_request(&self, path: &str, request_body: serde_json::Value) -> Result<()> {
+        self.send_request_with_response(path, request_body)?;
+        Ok(())
+    }
+
+    /// Same as `send_request`, but returns the raw response body.
+    pub fn send_request_with_response(
+        &self,
+        path: &str,
+        request_body: serde_json::Value,
+    ) -> Result<bytes::Bytes> {
         let resp = self
             .http_client
             .post(format!("http://127.0.0.1:{}/api/{}", self.api_port, path))
@@ -79,7 +89,31 @@ impl CompositorInstance {
             ));
         }
 
-        Ok(())
+        Ok(resp.bytes()?)
+    }
+
+    pub fn get_request(&self, path: &str) -> Result<serde_json::Value> {
+        let body = self.get_request_with_response(path)?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Same as `get_request`, but returns the raw response body.
+    pub fn get_request_with_response(&self, path: &str) -> Result<bytes::Bytes> {
+        let resp = self
+            .http_client
+            .get(format!("http://127.0.0.1:{}/api/{}", self.api_port, path))
+            .timeout(Duration::from_secs(100))
+            .send()?;
+
+        if resp.status() >= StatusCode::BAD_REQUEST {
+            let status = resp.status();
+            let body_str = resp.text().unwrap();
+            return Err(anyhow::anyhow!(
+                "Request failed with status: {status}\nResponse: {body_str}",
+            ));
+        }
+
+        Ok(resp.bytes()?)
     }
 
     pub fn get_port(&self) -> u16 {
@@ -9,6 +9,7 @@ use crate::common::CommunicationProtocol;
 use anyhow::{Context, Result};
 use bytes::{Bytes, BytesMut};
 use crossbeam_channel::Receiver;
+use rtcp::header::PacketType;
 use tracing::error;
 use webrtc_util::Unmarshal;
 
@@ -41,6 +42,8 @@ impl OutputReceiver {
                     output_dump.extend(packet_len.to_be_bytes());
                     output_dump.extend(&packet_bytes);
                 }
+                // sender reports are not part of the dump
+                Packet::Rtcp => (),
             }
         });
 
@@ -112,7 +115,14 @@ fn unmarshal_packet(mut buffer: Bytes) -> Result<Packet> {
     let packet = if rtp_packet.header.payload_type < 64 || rtp_packet.header.payload_type > 95 {
         Packet::Rtp(buffer)
     } else {
-        rtcp::goodbye::Goodbye::unmarshal(&mut buffer).map(|_| Packet::RtcpGoodbye)?
+        let rtcp_packets = rtcp::packet::unmarshal(&mut buffer)?;
+        let is_goodbye = rtcp_packets
+            .iter()
+            .any(|packet| packet.header().packet_type == PacketType::Goodbye);
+        match is_goodbye {
+            true => Packet::RtcpGoodbye,
+            false => Packet::Rtcp,
+        }
     };
 
     Ok(packet)
@@ -121,5 +131,6 @@ fn unmarshal_packet(mut buffer: Bytes) -> Result<Packet> {
 #[derive(Debug, PartialEq, Eq)]
 enum Packet {
     RtcpGoodbye,
+    Rtcp,
     Rtp(Bytes),
 }
@@ -1,12 +1,14 @@
 use std::{
-    io::Write,
+    io::{self, Read, Write},
     net::{Ipv4Addr, SocketAddr},
     thread::{self, JoinHandle},
+    time::Duration,
 };
 
 use crate::common::CommunicationProtocol;
 use anyhow::Result;
-use bytes::Bytes;
+use bytes::{BufMut, Bytes, BytesMut};
+use webrtc_util::{Marshal, Unmarshal};
 
 pub struct PacketSender {
     protocol: CommunicationProtocol,
@@ -46,6 +48,112 @@ impl PacketSender {
         })
     }
 
+    /// Sends packets from the dump, skipping packets for which `should_drop` returns true.
+    /// Returns dropped packets.
+    pub fn send_with_loss<F>(
+        &mut self,
+        rtp_packets: &[u8],
+        should_drop: F,
+    ) -> Result<Vec<rtp::packet::Packet>>
+    where
+        F: Fn(usize, &rtp::packet::Packet) -> bool,
+    {
+        let mut dropped = Vec::new();
+        let mut read_bytes = 0;
+        let mut index = 0;
+        while read_bytes < rtp_packets.len() {
+            let packet_len =
+                u16::from_be_bytes([rtp_packets[read_bytes], rtp_packets[read_bytes + 1]]) as usize;
+            read_bytes += 2;
+
+            let data = &rtp_packets[read_bytes..(read_bytes + packet_len)];
+            read_bytes += packet_len;
+
+            let packet = rtp::packet::Packet::unmarshal(&mut &data[..])?;
+            if should_drop(index, &packet) {
+                dropped.push(packet);
+            } else {
+                self.send_packet(data)?;
+            }
+            index += 1;
+        }
+
+        Ok(dropped)
+    }
+
+    /// Sends retransmission of the packet ([RFC 4588](https://datatracker.ietf.org/doc/html/rfc4588)).
+    pub fn send_rtx(
+        &mut self,
+        packet: &rtp::packet::Packet,
+        rtx_payload_type: u8,
+        rtx_ssrc: u32,
+        rtx_sequence_number: u16,
+    ) -> Result<()> {
+        let mut payload = BytesMut::with_capacity(packet.payload.len() + 2);
+        payload.put_u16(packet.header.sequence_number);
+        payload.put(packet.payload.clone());
+
+        let rtx_packet = rtp::packet::Packet {
+            header: rtp::header::Header {
+                payload_type: rtx_payload_type,
+                ssrc: rtx_ssrc,
+                sequence_number: rtx_sequence_number,
+                ..packet.header.clone()
+            },
+            payload: payload.freeze(),
+        };
+        self.send_packet(&rtx_packet.marshal()?)
+    }
+
+    /// Waits for RTCP packets sent back by the receiver. Returns an empty list on timeout.
+    pub fn recv_rtcp(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Vec<Box<dyn rtcp::packet::Packet + Send + Sync>>> {
+        self.socket.set_read_timeout(Some(timeout))?;
+        let result = match self.protocol {
+            CommunicationProtocol::Udp => {
+                let mut buffer = vec![0; 65536];
+                (&self.socket).read(&mut buffer).map(|len| {
+                    buffer.truncate(len);
+                    buffer
+                })
+            }
+            CommunicationProtocol::Tcp => {
+                let mut len = [0; 2];
+                (&self.socket).read_exact(&mut len).and_then(|_| {
+                    let mut buffer = vec![0; u16::from_be_bytes(len) as usize];
+                    (&self.socket).read_exact(&mut buffer).map(|_| buffer)
+                })
+            }
+        };
+
+        match result {
+            Ok(data) => Ok(rtcp::packet::unmarshal(&mut Bytes::from(data))?),
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                Ok(Vec::new())
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn send_packet(&mut self, packet: &[u8]) -> Result<()> {
+        match self.protocol {
+            CommunicationProtocol::Udp => self.socket.write_all(packet)?,
+            CommunicationProtocol::Tcp => {
+                self.socket
+                    .write_all(&(packet.len() as u16).to_be_bytes())?;
+                self.socket.write_all(packet)?;
+            }
+        }
+        Ok(())
+    }
+
     fn send_via_udp(&mut self, rtp_packets: &[u8]) -> Result<()> {
         let mut sent_bytes = 0;
         while sent_bytes < rtp_packets.len() {
@@ -1,9 +1,19 @@
 mod audio_only;
+mod batch;
+mod introspection;
+mod metrics;
 mod offline_processing;
+mod pipeline_state;
 mod push_input_before_start;
+mod render_preview;
 mod required_inputs;
+mod rtp_retransmission;
 mod schedule_update;
+mod scheduled_events;
+mod snapshot;
+mod timeline;
 mod unregistering;
+mod validate_update;
 mod video_audio;
 
 use crossbeam_channel::Sender;
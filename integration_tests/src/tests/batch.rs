@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::CompositorInstance;
+
+/// Move a component between outputs in a single batch and check that a failing
+/// batch does not apply anything, including its registrations.
+#[test]
+pub fn batch() -> Result<()> {
+    let instance = CompositorInstance::start(None);
+
+    let register_output = |output_id: &str, scene: serde_json::Value| {
+        instance.send_request(
+            &format!("output/{output_id}/register"),
+            json!({
+                "type": "rtp_stream",
+                "transport_protocol": "tcp_server",
+                "port": instance.get_port(),
+                "video": {
+                    "resolution": {
+                        "width": 640,
+                        "height": 360,
+                    },
+                    "encoder": {
+                        "type": "ffmpeg_h264",
+                        "preset": "ultrafast"
+                    },
+                    "initial": scene
+                },
+            }),
+        )
+    };
+    let scene_with_box = json!({
+        "root": {
+            "type": "view",
+            "children": [{ "type": "view", "id": "box", "background_color": "#FF0000FF" }],
+        }
+    });
+    let empty_scene = json!({
+        "root": { "type": "view" }
+    });
+    register_output("output_1", scene_with_box.clone())?;
+    register_output("output_2", empty_scene.clone())?;
+
+    let image = json!({
+        "asset_type": "svg",
+        "path": PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/assets/rust.svg"),
+        "resolution": { "width": 100, "height": 100 },
+    });
+
+    // Last operation fails, so neither the image nor the updates are applied.
+    let result = instance.send_request(
+        "batch",
+        json!({
+            "operations": [
+                { "type": "register_image", "image_id": "image_1", "image": image },
+                { "type": "update_output", "output_id": "output_1", "video": empty_scene },
+                { "type": "update_output", "output_id": "output_2", "video": scene_with_box },
+                { "type": "unregister_input", "input_id": "missing_input" },
+            ]
+        }),
+    );
+    assert!(result.is_err());
+    let outputs = instance.get_request("output")?;
+    assert_eq!(outputs["outputs"][0]["scene"]["video"], scene_with_box);
+    assert_eq!(outputs["outputs"][1]["scene"]["video"], empty_scene);
+    assert_eq!(instance.get_request("image")?["renderers"], json!([]));
+
+    // Component with id "box" is moved from "output_1" to "output_2". It would fail
+    // as two separate updates in this order, because component ids have to be unique.
+    let scene_with_image = json!({
+        "root": {
+            "type": "view",
+            "children": [
+                { "type": "view", "id": "box", "background_color": "#FF0000FF" },
+                { "type": "image", "image_id": "image_1" },
+            ],
+        }
+    });
+    let response = instance.send_request_with_response(
+        "batch",
+        json!({
+            "operations": [
+                { "type": "register_image", "image_id": "image_1", "image": image },
+                { "type": "update_output", "output_id": "output_2", "video": scene_with_image },
+                { "type": "update_output", "output_id": "output_1", "video": empty_scene },
+            ]
+        }),
+    )?;
+    let response: serde_json::Value = serde_json::from_slice(&response)?;
+    assert_eq!(response, json!({ "results": [{}, {}, {}] }));
+
+    let outputs = instance.get_request("output")?;
+    assert_eq!(outputs["outputs"][0]["scene"]["video"], empty_scene);
+    assert_eq!(outputs["outputs"][1]["scene"]["video"], scene_with_image);
+    assert_eq!(
+        instance.get_request("image")?["renderers"],
+        json!(["image_1"])
+    );
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use anyhow::Result;
+use serde_json::json;
+
+use crate::CompositorInstance;
+
+/// Register input, output and image and check whether they are listed
+/// by the introspection endpoints.
+#[test]
+pub fn introspection() -> Result<()> {
+    let instance = CompositorInstance::start(None);
+    let input_port = instance.get_port();
+    let output_port = instance.get_port();
+
+    instance.send_request(
+        "image/image_1/register",
+        json!({
+            "asset_type": "svg",
+            "path": format!("{}/../docs/static/img/logo.svg", env!("CARGO_MANIFEST_DIR"))
+        }),
+    )?;
+
+    instance.send_request(
+        "input/input_1/register",
+        json!({
+            "type": "rtp_stream",
+            "transport_protocol": "tcp_server",
+            "port": input_port,
+            "video": {
+                "decoder": "ffmpeg_h264"
+            },
+        }),
+    )?;
+
+    let initial_scene = json!({
+        "root": {
+            "type": "input_stream",
+            "input_id": "input_1",
+        }
+    });
+    instance.send_request(
+        "output/output_1/register",
+        json!({
+            "type": "rtp_stream",
+            "transport_protocol": "tcp_server",
+            "port": output_port,
+            "video": {
+                "resolution": {
+                    "width": 640,
+                    "height": 360,
+                },
+                "framerate": "25/1",
+                "encoder": {
+                    "type": "ffmpeg_h264",
+                    "preset": "ultrafast"
+                },
+                "initial": initial_scene
+            },
+        }),
+    )?;
+
+    let inputs = instance.get_request("input")?;
+    let input = &inputs["inputs"][0];
+    assert_eq!(input["input_id"], "input_1");
+    assert_eq!(input["type"], "rtp_stream");
+    assert_eq!(input["port"], input_port);
+    assert_eq!(input["video"]["decoder"], "ffmpeg_h264");
+    assert_eq!(input["video"]["state"], "waiting_for_start");
+    assert_eq!(input["video"]["eos_received"], false);
+    assert!(input["audio"].is_null());
+
+    let outputs = instance.get_request("output")?;
+    let output = &outputs["outputs"][0];
+    assert_eq!(output["output_id"], "output_1");
+    assert_eq!(output["type"], "rtp_stream");
+    assert_eq!(output["port"], output_port);
+    assert_eq!(output["video"]["resolution"]["width"], 640);
+    assert_eq!(output["video"]["framerate"], "25/1");
+    assert_eq!(output["video"]["encoder"]["preset"], "ultrafast");
+    assert_eq!(output["scene"]["video"]["root"]["input_id"], "input_1");
+    assert!(output["audio"].is_null());
+
+    let images = instance.get_request("image")?;
+    assert_eq!(images["renderers"], json!(["image_1"]));
+
+    let shaders = instance.get_request("shader")?;
+    assert_eq!(shaders["renderers"], json!([]));
+
+    Ok(())
+}
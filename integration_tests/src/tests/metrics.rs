@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::CompositorInstance;
+
+/// Register input and output and check whether their metrics are exposed
+/// by the `/metrics` endpoint.
+#[test]
+pub fn metrics() -> Result<()> {
+    let instance = CompositorInstance::start(None);
+    let input_port = instance.get_port();
+    let output_port = instance.get_port();
+
+    instance.send_request(
+        "input/input_1/register",
+        json!({
+            "type": "rtp_stream",
+            "transport_protocol": "tcp_server",
+            "port": input_port,
+            "video": {
+                "decoder": "ffmpeg_h264"
+            },
+        }),
+    )?;
+
+    instance.send_request(
+        "output/output_1/register",
+        json!({
+            "type": "rtp_stream",
+            "transport_protocol": "tcp_server",
+            "port": output_port,
+            "video": {
+                "resolution": {
+                    "width": 640,
+                    "height": 360,
+                },
+                "framerate": "25/1",
+                "encoder": {
+                    "type": "ffmpeg_h264",
+                    "preset": "ultrafast"
+                },
+                "initial": {
+                    "root": {
+                        "type": "input_stream",
+                        "input_id": "input_1",
+                    }
+                }
+            },
+        }),
+    )?;
+
+    let response = instance
+        .http_client
+        .get(format!("http://127.0.0.1:{}/metrics", instance.api_port))
+        .timeout(Duration::from_secs(10))
+        .send()?
+        .error_for_status()?;
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/plain; version=0.0.4"
+    );
+    let metrics = response.text()?;
+
+    for line in [
+        "# TYPE smelter_input_received_bytes_total counter",
+        "smelter_input_received_bytes_total{input_id=\"input_1\"} 0",
+        "smelter_input_decoded_frames_total{input_id=\"input_1\"} 0",
+        "# TYPE smelter_input_rtp_packets_lost gauge",
+        "smelter_input_rtp_packets_lost{input_id=\"input_1\"} 0",
+        "smelter_input_rtp_jitter_seconds{input_id=\"input_1\"} 0",
+        "# TYPE smelter_input_queue_buffered_seconds gauge",
+        "# TYPE smelter_output_encoded_bytes_total counter",
+        "smelter_output_encoded_bytes_total{output_id=\"output_1\"} 0",
+        "smelter_output_dropped_frames_total{output_id=\"output_1\"} 0",
+        "# TYPE smelter_output_encoder_latency_seconds summary",
+        "smelter_output_encoder_latency_seconds_count{output_id=\"output_1\"} 0",
+        "# TYPE smelter_renderer_frame_time_seconds summary",
+        "smelter_renderer_frame_time_seconds_count 0",
+    ] {
+        assert!(
+            metrics.lines().any(|l| l == line),
+            "Missing line \"{line}\" in metrics:\n{metrics}"
+        );
+    }
+
+    // Every sample has a numeric value.
+    for line in metrics.lines().filter(|line| !line.starts_with('#')) {
+        let (_, value) = line.rsplit_once(' ').unwrap();
+        assert!(value.parse::<f64>().is_ok(), "Invalid sample \"{line}\"");
+    }
+
+    Ok(())
+}
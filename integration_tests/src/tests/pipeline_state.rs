@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::CompositorInstance;
+
+/// Export the state of one instance and import it on another one. Pipelines are
+/// not started, so outputs never produce any frames.
+#[test]
+pub fn pipeline_state() -> Result<()> {
+    let instance = CompositorInstance::start(None);
+
+    let scene = |color: &str| json!({ "root": { "type": "view", "background_color": color } });
+    instance.send_request(
+        "image/image_1/register",
+        json!({
+            "asset_type": "svg",
+            "path": PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/assets/rust.svg"),
+            "resolution": { "width": 100, "height": 100 },
+        }),
+    )?;
+    instance.send_request(
+        "output/output_1/register",
+        json!({
+            "type": "rtp_stream",
+            "transport_protocol": "tcp_server",
+            "port": instance.get_port(),
+            "video": {
+                "resolution": {
+                    "width": 640,
+                    "height": 360,
+                },
+                "encoder": {
+                    "type": "ffmpeg_h264",
+                    "preset": "ultrafast"
+                },
+                "initial": scene("#000000FF")
+            },
+        }),
+    )?;
+    instance.send_request(
+        "output/output_1/update",
+        json!({ "video": scene("#FF0000FF") }),
+    )?;
+
+    let mut state = instance.get_request("state")?;
+    assert_eq!(state["fonts"], json!([]));
+    assert_eq!(state["started"], json!(false));
+    assert_eq!(state["renderers"][0]["type"], json!("image"));
+    assert_eq!(state["renderers"][0]["image_id"], json!("image_1"));
+    assert_eq!(state["outputs"][0]["output_id"], json!("output_1"));
+    assert_eq!(
+        state["outputs"][0]["output"]["video"]["initial"]["root"]["background_color"],
+        json!("#000000FF")
+    );
+    assert_eq!(
+        state["outputs"][0]["video"]["root"]["background_color"],
+        json!("#FF0000FF")
+    );
+
+    let standby_instance = CompositorInstance::start(None);
+    state["outputs"][0]["output"]["port"] = json!(standby_instance.get_port());
+    standby_instance.send_request("state/import", state.clone())?;
+    assert_eq!(standby_instance.get_request("state")?, state);
+
+    // Entities are already registered, so nothing is applied.
+    assert!(standby_instance
+        .send_request("state/import", state.clone())
+        .is_err());
+    assert_eq!(standby_instance.get_request("state")?, state);
+
+    instance.send_request("image/image_1/unregister", json!({}))?;
+    instance.send_request("output/output_1/unregister", json!({}))?;
+    let state = instance.get_request("state")?;
+    assert_eq!(state["renderers"], json!([]));
+    assert_eq!(state["outputs"], json!([]));
+
+    Ok(())
+}
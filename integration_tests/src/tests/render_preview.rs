@@ -0,0 +1,90 @@
+use anyhow::Result;
+use serde_json::json;
+
+use crate::CompositorInstance;
+
+/// Render scene with a still image as an input and check the returned PNG. Resolutions
+/// that exceed the GPU limits are rejected.
+#[test]
+pub fn render_preview() -> Result<()> {
+    let instance = CompositorInstance::start(None);
+
+    let scene = json!({
+        "root": {
+            "type": "view",
+            "background_color": "#FF0000FF",
+            "children": [{
+                "type": "rescaler",
+                "top": 0,
+                "right": 0,
+                "width": 32,
+                "height": 32,
+                "child": {
+                    "type": "input_stream",
+                    "input_id": "input_1",
+                }
+            }]
+        }
+    });
+    let response = instance.send_request_with_response(
+        "preview/render",
+        json!({
+            "video": scene,
+            "resolution": {
+                "width": 128,
+                "height": 64,
+            },
+            "inputs": [{
+                "input_id": "input_1",
+                "path": format!("{}/../docs/static/img/favicon.png", env!("CARGO_MANIFEST_DIR")),
+            }]
+        }),
+    )?;
+
+    let image = image::load_from_memory_with_format(&response, image::ImageFormat::Png)?;
+    let image = image.to_rgba8();
+    assert_eq!(image.dimensions(), (128, 64));
+
+    let [r, g, b, a] = image.get_pixel(0, 63).0;
+    assert!(
+        r > 240 && g < 15 && b < 15 && a == 255,
+        "{:?}",
+        (r, g, b, a)
+    );
+
+    // Alpha channel is preserved, the frame is not converted to YUV.
+    let response = instance.send_request_with_response(
+        "preview/render",
+        json!({
+            "video": {
+                "root": {
+                    "type": "view",
+                    "background_color": "#FF000000",
+                }
+            },
+            "resolution": {
+                "width": 15,
+                "height": 9,
+            },
+        }),
+    )?;
+    let image = image::load_from_memory_with_format(&response, image::ImageFormat::Png)?;
+    let image = image.to_rgba8();
+    assert_eq!(image.dimensions(), (15, 9));
+    assert_eq!(image.get_pixel(7, 4).0[3], 0);
+
+    assert!(instance
+        .send_request(
+            "preview/render",
+            json!({
+                "video": { "root": { "type": "view" } },
+                "resolution": {
+                    "width": 1_000_000,
+                    "height": 1_000_000,
+                },
+            }),
+        )
+        .is_err());
+
+    Ok(())
+}
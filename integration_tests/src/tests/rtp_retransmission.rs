@@ -0,0 +1,191 @@
+use std::{
+    collections::{HashMap, HashSet},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use rtcp::{
+    payload_feedbacks::picture_loss_indication::PictureLossIndication,
+    transport_feedbacks::transport_layer_nack::TransportLayerNack,
+};
+use serde_json::json;
+
+use crate::{
+    compare_video_dumps, input_dump_from_disk, CommunicationProtocol, CompositorInstance,
+    OutputReceiver, PacketSender, VideoValidationConfig,
+};
+
+const RTX_PAYLOAD_TYPE: u8 = 98;
+const RTX_SSRC: u32 = 0x5254_5800;
+
+/// Drops every 40th packet of the input stream and retransmits it as RTX packet
+/// after receiving a NACK.
+///
+/// Output (the same as in `push_input_before_start_udp`):
+/// - Display entire input stream from the beginning (16 seconds). No black frames at the
+///   beginning. Starts with a green screen.
+/// - Black screen for remaining 4 seconds.
+#[test]
+pub fn lost_packets_retransmission_udp() -> Result<()> {
+    const OUTPUT_DUMP_FILE: &str = "push_entire_input_before_start_udp.rtp";
+    let instance = CompositorInstance::start(None);
+    let input_port = instance.get_port();
+    let output_port = instance.get_port();
+
+    instance.send_request(
+        "output/output_1/register",
+        json!({
+            "type": "rtp_stream",
+            "transport_protocol": "tcp_server",
+            "port": output_port,
+            "video": {
+                "resolution": {
+                    "width": 640,
+                    "height": 360,
+                },
+                "encoder": {
+                    "type": "ffmpeg_h264",
+                    "preset": "ultrafast"
+                },
+                "initial": {
+                    "root": {
+                        "type": "input_stream",
+                        "input_id": "input_1",
+                    }
+                }
+            },
+        }),
+    )?;
+
+    let output_receiver = OutputReceiver::start(output_port, CommunicationProtocol::Tcp)?;
+
+    instance.send_request(
+        "output/output_1/unregister",
+        json!({
+            "schedule_time_ms": 20_000,
+        }),
+    )?;
+
+    instance.send_request(
+        "input/input_1/register",
+        json!({
+            "type": "rtp_stream",
+            "transport_protocol": "udp",
+            "port": input_port,
+            "video": {
+                "decoder": "ffmpeg_h264",
+                "rtx_payload_type": RTX_PAYLOAD_TYPE,
+            },
+            "jitter_buffer": {
+                // whole stream is delivered before start, so packets after the gap
+                // can wait for a long time
+                "latency_ms": 10_000,
+            },
+            "required": true,
+            "offset_ms": 0
+        }),
+    )?;
+
+    let input_1_dump = input_dump_from_disk("8_colors_input_video.rtp")?;
+    let mut sender = PacketSender::new(CommunicationProtocol::Udp, input_port)?;
+    let sender_handle = thread::spawn(move || -> Result<HashSet<u16>> {
+        let dropped = sender.send_with_loss(&input_1_dump, |index, _| index % 40 == 20)?;
+        let dropped: HashMap<u16, rtp::packet::Packet> = dropped
+            .into_iter()
+            .map(|packet| (packet.header.sequence_number, packet))
+            .collect();
+
+        let mut retransmitted = HashSet::new();
+        let mut rtx_sequence_number = 0;
+        let deadline = Instant::now() + Duration::from_secs(20);
+        while retransmitted.len() < dropped.len() && Instant::now() < deadline {
+            for rtcp_packet in sender.recv_rtcp(Duration::from_millis(100))? {
+                let Some(nack) = rtcp_packet.as_any().downcast_ref::<TransportLayerNack>() else {
+                    continue;
+                };
+                for sequence_number in nack.nacks.iter().flat_map(|pair| pair.packet_list()) {
+                    let Some(packet) = dropped.get(&sequence_number) else {
+                        continue;
+                    };
+                    sender.send_rtx(packet, RTX_PAYLOAD_TYPE, RTX_SSRC, rtx_sequence_number)?;
+                    rtx_sequence_number += 1;
+                    retransmitted.insert(sequence_number);
+                }
+            }
+        }
+        Ok(retransmitted)
+    });
+
+    thread::sleep(Duration::from_secs(5));
+
+    instance.send_request("start", json!({}))?;
+
+    let retransmitted = sender_handle.join().unwrap()?;
+    assert!(!retransmitted.is_empty());
+    let new_output_dump = output_receiver.wait_for_output()?;
+
+    compare_video_dumps(
+        OUTPUT_DUMP_FILE,
+        &new_output_dump,
+        VideoValidationConfig {
+            validation_intervals: vec![Duration::ZERO..Duration::from_secs(20)],
+            ..Default::default()
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Drops packets and never retransmits them. Receiver should request them with NACKs
+/// and then request a keyframe.
+#[test]
+pub fn unrecoverable_packet_loss_udp() -> Result<()> {
+    let instance = CompositorInstance::start(None);
+    let input_port = instance.get_port();
+
+    instance.send_request(
+        "input/input_1/register",
+        json!({
+            "type": "rtp_stream",
+            "transport_protocol": "udp",
+            "port": input_port,
+            "video": {
+                "decoder": "ffmpeg_h264",
+            },
+            "jitter_buffer": {
+                "latency_ms": 100,
+            },
+        }),
+    )?;
+
+    instance.send_request("start", json!({}))?;
+
+    let input_1_dump = input_dump_from_disk("8_colors_input_video.rtp")?;
+    let mut sender = PacketSender::new(CommunicationProtocol::Udp, input_port)?;
+    let dropped = sender.send_with_loss(&input_1_dump, |index, _| index == 100)?;
+    let dropped_sequence_number = dropped[0].header.sequence_number;
+
+    let mut nack_received = false;
+    let mut pli_received = false;
+    let deadline = Instant::now() + Duration::from_secs(20);
+    while !(nack_received && pli_received) && Instant::now() < deadline {
+        for rtcp_packet in sender.recv_rtcp(Duration::from_millis(100))? {
+            let packet = rtcp_packet.as_any();
+            if let Some(nack) = packet.downcast_ref::<TransportLayerNack>() {
+                nack_received |= nack
+                    .nacks
+                    .iter()
+                    .any(|pair| pair.packet_list().contains(&dropped_sequence_number));
+            }
+            if packet.downcast_ref::<PictureLossIndication>().is_some() {
+                pli_received = true;
+            }
+        }
+    }
+
+    assert!(nack_received, "NACK for the lost packet was not received");
+    assert!(pli_received, "PLI was not received");
+
+    Ok(())
+}
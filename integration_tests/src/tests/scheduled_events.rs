@@ -0,0 +1,136 @@
+use std::{
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::CompositorInstance;
+
+/// Schedule registration and unregister of an image, list the scheduled events and
+/// cancel them. Pipeline is not started, so the events are never executed.
+#[test]
+pub fn scheduled_events() -> Result<()> {
+    let instance = CompositorInstance::start(None);
+
+    let response = instance.send_request_with_response(
+        "image/image_1/register",
+        json!({
+            "asset_type": "svg",
+            "path": PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/assets/rust.svg"),
+            "resolution": { "width": 100, "height": 100 },
+            "schedule_time_ms": 10000,
+        }),
+    )?;
+    let register_event_id =
+        serde_json::from_slice::<serde_json::Value>(&response)?["event_id"].clone();
+
+    let response = instance.send_request_with_response(
+        "image/image_1/unregister",
+        json!({ "schedule_time_ms": 5000 }),
+    )?;
+    let unregister_event_id =
+        serde_json::from_slice::<serde_json::Value>(&response)?["event_id"].clone();
+
+    assert_eq!(
+        instance.get_request("scheduled-event")?,
+        json!({
+            "events": [
+                {
+                    "event_id": unregister_event_id,
+                    "schedule_time_ms": 5000.0,
+                    "type": "unregister_image",
+                    "entity_id": "image_1",
+                },
+                {
+                    "event_id": register_event_id,
+                    "schedule_time_ms": 10000.0,
+                    "type": "register_image",
+                    "entity_id": "image_1",
+                },
+            ]
+        })
+    );
+
+    instance.send_request(
+        &format!("scheduled-event/{register_event_id}/cancel"),
+        json!({}),
+    )?;
+    // Event can't be cancelled twice.
+    assert!(instance
+        .send_request(
+            &format!("scheduled-event/{register_event_id}/cancel"),
+            json!({}),
+        )
+        .is_err());
+
+    let events = instance.get_request("scheduled-event")?;
+    assert_eq!(events["events"].as_array().unwrap().len(), 1);
+    assert_eq!(events["events"][0]["event_id"], unregister_event_id);
+    assert_eq!(instance.get_request("image")?["renderers"], json!([]));
+
+    // The image from the cancelled event was never registered.
+    instance.send_request(
+        "image/image_1/register",
+        json!({
+            "asset_type": "svg",
+            "path": PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/assets/rust.svg"),
+            "resolution": { "width": 100, "height": 100 },
+        }),
+    )?;
+
+    Ok(())
+}
+
+/// Schedule registration of an output after the start and check that the output
+/// is not registered before its schedule time.
+#[test]
+pub fn scheduled_registration() -> Result<()> {
+    let instance = CompositorInstance::start(None);
+    instance.send_request("start", json!({}))?;
+
+    let scene = json!({ "root": { "type": "view", "background_color": "#FF0000FF" } });
+    instance.send_request(
+        "output/output_1/register",
+        json!({
+            "type": "rtp_stream",
+            "transport_protocol": "tcp_server",
+            "port": instance.get_port(),
+            "video": {
+                "resolution": {
+                    "width": 640,
+                    "height": 360,
+                },
+                "encoder": {
+                    "type": "ffmpeg_h264",
+                    "preset": "ultrafast"
+                },
+                "initial": scene
+            },
+            "schedule_time_ms": 3000,
+        }),
+    )?;
+
+    assert_eq!(instance.get_request("output")?["outputs"], json!([]));
+    assert!(instance
+        .send_request("output/output_1/update", json!({ "video": scene }))
+        .is_err());
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !instance.get_request("scheduled-event")?["events"]
+        .as_array()
+        .unwrap()
+        .is_empty()
+    {
+        assert!(Instant::now() < deadline, "Registration was not executed.");
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    let outputs = instance.get_request("output")?["outputs"].clone();
+    assert_eq!(outputs.as_array().unwrap().len(), 1);
+    instance.send_request("output/output_1/update", json!({ "video": scene }))?;
+
+    Ok(())
+}
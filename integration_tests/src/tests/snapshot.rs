@@ -0,0 +1,68 @@
+use std::{thread, time::Duration};
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::{CommunicationProtocol, CompositorInstance, OutputReceiver};
+
+/// Register output rendering a solid color and check the downscaled snapshot.
+#[test]
+pub fn output_snapshot() -> Result<()> {
+    let instance = CompositorInstance::start(None);
+    let output_port = instance.get_port();
+
+    instance.send_request(
+        "output/output_1/register",
+        json!({
+            "type": "rtp_stream",
+            "transport_protocol": "tcp_server",
+            "port": output_port,
+            "video": {
+                "resolution": {
+                    "width": 640,
+                    "height": 360,
+                },
+                "encoder": {
+                    "type": "ffmpeg_h264",
+                    "preset": "ultrafast"
+                },
+                "initial": {
+                    "root": {
+                        "type": "view",
+                        "background_color": "#0000FFFF",
+                    }
+                }
+            },
+        }),
+    )?;
+
+    let _output_receiver = OutputReceiver::start(output_port, CommunicationProtocol::Tcp)?;
+    instance.send_request("start", json!({}))?;
+
+    let snapshot_path = "output/output_1/snapshot?format=png&width=320";
+    let mut snapshot = instance.get_request_with_response(snapshot_path);
+    for _ in 0..50 {
+        if snapshot.is_ok() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+        snapshot = instance.get_request_with_response(snapshot_path);
+    }
+
+    let image = image::load_from_memory_with_format(&snapshot?, image::ImageFormat::Png)?;
+    let image = image.to_rgba8();
+    assert_eq!(image.dimensions(), (320, 180));
+
+    let [r, g, b, a] = image.get_pixel(160, 90).0;
+    assert!(
+        r < 15 && g < 15 && b > 240 && a == 255,
+        "{:?}",
+        (r, g, b, a)
+    );
+
+    assert!(instance
+        .get_request_with_response("output/output_2/snapshot")
+        .is_err());
+
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::CompositorInstance;
+
+/// Upload a timeline and edit its entries. Pipeline is not started, so no entry
+/// is applied.
+#[test]
+pub fn timeline() -> Result<()> {
+    let instance = CompositorInstance::start(None);
+
+    let scene = |color: &str| json!({ "root": { "type": "view", "background_color": color } });
+    instance.send_request(
+        "output/output_1/register",
+        json!({
+            "type": "rtp_stream",
+            "transport_protocol": "tcp_server",
+            "port": instance.get_port(),
+            "video": {
+                "resolution": {
+                    "width": 640,
+                    "height": 360,
+                },
+                "encoder": {
+                    "type": "ffmpeg_h264",
+                    "preset": "ultrafast"
+                },
+                "initial": scene("#000000FF")
+            },
+        }),
+    )?;
+
+    // Duplicated PTS
+    let result = instance.send_request(
+        "output/output_1/timeline",
+        json!({
+            "entries": [
+                { "pts_ms": 1000, "video": scene("#FF0000FF") },
+                { "pts_ms": 1000, "video": scene("#00FF00FF") },
+            ]
+        }),
+    );
+    assert!(result.is_err());
+
+    instance.send_request(
+        "output/output_1/timeline",
+        json!({
+            "entries": [
+                { "pts_ms": 2000, "video": scene("#00FF00FF") },
+                { "pts_ms": 1000, "video": scene("#FF0000FF") },
+            ]
+        }),
+    )?;
+    instance.send_request(
+        "output/output_1/timeline/update_entry",
+        json!({ "pts_ms": 2000, "video": scene("#0000FFFF") }),
+    )?;
+    instance.send_request(
+        "output/output_1/timeline/update_entry",
+        json!({ "pts_ms": 3000, "video": scene("#FFFFFFFF") }),
+    )?;
+    instance.send_request(
+        "output/output_1/timeline/remove_entry",
+        json!({ "pts_ms": 1000 }),
+    )?;
+    let result = instance.send_request(
+        "output/output_1/timeline/remove_entry",
+        json!({ "pts_ms": 1000 }),
+    );
+    assert!(result.is_err());
+
+    let timeline = instance.get_request("output/output_1/timeline")?;
+    assert_eq!(timeline["position_ms"], json!(null));
+    let entries = timeline["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["pts_ms"], json!(2000.0));
+    assert_eq!(entries[0]["video"], scene("#0000FFFF"));
+    assert_eq!(entries[0]["pending"], json!(true));
+    assert_eq!(entries[1]["pts_ms"], json!(3000.0));
+
+    // Replaced and removed entries are cancelled.
+    let events = instance.get_request("scheduled-event")?;
+    assert_eq!(events["events"].as_array().unwrap().len(), 2);
+
+    // Timeline is removed together with the output.
+    instance.send_request("output/output_1/unregister", json!({}))?;
+    assert_eq!(
+        instance.get_request("scheduled-event")?["events"],
+        json!([])
+    );
+    assert!(instance.get_request("output/output_1/timeline").is_err());
+
+    Ok(())
+}
+
+/// Replace timeline entries with earlier ones while the pipeline is running. New
+/// entries are applied even if cancelled entries were scheduled after them.
+#[test]
+pub fn timeline_earlier_entries() -> Result<()> {
+    let instance = CompositorInstance::start(None);
+    instance.send_request("start", json!({}))?;
+
+    let scene = |color: &str| json!({ "root": { "type": "view", "background_color": color } });
+    instance.send_request(
+        "output/output_1/register",
+        json!({
+            "type": "rtp_stream",
+            "transport_protocol": "tcp_server",
+            "port": instance.get_port(),
+            "video": {
+                "resolution": {
+                    "width": 640,
+                    "height": 360,
+                },
+                "encoder": {
+                    "type": "ffmpeg_h264",
+                    "preset": "ultrafast"
+                },
+                "initial": scene("#000000FF")
+            },
+        }),
+    )?;
+    let position_ms = || -> Result<f64> {
+        let timeline = instance.get_request("output/output_1/timeline")?;
+        Ok(timeline["position_ms"].as_f64().unwrap_or(0.0))
+    };
+    let wait_for_entries = || -> Result<()> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !instance.get_request("scheduled-event")?["events"]
+            .as_array()
+            .unwrap()
+            .is_empty()
+        {
+            assert!(
+                Instant::now() < deadline,
+                "Timeline entries were not applied."
+            );
+            thread::sleep(Duration::from_millis(100));
+        }
+        Ok(())
+    };
+    let output_scene = || -> Result<serde_json::Value> {
+        Ok(instance.get_request("output")?["outputs"][0]["scene"]["video"].clone())
+    };
+
+    // Uploaded timeline replaces a timeline with a later entry.
+    let start_ms = position_ms()?;
+    instance.send_request(
+        "output/output_1/timeline",
+        json!({ "entries": [{ "pts_ms": start_ms + 5000.0, "video": scene("#FF0000FF") }] }),
+    )?;
+    instance.send_request(
+        "output/output_1/timeline",
+        json!({ "entries": [{ "pts_ms": start_ms + 2000.0, "video": scene("#00FF00FF") }] }),
+    )?;
+    wait_for_entries()?;
+    assert_eq!(output_scene()?, scene("#00FF00FF"));
+
+    // Entry is added before an existing entry, which is removed afterwards.
+    let start_ms = position_ms()?;
+    instance.send_request(
+        "output/output_1/timeline",
+        json!({ "entries": [{ "pts_ms": start_ms + 5000.0, "video": scene("#FF0000FF") }] }),
+    )?;
+    instance.send_request(
+        "output/output_1/timeline/update_entry",
+        json!({ "pts_ms": start_ms + 2000.0, "video": scene("#0000FFFF") }),
+    )?;
+    instance.send_request(
+        "output/output_1/timeline/remove_entry",
+        json!({ "pts_ms": start_ms + 5000.0 }),
+    )?;
+    wait_for_entries()?;
+    assert_eq!(output_scene()?, scene("#0000FFFF"));
+
+    Ok(())
+}
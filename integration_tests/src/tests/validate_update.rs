@@ -0,0 +1,132 @@
+use anyhow::Result;
+use serde_json::json;
+
+use crate::CompositorInstance;
+
+/// Validate invalid scenes and check that malformed components and errors from
+/// building the scene are all reported with paths of the components, and that
+/// nothing is applied.
+#[test]
+pub fn validate_update() -> Result<()> {
+    let instance = CompositorInstance::start(None);
+    let output_port = instance.get_port();
+
+    let initial_scene = json!({
+        "root": {
+            "type": "view",
+            "background_color": "#000000FF",
+        }
+    });
+    instance.send_request(
+        "output/output_1/register",
+        json!({
+            "type": "rtp_stream",
+            "transport_protocol": "tcp_server",
+            "port": output_port,
+            "video": {
+                "resolution": {
+                    "width": 640,
+                    "height": 360,
+                },
+                "encoder": {
+                    "type": "ffmpeg_h264",
+                    "preset": "ultrafast"
+                },
+                "initial": initial_scene
+            },
+        }),
+    )?;
+
+    let malformed_scene = json!({
+        "root": {
+            "type": "view",
+            "children": [
+                { "type": "view", "padding": -1 },
+                {
+                    "type": "rescaler",
+                    "child": { "type": "view", "padding": -2 },
+                },
+            ]
+        }
+    });
+    let response = instance.send_request_with_response(
+        "output/output_1/validate",
+        json!({ "video": malformed_scene }),
+    )?;
+    let response: serde_json::Value = serde_json::from_slice(&response)?;
+    assert_eq!(response["valid"], false);
+    let paths: Vec<_> = response["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|err| (err["path"].clone(), err["error_code"].clone()))
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            (json!("video.root.children[0]"), json!("MALFORMED_REQUEST")),
+            (
+                json!("video.root.children[1].child"),
+                json!("MALFORMED_REQUEST")
+            ),
+        ]
+    );
+
+    let invalid_scene = json!({
+        "root": {
+            "type": "view",
+            "children": [
+                {
+                    "type": "shader",
+                    "shader_id": "missing_shader",
+                    "resolution": { "width": 100, "height": 100 },
+                    "children": [{ "type": "view" }],
+                },
+                { "type": "image", "image_id": "missing_image" },
+            ]
+        }
+    });
+    let response = instance.send_request_with_response(
+        "output/output_1/validate",
+        json!({ "video": invalid_scene }),
+    )?;
+    let response: serde_json::Value = serde_json::from_slice(&response)?;
+    assert_eq!(response["valid"], false);
+    let paths: Vec<_> = response["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|err| (err["path"].clone(), err["error_code"].clone()))
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            (json!("video.root.children[0]"), json!("BUILD_SCENE_ERROR")),
+            (
+                json!("video.root.children[0].children[0]"),
+                json!("BUILD_SCENE_ERROR")
+            ),
+            (json!("video.root.children[1]"), json!("BUILD_SCENE_ERROR")),
+        ]
+    );
+    assert_eq!(
+        response["errors"][0]["message"],
+        "Shader \"missing_shader\" does not exist. You have to register it first before using it in the scene definition."
+    );
+
+    let valid_scene = json!({
+        "root": {
+            "type": "view",
+            "background_color": "#FF0000FF",
+        }
+    });
+    let response = instance
+        .send_request_with_response("output/output_1/validate", json!({ "video": valid_scene }))?;
+    let response: serde_json::Value = serde_json::from_slice(&response)?;
+    assert_eq!(response, json!({ "valid": true, "errors": [] }));
+
+    let outputs = instance.get_request("output")?;
+    assert_eq!(outputs["outputs"][0]["scene"]["video"], initial_scene);
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+/// Left third: current input
+/// Middle third: input frame from 1 frame ago
+/// Right third: previous output of the shader blended with white
+
+/// Depends on previous_frame and input_history_len
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+@group(0) @binding(0) var textures: binding_array<texture_2d<f32>, 16>;
+@group(2) @binding(0) var sampler_: sampler;
+@group(3) @binding(0) var history: texture_2d_array<f32>;
+
+struct BaseShaderParameters {
+    plane_id: i32,
+    time: f32,
+    output_resolution: vec2<u32>,
+    texture_count: u32,
+}
+
+var<push_constant> base_params: BaseShaderParameters;
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var output: VertexOutput;
+
+    output.position = vec4(input.position.x, input.position.y, input.position.z, 1.0);
+    output.tex_coords = input.tex_coords;
+
+    return output;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    if (input.tex_coords.x < 1.0 / 3.0) {
+        return textureSample(textures[0], sampler_, input.tex_coords);
+    }
+    if (input.tex_coords.x < 2.0 / 3.0) {
+        // Layer 1 contains frame of the first input from 1 frame ago,
+        // it's transparent when the first frame is rendered.
+        return textureSample(history, sampler_, input.tex_coords, 1);
+    }
+
+    // Layer 0 contains previous output, each frame moves it halfway towards white.
+    let previous = textureSample(history, sampler_, input.tex_coords, 0);
+    return mix(previous, vec4(1.0, 1.0, 1.0, 1.0), 0.5);
+}
@@ -13,6 +13,13 @@ use tracing::error;
 
 use crate::logger::FfmpegLogLevel;
 
+mod file;
+
+pub use file::{read_config_file, ConfigFileError, StartupOutput, StartupResources};
+
+/// Valid Opus sample rates
+const SUPPORTED_SAMPLE_RATES: [u32; 5] = [8_000, 12_000, 16_000, 24_000, 48_000];
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub instance_id: String,
@@ -82,8 +89,6 @@ fn try_read_config() -> Result<Config, String> {
         Err(_) => DEFAULT_FRAMERATE,
     };
 
-    /// Valid Opus sample rates
-    const SUPPORTED_SAMPLE_RATES: [u32; 5] = [8_000, 12_000, 16_000, 24_000, 48_000];
     const DEFAULT_MIXING_SAMPLE_RATE: u32 = 48_000;
     let mixing_sample_rate: u32 = match env::var("SMELTER_MIXING_SAMPLE_RATE") {
         Ok(sample_rate) => {
@@ -0,0 +1,503 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use bytes::Bytes;
+use compositor_api::types::{ImageSpec, TypeError};
+use compositor_pipeline::pipeline::{
+    output::OutputOptions, RegisterInputOptions, RegisterOutputOptions,
+};
+use compositor_render::{Framerate, RendererSpec};
+use serde::Deserialize;
+
+use crate::{
+    logger::FfmpegLogLevel,
+    routes::{ProjectInput, ProjectOutput, ProjectRenderer, RegisterInput, RegisterOutput},
+    state::OutputScene,
+};
+
+use super::{
+    framerate_from_str, try_read_config, wgpu_features_from_str, Config, LoggerFormat,
+    SUPPORTED_SAMPLE_RATES,
+};
+
+/// Content of the file passed with `--config`. Every field is optional, values
+/// that are not defined fall back to the environment variables.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    instance_id: Option<String>,
+    api_port: Option<u16>,
+    logger_level: Option<String>,
+    logger_format: Option<String>,
+    ffmpeg_logger_level: Option<String>,
+    log_file: Option<PathBuf>,
+    stream_fallback_timeout_ms: Option<f64>,
+    web_renderer_enable: Option<bool>,
+    web_renderer_gpu_enable: Option<bool>,
+    force_gpu: Option<bool>,
+    download_dir: Option<PathBuf>,
+    output_framerate: Option<FramerateValue>,
+    input_buffer_duration_ms: Option<f64>,
+    offline_processing_enable: Option<bool>,
+    ahead_of_time_processing_enable: Option<bool>,
+    never_drop_output_frames: Option<bool>,
+    run_late_scheduled_events: Option<bool>,
+    mixing_sample_rate: Option<u32>,
+    stun_servers: Option<Vec<String>>,
+    required_wgpu_features: Option<Vec<String>>,
+    load_system_fonts: Option<bool>,
+    whip_whep_server_port: Option<u16>,
+    start_whip_whep_server: Option<bool>,
+
+    #[serde(default)]
+    fonts: Vec<PathBuf>,
+    #[serde(default)]
+    renderers: Vec<ProjectRenderer>,
+    #[serde(default)]
+    inputs: Vec<ProjectInput>,
+    #[serde(default)]
+    outputs: Vec<ProjectOutput>,
+    /// Start the pipeline after all entities are registered.
+    #[serde(default)]
+    start: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum FramerateValue {
+    Integer(u32),
+    String(String),
+}
+
+/// Entities from the config file converted to the pipeline types. They are
+/// registered before the HTTP server starts.
+#[derive(Default)]
+pub struct StartupResources {
+    pub fonts: Vec<Bytes>,
+    pub renderers: Vec<(compositor_render::RendererId, RendererSpec, ProjectRenderer)>,
+    pub inputs: Vec<(
+        compositor_render::InputId,
+        RegisterInputOptions,
+        RegisterInput,
+    )>,
+    pub outputs: Vec<StartupOutput>,
+    pub start: bool,
+}
+
+pub struct StartupOutput {
+    pub output_id: compositor_render::OutputId,
+    pub options: RegisterOutputOptions<OutputOptions>,
+    pub scene: OutputScene,
+    pub request: RegisterOutput,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigFileError {
+    #[error("Failed to read config file \"{}\".", .0.display())]
+    Read(PathBuf, #[source] io::Error),
+
+    #[error("Failed to parse config file \"{}\".", .0.display())]
+    Parse(PathBuf, #[source] serde_json::Error),
+
+    #[error("Failed to read the config from environment variables: {0}")]
+    Environment(String),
+
+    #[error("Invalid value of \"{field}\" in config file \"{}\": {message}", file.display())]
+    InvalidField {
+        file: PathBuf,
+        field: &'static str,
+        message: String,
+    },
+
+    #[error("Invalid definition of {location} in config file \"{}\".", file.display())]
+    InvalidEntity {
+        file: PathBuf,
+        location: String,
+        #[source]
+        source: TypeError,
+    },
+
+    #[error("Failed to read font \"{}\" defined in {location} in config file \"{}\".", path.display(), file.display())]
+    ReadFont {
+        file: PathBuf,
+        location: String,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// Reads the config file. Values defined in the file take precedence over the
+/// environment variables. Relative paths of fonts, renderers and inputs are
+/// resolved against the directory of the config file.
+pub fn read_config_file(path: &Path) -> Result<(Config, StartupResources), ConfigFileError> {
+    let content =
+        fs::read_to_string(path).map_err(|err| ConfigFileError::Read(path.into(), err))?;
+    let file: ConfigFile =
+        serde_json::from_str(&content).map_err(|err| ConfigFileError::Parse(path.into(), err))?;
+
+    let mut config = try_read_config().map_err(ConfigFileError::Environment)?;
+    let invalid_field = |field: &'static str, message: &str| ConfigFileError::InvalidField {
+        file: path.into(),
+        field,
+        message: message.to_string(),
+    };
+    let duration_from_ms = |field: &'static str, ms: f64| {
+        if ms.is_finite() && ms >= 0.0 {
+            Ok(Duration::from_secs_f64(ms / 1000.0))
+        } else {
+            Err(invalid_field(
+                field,
+                "Value has to be a non-negative number.",
+            ))
+        }
+    };
+
+    if let Some(instance_id) = file.instance_id {
+        config.instance_id = instance_id;
+    }
+    if let Some(api_port) = file.api_port {
+        config.api_port = api_port;
+    }
+    if let Some(level) = file.logger_level {
+        config.logger.level = level;
+    }
+    if let Some(format) = file.logger_format {
+        config.logger.format =
+            LoggerFormat::from_str(&format).map_err(|err| invalid_field("logger_format", err))?;
+    }
+    if let Some(level) = file.ffmpeg_logger_level {
+        config.logger.ffmpeg_logger_level = FfmpegLogLevel::from_str(&level)
+            .map_err(|err| invalid_field("ffmpeg_logger_level", err))?;
+    }
+    if let Some(log_file) = file.log_file {
+        config.logger.log_file = Some(Arc::from(log_file));
+    }
+    if let Some(timeout_ms) = file.stream_fallback_timeout_ms {
+        config.stream_fallback_timeout =
+            duration_from_ms("stream_fallback_timeout_ms", timeout_ms)?;
+    }
+    if let Some(enable) = file.web_renderer_enable {
+        config.web_renderer.enable = enable;
+    }
+    if let Some(enable) = file.web_renderer_gpu_enable {
+        config.web_renderer.enable_gpu = enable;
+    }
+    if let Some(force_gpu) = file.force_gpu {
+        config.force_gpu = force_gpu;
+    }
+    if let Some(download_dir) = file.download_dir {
+        config.download_root = download_dir;
+    }
+    if let Some(framerate) = file.output_framerate {
+        config.queue_options.output_framerate = match framerate {
+            FramerateValue::Integer(num) => Framerate { num, den: 1 },
+            FramerateValue::String(framerate) => framerate_from_str(&framerate)
+                .map_err(|err| invalid_field("output_framerate", err))?,
+        };
+    }
+    if let Some(duration_ms) = file.input_buffer_duration_ms {
+        config.queue_options.default_buffer_duration =
+            duration_from_ms("input_buffer_duration_ms", duration_ms)?;
+    }
+    if let Some(enable) = file
+        .ahead_of_time_processing_enable
+        .or(file.offline_processing_enable)
+    {
+        config.queue_options.ahead_of_time_processing = enable;
+    }
+    if let Some(enable) = file
+        .never_drop_output_frames
+        .or(file.offline_processing_enable)
+    {
+        config.queue_options.never_drop_output_frames = enable;
+    }
+    if let Some(enable) = file.run_late_scheduled_events {
+        config.queue_options.run_late_scheduled_events = enable;
+    }
+    if let Some(sample_rate) = file.mixing_sample_rate {
+        if !SUPPORTED_SAMPLE_RATES.contains(&sample_rate) {
+            return Err(invalid_field(
+                "mixing_sample_rate",
+                "Supported sample rates are: 8000, 12000, 16000, 24000, 48000",
+            ));
+        }
+        config.mixing_sample_rate = sample_rate;
+    }
+    if let Some(stun_servers) = file.stun_servers {
+        config.stun_servers = Arc::new(stun_servers);
+    }
+    if let Some(features) = file.required_wgpu_features {
+        config.required_wgpu_features = wgpu_features_from_str(&features.join(","))
+            .map_err(|err| invalid_field("required_wgpu_features", &err))?;
+    }
+    if let Some(enable) = file.load_system_fonts {
+        config.load_system_fonts = enable;
+    }
+    if let Some(port) = file.whip_whep_server_port {
+        config.whip_whep_server_port = port;
+    }
+    if let Some(enable) = file.start_whip_whep_server {
+        config.start_whip_whep = enable;
+    }
+
+    let config_dir = path.parent().unwrap_or(Path::new(""));
+    let invalid_entity = |location: String| {
+        move |source: TypeError| ConfigFileError::InvalidEntity {
+            file: path.into(),
+            location,
+            source,
+        }
+    };
+
+    let fonts = file
+        .fonts
+        .into_iter()
+        .enumerate()
+        .map(|(index, font_path)| {
+            let font_path = config_dir.join(font_path);
+            let font = fs::read(&font_path).map_err(|source| ConfigFileError::ReadFont {
+                file: path.into(),
+                location: format!("fonts[{index}]"),
+                path: font_path,
+                source,
+            })?;
+            Ok(Bytes::from(font))
+        })
+        .collect::<Result<Vec<_>, ConfigFileError>>()?;
+
+    let renderers = file
+        .renderers
+        .into_iter()
+        .enumerate()
+        .map(|(index, renderer)| {
+            let renderer = resolve_renderer_paths(config_dir, renderer);
+            let (renderer_id, spec) = renderer.clone().into_spec();
+            let spec = spec.map_err(invalid_entity(format!(
+                "renderers[{index}] (\"{renderer_id}\")"
+            )))?;
+            Ok((renderer_id, spec, renderer))
+        })
+        .collect::<Result<Vec<_>, ConfigFileError>>()?;
+
+    let inputs = file
+        .inputs
+        .into_iter()
+        .enumerate()
+        .map(|(index, ProjectInput { input_id, input })| {
+            let input = resolve_input_paths(config_dir, input);
+            let options = RegisterInputOptions::try_from(input.clone())
+                .map_err(invalid_entity(format!("inputs[{index}] (\"{input_id}\")")))?;
+            Ok((input_id.into(), options, input))
+        })
+        .collect::<Result<Vec<_>, ConfigFileError>>()?;
+
+    let outputs = file
+        .outputs
+        .into_iter()
+        .enumerate()
+        .map(|(index, ProjectOutput { output_id, output })| {
+            let scene = output.initial_scene();
+            let options = RegisterOutputOptions::<OutputOptions>::try_from(output.clone())
+                .map_err(invalid_entity(format!(
+                    "outputs[{index}] (\"{output_id}\")"
+                )))?;
+            Ok(StartupOutput {
+                output_id: output_id.into(),
+                options,
+                scene,
+                request: output,
+            })
+        })
+        .collect::<Result<Vec<_>, ConfigFileError>>()?;
+
+    let resources = StartupResources {
+        fonts,
+        renderers,
+        inputs,
+        outputs,
+        start: file.start,
+    };
+    Ok((config, resources))
+}
+
+fn resolve_path(config_dir: &Path, path: Option<String>) -> Option<String> {
+    path.map(|path| config_dir.join(path).to_string_lossy().into_owned())
+}
+
+fn resolve_renderer_paths(config_dir: &Path, renderer: ProjectRenderer) -> ProjectRenderer {
+    match renderer {
+        ProjectRenderer::Image { image_id, image } => {
+            let image = match image {
+                ImageSpec::Png { url, path } => ImageSpec::Png {
+                    url,
+                    path: resolve_path(config_dir, path),
+                },
+                ImageSpec::Jpeg { url, path } => ImageSpec::Jpeg {
+                    url,
+                    path: resolve_path(config_dir, path),
+                },
+                ImageSpec::Svg {
+                    url,
+                    path,
+                    resolution,
+                } => ImageSpec::Svg {
+                    url,
+                    path: resolve_path(config_dir, path),
+                    resolution,
+                },
+                ImageSpec::Gif { url, path } => ImageSpec::Gif {
+                    url,
+                    path: resolve_path(config_dir, path),
+                },
+            };
+            ProjectRenderer::Image { image_id, image }
+        }
+        ProjectRenderer::Lottie {
+            lottie_id,
+            mut lottie,
+        } => {
+            lottie.path = resolve_path(config_dir, lottie.path);
+            ProjectRenderer::Lottie { lottie_id, lottie }
+        }
+        renderer => renderer,
+    }
+}
+
+fn resolve_input_paths(config_dir: &Path, input: RegisterInput) -> RegisterInput {
+    match input {
+        RegisterInput::Mp4(mut mp4) => {
+            mp4.path = resolve_path(config_dir, mp4.path);
+            RegisterInput::Mp4(mp4)
+        }
+        input => input,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, process};
+
+    use serde_json::json;
+
+    use super::*;
+
+    /// Writes the config file to a new directory and returns its path.
+    fn write_config(name: &str, config: serde_json::Value) -> PathBuf {
+        let dir = env::temp_dir().join(format!("smelter_config_{name}_{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        fs::write(&path, config.to_string()).unwrap();
+        path
+    }
+
+    fn config_error(name: &str, config: serde_json::Value) -> ConfigFileError {
+        match read_config_file(&write_config(name, config)) {
+            Ok(_) => panic!("Expected invalid config file."),
+            Err(err) => err,
+        }
+    }
+
+    fn mp4_output(path: &str) -> serde_json::Value {
+        json!({
+            "type": "mp4",
+            "path": path,
+            "audio": {
+                "encoder": { "type": "aac", "channels": "stereo" },
+                "initial": { "inputs": [] }
+            }
+        })
+    }
+
+    #[test]
+    fn test_file_takes_precedence_over_env() {
+        env::set_var("SMELTER_INSTANCE_ID", "env_instance");
+        env::set_var("SMELTER_API_PORT", "9000");
+        let path = write_config("precedence", json!({ "instance_id": "file_instance" }));
+        let (config, _) = read_config_file(&path).unwrap();
+        assert_eq!(config.instance_id, "file_instance");
+        assert_eq!(config.api_port, 9000);
+    }
+
+    #[test]
+    fn test_invalid_field() {
+        let cases = [
+            ("logger_format", json!({ "logger_format": "invalid" })),
+            (
+                "stream_fallback_timeout_ms",
+                json!({ "stream_fallback_timeout_ms": -1 }),
+            ),
+            ("mixing_sample_rate", json!({ "mixing_sample_rate": 44100 })),
+        ];
+        for (expected_field, config) in cases {
+            match config_error(expected_field, config) {
+                ConfigFileError::InvalidField { field, .. } => assert_eq!(field, expected_field),
+                err => panic!("Unexpected error: {err}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_entity_location() {
+        let err = config_error(
+            "invalid_entity",
+            json!({
+                "outputs": [
+                    { "output_id": "output_1", "output": mp4_output("output_1.mp4") },
+                    { "output_id": "output_2", "output": { "type": "mp4", "path": "output_2.mp4" } }
+                ]
+            }),
+        );
+        let ConfigFileError::InvalidEntity { location, .. } = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(location, "outputs[1] (\"output_2\")");
+        assert!(err
+            .to_string()
+            .starts_with("Invalid definition of outputs[1] (\"output_2\") in config file"));
+    }
+
+    #[test]
+    fn test_paths_relative_to_config_file() {
+        let path = write_config(
+            "relative_paths",
+            json!({
+                "fonts": ["font.ttf"],
+                "renderers": [{
+                    "type": "image",
+                    "image_id": "image_1",
+                    "image": { "asset_type": "png", "path": "image.png" }
+                }],
+                "inputs": [{
+                    "input_id": "input_1",
+                    "input": { "type": "mp4", "path": "/absolute/input.mp4" }
+                }]
+            }),
+        );
+        let config_dir = path.parent().unwrap();
+        fs::write(config_dir.join("font.ttf"), b"font").unwrap();
+
+        let (_, resources) = read_config_file(&path).unwrap();
+        assert_eq!(resources.fonts, vec![Bytes::from_static(b"font")]);
+        let ProjectRenderer::Image {
+            image: ImageSpec::Png {
+                path: image_path, ..
+            },
+            ..
+        } = &resources.renderers[0].2
+        else {
+            panic!("Expected PNG image.");
+        };
+        let expected_path = config_dir.join("image.png");
+        assert_eq!(image_path.as_deref(), expected_path.to_str());
+        let RegisterInput::Mp4(mp4) = &resources.inputs[0].2 else {
+            panic!("Expected MP4 input.");
+        };
+        assert_eq!(mp4.path.as_deref(), Some("/absolute/input.mp4"));
+    }
+}
@@ -1,6 +1,7 @@
 pub mod config;
 pub mod logger;
 pub mod middleware;
+pub mod offline_render;
 pub mod routes;
 pub mod server;
 pub mod state;
@@ -22,11 +22,11 @@ pub enum FfmpegLogLevel {
     Debug,
 }
 
-fn ffmpeg_logger_level() -> FfmpegLogLevel {
-    static LOG_LEVEL: OnceLock<FfmpegLogLevel> = OnceLock::new();
+static FFMPEG_LOG_LEVEL: OnceLock<FfmpegLogLevel> = OnceLock::new();
 
-    // This will read config second time
-    *LOG_LEVEL.get_or_init(|| read_config().logger.ffmpeg_logger_level)
+fn ffmpeg_logger_level() -> FfmpegLogLevel {
+    // This will read config second time if logger was not initialized
+    *FFMPEG_LOG_LEVEL.get_or_init(|| read_config().logger.ffmpeg_logger_level)
 }
 
 impl FromStr for FfmpegLogLevel {
@@ -101,6 +101,8 @@ pub fn init_logger(opts: LoggerConfig) {
             .init(),
     }
 
+    // Level can come from the config file, so it can't be read from the environment again
+    let _ = FFMPEG_LOG_LEVEL.set(opts.ffmpeg_logger_level);
     unsafe {
         ffmpeg_next::sys::av_log_set_callback(Some(ffmpeg_log_callback));
     }
@@ -1,8 +1,11 @@
+use std::{env, path::PathBuf, process};
+
 use log::info;
 
 mod config;
 mod logger;
 mod middleware;
+mod offline_render;
 mod routes;
 mod server;
 mod state;
@@ -24,8 +27,72 @@ fn main() {
 
     ffmpeg_next::format::network::init();
 
-    server::run();
+    let args: Vec<String> = env::args().skip(1).collect();
+    match parse_args(&args) {
+        Some(Command::Server) => server::run(),
+        Some(Command::ServerWithConfig(config_path)) => server::run_with_config_file(&config_path),
+        Some(Command::Render(project_path)) => offline_render::run(&project_path),
+        None => {
+            eprintln!("Usage:\n  smelter\n  smelter --config <config.json>\n  smelter render <project.json>");
+            process::exit(2);
+        }
+    }
 
     info!("Received exit signal. Terminating...")
     // TODO: add graceful shutdown
 }
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Server,
+    ServerWithConfig(PathBuf),
+    Render(PathBuf),
+}
+
+/// Parses command line arguments without the program name. Arguments that don't
+/// start with a known command or flag are ignored and the server is started.
+/// Returns `None` if a known command or flag is used incorrectly.
+fn parse_args(args: &[String]) -> Option<Command> {
+    match args {
+        [flag, config_path] if flag == "--config" => {
+            Some(Command::ServerWithConfig(config_path.into()))
+        }
+        [command, project_path] if command == "render" => {
+            Some(Command::Render(project_path.into()))
+        }
+        [first, ..] if first == "--config" || first == "render" => None,
+        _ => Some(Command::Server),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Option<Command> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        parse_args(&args)
+    }
+
+    #[test]
+    fn test_parse_args_render() {
+        assert_eq!(
+            parse(&["render", "project.json"]),
+            Some(Command::Render("project.json".into()))
+        );
+        assert_eq!(parse(&["render"]), None);
+        assert_eq!(parse(&["render", "project.json", "other.json"]), None);
+    }
+
+    #[test]
+    fn test_parse_args_server() {
+        assert_eq!(parse(&[]), Some(Command::Server));
+        assert_eq!(
+            parse(&["--config", "config.json"]),
+            Some(Command::ServerWithConfig("config.json".into()))
+        );
+        assert_eq!(parse(&["project.json", "render"]), Some(Command::Server));
+        assert_eq!(parse(&["--unknown-flag"]), Some(Command::Server));
+        assert_eq!(parse(&["--config"]), None);
+    }
+}
@@ -1,7 +1,7 @@
 use axum::{
     body::Body,
     extract::Request,
-    http::StatusCode,
+    http::{header, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
 };
@@ -19,11 +19,23 @@ pub async fn body_logger_middleware(
     }
     let request = buffer_request_body(request).await?;
     let response = next.run(request).await;
+    if is_streamed(&response) {
+        return Ok(response);
+    }
     let response = buffer_response_body(response).await?;
 
     Ok(response)
 }
 
+/// Streamed responses (e.g. MJPEG snapshots) never end, so they can't be buffered.
+fn is_streamed(response: &Response) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("multipart/x-mixed-replace"))
+}
+
 async fn buffer_request_body(request: Request) -> Result<Request, Response> {
     let (parts, body) = request.into_parts();
 
@@ -0,0 +1,466 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use compositor_api::types::{Audio, OutputId, TypeError, Video};
+use compositor_pipeline::{
+    audio_mixer::AudioMixingParams,
+    error::{RegisterInputError, RegisterOutputError},
+    event::{Event, EventError, OutputConnectionState},
+    pipeline::{output::OutputOptions, RegisterInputOptions, RegisterOutputOptions},
+    queue::ScheduledEventDescription,
+};
+use compositor_render::{
+    error::{ErrorStack, RegisterRendererError},
+    scene::Component,
+    RendererSpec,
+};
+use crossbeam_channel::{select, tick};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use signal_hook::{consts, iterator::Signals};
+use tokio::runtime::Runtime;
+use tracing::error;
+
+use crate::{
+    config::read_config,
+    logger::init_logger,
+    routes::{schedule_output_update, ProjectInput, ProjectOutput, ProjectRenderer},
+    state::{ApiState, OutputScene, Pipeline},
+};
+
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Project rendered by the `render` command. All entities are registered and all
+/// updates are scheduled before the pipeline is started.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RenderProject {
+    #[serde(default)]
+    pub renderers: Vec<ProjectRenderer>,
+    #[serde(default)]
+    pub inputs: Vec<ProjectInput>,
+    /// Render finishes when all outputs are done, so each output should define
+    /// `send_eos_when`.
+    pub outputs: Vec<ProjectOutput>,
+    #[serde(default)]
+    pub updates: Vec<ProjectUpdate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectUpdate {
+    pub output_id: OutputId,
+    /// Time in milliseconds when the update should be applied. Value `0` represents
+    /// the start of the render.
+    pub schedule_time_ms: f64,
+    pub video: Option<Video>,
+    pub audio: Option<Audio>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RenderError {
+    #[error("Failed to read project file \"{}\".", .0.display())]
+    ReadProject(PathBuf, #[source] io::Error),
+
+    #[error("Failed to parse project file.")]
+    ParseProject(#[from] serde_json::Error),
+
+    #[error("Invalid definition of {0}.")]
+    InvalidProject(String, #[source] TypeError),
+
+    #[error("Project does not define any outputs.")]
+    NoOutputs,
+
+    #[error(transparent)]
+    RegisterInput(#[from] RegisterInputError),
+
+    #[error(transparent)]
+    RegisterOutput(#[from] RegisterOutputError),
+
+    #[error(transparent)]
+    RegisterRenderer(#[from] RegisterRendererError),
+
+    #[error("Update of output \"{output_id}\" scheduled for {}ms failed: {}", schedule_time.as_millis(), error.message)]
+    UpdateFailed {
+        output_id: compositor_render::OutputId,
+        schedule_time: Duration,
+        error: EventError,
+    },
+
+    #[error("Failed to schedule update of output \"{output_id}\": {message}")]
+    ScheduleUpdate {
+        output_id: compositor_render::OutputId,
+        message: String,
+    },
+
+    #[error("Connection of output \"{0}\" failed.")]
+    OutputFailed(compositor_render::OutputId),
+
+    #[error("Pipeline stopped before all outputs were done.")]
+    PipelineStopped,
+}
+
+/// Project converted to the pipeline types.
+struct Project {
+    renderers: Vec<(compositor_render::RendererId, RendererSpec)>,
+    inputs: Vec<(compositor_render::InputId, RegisterInputOptions)>,
+    outputs: Vec<(
+        compositor_render::OutputId,
+        RegisterOutputOptions<OutputOptions>,
+    )>,
+    updates: Vec<Update>,
+}
+
+struct Update {
+    output_id: compositor_render::OutputId,
+    schedule_time: Duration,
+    scene: OutputScene,
+}
+
+/// Renders the project file without starting the HTTP server. Pipeline processes
+/// frames as fast as possible and the process exits when all outputs are done,
+/// with status code `0` on success and `1` on failure.
+pub fn run(project_path: &Path) {
+    let mut config = read_config();
+    config.queue_options.ahead_of_time_processing = true;
+    config.queue_options.never_drop_output_frames = true;
+    config.start_whip_whep = false;
+    init_logger(config.logger.clone());
+
+    let project = match read_project(project_path) {
+        Ok(project) => project,
+        Err(err) => exit_with_error(&err),
+    };
+
+    info!("Starting offline render with config:\n{:#?}", config);
+    let runtime = Arc::new(Runtime::new().unwrap());
+    let (state, event_loop) = ApiState::new(config, runtime).unwrap_or_else(|err| {
+        panic!(
+            "Failed to start event loop.\n{}",
+            ErrorStack::new(&err).into_string()
+        )
+    });
+
+    thread::Builder::new()
+        .name("Offline render thread".to_string())
+        .spawn(move || match render(&state, project) {
+            Ok(()) => {
+                println!("Render finished.");
+                process::exit(0);
+            }
+            Err(err) => exit_with_error(&err),
+        })
+        .unwrap();
+
+    let event_loop_fallback = || {
+        let mut signals = Signals::new([consts::SIGINT]).unwrap();
+        signals.forever().next();
+    };
+    if let Err(err) = event_loop.run_with_fallback(&event_loop_fallback) {
+        panic!(
+            "Failed to start event loop.\n{}",
+            ErrorStack::new(&err).into_string()
+        )
+    }
+    println!("Render interrupted.");
+    process::exit(1);
+}
+
+fn read_project(path: &Path) -> Result<Project, RenderError> {
+    let content =
+        fs::read_to_string(path).map_err(|err| RenderError::ReadProject(path.into(), err))?;
+    let project: RenderProject = serde_json::from_str(&content)?;
+    Project::try_from(project)
+}
+
+fn render(api: &ApiState, project: Project) -> Result<(), RenderError> {
+    if project.outputs.is_empty() {
+        return Err(RenderError::NoOutputs);
+    }
+    let pipeline = &api.pipeline;
+    let events = pipeline.lock().unwrap().subscribe_pipeline_events();
+
+    for (renderer_id, spec) in project.renderers {
+        Pipeline::register_renderer(pipeline, renderer_id, spec)?;
+    }
+    for (input_id, options) in project.inputs {
+        Pipeline::register_input(pipeline, input_id, options)?;
+    }
+    let mut pending_outputs = HashSet::new();
+    for (output_id, options) in project.outputs {
+        Pipeline::register_output(pipeline, output_id.clone(), options)?;
+        pending_outputs.insert(output_id);
+    }
+    let queue = pipeline.lock().unwrap().queue().clone();
+    for Update {
+        output_id,
+        schedule_time,
+        scene,
+    } in project.updates
+    {
+        let description = ScheduledEventDescription {
+            kind: "update_output",
+            entity_id: Some(output_id.to_string()),
+        };
+        schedule_output_update(
+            api,
+            &queue,
+            output_id.clone(),
+            schedule_time,
+            description,
+            scene,
+        )
+        .map_err(|err| RenderError::ScheduleUpdate {
+            output_id,
+            message: err.message,
+        })?;
+    }
+
+    let start_time = Instant::now();
+    Pipeline::start(pipeline);
+
+    let ticker = tick(PROGRESS_INTERVAL);
+    while !pending_outputs.is_empty() {
+        select! {
+            recv(events) -> event => match event {
+                Ok(Event::OutputDone(output_id)) => {
+                    if pending_outputs.remove(&output_id) {
+                        println!("Output \"{output_id}\" done.");
+                    }
+                }
+                Ok(Event::ScheduledOutputUpdateFailed {
+                    output_id,
+                    schedule_time,
+                    error,
+                }) => {
+                    return Err(RenderError::UpdateFailed {
+                        output_id,
+                        schedule_time,
+                        error,
+                    })
+                }
+                Ok(Event::OutputConnectionStateChanged {
+                    output_id,
+                    state: OutputConnectionState::Failed,
+                }) => return Err(RenderError::OutputFailed(output_id)),
+                Ok(_) => (),
+                Err(_) => return Err(RenderError::PipelineStopped),
+            },
+            recv(ticker) -> _ => {
+                let pts = queue.current_pts();
+                print_progress(pts, start_time.elapsed(), pending_outputs.len());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_progress(pts: Option<Duration>, elapsed: Duration, pending_outputs: usize) {
+    let Some(pts) = pts else {
+        println!("Waiting for inputs...");
+        return;
+    };
+    println!(
+        "Rendered {:.1}s ({:.1}x realtime), {} output(s) in progress.",
+        pts.as_secs_f64(),
+        pts.as_secs_f64() / elapsed.as_secs_f64(),
+        pending_outputs
+    );
+}
+
+fn exit_with_error(err: &RenderError) -> ! {
+    error!("Render failed.\n{}", ErrorStack::new(err).into_string());
+    process::exit(1);
+}
+
+impl TryFrom<RenderProject> for Project {
+    type Error = RenderError;
+
+    fn try_from(project: RenderProject) -> Result<Self, Self::Error> {
+        let invalid =
+            |entity: String| move |err: TypeError| RenderError::InvalidProject(entity, err);
+
+        let renderers = project
+            .renderers
+            .into_iter()
+            .map(|renderer| {
+                let (renderer_id, spec) = renderer.into_spec();
+                let spec = spec.map_err(invalid(format!("renderer \"{renderer_id}\"")))?;
+                Ok((renderer_id, spec))
+            })
+            .collect::<Result<Vec<_>, RenderError>>()?;
+
+        let inputs = project
+            .inputs
+            .into_iter()
+            .map(|ProjectInput { input_id, input }| {
+                let options = RegisterInputOptions::try_from(input)
+                    .map_err(invalid(format!("input \"{input_id}\"")))?;
+                Ok((input_id.into(), options))
+            })
+            .collect::<Result<Vec<_>, RenderError>>()?;
+
+        let outputs = project
+            .outputs
+            .into_iter()
+            .map(|ProjectOutput { output_id, output }| {
+                let options = RegisterOutputOptions::<OutputOptions>::try_from(output)
+                    .map_err(invalid(format!("output \"{output_id}\"")))?;
+                Ok((output_id.into(), options))
+            })
+            .collect::<Result<Vec<_>, RenderError>>()?;
+
+        let updates = project
+            .updates
+            .into_iter()
+            .map(|update| {
+                let entity = format!(
+                    "update of output \"{}\" at {}ms",
+                    update.output_id, update.schedule_time_ms
+                );
+                if !(update.schedule_time_ms.is_finite() && update.schedule_time_ms >= 0.0) {
+                    return Err(RenderError::InvalidProject(
+                        entity,
+                        TypeError::new("\"schedule_time_ms\" has to be a non-negative number."),
+                    ));
+                }
+                // Scene is converted again when the update is scheduled, here it is
+                // only checked, so errors point to the invalid update.
+                if let Some(video) = update.video.clone() {
+                    Component::try_from(video).map_err(invalid(entity.clone()))?;
+                }
+                if let Some(audio) = update.audio.clone() {
+                    AudioMixingParams::try_from(audio).map_err(invalid(entity))?;
+                }
+                Ok(Update {
+                    output_id: update.output_id.into(),
+                    schedule_time: Duration::from_secs_f64(update.schedule_time_ms / 1000.0),
+                    scene: OutputScene {
+                        video: update.video,
+                        audio: update.audio,
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>, RenderError>>()?;
+
+        Ok(Self {
+            renderers,
+            inputs,
+            outputs,
+            updates,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn output() -> serde_json::Value {
+        json!({
+            "output_id": "output_1",
+            "output": {
+                "type": "mp4",
+                "path": "output.mp4",
+                "audio": {
+                    "encoder": { "type": "aac", "channels": "stereo" },
+                    "initial": { "inputs": [] }
+                }
+            }
+        })
+    }
+
+    fn project(value: serde_json::Value) -> Result<Project, RenderError> {
+        let project: RenderProject = serde_json::from_value(value).unwrap();
+        Project::try_from(project)
+    }
+
+    fn project_error(value: serde_json::Value) -> RenderError {
+        match project(value) {
+            Ok(_) => panic!("Expected invalid project."),
+            Err(err) => err,
+        }
+    }
+
+    fn update(schedule_time_ms: f64) -> serde_json::Value {
+        json!({
+            "outputs": [output()],
+            "updates": [{
+                "output_id": "output_1",
+                "schedule_time_ms": schedule_time_ms,
+                "audio": { "inputs": [] }
+            }]
+        })
+    }
+
+    #[test]
+    fn test_project_valid() {
+        let project = project(update(1500.0)).unwrap();
+        assert_eq!(project.outputs.len(), 1);
+        assert_eq!(project.updates.len(), 1);
+        assert_eq!(
+            project.updates[0].schedule_time,
+            Duration::from_millis(1500)
+        );
+    }
+
+    #[test]
+    fn test_project_invalid_schedule_time() {
+        for schedule_time_ms in [-1.0, f64::INFINITY, f64::NAN] {
+            let err = project_error(update(schedule_time_ms));
+            assert!(
+                matches!(&err, RenderError::InvalidProject(entity, _) if entity.starts_with("update of output \"output_1\"")),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_project_invalid_entity_messages() {
+        let err = project_error(json!({
+            "renderers": [{ "type": "image", "image_id": "image_1", "image": { "asset_type": "png" } }],
+            "outputs": [output()]
+        }));
+        assert_eq!(
+            err.to_string(),
+            "Invalid definition of renderer \"image_1\"."
+        );
+
+        let err = project_error(json!({
+            "inputs": [{ "input_id": "input_1", "input": { "type": "mp4" } }],
+            "outputs": [output()]
+        }));
+        assert_eq!(err.to_string(), "Invalid definition of input \"input_1\".");
+
+        let err = project_error(json!({
+            "outputs": [{ "output_id": "output_1", "output": { "type": "mp4", "path": "output.mp4" } }]
+        }));
+        assert_eq!(
+            err.to_string(),
+            "Invalid definition of output \"output_1\"."
+        );
+
+        let err = project_error(json!({
+            "outputs": [output()],
+            "updates": [{
+                "output_id": "output_1",
+                "schedule_time_ms": 1000,
+                "video": { "root": { "type": "view", "padding": -1 } }
+            }]
+        }));
+        assert_eq!(
+            err.to_string(),
+            "Invalid definition of update of output \"output_1\" at 1000ms."
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use axum::{
     async_trait,
-    extract::{rejection::JsonRejection, ws::WebSocketUpgrade, FromRequest, Request, State},
+    body::Bytes,
+    extract::{rejection::JsonRejection, ws::WebSocketUpgrade, FromRequest, Query, Request, State},
     http::StatusCode,
     middleware,
     response::IntoResponse,
@@ -8,6 +9,7 @@ use axum::{
     Router,
 };
 use compositor_pipeline::Pipeline;
+use serde::de::DeserializeOwned;
 use serde_json::{json, Value};
 
 use crate::state::{ApiState, Response};
@@ -15,37 +17,87 @@ use crate::state::{ApiState, Response};
 use compositor_api::error::ApiError;
 
 use self::{
-    update_output::handle_keyframe_request, update_output::handle_output_update,
-    ws::handle_ws_upgrade,
+    update_output::handle_keyframe_request,
+    update_output::handle_output_patch,
+    update_output::handle_output_update,
+    update_output::handle_output_validate,
+    ws::{handle_ws_upgrade, WsOptions, WsParams},
 };
 use crate::middleware::body_logger_middleware;
 
+mod batch;
+mod list_request;
+mod metrics;
+mod pipeline_state;
 mod register_request;
+mod render_preview;
+mod schedule;
+mod snapshot;
+mod timeline;
 mod unregister_request;
 mod update_output;
 mod ws;
 
 #[allow(unused_imports)]
-pub use register_request::{RegisterInput, RegisterOutput};
+pub use batch::{BatchOperation, BatchRequest};
 #[allow(unused_imports)]
-pub use unregister_request::{UnregisterInput, UnregisterOutput};
+pub use pipeline_state::{OutputState, PipelineState};
+#[allow(unused_imports)]
+pub use register_request::{
+    ProjectInput, ProjectOutput, ProjectRenderer, RegisterInput, RegisterOutput,
+};
+#[allow(unused_imports)]
+pub use schedule::ScheduledRequest;
+#[allow(unused_imports)]
+pub use timeline::{RemoveTimelineEntryRequest, TimelineEntryRequest, TimelineRequest};
+#[allow(unused_imports)]
+pub use unregister_request::{UnregisterInput, UnregisterOutput, UnregisterRenderer};
+
+pub(crate) use update_output::schedule_output_update;
 
 pub fn routes(state: ApiState) -> Router {
     let inputs = Router::new()
+        .route("/", get(list_request::handle_inputs))
         .route("/:id/register", post(register_request::handle_input))
-        .route("/:id/unregister", post(unregister_request::handle_input));
+        .route("/:id/unregister", post(unregister_request::handle_input))
+        .route("/:id/snapshot", get(snapshot::handle_input_snapshot))
+        .route("/:id/snapshot/mjpeg", get(snapshot::handle_input_mjpeg));
 
     let outputs = Router::new()
+        .route("/", get(list_request::handle_outputs))
         .route("/:id/register", post(register_request::handle_output))
         .route("/:id/unregister", post(unregister_request::handle_output))
         .route("/:id/update", post(handle_output_update))
-        .route("/:id/request_keyframe", post(handle_keyframe_request));
+        .route("/:id/validate", post(handle_output_validate))
+        .route("/:id/patch", post(handle_output_patch))
+        .route("/:id/request_keyframe", post(handle_keyframe_request))
+        .route(
+            "/:id/timeline",
+            get(timeline::handle_timeline).post(timeline::handle_timeline_upload),
+        )
+        .route(
+            "/:id/timeline/update_entry",
+            post(timeline::handle_update_entry),
+        )
+        .route(
+            "/:id/timeline/remove_entry",
+            post(timeline::handle_remove_entry),
+        )
+        .route("/:id/snapshot", get(snapshot::handle_output_snapshot))
+        .route("/:id/snapshot/mjpeg", get(snapshot::handle_output_mjpeg));
 
     let image = Router::new()
+        .route("/", get(list_request::handle_images))
         .route("/:id/register", post(register_request::handle_image))
         .route("/:id/unregister", post(unregister_request::handle_image));
 
+    let lottie = Router::new()
+        .route("/", get(list_request::handle_lotties))
+        .route("/:id/register", post(register_request::handle_lottie))
+        .route("/:id/unregister", post(unregister_request::handle_lottie));
+
     let web = Router::new()
+        .route("/", get(list_request::handle_web_renderers))
         .route("/:id/register", post(register_request::handle_web_renderer))
         .route(
             "/:id/unregister",
@@ -53,10 +105,24 @@ pub fn routes(state: ApiState) -> Router {
         );
 
     let shader = Router::new()
+        .route("/", get(list_request::handle_shaders))
         .route("/:id/register", post(register_request::handle_shader))
         .route("/:id/unregister", post(unregister_request::handle_shader));
 
-    let font = Router::new().route("/register", post(register_request::handle_font));
+    let compute_shader = Router::new()
+        .route("/", get(list_request::handle_compute_shaders))
+        .route(
+            "/:id/register",
+            post(register_request::handle_compute_shader),
+        )
+        .route(
+            "/:id/unregister",
+            post(unregister_request::handle_compute_shader),
+        );
+
+    let font = Router::new()
+        .route("/", get(list_request::handle_fonts))
+        .route("/register", post(register_request::handle_font));
 
     async fn handle_start(State(state): State<ApiState>) -> Result<Response, ApiError> {
         Pipeline::start(&state.pipeline);
@@ -67,13 +133,31 @@ pub fn routes(state: ApiState) -> Router {
         .nest("/api/input", inputs)
         .nest("/api/output", outputs)
         .nest("/api/image", image)
+        .nest("/api/lottie", lottie)
         .nest("/api/web-renderer", web)
         .nest("/api/shader", shader)
+        .nest("/api/compute-shader", compute_shader)
         .nest("/api/font", font)
+        .route(
+            "/api/preview/render",
+            post(render_preview::handle_render_preview),
+        )
+        .route("/api/batch", post(batch::handle_batch))
+        .route("/api/state", get(pipeline_state::handle_export))
+        .route("/api/state/import", post(pipeline_state::handle_import))
+        .route(
+            "/api/scheduled-event",
+            get(schedule::handle_scheduled_events),
+        )
+        .route(
+            "/api/scheduled-event/:id/cancel",
+            post(schedule::handle_cancel),
+        )
         // Start request
         .route("/api/start", post(handle_start))
         // WebSocket - events
         .route("/ws", get(ws_handler))
+        .route("/metrics", get(metrics::handle_metrics))
         .route(
             "/status",
             get(axum::Json(json!({
@@ -84,9 +168,14 @@ pub fn routes(state: ApiState) -> Router {
         .with_state(state)
 }
 
-async fn ws_handler(ws: WebSocketUpgrade) -> impl IntoResponse {
+async fn ws_handler(
+    State(state): State<ApiState>,
+    Query(params): Query<WsParams>,
+    ws: WebSocketUpgrade,
+) -> Result<impl IntoResponse, ApiError> {
+    let options = WsOptions::try_from(params)?;
     // finalize the upgrade process by returning upgrade callback.
-    ws.on_upgrade(handle_ws_upgrade)
+    Ok(ws.on_upgrade(move |socket| handle_ws_upgrade(socket, state, options)))
 }
 
 /// Wrap axum::Json to return serialization errors as json
@@ -118,6 +207,39 @@ where
     }
 }
 
+/// Same as [`Json`], but an empty body is treated as `T::default()`. Used for requests
+/// that originally did not have a body.
+pub(super) struct OptionalJson<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for OptionalJson<T>
+where
+    T: DeserializeOwned + Default,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, axum::Json<Value>);
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let malformed_request = |status: StatusCode, message: String| {
+            let payload = json!({
+                "error_code": "MALFORMED_REQUEST",
+                "message": message,
+            });
+            (status, axum::Json(payload))
+        };
+
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|rejection| malformed_request(rejection.status(), rejection.body_text()))?;
+        if body.is_empty() {
+            return Ok(Self(T::default()));
+        }
+        serde_json::from_slice(&body)
+            .map(Self)
+            .map_err(|err| malformed_request(StatusCode::UNPROCESSABLE_ENTITY, err.to_string()))
+    }
+}
+
 pub(super) struct Multipart(pub axum::extract::Multipart);
 
 #[async_trait]
@@ -0,0 +1,458 @@
+use std::{collections::HashSet, time::Duration};
+
+use axum::extract::State;
+use compositor_api::{
+    error::ApiError,
+    types::{
+        Audio, ComputeShaderSpec, ImageSpec, InputId, LottieSpec, OutputId, RendererId, ShaderSpec,
+        Video, WebRendererSpec,
+    },
+};
+use compositor_pipeline::{
+    error::ApplyChangesError,
+    event::{Event, EventError},
+    pipeline::{
+        output::OutputOptions, OutputUpdate, PipelineChanges, RegisterInputOptions,
+        RegisterOutputOptions,
+    },
+    queue::ScheduledEventDescription,
+};
+use compositor_render::{RegistryType, RendererSpec};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::state::{
+    ApiState, OutputScene, OutputScenes, Pipeline, Registrations, Response, Timelines,
+};
+
+use super::{
+    register_request::{
+        input_response, output_response, record_renderer, ProjectRenderer, RegisterInput,
+        RegisterOutput,
+    },
+    timeline::remove_timeline,
+    Json,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BatchRequest {
+    /// Operations that should be applied together.
+    pub operations: Vec<BatchOperation>,
+    /// Time in milliseconds when output updates and unregisters should be applied.
+    /// Value `0` represents time of the start request.
+    pub schedule_time_ms: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum BatchOperation {
+    RegisterInput {
+        input_id: InputId,
+        input: RegisterInput,
+    },
+    RegisterOutput {
+        output_id: OutputId,
+        output: RegisterOutput,
+    },
+    RegisterImage {
+        image_id: RendererId,
+        image: ImageSpec,
+    },
+    RegisterLottie {
+        lottie_id: RendererId,
+        lottie: LottieSpec,
+    },
+    RegisterShader {
+        shader_id: RendererId,
+        shader: ShaderSpec,
+    },
+    RegisterComputeShader {
+        shader_id: RendererId,
+        shader: ComputeShaderSpec,
+    },
+    RegisterWebRenderer {
+        instance_id: RendererId,
+        web_renderer: WebRendererSpec,
+    },
+    UpdateOutput {
+        output_id: OutputId,
+        video: Option<Video>,
+        audio: Option<Audio>,
+    },
+    UnregisterInput {
+        input_id: InputId,
+    },
+    UnregisterOutput {
+        output_id: OutputId,
+    },
+    UnregisterImage {
+        image_id: RendererId,
+    },
+    UnregisterLottie {
+        lottie_id: RendererId,
+    },
+    UnregisterShader {
+        shader_id: RendererId,
+    },
+    UnregisterComputeShader {
+        shader_id: RendererId,
+    },
+    UnregisterWebRenderer {
+        instance_id: RendererId,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct BatchResponse {
+    /// Result of each operation, in the same order as in the request. Not available
+    /// if the batch was scheduled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    results: Option<Vec<Response>>,
+    /// Id of the scheduled event, if the batch was scheduled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event_id: Option<u64>,
+}
+
+/// Operations of the request converted to the pipeline types.
+struct Batch {
+    /// Number of operations in the request.
+    operations_count: usize,
+    /// Registrations together with the position of the operation in the request.
+    registrations: Vec<(usize, Registration)>,
+    changes: PipelineChanges,
+    /// Updated scenes in the form they were sent to the API.
+    output_scenes: Vec<(compositor_render::OutputId, OutputScene)>,
+}
+
+/// Converted registrations together with their original requests.
+enum Registration {
+    Input(
+        compositor_render::InputId,
+        RegisterInputOptions,
+        RegisterInput,
+    ),
+    Output(
+        compositor_render::OutputId,
+        RegisterOutputOptions<OutputOptions>,
+        OutputScene,
+        RegisterOutput,
+    ),
+    Renderer(compositor_render::RendererId, RendererSpec, ProjectRenderer),
+}
+
+/// Registers new entities, then applies output updates and unregisters in a single
+/// step, so all of them take effect for the same frames.
+///
+/// If `schedule_time_ms` is specified, the whole batch, including registrations, is
+/// executed at that time. If anything fails, nothing is applied and entities registered
+/// by the batch are unregistered.
+pub(super) async fn handle_batch(
+    State(api): State<ApiState>,
+    Json(request): Json<BatchRequest>,
+) -> Result<axum::Json<BatchResponse>, ApiError> {
+    tokio::task::spawn_blocking(move || run_batch(&api, request).map(axum::Json))
+        .await
+        // `unwrap()` panics only when the task panicked or `response.abort()` was called
+        .unwrap()
+}
+
+pub(super) fn run_batch(api: &ApiState, request: BatchRequest) -> Result<BatchResponse, ApiError> {
+    let batch = Batch::new(request.operations)?;
+    let Some(schedule_time_ms) = request.schedule_time_ms else {
+        let results = batch.execute(api)?;
+        return Ok(BatchResponse {
+            results: Some(results),
+            event_id: None,
+        });
+    };
+
+    let state = api.clone();
+    let schedule_time = Duration::from_secs_f64(schedule_time_ms / 1000.0);
+    let description = ScheduledEventDescription {
+        kind: "batch",
+        entity_id: None,
+    };
+    // The callback locks the pipeline, so it has to be released before scheduling.
+    let queue = api.pipeline().queue().clone();
+    let id = queue.schedule_event(
+        schedule_time,
+        description,
+        Box::new(move || {
+            if let Err(err) = batch.execute(&state) {
+                error!(
+                    "Error while running scheduled batch for pts {}ms: {}",
+                    schedule_time.as_millis(),
+                    err.message
+                );
+                state.pipeline().emit_event(Event::ScheduledBatchFailed {
+                    schedule_time,
+                    error: EventError {
+                        error_code: err.error_code,
+                        message: err.message,
+                        stack: err.stack,
+                    },
+                });
+            }
+        }),
+    );
+    Ok(BatchResponse {
+        results: None,
+        event_id: Some(id.0),
+    })
+}
+
+impl Batch {
+    fn new(operations: Vec<BatchOperation>) -> Result<Self, ApiError> {
+        let mut batch = Batch {
+            operations_count: operations.len(),
+            registrations: Vec::new(),
+            changes: PipelineChanges::default(),
+            output_scenes: Vec::new(),
+        };
+        let mut updated_outputs = HashSet::new();
+
+        for (index, operation) in operations.into_iter().enumerate() {
+            let registration = match operation {
+                BatchOperation::RegisterInput { input_id, input } => {
+                    Registration::Input(input_id.into(), input.clone().try_into()?, input)
+                }
+                BatchOperation::RegisterOutput { output_id, output } => {
+                    let scene = output.initial_scene();
+                    Registration::Output(
+                        output_id.into(),
+                        output.clone().try_into()?,
+                        scene,
+                        output,
+                    )
+                }
+                BatchOperation::RegisterImage { image_id, image } => {
+                    renderer_registration(ProjectRenderer::Image { image_id, image })?
+                }
+                BatchOperation::RegisterLottie { lottie_id, lottie } => {
+                    renderer_registration(ProjectRenderer::Lottie { lottie_id, lottie })?
+                }
+                BatchOperation::RegisterShader { shader_id, shader } => {
+                    renderer_registration(ProjectRenderer::Shader { shader_id, shader })?
+                }
+                BatchOperation::RegisterComputeShader { shader_id, shader } => {
+                    renderer_registration(ProjectRenderer::ComputeShader { shader_id, shader })?
+                }
+                BatchOperation::RegisterWebRenderer {
+                    instance_id,
+                    web_renderer,
+                } => renderer_registration(ProjectRenderer::WebRenderer {
+                    instance_id,
+                    web_renderer,
+                })?,
+                BatchOperation::UpdateOutput {
+                    output_id,
+                    video,
+                    audio,
+                } => {
+                    let output_id: compositor_render::OutputId = output_id.into();
+                    if !updated_outputs.insert(output_id.clone()) {
+                        return Err(ApiError::malformed_request(&format!(
+                            "Output \"{output_id}\" is updated more than once."
+                        )));
+                    }
+                    batch.changes.output_updates.push(OutputUpdate {
+                        output_id: output_id.clone(),
+                        video: video.clone().map(|video| video.try_into()).transpose()?,
+                        audio: audio.clone().map(|audio| audio.try_into()).transpose()?,
+                    });
+                    batch
+                        .output_scenes
+                        .push((output_id, OutputScene { video, audio }));
+                    continue;
+                }
+                BatchOperation::UnregisterInput { input_id } => {
+                    batch.changes.unregister_inputs.push(input_id.into());
+                    continue;
+                }
+                BatchOperation::UnregisterOutput { output_id } => {
+                    batch.changes.unregister_outputs.push(output_id.into());
+                    continue;
+                }
+                BatchOperation::UnregisterImage { image_id } => {
+                    batch.unregister_renderer(image_id, RegistryType::Image);
+                    continue;
+                }
+                BatchOperation::UnregisterLottie { lottie_id } => {
+                    batch.unregister_renderer(lottie_id, RegistryType::Lottie);
+                    continue;
+                }
+                BatchOperation::UnregisterShader { shader_id } => {
+                    batch.unregister_renderer(shader_id, RegistryType::Shader);
+                    continue;
+                }
+                BatchOperation::UnregisterComputeShader { shader_id } => {
+                    batch.unregister_renderer(shader_id, RegistryType::ComputeShader);
+                    continue;
+                }
+                BatchOperation::UnregisterWebRenderer { instance_id } => {
+                    batch.unregister_renderer(instance_id, RegistryType::WebRenderer);
+                    continue;
+                }
+            };
+            batch.registrations.push((index, registration));
+        }
+        Ok(batch)
+    }
+
+    /// Executes registrations in order, then applies updates and unregisters while the
+    /// pipeline is locked. If anything fails, entities registered by the batch are
+    /// unregistered.
+    fn execute(self, api: &ApiState) -> Result<Vec<Response>, ApiError> {
+        let mut results: Vec<Response> = (0..self.operations_count)
+            .map(|_| Response::Ok {})
+            .collect();
+
+        // Registered entities are tracked as changes that revert them.
+        let mut registered = PipelineChanges::default();
+        for (index, registration) in self.registrations {
+            match register(api, registration, &mut registered) {
+                Ok(response) => results[index] = response,
+                Err(err) => {
+                    revert_registrations(
+                        &mut api.pipeline(),
+                        &api.output_scenes,
+                        &api.registrations,
+                        registered,
+                    );
+                    return Err(err);
+                }
+            }
+        }
+
+        let mut pipeline = api.pipeline();
+        let result = apply_changes(
+            &mut pipeline,
+            &api.output_scenes,
+            &api.timelines,
+            &api.registrations,
+            self.changes,
+            self.output_scenes,
+        );
+        if let Err(err) = result {
+            revert_registrations(
+                &mut pipeline,
+                &api.output_scenes,
+                &api.registrations,
+                registered,
+            );
+            return Err(err.into());
+        }
+        Ok(results)
+    }
+
+    fn unregister_renderer(&mut self, renderer_id: RendererId, registry_type: RegistryType) {
+        self.changes
+            .unregister_renderers
+            .push((renderer_id.into(), registry_type));
+    }
+}
+
+fn renderer_registration(renderer: ProjectRenderer) -> Result<Registration, ApiError> {
+    let (renderer_id, spec) = renderer.clone().into_spec();
+    Ok(Registration::Renderer(renderer_id, spec?, renderer))
+}
+
+fn register(
+    api: &ApiState,
+    registration: Registration,
+    registered: &mut PipelineChanges,
+) -> Result<Response, ApiError> {
+    match registration {
+        Registration::Input(input_id, options, input) => {
+            let init_info = Pipeline::register_input(&api.pipeline, input_id.clone(), options)?;
+            api.record_registration(|registrations, _| {
+                registrations.inputs.insert(input_id.clone(), input);
+            });
+            registered.unregister_inputs.push(input_id);
+            Ok(input_response(init_info))
+        }
+        Registration::Output(output_id, options, scene, output) => {
+            let port = Pipeline::register_output(&api.pipeline, output_id.clone(), options)?;
+            api.record_registration(|registrations, output_scenes| {
+                output_scenes.insert(output_id.clone(), scene);
+                registrations.outputs.insert(output_id.clone(), output);
+            });
+            registered.unregister_outputs.push(output_id);
+            Ok(output_response(port))
+        }
+        Registration::Renderer(renderer_id, spec, renderer) => {
+            let registry_type = renderer.registry_type();
+            Pipeline::register_renderer(&api.pipeline, renderer_id.clone(), spec)?;
+            api.record_registration(|registrations, _| {
+                record_renderer(registrations, renderer_id.clone(), renderer)
+            });
+            registered
+                .unregister_renderers
+                .push((renderer_id, registry_type));
+            Ok(Response::Ok {})
+        }
+    }
+}
+
+fn apply_changes(
+    pipeline: &mut Pipeline,
+    api_output_scenes: &OutputScenes,
+    timelines: &Timelines,
+    registrations: &Registrations,
+    changes: PipelineChanges,
+    output_scenes: Vec<(compositor_render::OutputId, OutputScene)>,
+) -> Result<(), ApplyChangesError> {
+    let unregistered = PipelineChanges {
+        unregister_inputs: changes.unregister_inputs.clone(),
+        unregister_outputs: changes.unregister_outputs.clone(),
+        unregister_renderers: changes.unregister_renderers.clone(),
+        ..Default::default()
+    };
+    pipeline.apply_changes(changes)?;
+    for (output_id, scene) in output_scenes {
+        api_output_scenes.update(&output_id, scene.video, scene.audio);
+    }
+    for output_id in &unregistered.unregister_outputs {
+        api_output_scenes.remove(output_id);
+        remove_timeline(pipeline.queue(), timelines, output_id);
+    }
+    remove_registrations(registrations, &unregistered);
+    Ok(())
+}
+
+/// Unregisters entities registered by a batch that failed. Entities that were
+/// already unregistered in the meantime are skipped.
+fn revert_registrations(
+    pipeline: &mut Pipeline,
+    api_output_scenes: &OutputScenes,
+    registrations: &Registrations,
+    registered: PipelineChanges,
+) {
+    for output_id in &registered.unregister_outputs {
+        if pipeline.unregister_output(output_id).is_ok() {
+            api_output_scenes.remove(output_id);
+        }
+    }
+    for input_id in &registered.unregister_inputs {
+        let _ = pipeline.unregister_input(input_id);
+    }
+    for (renderer_id, registry_type) in &registered.unregister_renderers {
+        let _ = pipeline.unregister_renderer(renderer_id, *registry_type);
+    }
+    remove_registrations(registrations, &registered);
+}
+
+fn remove_registrations(registrations: &Registrations, unregistered: &PipelineChanges) {
+    let mut registrations = registrations.lock();
+    for output_id in &unregistered.unregister_outputs {
+        registrations.outputs.remove(output_id);
+    }
+    for input_id in &unregistered.unregister_inputs {
+        registrations.inputs.remove(input_id);
+    }
+    for key in &unregistered.unregister_renderers {
+        registrations.renderers.remove(key);
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use axum::{extract::State, Json};
+use compositor_pipeline::{
+    audio_mixer::AudioChannels,
+    pipeline::{
+        encoder::{AudioEncoderOptions, AudioEncoderPreset, VideoEncoderOptions},
+        AudioCodec, InputInfo, InputType, OutputInfo, OutputType,
+    },
+    queue::QueueInputState,
+};
+use compositor_render::{Framerate, RegistryType, Resolution};
+use serde::Serialize;
+
+use crate::state::{ApiState, OutputScene};
+
+#[derive(Debug, Serialize)]
+pub(super) struct InputsResponse {
+    inputs: Vec<InputResponse>,
+}
+
+#[derive(Debug, Serialize)]
+struct InputResponse {
+    input_id: String,
+    #[serde(rename = "type")]
+    input_type: &'static str,
+    port: Option<u16>,
+    video: Option<InputVideoResponse>,
+    audio: Option<InputAudioResponse>,
+    clock_drift_ppm: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct InputVideoResponse {
+    decoder: Option<&'static str>,
+    resolution: Option<ResolutionResponse>,
+    state: Option<&'static str>,
+    eos_received: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct InputAudioResponse {
+    codec: Option<&'static str>,
+    state: Option<&'static str>,
+    eos_received: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct OutputsResponse {
+    outputs: Vec<OutputResponse>,
+}
+
+#[derive(Debug, Serialize)]
+struct OutputResponse {
+    output_id: String,
+    #[serde(rename = "type")]
+    output_type: &'static str,
+    port: Option<u16>,
+    video: Option<OutputVideoResponse>,
+    audio: Option<OutputAudioResponse>,
+    scene: OutputScene,
+}
+
+#[derive(Debug, Serialize)]
+struct OutputVideoResponse {
+    resolution: Option<ResolutionResponse>,
+    framerate: Option<String>,
+    encoder: Option<VideoEncoderResponse>,
+    eos_sent: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct OutputAudioResponse {
+    encoder: Option<AudioEncoderResponse>,
+    eos_sent: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum VideoEncoderResponse {
+    #[serde(rename = "ffmpeg_h264")]
+    FfmpegH264 {
+        preset: &'static str,
+        ffmpeg_options: HashMap<String, String>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AudioEncoderResponse {
+    Opus {
+        channels: &'static str,
+        preset: &'static str,
+        sample_rate: u32,
+    },
+    Aac {
+        channels: &'static str,
+        sample_rate: u32,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct ResolutionResponse {
+    width: usize,
+    height: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct RenderersResponse {
+    renderers: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct FontsResponse {
+    families: Vec<String>,
+}
+
+pub(super) async fn handle_inputs(State(api): State<ApiState>) -> Json<InputsResponse> {
+    let mut inputs: Vec<_> = api
+        .pipeline()
+        .inputs_info()
+        .into_iter()
+        .map(InputResponse::from)
+        .collect();
+    inputs.sort_by(|a, b| a.input_id.cmp(&b.input_id));
+    Json(InputsResponse { inputs })
+}
+
+pub(super) async fn handle_outputs(State(api): State<ApiState>) -> Json<OutputsResponse> {
+    let outputs_info = api.pipeline().outputs_info();
+    let mut outputs: Vec<_> = outputs_info
+        .into_iter()
+        .map(|info| {
+            let scene = api.output_scenes.get(&info.output_id);
+            OutputResponse::new(info, scene)
+        })
+        .collect();
+    outputs.sort_by(|a, b| a.output_id.cmp(&b.output_id));
+    Json(OutputsResponse { outputs })
+}
+
+pub(super) async fn handle_shaders(State(api): State<ApiState>) -> Json<RenderersResponse> {
+    Json(RenderersResponse::new(&api, RegistryType::Shader))
+}
+
+pub(super) async fn handle_compute_shaders(State(api): State<ApiState>) -> Json<RenderersResponse> {
+    Json(RenderersResponse::new(&api, RegistryType::ComputeShader))
+}
+
+pub(super) async fn handle_web_renderers(State(api): State<ApiState>) -> Json<RenderersResponse> {
+    Json(RenderersResponse::new(&api, RegistryType::WebRenderer))
+}
+
+pub(super) async fn handle_images(State(api): State<ApiState>) -> Json<RenderersResponse> {
+    Json(RenderersResponse::new(&api, RegistryType::Image))
+}
+
+pub(super) async fn handle_lotties(State(api): State<ApiState>) -> Json<RenderersResponse> {
+    Json(RenderersResponse::new(&api, RegistryType::Lottie))
+}
+
+pub(super) async fn handle_fonts(State(api): State<ApiState>) -> Json<FontsResponse> {
+    Json(FontsResponse {
+        families: api.pipeline().font_families(),
+    })
+}
+
+impl RenderersResponse {
+    fn new(api: &ApiState, registry_type: RegistryType) -> Self {
+        let mut renderers: Vec<String> = api
+            .pipeline()
+            .registered_renderers(registry_type)
+            .into_iter()
+            .map(|id| id.to_string())
+            .collect();
+        renderers.sort();
+        Self { renderers }
+    }
+}
+
+impl From<InputInfo> for InputResponse {
+    fn from(info: InputInfo) -> Self {
+        let input_type = match info.input_type {
+            InputType::Rtp => "rtp_stream",
+            InputType::Mp4 => "mp4",
+            InputType::Whip => "whip",
+            InputType::DeckLink => "decklink",
+            InputType::RawData => "raw_data",
+        };
+        Self {
+            input_id: info.input_id.to_string(),
+            input_type,
+            port: info.port.map(|port| port.0),
+            video: info.video.map(|video| InputVideoResponse {
+                decoder: video.decoder.map(|decoder| decoder.to_str()),
+                resolution: video.resolution.map(ResolutionResponse::from),
+                state: video.state.map(input_state_name),
+                eos_received: video.eos_received,
+            }),
+            audio: info.audio.map(|audio| InputAudioResponse {
+                codec: audio.codec.map(|codec| match codec {
+                    AudioCodec::Aac => "aac",
+                    AudioCodec::Opus => "opus",
+                }),
+                state: audio.state.map(input_state_name),
+                eos_received: audio.eos_received,
+            }),
+            clock_drift_ppm: info.clock_drift_ppm,
+        }
+    }
+}
+
+impl OutputResponse {
+    fn new(info: OutputInfo, scene: OutputScene) -> Self {
+        let output_type = match info.output_type {
+            OutputType::Rtp => "rtp_stream",
+            OutputType::Mp4 => "mp4",
+            OutputType::Whip => "whip",
+            OutputType::EncodedData => "encoded_data",
+            OutputType::RawData => "raw_data",
+        };
+        Self {
+            output_id: info.output_id.to_string(),
+            output_type,
+            port: info.port.map(|port| port.0),
+            video: info.video.map(|video| OutputVideoResponse {
+                resolution: video.resolution.map(ResolutionResponse::from),
+                framerate: video.framerate.map(framerate_string),
+                encoder: video.encoder.map(VideoEncoderResponse::from),
+                eos_sent: video.eos_sent,
+            }),
+            audio: info.audio.map(|audio| OutputAudioResponse {
+                encoder: audio.encoder.map(AudioEncoderResponse::from),
+                eos_sent: audio.eos_sent,
+            }),
+            scene,
+        }
+    }
+}
+
+impl From<VideoEncoderOptions> for VideoEncoderResponse {
+    fn from(options: VideoEncoderOptions) -> Self {
+        match options {
+            VideoEncoderOptions::H264(options) => VideoEncoderResponse::FfmpegH264 {
+                preset: options.preset.to_str(),
+                ffmpeg_options: options.raw_options.into_iter().collect(),
+            },
+        }
+    }
+}
+
+impl From<AudioEncoderOptions> for AudioEncoderResponse {
+    fn from(options: AudioEncoderOptions) -> Self {
+        match options {
+            AudioEncoderOptions::Opus(options) => AudioEncoderResponse::Opus {
+                channels: audio_channels_name(options.channels),
+                preset: match options.preset {
+                    AudioEncoderPreset::Quality => "quality",
+                    AudioEncoderPreset::Voip => "voip",
+                    AudioEncoderPreset::LowestLatency => "lowest_latency",
+                },
+                sample_rate: options.sample_rate,
+            },
+            AudioEncoderOptions::Aac(options) => AudioEncoderResponse::Aac {
+                channels: audio_channels_name(options.channels),
+                sample_rate: options.sample_rate,
+            },
+        }
+    }
+}
+
+impl From<Resolution> for ResolutionResponse {
+    fn from(resolution: Resolution) -> Self {
+        Self {
+            width: resolution.width,
+            height: resolution.height,
+        }
+    }
+}
+
+fn input_state_name(state: QueueInputState) -> &'static str {
+    match state {
+        QueueInputState::WaitingForStart => "waiting_for_start",
+        QueueInputState::Buffering => "buffering",
+        QueueInputState::Ready => "ready",
+        QueueInputState::Done => "done",
+    }
+}
+
+fn audio_channels_name(channels: AudioChannels) -> &'static str {
+    match channels {
+        AudioChannels::Mono => "mono",
+        AudioChannels::Stereo => "stereo",
+    }
+}
+
+fn framerate_string(framerate: Framerate) -> String {
+    format!("{}/{}", framerate.num, framerate.den)
+}
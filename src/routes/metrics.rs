@@ -0,0 +1,292 @@
+use std::{collections::HashSet, fmt::Write};
+
+use axum::{extract::State, http::header, response::IntoResponse};
+use compositor_pipeline::{
+    metrics::{Counter, Summary},
+    pipeline::input::Input,
+};
+use compositor_render::{InputId, OutputId};
+
+use crate::state::ApiState;
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Renders metrics collected by the pipeline in the Prometheus text format.
+pub(super) async fn handle_metrics(State(api): State<ApiState>) -> impl IntoResponse {
+    let (metrics, mut buffered, rtp_inputs) = {
+        let pipeline = api.pipeline();
+        let buffered: Vec<_> = pipeline
+            .inputs()
+            .filter_map(|(input_id, _)| {
+                let stats = pipeline.queue().input_stats(input_id)?;
+                Some((input_id.to_string(), stats))
+            })
+            .collect();
+        let rtp_inputs: HashSet<_> = pipeline
+            .inputs()
+            .filter(|(_, input)| matches!(input.input, Input::Rtp(_)))
+            .map(|(input_id, _)| input_id.clone())
+            .collect();
+        (pipeline.metrics().clone(), buffered, rtp_inputs)
+    };
+    buffered.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut inputs = metrics.inputs();
+    inputs.sort_by(|(a, _), (b, _)| a.0.cmp(&b.0));
+    let mut outputs = metrics.outputs();
+    outputs.sort_by(|(a, _), (b, _)| a.0.cmp(&b.0));
+
+    let mut writer = MetricsWriter::default();
+
+    writer.counter(
+        "smelter_input_received_bytes_total",
+        "Number of bytes received by the input decoders.",
+        inputs
+            .iter()
+            .map(|(id, m)| (input_label(id), &m.received_bytes)),
+    );
+    writer.counter(
+        "smelter_input_received_packets_total",
+        "Number of encoded packets received by the input decoders.",
+        inputs
+            .iter()
+            .map(|(id, m)| (input_label(id), &m.received_packets)),
+    );
+    writer.counter(
+        "smelter_input_decoded_frames_total",
+        "Number of video frames and audio sample batches produced by the input decoders.",
+        inputs
+            .iter()
+            .map(|(id, m)| (input_label(id), &m.decoded_frames)),
+    );
+    writer.counter(
+        "smelter_input_decode_errors_total",
+        "Number of packets that input decoders failed to decode.",
+        inputs
+            .iter()
+            .map(|(id, m)| (input_label(id), &m.decode_errors)),
+    );
+
+    writer.counter(
+        "smelter_input_late_frames_total",
+        "Number of video frames that reached the queue after their timestamp was already rendered.",
+        inputs
+            .iter()
+            .map(|(id, m)| (input_label(id), &m.late_frames)),
+    );
+
+    let rtp_input_metrics: Vec<_> = inputs
+        .iter()
+        .filter(|(id, _)| rtp_inputs.contains(id))
+        .collect();
+    writer.gauge(
+        "smelter_input_rtp_packets_lost",
+        "Cumulative number of lost RTP packets.",
+        rtp_input_metrics
+            .iter()
+            .map(|(id, m)| (input_label(id), m.packets_lost.get() as f64)),
+    );
+    writer.gauge(
+        "smelter_input_rtp_jitter_seconds",
+        "Interarrival jitter of the RTP packets.",
+        rtp_input_metrics
+            .iter()
+            .map(|(id, m)| (input_label(id), m.jitter_micros.get() as f64 / 1_000_000.0)),
+    );
+
+    writer.header(
+        "smelter_input_queue_buffered_seconds",
+        "Duration of the media waiting in the queue for the input.",
+        "gauge",
+    );
+    for (input_id, stats) in buffered.iter() {
+        let tracks = [
+            ("video", stats.video_buffered),
+            ("audio", stats.audio_buffered),
+        ];
+        for (track, value) in tracks {
+            if let Some(value) = value {
+                writer.sample(
+                    "smelter_input_queue_buffered_seconds",
+                    &format!(
+                        "input_id=\"{}\",track=\"{track}\"",
+                        escape_label_value(input_id)
+                    ),
+                    value.as_secs_f64(),
+                );
+            }
+        }
+    }
+
+    writer.counter(
+        "smelter_output_encoded_bytes_total",
+        "Number of bytes produced by the output encoders.",
+        outputs
+            .iter()
+            .map(|(id, m)| (output_label(id), &m.encoded_bytes)),
+    );
+    writer.counter(
+        "smelter_output_encoded_packets_total",
+        "Number of encoded packets produced by the output encoders.",
+        outputs
+            .iter()
+            .map(|(id, m)| (output_label(id), &m.encoded_packets)),
+    );
+    writer.counter(
+        "smelter_output_encoded_frames_total",
+        "Number of video frames passed to the output encoders.",
+        outputs
+            .iter()
+            .map(|(id, m)| (output_label(id), &m.encoded_frames)),
+    );
+    writer.counter(
+        "smelter_output_dropped_frames_total",
+        "Number of video frames that were not delivered to the output encoder.",
+        outputs
+            .iter()
+            .map(|(id, m)| (output_label(id), &m.dropped_frames)),
+    );
+    writer.summary(
+        "smelter_output_encoder_latency_seconds",
+        "Time spent on encoding a single video frame.",
+        outputs
+            .iter()
+            .map(|(id, m)| (output_label(id), &m.encoder_latency)),
+    );
+
+    writer.summary(
+        "smelter_renderer_frame_time_seconds",
+        "Time spent on rendering all outputs for a single PTS.",
+        [(String::new(), &metrics.render_time)],
+    );
+    writer.summary(
+        "smelter_queue_lateness_seconds",
+        "Delay between the expected and the actual time of pushing frames from the queue.",
+        [(String::new(), &metrics.queue_lateness)],
+    );
+
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], writer.0)
+}
+
+#[derive(Default)]
+struct MetricsWriter(String);
+
+impl MetricsWriter {
+    fn header(&mut self, name: &str, help: &str, metric_type: &str) {
+        let _ = writeln!(self.0, "# HELP {name} {help}");
+        let _ = writeln!(self.0, "# TYPE {name} {metric_type}");
+    }
+
+    fn sample(&mut self, name: &str, labels: &str, value: impl std::fmt::Display) {
+        match labels.is_empty() {
+            true => {
+                let _ = writeln!(self.0, "{name} {value}");
+            }
+            false => {
+                let _ = writeln!(self.0, "{name}{{{labels}}} {value}");
+            }
+        }
+    }
+
+    fn counter<'a>(
+        &mut self,
+        name: &str,
+        help: &str,
+        values: impl IntoIterator<Item = (String, &'a Counter)>,
+    ) {
+        self.header(name, help, "counter");
+        for (labels, counter) in values {
+            self.sample(name, &labels, counter.get());
+        }
+    }
+
+    fn gauge(&mut self, name: &str, help: &str, values: impl IntoIterator<Item = (String, f64)>) {
+        self.header(name, help, "gauge");
+        for (labels, value) in values {
+            self.sample(name, &labels, value);
+        }
+    }
+
+    fn summary<'a>(
+        &mut self,
+        name: &str,
+        help: &str,
+        values: impl IntoIterator<Item = (String, &'a Summary)>,
+    ) {
+        self.header(name, help, "summary");
+        for (labels, summary) in values {
+            self.sample(&format!("{name}_sum"), &labels, summary.sum().as_secs_f64());
+            self.sample(&format!("{name}_count"), &labels, summary.count());
+        }
+    }
+}
+
+fn input_label(input_id: &InputId) -> String {
+    format!("input_id=\"{}\"", escape_label_value(&input_id.0))
+}
+
+fn output_label(output_id: &OutputId) -> String {
+    format!("output_id=\"{}\"", escape_label_value(&output_id.0))
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_escape_label_value() {
+        assert_eq!(escape_label_value("input_1"), "input_1");
+        assert_eq!(escape_label_value(r#"say "hi""#), r#"say \"hi\""#);
+        assert_eq!(escape_label_value(r"C:\videos"), r"C:\\videos");
+        assert_eq!(escape_label_value("line1\nline2"), r"line1\nline2");
+        // Backslashes are escaped first, so escape sequences are not doubled.
+        assert_eq!(escape_label_value("\\\"\n"), r#"\\\"\n"#);
+    }
+
+    #[test]
+    fn test_metrics_writer_format() {
+        let counter = Counter::default();
+        let summary = Summary::default();
+        let mut writer = MetricsWriter::default();
+
+        writer.counter(
+            "test_total",
+            "Test counter.",
+            [(input_label(&InputId("input_\"1\"".into())), &counter)],
+        );
+        writer.summary(
+            "test_seconds",
+            "Test summary.",
+            [
+                (output_label(&OutputId("output_1".into())), &summary),
+                (String::new(), &summary),
+            ],
+        );
+        writer.gauge("test_gauge", "Test gauge.", [(String::new(), 0.5)]);
+
+        assert_eq!(
+            writer.0,
+            concat!(
+                "# HELP test_total Test counter.\n",
+                "# TYPE test_total counter\n",
+                "test_total{input_id=\"input_\\\"1\\\"\"} 0\n",
+                "# HELP test_seconds Test summary.\n",
+                "# TYPE test_seconds summary\n",
+                "test_seconds_sum{output_id=\"output_1\"} 0\n",
+                "test_seconds_count{output_id=\"output_1\"} 0\n",
+                "test_seconds_sum 0\n",
+                "test_seconds_count 0\n",
+                "# HELP test_gauge Test gauge.\n",
+                "# TYPE test_gauge gauge\n",
+                "test_gauge 0.5\n",
+            )
+        );
+    }
+}
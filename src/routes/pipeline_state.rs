@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::Bytes;
+use compositor_api::{
+    error::ApiError,
+    types::{Audio, OutputId, Video},
+};
+use glyphon::fontdb::Source;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::state::{ApiState, Pipeline};
+
+use super::{
+    batch::{run_batch, BatchOperation, BatchRequest, BatchResponse},
+    register_request::{ProjectInput, ProjectRenderer, RegisterOutput},
+    Json,
+};
+
+/// State of the instance returned by `GET /api/state`. Importing it recreates
+/// all registered entities together with the latest scenes of the outputs.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineState {
+    /// Registered fonts encoded in base64.
+    #[serde(default)]
+    pub fonts: Vec<String>,
+    #[serde(default)]
+    pub renderers: Vec<ProjectRenderer>,
+    #[serde(default)]
+    pub inputs: Vec<ProjectInput>,
+    #[serde(default)]
+    pub outputs: Vec<OutputState>,
+    /// Whether the pipeline was started.
+    #[serde(default)]
+    pub started: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct OutputState {
+    pub output_id: OutputId,
+    /// Request used to register the output.
+    pub output: RegisterOutput,
+    /// Latest scene of the output.
+    pub video: Option<Video>,
+    /// Latest audio mix of the output.
+    pub audio: Option<Audio>,
+}
+
+pub(super) async fn handle_export(
+    State(api): State<ApiState>,
+) -> Result<axum::Json<PipelineState>, ApiError> {
+    // Pipeline is locked, so scheduled events can't change the state in the meantime.
+    let pipeline = api.pipeline();
+    let registrations = api.registrations.lock();
+
+    let fonts = registrations
+        .fonts
+        .iter()
+        .map(|font| STANDARD.encode(font))
+        .collect();
+
+    let mut renderers: Vec<_> = registrations.renderers.iter().collect();
+    renderers.sort_by(|((a, _), _), ((b, _), _)| a.0.cmp(&b.0));
+    let renderers = renderers
+        .into_iter()
+        .map(|(_, renderer)| renderer.clone())
+        .collect();
+
+    let mut inputs: Vec<_> = registrations.inputs.iter().collect();
+    inputs.sort_by(|(a, _), (b, _)| a.0.cmp(&b.0));
+    let inputs = inputs
+        .into_iter()
+        .map(|(input_id, input)| ProjectInput {
+            input_id: input_id.clone().into(),
+            input: input.clone(),
+        })
+        .collect();
+
+    let mut outputs: Vec<_> = registrations.outputs.iter().collect();
+    outputs.sort_by(|(a, _), (b, _)| a.0.cmp(&b.0));
+    let outputs = outputs
+        .into_iter()
+        .map(|(output_id, output)| {
+            let scene = api.output_scenes.get(output_id);
+            OutputState {
+                output_id: output_id.clone().into(),
+                output: output.clone(),
+                video: scene.video,
+                audio: scene.audio,
+            }
+        })
+        .collect();
+
+    Ok(axum::Json(PipelineState {
+        fonts,
+        renderers,
+        inputs,
+        outputs,
+        started: pipeline.is_started(),
+    }))
+}
+
+/// Registers fonts first, then registers renderers, inputs and outputs and applies
+/// the latest scenes in a single batch. If the batch fails, nothing from it is
+/// registered, but fonts can't be unregistered, so they are kept.
+pub(super) async fn handle_import(
+    State(api): State<ApiState>,
+    Json(state): Json<PipelineState>,
+) -> Result<axum::Json<BatchResponse>, ApiError> {
+    let fonts = state
+        .fonts
+        .iter()
+        .map(|font| STANDARD.decode(font).map(Bytes::from))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| ApiError::malformed_request(&format!("Invalid font encoding: {err}")))?;
+
+    let mut operations: Vec<BatchOperation> = state
+        .renderers
+        .into_iter()
+        .map(BatchOperation::from)
+        .collect();
+    operations.extend(
+        state
+            .inputs
+            .into_iter()
+            .map(
+                |ProjectInput { input_id, input }| BatchOperation::RegisterInput {
+                    input_id,
+                    input,
+                },
+            ),
+    );
+    let mut updates = Vec::new();
+    for OutputState {
+        output_id,
+        output,
+        video,
+        audio,
+    } in state.outputs
+    {
+        operations.push(BatchOperation::RegisterOutput {
+            output_id: output_id.clone(),
+            output,
+        });
+        if video.is_some() || audio.is_some() {
+            updates.push(BatchOperation::UpdateOutput {
+                output_id,
+                video,
+                audio,
+            });
+        }
+    }
+    operations.extend(updates);
+    let start = state.started;
+
+    tokio::task::spawn_blocking(move || {
+        for font in fonts {
+            let pipeline = api.pipeline();
+            Pipeline::register_font(&pipeline, Source::Binary(Arc::new(font.clone())));
+            api.registrations.lock().fonts.push(font);
+        }
+        let request = BatchRequest {
+            operations,
+            schedule_time_ms: None,
+        };
+        let response = run_batch(&api, request)?;
+        if start && !api.pipeline().is_started() {
+            Pipeline::start(&api.pipeline);
+        }
+        Ok(axum::Json(response))
+    })
+    .await
+    // `unwrap()` panics only when the task panicked or `response.abort()` was called
+    .unwrap()
+}
+
+impl From<ProjectRenderer> for BatchOperation {
+    fn from(renderer: ProjectRenderer) -> Self {
+        match renderer {
+            ProjectRenderer::Image { image_id, image } => {
+                BatchOperation::RegisterImage { image_id, image }
+            }
+            ProjectRenderer::Lottie { lottie_id, lottie } => {
+                BatchOperation::RegisterLottie { lottie_id, lottie }
+            }
+            ProjectRenderer::Shader { shader_id, shader } => {
+                BatchOperation::RegisterShader { shader_id, shader }
+            }
+            ProjectRenderer::ComputeShader { shader_id, shader } => {
+                BatchOperation::RegisterComputeShader { shader_id, shader }
+            }
+            ProjectRenderer::WebRenderer {
+                instance_id,
+                web_renderer,
+            } => BatchOperation::RegisterWebRenderer {
+                instance_id,
+                web_renderer,
+            },
+        }
+    }
+}
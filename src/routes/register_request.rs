@@ -1,20 +1,31 @@
 use std::sync::Arc;
 
 use axum::extract::{Path, State};
-use compositor_pipeline::pipeline::{input::InputInitInfo, Port};
+use compositor_pipeline::{
+    pipeline::{
+        input::InputInitInfo, output::OutputOptions, Port, RegisterInputOptions,
+        RegisterOutputOptions,
+    },
+    queue::ScheduledEventDescription,
+};
+use compositor_render::{RegistryType, RendererSpec};
 use glyphon::fontdb::Source;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    routes::{Json, Multipart},
-    state::{Pipeline, Response},
+    routes::{
+        schedule::{schedule_registration, ScheduledRequest},
+        Json, Multipart,
+    },
+    state::{OutputScene, Pipeline, RegisteredRequests, Response},
 };
 use compositor_api::{
     error::ApiError,
     types::{
-        DeckLink, ImageSpec, InputId, Mp4Input, Mp4Output, OutputId, RendererId, RtpInput,
-        RtpOutput, ShaderSpec, WebRendererSpec, WhipInput, WhipOutput,
+        ComputeShaderSpec, DeckLink, ImageSpec, InputId, LottieSpec, Mp4Input, Mp4Output, OutputId,
+        RendererId, RtpInput, RtpOutput, ShaderSpec, TypeError, WebRendererSpec, WhipInput,
+        WhipOutput,
     },
 };
 
@@ -38,41 +49,179 @@ pub enum RegisterOutput {
     Whip(WhipOutput),
 }
 
+impl RegisterOutput {
+    pub(crate) fn initial_scene(&self) -> OutputScene {
+        let (video, audio) = match self {
+            RegisterOutput::RtpStream(rtp) => (
+                rtp.video.as_ref().map(|video| video.initial.clone()),
+                rtp.audio.as_ref().map(|audio| audio.initial.clone()),
+            ),
+            RegisterOutput::Mp4(mp4) => (
+                mp4.video.as_ref().map(|video| video.initial.clone()),
+                mp4.audio.as_ref().map(|audio| audio.initial.clone()),
+            ),
+            RegisterOutput::Whip(whip) => (
+                whip.video.as_ref().map(|video| video.initial.clone()),
+                whip.audio.as_ref().map(|audio| audio.initial.clone()),
+            ),
+        };
+        OutputScene { video, audio }
+    }
+}
+
+impl TryFrom<RegisterInput> for RegisterInputOptions {
+    type Error = TypeError;
+
+    fn try_from(value: RegisterInput) -> Result<Self, Self::Error> {
+        match value {
+            RegisterInput::RtpStream(rtp) => rtp.try_into(),
+            RegisterInput::Mp4(mp4) => mp4.try_into(),
+            RegisterInput::Whip(whip) => whip.try_into(),
+            RegisterInput::DeckLink(decklink) => decklink.try_into(),
+        }
+    }
+}
+
+impl TryFrom<RegisterOutput> for RegisterOutputOptions<OutputOptions> {
+    type Error = TypeError;
+
+    fn try_from(value: RegisterOutput) -> Result<Self, Self::Error> {
+        match value {
+            RegisterOutput::RtpStream(rtp) => rtp.try_into(),
+            RegisterOutput::Mp4(mp4) => mp4.try_into(),
+            RegisterOutput::Whip(whip) => whip.try_into(),
+        }
+    }
+}
+
+/// Renderer in the form used by the render project, the config file and the state
+/// export.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ProjectRenderer {
+    Image {
+        image_id: RendererId,
+        image: ImageSpec,
+    },
+    Lottie {
+        lottie_id: RendererId,
+        lottie: LottieSpec,
+    },
+    Shader {
+        shader_id: RendererId,
+        shader: ShaderSpec,
+    },
+    ComputeShader {
+        shader_id: RendererId,
+        shader: ComputeShaderSpec,
+    },
+    WebRenderer {
+        instance_id: RendererId,
+        web_renderer: WebRendererSpec,
+    },
+}
+
+impl ProjectRenderer {
+    pub(crate) fn into_spec(
+        self,
+    ) -> (
+        compositor_render::RendererId,
+        Result<RendererSpec, TypeError>,
+    ) {
+        let (renderer_id, spec): (RendererId, Result<RendererSpec, TypeError>) = match self {
+            ProjectRenderer::Image { image_id, image } => (image_id, image.try_into()),
+            ProjectRenderer::Lottie { lottie_id, lottie } => (lottie_id, lottie.try_into()),
+            ProjectRenderer::Shader { shader_id, shader } => (shader_id, shader.try_into()),
+            ProjectRenderer::ComputeShader { shader_id, shader } => (shader_id, shader.try_into()),
+            ProjectRenderer::WebRenderer {
+                instance_id,
+                web_renderer,
+            } => (instance_id, web_renderer.try_into()),
+        };
+        (renderer_id.into(), spec)
+    }
+
+    pub(crate) fn registry_type(&self) -> RegistryType {
+        match self {
+            ProjectRenderer::Image { .. } => RegistryType::Image,
+            ProjectRenderer::Lottie { .. } => RegistryType::Lottie,
+            ProjectRenderer::Shader { .. } => RegistryType::Shader,
+            ProjectRenderer::ComputeShader { .. } => RegistryType::ComputeShader,
+            ProjectRenderer::WebRenderer { .. } => RegistryType::WebRenderer,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectInput {
+    pub input_id: InputId,
+    pub input: RegisterInput,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectOutput {
+    pub output_id: OutputId,
+    pub output: RegisterOutput,
+}
+
+pub(super) fn input_response(init_info: InputInitInfo) -> Response {
+    match init_info {
+        InputInitInfo::Rtp { port } => Response::RegisteredPort {
+            port: port.map(|p| p.0),
+        },
+        InputInitInfo::Mp4 {
+            video_duration,
+            audio_duration,
+        } => Response::RegisteredMp4 {
+            video_duration_ms: video_duration.map(|v| v.as_millis() as u64),
+            audio_duration_ms: audio_duration.map(|a| a.as_millis() as u64),
+        },
+        InputInitInfo::Whip { bearer_token } => Response::BearerToken { bearer_token },
+        InputInitInfo::Other => Response::Ok {},
+    }
+}
+
+pub(super) fn output_response(port: Option<Port>) -> Response {
+    match port {
+        Some(Port(port)) => Response::RegisteredPort { port: Some(port) },
+        None => Response::Ok {},
+    }
+}
+
 pub(super) async fn handle_input(
     State(api): State<ApiState>,
     Path(input_id): Path<InputId>,
-    Json(request): Json<RegisterInput>,
+    Json(request): Json<ScheduledRequest<RegisterInput>>,
 ) -> Result<Response, ApiError> {
-    let api = api.clone();
+    let input_id: compositor_render::InputId = input_id.into();
+    let input = request.request;
+    let options: RegisterInputOptions = input.clone().try_into()?;
+
+    let description = ScheduledEventDescription {
+        kind: "register_input",
+        entity_id: Some(input_id.to_string()),
+    };
+    let register = move |api: &ApiState| {
+        Pipeline::register_input(&api.pipeline, input_id.clone(), options).map(|init_info| {
+            api.record_registration(|registrations, _| {
+                registrations.inputs.insert(input_id, input);
+            });
+            init_info
+        })
+    };
+
     tokio::task::spawn_blocking(move || {
-        let response = match request {
-            RegisterInput::RtpStream(rtp) => {
-                Pipeline::register_input(&api.pipeline, input_id.into(), rtp.try_into()?)?
-            }
-            RegisterInput::Mp4(mp4) => {
-                Pipeline::register_input(&api.pipeline, input_id.into(), mp4.try_into()?)?
-            }
-            RegisterInput::DeckLink(decklink) => {
-                Pipeline::register_input(&api.pipeline, input_id.into(), decklink.try_into()?)?
-            }
-            RegisterInput::Whip(whip) => {
-                Pipeline::register_input(&api.pipeline, input_id.into(), whip.try_into()?)?
-            }
+        let Some(schedule_time_ms) = request.schedule_time_ms else {
+            return Ok(input_response(register(&api)?));
         };
-        match response {
-            InputInitInfo::Rtp { port } => Ok(Response::RegisteredPort {
-                port: port.map(|p| p.0),
-            }),
-            InputInitInfo::Mp4 {
-                video_duration,
-                audio_duration,
-            } => Ok(Response::RegisteredMp4 {
-                video_duration_ms: video_duration.map(|v| v.as_millis() as u64),
-                audio_duration_ms: audio_duration.map(|a| a.as_millis() as u64),
-            }),
-            InputInitInfo::Whip { bearer_token } => Ok(Response::BearerToken { bearer_token }),
-            InputInitInfo::Other => Ok(Response::Ok {}),
-        }
+        Ok(schedule_registration(
+            &api,
+            schedule_time_ms,
+            description,
+            move |api| register(api).map(|_| ()),
+        ))
     })
     .await
     // `unwrap()` panics only when the task panicked or `response.abort()` was called
@@ -82,25 +231,37 @@ pub(super) async fn handle_input(
 pub(super) async fn handle_output(
     State(api): State<ApiState>,
     Path(output_id): Path<OutputId>,
-    Json(request): Json<RegisterOutput>,
+    Json(request): Json<ScheduledRequest<RegisterOutput>>,
 ) -> Result<Response, ApiError> {
-    let api = api.clone();
+    let output_id: compositor_render::OutputId = output_id.into();
+    let output = request.request;
+    let scene = output.initial_scene();
+    let options: RegisterOutputOptions<OutputOptions> = output.clone().try_into()?;
+
+    let description = ScheduledEventDescription {
+        kind: "register_output",
+        entity_id: Some(output_id.to_string()),
+    };
+    let register = move |api: &ApiState| {
+        Pipeline::register_output(&api.pipeline, output_id.clone(), options).map(|port| {
+            api.record_registration(|registrations, output_scenes| {
+                output_scenes.insert(output_id.clone(), scene);
+                registrations.outputs.insert(output_id, output);
+            });
+            port
+        })
+    };
+
     tokio::task::spawn_blocking(move || {
-        let response = match request {
-            RegisterOutput::RtpStream(rtp) => {
-                Pipeline::register_output(&api.pipeline, output_id.into(), rtp.try_into()?)?
-            }
-            RegisterOutput::Mp4(mp4) => {
-                Pipeline::register_output(&api.pipeline, output_id.into(), mp4.try_into()?)?
-            }
-            RegisterOutput::Whip(whip) => {
-                Pipeline::register_output(&api.pipeline, output_id.into(), whip.try_into()?)?
-            }
+        let Some(schedule_time_ms) = request.schedule_time_ms else {
+            return Ok(output_response(register(&api)?));
         };
-        match response {
-            Some(Port(port)) => Ok(Response::RegisteredPort { port: Some(port) }),
-            None => Ok(Response::Ok {}),
-        }
+        Ok(schedule_registration(
+            &api,
+            schedule_time_ms,
+            description,
+            move |api| register(api).map(|_| ()),
+        ))
     })
     .await
     .unwrap()
@@ -109,40 +270,116 @@ pub(super) async fn handle_output(
 pub(super) async fn handle_shader(
     State(api): State<ApiState>,
     Path(shader_id): Path<RendererId>,
-    Json(request): Json<ShaderSpec>,
+    Json(request): Json<ScheduledRequest<ShaderSpec>>,
 ) -> Result<Response, ApiError> {
-    let api = api.clone();
-    tokio::task::spawn_blocking(move || {
-        Pipeline::register_renderer(&api.pipeline, shader_id.into(), request.try_into()?)?;
-        Ok(Response::Ok {})
-    })
+    register_renderer(
+        api,
+        shader_id,
+        "register_shader",
+        request,
+        |shader_id, shader| ProjectRenderer::Shader { shader_id, shader },
+    )
+    .await
+}
+
+pub(super) async fn handle_compute_shader(
+    State(api): State<ApiState>,
+    Path(shader_id): Path<RendererId>,
+    Json(request): Json<ScheduledRequest<ComputeShaderSpec>>,
+) -> Result<Response, ApiError> {
+    register_renderer(
+        api,
+        shader_id,
+        "register_compute_shader",
+        request,
+        |shader_id, shader| ProjectRenderer::ComputeShader { shader_id, shader },
+    )
     .await
-    .unwrap()
 }
 
 pub(super) async fn handle_web_renderer(
     State(api): State<ApiState>,
     Path(instance_id): Path<RendererId>,
-    Json(request): Json<WebRendererSpec>,
+    Json(request): Json<ScheduledRequest<WebRendererSpec>>,
 ) -> Result<Response, ApiError> {
-    let api = api.clone();
-    tokio::task::spawn_blocking(move || {
-        Pipeline::register_renderer(&api.pipeline, instance_id.into(), request.try_into()?)?;
-        Ok(Response::Ok {})
-    })
+    register_renderer(
+        api,
+        instance_id,
+        "register_web_renderer",
+        request,
+        |instance_id, web_renderer| ProjectRenderer::WebRenderer {
+            instance_id,
+            web_renderer,
+        },
+    )
     .await
-    .unwrap()
 }
 
 pub(super) async fn handle_image(
     State(api): State<ApiState>,
     Path(image_id): Path<RendererId>,
-    Json(request): Json<ImageSpec>,
+    Json(request): Json<ScheduledRequest<ImageSpec>>,
+) -> Result<Response, ApiError> {
+    register_renderer(
+        api,
+        image_id,
+        "register_image",
+        request,
+        |image_id, image| ProjectRenderer::Image { image_id, image },
+    )
+    .await
+}
+
+pub(super) async fn handle_lottie(
+    State(api): State<ApiState>,
+    Path(lottie_id): Path<RendererId>,
+    Json(request): Json<ScheduledRequest<LottieSpec>>,
 ) -> Result<Response, ApiError> {
-    let api = api.clone();
+    register_renderer(
+        api,
+        lottie_id,
+        "register_lottie",
+        request,
+        |lottie_id, lottie| ProjectRenderer::Lottie { lottie_id, lottie },
+    )
+    .await
+}
+
+/// `into_request` recreates the request in the form used by the state export.
+async fn register_renderer<T: TryInto<RendererSpec, Error = TypeError> + Clone>(
+    api: ApiState,
+    renderer_id: RendererId,
+    kind: &'static str,
+    request: ScheduledRequest<T>,
+    into_request: impl FnOnce(RendererId, T) -> ProjectRenderer,
+) -> Result<Response, ApiError> {
+    let spec: RendererSpec = request.request.clone().try_into()?;
+    let renderer = into_request(renderer_id.clone(), request.request);
+    let renderer_id: compositor_render::RendererId = renderer_id.into();
+
+    let description = ScheduledEventDescription {
+        kind,
+        entity_id: Some(renderer_id.to_string()),
+    };
+    let register = move |api: &ApiState| {
+        Pipeline::register_renderer(&api.pipeline, renderer_id.clone(), spec).map(|()| {
+            api.record_registration(|registrations, _| {
+                record_renderer(registrations, renderer_id, renderer)
+            });
+        })
+    };
+
     tokio::task::spawn_blocking(move || {
-        Pipeline::register_renderer(&api.pipeline, image_id.into(), request.try_into()?)?;
-        Ok(Response::Ok {})
+        let Some(schedule_time_ms) = request.schedule_time_ms else {
+            register(&api)?;
+            return Ok(Response::Ok {});
+        };
+        Ok(schedule_registration(
+            &api,
+            schedule_time_ms,
+            description,
+            register,
+        ))
     })
     .await
     .unwrap()
@@ -165,12 +402,23 @@ pub(super) async fn handle_font(
         .await
         .map_err(|err| ApiError::malformed_request(&err))?;
 
-    let binary_font_source = Source::Binary(Arc::new(bytes));
+    let binary_font_source = Source::Binary(Arc::new(bytes.clone()));
 
     tokio::task::spawn_blocking(move || {
-        Pipeline::register_font(&api.pipeline.lock().unwrap(), binary_font_source);
+        let pipeline = api.pipeline();
+        Pipeline::register_font(&pipeline, binary_font_source);
+        api.registrations.lock().fonts.push(bytes);
         Ok(Response::Ok {})
     })
     .await
     .unwrap()
 }
+
+pub(super) fn record_renderer(
+    registrations: &mut RegisteredRequests,
+    renderer_id: compositor_render::RendererId,
+    renderer: ProjectRenderer,
+) {
+    let key = (renderer_id, renderer.registry_type());
+    registrations.renderers.insert(key, renderer);
+}
@@ -0,0 +1,141 @@
+use std::{collections::HashMap, io::Cursor, time::Duration};
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use bytes::Bytes;
+use compositor_api::{
+    error::ApiError,
+    types::{PreviewInput, RenderPreviewRequest},
+};
+use compositor_render::{scene::Component, InputId, Resolution, Snapshot};
+use image::{ImageFormat, RgbaImage};
+
+use crate::state::ApiState;
+
+use super::Json;
+
+/// Renders a single frame of the scene and returns it as a PNG image. The scene is
+/// rendered in isolation, so registered outputs are not affected.
+pub(super) async fn handle_render_preview(
+    State(api): State<ApiState>,
+    Json(request): Json<RenderPreviewRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let resolution: Resolution = request.resolution.into();
+    if resolution.width == 0 || resolution.height == 0 {
+        return Err(ApiError::malformed_request(
+            &"Preview resolution has to be a positive number of pixels.",
+        ));
+    }
+    let pts = Duration::try_from_secs_f64(request.pts_ms.unwrap_or(0.0) / 1000.0)
+        .map_err(|_| ApiError::malformed_request(&"\"pts_ms\" has to be a non-negative number."))?;
+    let scene_root: Component = request.video.try_into()?;
+
+    let mut inputs = Vec::new();
+    for input in request.inputs.unwrap_or_default() {
+        let input_id = InputId::from(input.input_id.clone());
+        let image = read_input_image(&input_id, input).await?;
+        inputs.push((input_id, image));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let max_dimension = api.pipeline().max_texture_dimension() as u32;
+        let mut frames = HashMap::new();
+        for (input_id, image) in inputs {
+            let image = decode_input_image(&input_id, &image, max_dimension)?;
+            let resolution = Resolution {
+                width: image.width() as usize,
+                height: image.height() as usize,
+            };
+            let data = Bytes::from(image.into_raw());
+            frames.insert(input_id, Snapshot { resolution, data });
+        }
+
+        let frame = api
+            .pipeline()
+            .render_preview(resolution, scene_root, pts, frames)?;
+        let png = encode_png(frame)?;
+        Ok(([(header::CONTENT_TYPE, "image/png")], png))
+    })
+    .await
+    .unwrap()
+}
+
+async fn read_input_image(input_id: &InputId, input: PreviewInput) -> Result<Bytes, ApiError> {
+    match (input.url, input.path) {
+        (Some(url), None) => {
+            let response = reqwest::get(&url)
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|err| invalid_input(input_id, &err))?;
+            response
+                .bytes()
+                .await
+                .map_err(|err| invalid_input(input_id, &err))
+        }
+        (None, Some(path)) => tokio::fs::read(&path)
+            .await
+            .map(Bytes::from)
+            .map_err(|err| invalid_input(input_id, &format!("Failed to read \"{path}\": {err}"))),
+        _ => Err(invalid_input(
+            input_id,
+            &"Exactly one of \"url\" and \"path\" has to be specified.",
+        )),
+    }
+}
+
+/// Images larger than `max_dimension` could not be uploaded to the GPU, so they are
+/// rejected before they are decoded.
+fn decode_input_image(
+    input_id: &InputId,
+    image: &[u8],
+    max_dimension: u32,
+) -> Result<RgbaImage, ApiError> {
+    let mut reader = image::io::Reader::new(Cursor::new(image))
+        .with_guessed_format()
+        .map_err(|err| invalid_input(input_id, &err))?;
+    let mut limits = image::io::Limits::default();
+    limits.max_image_width = Some(max_dimension);
+    limits.max_image_height = Some(max_dimension);
+    reader.limits(limits);
+    let image = reader
+        .decode()
+        .map_err(|err| invalid_input(input_id, &err))?;
+    Ok(image.to_rgba8())
+}
+
+fn invalid_input(input_id: &InputId, err: &dyn std::fmt::Display) -> ApiError {
+    ApiError::new(
+        "INVALID_PREVIEW_INPUT",
+        format!("Failed to load image for input \"{input_id}\": {err}"),
+        StatusCode::BAD_REQUEST,
+    )
+}
+
+fn encode_png(frame: Snapshot) -> Result<Vec<u8>, ApiError> {
+    let Snapshot { resolution, data } = frame;
+    let image = RgbaImage::from_raw(
+        resolution.width as u32,
+        resolution.height as u32,
+        data.to_vec(),
+    )
+    .ok_or_else(|| {
+        ApiError::new(
+            "INTERNAL_SERVER_ERROR",
+            "Rendered frame does not match its resolution.".to_string(),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    })?;
+
+    let mut png = Cursor::new(Vec::new());
+    image.write_to(&mut png, ImageFormat::Png).map_err(|err| {
+        ApiError::new(
+            "INTERNAL_SERVER_ERROR",
+            format!("Failed to encode PNG: {err}"),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    })?;
+    Ok(png.into_inner())
+}
@@ -0,0 +1,168 @@
+use std::{error::Error, time::Duration};
+
+use axum::extract::{Path, State};
+use compositor_api::error::ApiError;
+use compositor_pipeline::{
+    error::PipelineErrorInfo,
+    event::{Event, EventError},
+    queue::{ScheduledEventDescription, ScheduledEventId, ScheduledEventInfo},
+};
+use compositor_render::error::ErrorStack;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::state::{ApiState, Pipeline, Response};
+
+/// Request body extended with `schedule_time_ms`.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct ScheduledRequest<T> {
+    #[serde(flatten)]
+    pub request: T,
+    /// Time in milliseconds when this request should be applied. Value `0` represents
+    /// time of the start request.
+    pub schedule_time_ms: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct ScheduledEventsResponse {
+    events: Vec<ScheduledEventResponse>,
+}
+
+#[derive(Debug, Serialize)]
+struct ScheduledEventResponse {
+    event_id: u64,
+    schedule_time_ms: f64,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    entity_id: Option<String>,
+}
+
+pub(super) async fn handle_scheduled_events(
+    State(api): State<ApiState>,
+) -> axum::Json<ScheduledEventsResponse> {
+    let events = api
+        .pipeline()
+        .queue()
+        .scheduled_events()
+        .into_iter()
+        .map(ScheduledEventResponse::from)
+        .collect();
+    axum::Json(ScheduledEventsResponse { events })
+}
+
+pub(super) async fn handle_cancel(
+    State(api): State<ApiState>,
+    Path(event_id): Path<u64>,
+) -> Result<Response, ApiError> {
+    api.pipeline()
+        .queue()
+        .cancel_scheduled_event(ScheduledEventId(event_id))?;
+    Ok(Response::Ok {})
+}
+
+/// Schedules `register` that registers an entity in the pipeline and records it in
+/// the API state, so the entity is not used before `schedule_time_ms`, e.g. an output
+/// does not produce frames and an MP4 input does not play. Options have to be validated
+/// before scheduling, errors of the registration itself are reported with the
+/// `SCHEDULED_REGISTER_FAILED` event.
+pub(super) fn schedule_registration<E>(
+    api: &ApiState,
+    schedule_time_ms: f64,
+    description: ScheduledEventDescription,
+    register: impl FnOnce(&ApiState) -> Result<(), E> + Send + 'static,
+) -> Response
+where
+    E: Error + 'static,
+    for<'a> PipelineErrorInfo: From<&'a E>,
+{
+    schedule_action(
+        api,
+        schedule_time_ms,
+        description,
+        register,
+        |description, schedule_time, error| Event::ScheduledRegisterFailed {
+            description,
+            schedule_time,
+            error,
+        },
+    )
+}
+
+/// Schedules `action` that unregisters an entity while the pipeline is locked. Errors
+/// are reported with the `SCHEDULED_UNREGISTER_FAILED` event.
+pub(super) fn schedule_unregister<E>(
+    api: &ApiState,
+    schedule_time_ms: f64,
+    description: ScheduledEventDescription,
+    action: impl FnOnce(&mut Pipeline, &ApiState) -> Result<(), E> + Send + 'static,
+) -> Response
+where
+    E: Error + 'static,
+    for<'a> PipelineErrorInfo: From<&'a E>,
+{
+    schedule_action(
+        api,
+        schedule_time_ms,
+        description,
+        move |api| action(&mut api.pipeline(), api),
+        |description, schedule_time, error| Event::ScheduledUnregisterFailed {
+            description,
+            schedule_time,
+            error,
+        },
+    )
+}
+
+/// Errors of scheduled actions are reported with an event created by `failed_event`,
+/// because the request was already answered when the action runs.
+fn schedule_action<E>(
+    api: &ApiState,
+    schedule_time_ms: f64,
+    description: ScheduledEventDescription,
+    action: impl FnOnce(&ApiState) -> Result<(), E> + Send + 'static,
+    failed_event: fn(ScheduledEventDescription, Duration, EventError) -> Event,
+) -> Response
+where
+    E: Error + 'static,
+    for<'a> PipelineErrorInfo: From<&'a E>,
+{
+    let schedule_time = Duration::from_secs_f64(schedule_time_ms / 1000.0);
+    let state = api.clone();
+    let event_description = description.clone();
+    // The action locks the pipeline, so it has to be released before scheduling.
+    let queue = api.pipeline().queue().clone();
+    let event_id = queue.schedule_event(
+        schedule_time,
+        description,
+        Box::new(move || {
+            if let Err(err) = action(&state) {
+                error!(
+                    "Error while running scheduled {} for pts {}ms: {}",
+                    event_description.kind,
+                    schedule_time.as_millis(),
+                    ErrorStack::new(&err).into_string()
+                );
+                let error_code = PipelineErrorInfo::from(&err).error_code;
+                let error = EventError::new(error_code, &err);
+                state
+                    .pipeline()
+                    .emit_event(failed_event(event_description, schedule_time, error));
+            }
+        }),
+    );
+    Response::Scheduled {
+        event_id: event_id.0,
+    }
+}
+
+impl From<ScheduledEventInfo> for ScheduledEventResponse {
+    fn from(info: ScheduledEventInfo) -> Self {
+        Self {
+            event_id: info.id.0,
+            schedule_time_ms: info.pts.as_secs_f64() * 1000.0,
+            kind: info.description.kind,
+            entity_id: info.description.entity_id,
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use std::{convert::Infallible, io::Cursor, time::Duration};
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use bytes::Bytes;
+use compositor_api::{
+    error::ApiError,
+    types::{InputId, OutputId},
+};
+use compositor_pipeline::error::SnapshotError;
+use compositor_render::Snapshot;
+use futures_util::{stream, StreamExt};
+use image::{codecs::jpeg::JpegEncoder, imageops, DynamicImage, ImageFormat, RgbaImage};
+use serde::Deserialize;
+use tokio::time::MissedTickBehavior;
+
+use crate::state::ApiState;
+
+const JPEG_QUALITY: u8 = 85;
+const DEFAULT_MJPEG_FPS: f64 = 5.0;
+const MAX_MJPEG_FPS: f64 = 30.0;
+const MJPEG_BOUNDARY: &str = "smelter-snapshot";
+
+#[derive(Debug, Deserialize)]
+pub(super) struct SnapshotParams {
+    format: Option<SnapshotFormat>,
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct MjpegParams {
+    fps: Option<f64>,
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SnapshotFormat {
+    #[default]
+    Jpeg,
+    Png,
+}
+
+/// Bounding box that the snapshot is downscaled to fit in. Snapshots are never
+/// upscaled and aspect ratio is always preserved.
+#[derive(Debug, Clone, Copy)]
+struct MaxSize {
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+pub(super) async fn handle_input_snapshot(
+    State(api): State<ApiState>,
+    Path(input_id): Path<InputId>,
+    Query(params): Query<SnapshotParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let input_id = input_id.into();
+    snapshot_response(params, move || input_snapshot(&api, &input_id)).await
+}
+
+pub(super) async fn handle_output_snapshot(
+    State(api): State<ApiState>,
+    Path(output_id): Path<OutputId>,
+    Query(params): Query<SnapshotParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let output_id = output_id.into();
+    snapshot_response(params, move || output_snapshot(&api, &output_id)).await
+}
+
+pub(super) async fn handle_input_mjpeg(
+    State(api): State<ApiState>,
+    Path(input_id): Path<InputId>,
+    Query(params): Query<MjpegParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let input_id = input_id.into();
+    mjpeg_response(params, move || input_snapshot(&api, &input_id)).await
+}
+
+pub(super) async fn handle_output_mjpeg(
+    State(api): State<ApiState>,
+    Path(output_id): Path<OutputId>,
+    Query(params): Query<MjpegParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let output_id = output_id.into();
+    mjpeg_response(params, move || output_snapshot(&api, &output_id)).await
+}
+
+/// Pipeline is locked only while the copy is scheduled, waiting for the GPU
+/// happens after the lock is released.
+fn input_snapshot(
+    api: &ApiState,
+    input_id: &compositor_render::InputId,
+) -> Result<Snapshot, SnapshotError> {
+    let download = api.pipeline().start_input_snapshot(input_id)?;
+    download.wait().map_err(SnapshotError::Download)
+}
+
+fn output_snapshot(
+    api: &ApiState,
+    output_id: &compositor_render::OutputId,
+) -> Result<Snapshot, SnapshotError> {
+    let download = api.pipeline().start_output_snapshot(output_id)?;
+    download.wait().map_err(SnapshotError::Download)
+}
+
+async fn snapshot_response(
+    params: SnapshotParams,
+    take_snapshot: impl FnOnce() -> Result<Snapshot, SnapshotError> + Send + 'static,
+) -> Result<impl IntoResponse, ApiError> {
+    let max_size = MaxSize::new(params.width, params.height)?;
+    let format = params.format.unwrap_or_default();
+
+    // GPU readback and encoding are blocking
+    tokio::task::spawn_blocking(move || {
+        let image = encode_snapshot(take_snapshot()?, max_size, format)?;
+        Ok(([(header::CONTENT_TYPE, format.content_type())], image))
+    })
+    .await
+    .unwrap()
+}
+
+/// Returns `multipart/x-mixed-replace` stream of JPEG images. Frames are read back
+/// only while the client is connected, the stream ends when the input or output
+/// is unregistered.
+async fn mjpeg_response(
+    params: MjpegParams,
+    take_snapshot: impl Fn() -> Result<Snapshot, SnapshotError> + Clone + Send + 'static,
+) -> Result<impl IntoResponse, ApiError> {
+    let max_size = MaxSize::new(params.width, params.height)?;
+    let fps = params.fps.unwrap_or(DEFAULT_MJPEG_FPS);
+    if !(fps > 0.0 && fps <= MAX_MJPEG_FPS) {
+        return Err(ApiError::malformed_request(&format!(
+            "\"fps\" has to be a positive number not larger than {MAX_MJPEG_FPS}."
+        )));
+    }
+
+    // First frame is read before the response is sent, so a missing input or
+    // output results in a regular error response.
+    let first_part = mjpeg_part(take_snapshot.clone(), max_size).await?;
+
+    let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / fps));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // first tick completes immediately
+    interval.tick().await;
+    let parts = stream::unfold(
+        (interval, take_snapshot),
+        move |(mut interval, take_snapshot)| async move {
+            loop {
+                interval.tick().await;
+                match mjpeg_part(take_snapshot.clone(), max_size).await {
+                    Ok(Some(part)) => return Some((part, (interval, take_snapshot))),
+                    Ok(None) => continue,
+                    Err(_) => return None,
+                }
+            }
+        },
+    );
+    let body = stream::iter(first_part)
+        .chain(parts)
+        .map(Ok::<_, Infallible>);
+
+    Ok((
+        [(
+            header::CONTENT_TYPE,
+            format!("multipart/x-mixed-replace; boundary={MJPEG_BOUNDARY}"),
+        )],
+        Body::from_stream(body),
+    ))
+}
+
+/// Returns `None` if there is no frame available yet.
+async fn mjpeg_part(
+    take_snapshot: impl FnOnce() -> Result<Snapshot, SnapshotError> + Send + 'static,
+    max_size: MaxSize,
+) -> Result<Option<Bytes>, ApiError> {
+    tokio::task::spawn_blocking(move || {
+        let snapshot = match take_snapshot() {
+            Ok(snapshot) => snapshot,
+            Err(SnapshotError::NoFrame) => return Ok(None),
+            Err(err) => return Err(ApiError::from(err)),
+        };
+        let jpeg = encode_snapshot(snapshot, max_size, SnapshotFormat::Jpeg)?;
+        let mut part = format!(
+            "--{MJPEG_BOUNDARY}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+            jpeg.len()
+        )
+        .into_bytes();
+        part.extend_from_slice(&jpeg);
+        part.extend_from_slice(b"\r\n");
+        Ok(Some(Bytes::from(part)))
+    })
+    .await
+    .unwrap()
+}
+
+fn encode_snapshot(
+    snapshot: Snapshot,
+    max_size: MaxSize,
+    format: SnapshotFormat,
+) -> Result<Vec<u8>, ApiError> {
+    let Snapshot { resolution, data } = snapshot;
+    let image = RgbaImage::from_raw(
+        resolution.width as u32,
+        resolution.height as u32,
+        data.to_vec(),
+    )
+    .ok_or_else(|| internal_error(&"Snapshot data does not match its resolution."))?;
+
+    let (width, height) = max_size.fit(image.width(), image.height());
+    let image = match (width, height) == image.dimensions() {
+        true => image,
+        false => imageops::resize(&image, width, height, imageops::FilterType::Triangle),
+    };
+
+    let mut buffer = Cursor::new(Vec::new());
+    let result = match format {
+        SnapshotFormat::Png => image.write_to(&mut buffer, ImageFormat::Png),
+        SnapshotFormat::Jpeg => {
+            // JPEG does not support alpha channel
+            let image = DynamicImage::ImageRgba8(image).to_rgb8();
+            JpegEncoder::new_with_quality(&mut buffer, JPEG_QUALITY).encode_image(&image)
+        }
+    };
+    result.map_err(|err| internal_error(&format!("Failed to encode snapshot: {err}")))?;
+    Ok(buffer.into_inner())
+}
+
+fn internal_error(err: &dyn std::fmt::Display) -> ApiError {
+    ApiError::new(
+        "INTERNAL_SERVER_ERROR",
+        err.to_string(),
+        StatusCode::INTERNAL_SERVER_ERROR,
+    )
+}
+
+impl SnapshotFormat {
+    fn content_type(self) -> &'static str {
+        match self {
+            SnapshotFormat::Jpeg => "image/jpeg",
+            SnapshotFormat::Png => "image/png",
+        }
+    }
+}
+
+impl MaxSize {
+    fn new(width: Option<u32>, height: Option<u32>) -> Result<Self, ApiError> {
+        if width == Some(0) || height == Some(0) {
+            return Err(ApiError::malformed_request(
+                &"\"width\" and \"height\" have to be positive numbers.",
+            ));
+        }
+        Ok(Self { width, height })
+    }
+
+    /// Largest size with the same aspect ratio that fits in the bounding box.
+    fn fit(&self, width: u32, height: u32) -> (u32, u32) {
+        let scale_x = self.width.map_or(1.0, |max| max as f64 / width as f64);
+        let scale_y = self.height.map_or(1.0, |max| max as f64 / height as f64);
+        let scale = f64::min(1.0, f64::min(scale_x, scale_y));
+        if scale >= 1.0 {
+            return (width, height);
+        }
+        let scaled = |value: u32| u32::max(1, (value as f64 * scale).round() as u32);
+        (scaled(width), scaled(height))
+    }
+}
@@ -0,0 +1,397 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::Duration,
+};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+};
+use compositor_api::{
+    error::ApiError,
+    types::{Audio, InputId, OutputId, Video},
+};
+use compositor_pipeline::{
+    audio_mixer::AudioMixingParams,
+    pipeline::RegisterInputOptions,
+    queue::{Queue, ScheduledEventDescription, ScheduledEventId},
+};
+use compositor_render::{error::UpdateSceneError, scene::Component};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::state::{ApiState, OutputScene, Pipeline, Response, Timeline, TimelineEntry, Timelines};
+
+use super::{
+    register_request::{input_response, RegisterInput},
+    update_output::schedule_output_update,
+    Json,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TimelineRequest {
+    /// Inputs registered when the timeline is uploaded. Use `offset_ms` to start
+    /// an input at a specific point of the timeline.
+    #[serde(default)]
+    pub inputs: Vec<TimelineInput>,
+    /// Scenes and audio mixes of the output, applied at their PTS.
+    pub entries: Vec<TimelineEntryRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TimelineInput {
+    pub input_id: InputId,
+    pub input: RegisterInput,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TimelineEntryRequest {
+    /// Time in milliseconds when the entry should be applied. Value `0` represents
+    /// time of the start request.
+    pub pts_ms: f64,
+    pub video: Option<Video>,
+    pub audio: Option<Audio>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RemoveTimelineEntryRequest {
+    pub pts_ms: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct TimelineUploadResponse {
+    /// Responses of the input registrations, in the same order as in the request.
+    inputs: Vec<Response>,
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct TimelineResponse {
+    /// PTS of the last frame produced by the pipeline, `null` if the pipeline did
+    /// not produce any frames yet.
+    position_ms: Option<f64>,
+    entries: Vec<TimelineEntryResponse>,
+}
+
+#[derive(Debug, Serialize)]
+struct TimelineEntryResponse {
+    pts_ms: f64,
+    #[serde(flatten)]
+    scene: OutputScene,
+    /// `false` if the entry was already applied (or its event was cancelled).
+    pending: bool,
+}
+
+/// Replaces the timeline of the output. Entries of the previous timeline that were
+/// not applied yet are cancelled.
+pub(super) async fn handle_timeline_upload(
+    State(api): State<ApiState>,
+    Path(output_id): Path<OutputId>,
+    Json(request): Json<TimelineRequest>,
+) -> Result<axum::Json<TimelineUploadResponse>, ApiError> {
+    let output_id: compositor_render::OutputId = output_id.into();
+    let mut entries = BTreeMap::new();
+    for entry in request.entries {
+        let (pts, scene) = timeline_entry(entry)?;
+        if entries.insert(pts, scene).is_some() {
+            return Err(ApiError::malformed_request(&format!(
+                "Timeline has more than one entry for PTS {}ms.",
+                pts.as_millis()
+            )));
+        }
+    }
+    let inputs = request
+        .inputs
+        .into_iter()
+        .map(
+            |input| -> Result<(compositor_render::InputId, _, _), ApiError> {
+                let options = RegisterInputOptions::try_from(input.input.clone())?;
+                Ok((input.input_id.into(), options, input.input))
+            },
+        )
+        .collect::<Result<Vec<_>, _>>()?;
+
+    tokio::task::spawn_blocking(move || {
+        check_entries(&api.pipeline(), &output_id, entries.keys())?;
+
+        let mut registered_inputs = Vec::new();
+        let mut input_requests = Vec::new();
+        let mut responses = Vec::new();
+        for (input_id, options, input) in inputs {
+            match Pipeline::register_input(&api.pipeline, input_id.clone(), options) {
+                Ok(init_info) => {
+                    registered_inputs.push(input_id.clone());
+                    input_requests.push((input_id, input));
+                    responses.push(input_response(init_info));
+                }
+                Err(err) => {
+                    unregister_inputs(&mut api.pipeline(), &registered_inputs);
+                    return Err(err.into());
+                }
+            }
+        }
+
+        let queue = {
+            let mut pipeline = api.pipeline();
+            // Registration can take a while, pipeline could produce frames for
+            // the first entries in the meantime.
+            if let Err(err) = check_entries(&pipeline, &output_id, entries.keys()) {
+                unregister_inputs(&mut pipeline, &registered_inputs);
+                return Err(err);
+            }
+            // Scheduled callbacks lock the pipeline, so it is released before
+            // the entries are scheduled.
+            pipeline.queue().clone()
+        };
+        let mut timelines = api.timelines.lock();
+        if let Some(old_timeline) = timelines.remove(&output_id) {
+            cancel_entries(&queue, old_timeline);
+        }
+        let mut timeline = Timeline::default();
+        for (pts, scene) in entries {
+            let event_id = schedule_entry(&api, &queue, &output_id, pts, scene.clone())?;
+            timeline
+                .entries
+                .insert(pts, TimelineEntry { scene, event_id });
+        }
+        timelines.insert(output_id, timeline);
+        // Scheduled callbacks lock the pipeline before timelines, so timelines are
+        // released before the pipeline is locked again.
+        drop(timelines);
+        api.record_registration(|registrations, _| registrations.inputs.extend(input_requests));
+
+        Ok(axum::Json(TimelineUploadResponse { inputs: responses }))
+    })
+    .await
+    // `unwrap()` panics only when the task panicked or `response.abort()` was called
+    .unwrap()
+}
+
+pub(super) async fn handle_timeline(
+    State(api): State<ApiState>,
+    Path(output_id): Path<OutputId>,
+) -> Result<axum::Json<TimelineResponse>, ApiError> {
+    let output_id: compositor_render::OutputId = output_id.into();
+    let pipeline = api.pipeline();
+    check_output_registered(&pipeline, &output_id)?;
+
+    let queue = pipeline.queue();
+    let pending_events: HashSet<_> = queue
+        .scheduled_events()
+        .into_iter()
+        .map(|event| event.id)
+        .collect();
+    let entries = api
+        .timelines
+        .lock()
+        .get(&output_id)
+        .map(|timeline| {
+            timeline
+                .entries
+                .iter()
+                .map(|(pts, entry)| TimelineEntryResponse {
+                    pts_ms: duration_to_ms(*pts),
+                    scene: entry.scene.clone(),
+                    pending: pending_events.contains(&entry.event_id),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(axum::Json(TimelineResponse {
+        position_ms: queue.current_pts().map(duration_to_ms),
+        entries,
+    }))
+}
+
+/// Adds a new entry to the timeline or replaces an entry with the same PTS. Only
+/// entries that were not applied yet can be replaced.
+pub(super) async fn handle_update_entry(
+    State(api): State<ApiState>,
+    Path(output_id): Path<OutputId>,
+    Json(request): Json<TimelineEntryRequest>,
+) -> Result<Response, ApiError> {
+    let output_id: compositor_render::OutputId = output_id.into();
+    let (pts, scene) = timeline_entry(request)?;
+
+    tokio::task::spawn_blocking(move || {
+        let queue = {
+            let pipeline = api.pipeline();
+            check_entries(&pipeline, &output_id, [&pts])?;
+            pipeline.queue().clone()
+        };
+        let mut timelines = api.timelines.lock();
+        let timeline = timelines.entry(output_id.clone()).or_default();
+        if let Some(entry) = timeline.entries.get(&pts) {
+            queue
+                .cancel_scheduled_event(entry.event_id)
+                .map_err(|_| entry_already_applied(pts))?;
+        }
+        let event_id = schedule_entry(&api, &queue, &output_id, pts, scene.clone())?;
+        timeline
+            .entries
+            .insert(pts, TimelineEntry { scene, event_id });
+
+        Ok(Response::Scheduled {
+            event_id: event_id.0,
+        })
+    })
+    .await
+    // `unwrap()` panics only when the task panicked or `response.abort()` was called
+    .unwrap()
+}
+
+/// Removes an entry that was not applied yet from the timeline.
+pub(super) async fn handle_remove_entry(
+    State(api): State<ApiState>,
+    Path(output_id): Path<OutputId>,
+    Json(request): Json<RemoveTimelineEntryRequest>,
+) -> Result<Response, ApiError> {
+    let output_id: compositor_render::OutputId = output_id.into();
+    let pts = pts_from_ms(request.pts_ms)?;
+
+    tokio::task::spawn_blocking(move || {
+        let queue = api.pipeline().queue().clone();
+        let mut timelines = api.timelines.lock();
+        let Some(entry) = timelines
+            .get(&output_id)
+            .and_then(|timeline| timeline.entries.get(&pts))
+        else {
+            return Err(ApiError::new(
+                "TIMELINE_ENTRY_NOT_FOUND",
+                format!(
+                    "Timeline of output \"{output_id}\" does not have an entry for PTS {}ms.",
+                    pts.as_millis()
+                ),
+                StatusCode::NOT_FOUND,
+            ));
+        };
+        queue
+            .cancel_scheduled_event(entry.event_id)
+            .map_err(|_| entry_already_applied(pts))?;
+        if let Some(timeline) = timelines.get_mut(&output_id) {
+            timeline.entries.remove(&pts);
+        }
+
+        Ok(Response::Ok {})
+    })
+    .await
+    .unwrap()
+}
+
+/// Removes the timeline of the output and cancels entries that were not applied yet.
+pub(super) fn remove_timeline(
+    queue: &Queue,
+    timelines: &Timelines,
+    output_id: &compositor_render::OutputId,
+) {
+    if let Some(timeline) = timelines.lock().remove(output_id) {
+        cancel_entries(queue, timeline);
+    }
+}
+
+fn cancel_entries(queue: &Queue, timeline: Timeline) {
+    for entry in timeline.entries.into_values() {
+        // Fails for entries that were already applied.
+        let _ = queue.cancel_scheduled_event(entry.event_id);
+    }
+}
+
+fn schedule_entry(
+    api: &ApiState,
+    queue: &Queue,
+    output_id: &compositor_render::OutputId,
+    pts: Duration,
+    scene: OutputScene,
+) -> Result<ScheduledEventId, ApiError> {
+    let description = ScheduledEventDescription {
+        kind: "timeline_entry",
+        entity_id: Some(output_id.to_string()),
+    };
+    schedule_output_update(api, queue, output_id.clone(), pts, description, scene)
+}
+
+/// Converts the entry and checks if its scene is valid, so it can be scheduled
+/// without errors.
+fn timeline_entry(entry: TimelineEntryRequest) -> Result<(Duration, OutputScene), ApiError> {
+    let pts = pts_from_ms(entry.pts_ms)?;
+    if let Some(video) = entry.video.clone() {
+        Component::try_from(video)?;
+    }
+    if let Some(audio) = entry.audio.clone() {
+        AudioMixingParams::try_from(audio)?;
+    }
+    let scene = OutputScene {
+        video: entry.video,
+        audio: entry.audio,
+    };
+    Ok((pts, scene))
+}
+
+/// Checks if the output is registered and all entries are after the current
+/// position of the pipeline.
+fn check_entries<'a>(
+    pipeline: &Pipeline,
+    output_id: &compositor_render::OutputId,
+    pts: impl IntoIterator<Item = &'a Duration>,
+) -> Result<(), ApiError> {
+    check_output_registered(pipeline, output_id)?;
+    let Some(position) = pipeline.queue().current_pts() else {
+        return Ok(());
+    };
+    match pts.into_iter().find(|pts| **pts <= position) {
+        Some(pts) => Err(ApiError::new(
+            "TIMELINE_ENTRY_IN_PAST",
+            format!(
+                "Entry for PTS {}ms is in the past, the pipeline is already at {}ms.",
+                pts.as_millis(),
+                position.as_millis()
+            ),
+            StatusCode::BAD_REQUEST,
+        )),
+        None => Ok(()),
+    }
+}
+
+fn check_output_registered(
+    pipeline: &Pipeline,
+    output_id: &compositor_render::OutputId,
+) -> Result<(), ApiError> {
+    match pipeline.outputs().any(|(id, _)| id == output_id) {
+        true => Ok(()),
+        false => Err(UpdateSceneError::OutputNotRegistered(output_id.clone()).into()),
+    }
+}
+
+fn unregister_inputs(pipeline: &mut Pipeline, input_ids: &[compositor_render::InputId]) {
+    for input_id in input_ids {
+        let _ = pipeline.unregister_input(input_id);
+    }
+}
+
+fn entry_already_applied(pts: Duration) -> ApiError {
+    ApiError::new(
+        "TIMELINE_ENTRY_ALREADY_APPLIED",
+        format!(
+            "Entry for PTS {}ms was already applied and can't be modified.",
+            pts.as_millis()
+        ),
+        StatusCode::BAD_REQUEST,
+    )
+}
+
+fn pts_from_ms(pts_ms: f64) -> Result<Duration, ApiError> {
+    if !(pts_ms.is_finite() && pts_ms >= 0.0) {
+        return Err(ApiError::malformed_request(
+            &"\"pts_ms\" has to be a non-negative number.",
+        ));
+    }
+    Ok(Duration::from_secs_f64(pts_ms / 1000.0))
+}
+
+fn duration_to_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
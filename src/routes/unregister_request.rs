@@ -1,8 +1,9 @@
-use std::time::Duration;
-
 use axum::extract::{Path, State};
-use compositor_render::{error::ErrorStack, RegistryType};
-use log::error;
+use compositor_pipeline::{
+    error::{UnregisterInputError, UnregisterOutputError},
+    queue::ScheduledEventDescription,
+};
+use compositor_render::{error::UnregisterRendererError, RegistryType};
 use serde::{Deserialize, Serialize};
 
 use crate::state::{ApiState, Response};
@@ -12,7 +13,7 @@ use compositor_api::{
     types::{InputId, OutputId, RendererId},
 };
 
-use super::Json;
+use super::{schedule::schedule_unregister, timeline::remove_timeline, Json, OptionalJson};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UnregisterInput {
@@ -28,12 +29,11 @@ pub struct UnregisterOutput {
     schedule_time_ms: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(tag = "type", rename_all = "snake_case")]
-pub enum UnregisterRenderer {
-    Shader { shader_id: RendererId },
-    WebRenderer { instance_id: RendererId },
-    Image { image_id: RendererId },
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UnregisterRenderer {
+    /// Time in milliseconds when this request should be applied. Value `0` represents
+    /// time of the start request.
+    schedule_time_ms: Option<f64>,
 }
 
 pub(super) async fn handle_input(
@@ -41,28 +41,30 @@ pub(super) async fn handle_input(
     Path(input_id): Path<InputId>,
     Json(request): Json<UnregisterInput>,
 ) -> Result<Response, ApiError> {
+    let input_id: compositor_render::InputId = input_id.into();
     match request.schedule_time_ms {
         Some(schedule_time_ms) => {
-            let pipeline = api.pipeline.clone();
-            let schedule_time = Duration::from_secs_f64(schedule_time_ms / 1000.0);
-            api.pipeline().queue().schedule_event(
-                schedule_time,
-                Box::new(move || {
-                    if let Err(err) = pipeline.lock().unwrap().unregister_input(&input_id.into()) {
-                        error!(
-                            "Error while running scheduled input unregister for pts {}ms: {}",
-                            schedule_time.as_millis(),
-                            ErrorStack::new(&err).into_string()
-                        )
-                    }
-                }),
-            );
+            let description = ScheduledEventDescription {
+                kind: "unregister_input",
+                entity_id: Some(input_id.to_string()),
+            };
+            Ok(schedule_unregister(
+                &api,
+                schedule_time_ms,
+                description,
+                move |pipeline, api| {
+                    pipeline.unregister_input(&input_id)?;
+                    api.registrations.lock().inputs.remove(&input_id);
+                    Ok::<_, UnregisterInputError>(())
+                },
+            ))
         }
         None => {
-            api.pipeline().unregister_input(&input_id.into())?;
+            api.pipeline().unregister_input(&input_id)?;
+            api.registrations.lock().inputs.remove(&input_id);
+            Ok(Response::Ok {})
         }
     }
-    Ok(Response::Ok {})
 }
 
 pub(super) async fn handle_output(
@@ -70,57 +72,143 @@ pub(super) async fn handle_output(
     Path(output_id): Path<OutputId>,
     Json(request): Json<UnregisterOutput>,
 ) -> Result<Response, ApiError> {
+    let output_id: compositor_render::OutputId = output_id.into();
     match request.schedule_time_ms {
         Some(schedule_time_ms) => {
-            let pipeline = api.pipeline.clone();
-            let schedule_time = Duration::from_secs_f64(schedule_time_ms / 1000.0);
-            api.pipeline().queue().schedule_event(
-                schedule_time,
-                Box::new(move || {
-                    if let Err(err) = pipeline
-                        .lock()
-                        .unwrap()
-                        .unregister_output(&output_id.into())
-                    {
-                        error!(
-                            "Error while running scheduled output unregister for pts {}ms: {}",
-                            schedule_time.as_millis(),
-                            ErrorStack::new(&err).into_string()
-                        )
-                    }
-                }),
-            );
+            let description = ScheduledEventDescription {
+                kind: "unregister_output",
+                entity_id: Some(output_id.to_string()),
+            };
+            Ok(schedule_unregister(
+                &api,
+                schedule_time_ms,
+                description,
+                move |pipeline, api| {
+                    pipeline.unregister_output(&output_id)?;
+                    api.output_scenes.remove(&output_id);
+                    api.registrations.lock().outputs.remove(&output_id);
+                    remove_timeline(pipeline.queue(), &api.timelines, &output_id);
+                    Ok::<_, UnregisterOutputError>(())
+                },
+            ))
         }
         None => {
-            api.pipeline().unregister_output(&output_id.into())?;
+            let mut pipeline = api.pipeline();
+            pipeline.unregister_output(&output_id)?;
+            api.output_scenes.remove(&output_id);
+            api.registrations.lock().outputs.remove(&output_id);
+            remove_timeline(pipeline.queue(), &api.timelines, &output_id);
+            Ok(Response::Ok {})
         }
     }
-    Ok(Response::Ok {})
 }
 
 pub(super) async fn handle_shader(
     State(api): State<ApiState>,
     Path(shader_id): Path<RendererId>,
+    OptionalJson(request): OptionalJson<UnregisterRenderer>,
 ) -> Result<Response, ApiError> {
-    api.pipeline()
-        .unregister_renderer(&shader_id.into(), RegistryType::Shader)?;
-    Ok(Response::Ok {})
+    unregister_renderer(
+        api,
+        shader_id,
+        RegistryType::Shader,
+        "unregister_shader",
+        request,
+    )
+}
+
+pub(super) async fn handle_compute_shader(
+    State(api): State<ApiState>,
+    Path(shader_id): Path<RendererId>,
+    OptionalJson(request): OptionalJson<UnregisterRenderer>,
+) -> Result<Response, ApiError> {
+    unregister_renderer(
+        api,
+        shader_id,
+        RegistryType::ComputeShader,
+        "unregister_compute_shader",
+        request,
+    )
 }
 
 pub(super) async fn handle_web_renderer(
     State(api): State<ApiState>,
     Path(instance_id): Path<RendererId>,
+    OptionalJson(request): OptionalJson<UnregisterRenderer>,
 ) -> Result<Response, ApiError> {
-    api.pipeline()
-        .unregister_renderer(&instance_id.into(), RegistryType::WebRenderer)?;
-    Ok(Response::Ok {})
+    unregister_renderer(
+        api,
+        instance_id,
+        RegistryType::WebRenderer,
+        "unregister_web_renderer",
+        request,
+    )
 }
 
 pub(super) async fn handle_image(
     State(api): State<ApiState>,
     Path(image_id): Path<RendererId>,
+    OptionalJson(request): OptionalJson<UnregisterRenderer>,
+) -> Result<Response, ApiError> {
+    unregister_renderer(
+        api,
+        image_id,
+        RegistryType::Image,
+        "unregister_image",
+        request,
+    )
+}
+
+pub(super) async fn handle_lottie(
+    State(api): State<ApiState>,
+    Path(lottie_id): Path<RendererId>,
+    OptionalJson(request): OptionalJson<UnregisterRenderer>,
 ) -> Result<Response, ApiError> {
-    api.pipeline()
-        .unregister_renderer(&image_id.into(), RegistryType::Image)?;
-    Ok(Response::Ok {})
+    unregister_renderer(
+        api,
+        lottie_id,
+        RegistryType::Lottie,
+        "unregister_lottie",
+        request,
+    )
+}
+
+fn unregister_renderer(
+    api: ApiState,
+    renderer_id: RendererId,
+    registry_type: RegistryType,
+    kind: &'static str,
+    request: UnregisterRenderer,
+) -> Result<Response, ApiError> {
+    let renderer_id: compositor_render::RendererId = renderer_id.into();
+    match request.schedule_time_ms {
+        Some(schedule_time_ms) => {
+            let description = ScheduledEventDescription {
+                kind,
+                entity_id: Some(renderer_id.to_string()),
+            };
+            Ok(schedule_unregister(
+                &api,
+                schedule_time_ms,
+                description,
+                move |pipeline, api| {
+                    pipeline.unregister_renderer(&renderer_id, registry_type)?;
+                    api.registrations
+                        .lock()
+                        .renderers
+                        .remove(&(renderer_id, registry_type));
+                    Ok::<_, UnregisterRendererError>(())
+                },
+            ))
+        }
+        None => {
+            api.pipeline()
+                .unregister_renderer(&renderer_id, registry_type)?;
+            api.registrations
+                .lock()
+                .renderers
+                .remove(&(renderer_id, registry_type));
+            Ok(Response::Ok {})
+        }
+    }
 }
@@ -1,14 +1,21 @@
 use std::time::Duration;
 
 use axum::extract::{Path, State};
-use compositor_render::error::ErrorStack;
+use compositor_pipeline::{
+    audio_mixer::AudioMixingParams,
+    error::PipelineErrorInfo,
+    event::{Event, EventError},
+    queue::{Queue, ScheduledEventDescription, ScheduledEventId},
+};
+use compositor_render::error::{ErrorStack, UpdateSceneError};
+use serde::Serialize;
 use tracing::error;
 
-use crate::state::{ApiState, Response};
+use crate::state::{ApiState, OutputScene, Response};
 
 use compositor_api::{
     error::ApiError,
-    types::{OutputId, UpdateOutputRequest},
+    types::{ComponentConversionError, OutputId, ScenePatch, UpdateOutputRequest},
 };
 
 use super::Json;
@@ -18,39 +25,215 @@ pub(super) async fn handle_output_update(
     Path(output_id): Path<OutputId>,
     Json(request): Json<UpdateOutputRequest>,
 ) -> Result<Response, ApiError> {
-    let output_id = output_id.into();
-    let scene = match request.video {
-        Some(component) => Some(component.try_into()?),
-        None => None,
+    let output_id: compositor_render::OutputId = output_id.into();
+    let scene = OutputScene {
+        video: request.video,
+        audio: request.audio,
     };
-    let audio = request.audio.map(|a| a.try_into()).transpose()?;
 
     match request.schedule_time_ms {
         Some(schedule_time_ms) => {
-            let pipeline = api.pipeline.clone();
             let schedule_time = Duration::from_secs_f64(schedule_time_ms / 1000.0);
-            api.pipeline().queue().schedule_event(
-                schedule_time,
-                Box::new(move || {
-                    if let Err(err) = pipeline
-                        .lock()
-                        .unwrap()
-                        .update_output(output_id, scene, audio)
-                    {
-                        error!(
-                            "Error while running scheduled output update for pts {}ms: {}",
-                            schedule_time.as_millis(),
-                            ErrorStack::new(&err).into_string()
-                        )
-                    }
-                }),
-            );
+            let description = ScheduledEventDescription {
+                kind: "update_output",
+                entity_id: Some(output_id.to_string()),
+            };
+            let queue = api.pipeline().queue().clone();
+            let event_id =
+                schedule_output_update(&api, &queue, output_id, schedule_time, description, scene)?;
+            Ok(Response::Scheduled {
+                event_id: event_id.0,
+            })
+        }
+        None => {
+            let video = scene.video.clone().map(|v| v.try_into()).transpose()?;
+            let audio = scene.audio.clone().map(|a| a.try_into()).transpose()?;
+            // Lock is held until stored scene is updated, so patches can't be
+            // applied to an outdated scene.
+            let mut pipeline = api.pipeline();
+            pipeline.update_output(output_id.clone(), video, audio)?;
+            api.output_scenes
+                .update(&output_id, scene.video, scene.audio);
+            Ok(Response::Ok {})
         }
-        None => api.pipeline().update_output(output_id, scene, audio)?,
+    }
+}
+
+/// Schedules the output update on the `queue` of the pipeline. Errors at the time
+/// of the update are reported with the `ScheduledOutputUpdateFailed` event.
+pub(crate) fn schedule_output_update(
+    api: &ApiState,
+    queue: &Queue,
+    output_id: compositor_render::OutputId,
+    schedule_time: Duration,
+    description: ScheduledEventDescription,
+    scene: OutputScene,
+) -> Result<ScheduledEventId, ApiError> {
+    let video = scene.video.clone().map(|v| v.try_into()).transpose()?;
+    let audio = scene.audio.clone().map(|a| a.try_into()).transpose()?;
+
+    let pipeline = api.pipeline.clone();
+    let output_scenes = api.output_scenes.clone();
+    let event_id = queue.schedule_event(
+        schedule_time,
+        description,
+        Box::new(move || {
+            let mut pipeline = pipeline.lock().unwrap();
+            let result = pipeline.update_output(output_id.clone(), video, audio);
+            match result {
+                Ok(()) => output_scenes.update(&output_id, scene.video, scene.audio),
+                Err(err) => {
+                    error!(
+                        "Error while running scheduled output update for pts {}ms: {}",
+                        schedule_time.as_millis(),
+                        ErrorStack::new(&err).into_string()
+                    );
+                    let error_code = PipelineErrorInfo::from(&err).error_code;
+                    pipeline.emit_event(Event::ScheduledOutputUpdateFailed {
+                        output_id,
+                        schedule_time,
+                        error: EventError::new(error_code, &err),
+                    });
+                }
+            }
+        }),
+    );
+    Ok(event_id)
+}
+
+/// Applies the patch to the current scene of the output. Components that are not
+/// targeted by the patch continue their ongoing transitions.
+pub(super) async fn handle_output_patch(
+    State(api): State<ApiState>,
+    Path(output_id): Path<OutputId>,
+    Json(patch): Json<ScenePatch>,
+) -> Result<Response, ApiError> {
+    let output_id: compositor_render::OutputId = output_id.into();
+
+    // Lock is held for the whole operation, so concurrent patches and updates
+    // can't overwrite each other's changes.
+    let mut pipeline = api.pipeline();
+    let Some(video) = api.output_scenes.get(&output_id).video else {
+        let is_registered = pipeline.outputs().any(|(id, _)| *id == output_id);
+        return Err(match is_registered {
+            true => UpdateSceneError::AudioVideoNotMatching(output_id).into(),
+            false => UpdateSceneError::OutputNotRegistered(output_id).into(),
+        });
     };
+
+    let patched_video = video.patch(&patch)?;
+    let scene = video
+        .without_transitions_except(patch.component_id())
+        .patch(&patch)?;
+    pipeline.update_output_scene(output_id.clone(), scene.try_into()?)?;
+    api.output_scenes
+        .update(&output_id, Some(patched_video), None);
+
     Ok(Response::Ok {})
 }
 
+#[derive(Debug, Serialize)]
+pub(super) struct ValidateOutputResponse {
+    valid: bool,
+    errors: Vec<ComponentErrorResponse>,
+}
+
+#[derive(Debug, Serialize)]
+struct ComponentErrorResponse {
+    /// Location of the invalid component in the request, e.g. `video.root.children[0]`,
+    /// or `video` if the error is not related to a single component.
+    path: String,
+    error_code: &'static str,
+    message: String,
+    stack: Vec<String>,
+}
+
+/// Runs all checks of the output update without applying it. Errors in the scene
+/// are returned in the response body, the request itself fails only if the output
+/// can't be updated at all, e.g. it is not registered.
+pub(super) async fn handle_output_validate(
+    State(api): State<ApiState>,
+    Path(output_id): Path<OutputId>,
+    Json(request): Json<UpdateOutputRequest>,
+) -> Result<axum::Json<ValidateOutputResponse>, ApiError> {
+    let output_id: compositor_render::OutputId = output_id.into();
+
+    let mut errors: Vec<ComponentErrorResponse> = Vec::new();
+    if let Some(video) = &request.video {
+        let conversion_errors = video
+            .root
+            .clone()
+            .conversion_errors("video.root".to_string());
+        errors.extend(conversion_errors.into_iter().map(Into::into));
+    }
+    let audio = match request.audio.map(AudioMixingParams::try_from).transpose() {
+        Ok(audio) => audio,
+        Err(err) => {
+            errors.push(ComponentErrorResponse::malformed("audio".to_string(), &err));
+            None
+        }
+    };
+    // Scene can be checked against the pipeline state only if it was converted
+    // successfully.
+    if !errors.is_empty() {
+        return Ok(axum::Json(ValidateOutputResponse {
+            valid: false,
+            errors,
+        }));
+    }
+
+    let scene = request.video.map(|video| video.try_into()).transpose()?;
+    let errors = match api
+        .pipeline()
+        .validate_output_update(&output_id, scene, &audio)
+    {
+        Ok(scene_errors) => scene_errors
+            .into_iter()
+            .map(|err| {
+                let path = format!("video.{}", err.path);
+                ComponentErrorResponse::scene(path, &UpdateSceneError::SceneError(err.error))
+            })
+            .collect(),
+        // Errors found only when the whole scene is built, e.g. by wgpu, are not
+        // assigned to a specific component.
+        Err(err @ (UpdateSceneError::SceneError(_) | UpdateSceneError::WgpuError(_))) => {
+            vec![ComponentErrorResponse::scene("video".to_string(), &err)]
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    Ok(axum::Json(ValidateOutputResponse {
+        valid: errors.is_empty(),
+        errors,
+    }))
+}
+
+impl ComponentErrorResponse {
+    fn malformed(path: String, err: &(dyn std::error::Error + 'static)) -> Self {
+        Self {
+            path,
+            error_code: "MALFORMED_REQUEST",
+            message: err.to_string(),
+            stack: ErrorStack::new(err).map(|err| err.to_string()).collect(),
+        }
+    }
+
+    fn scene(path: String, err: &UpdateSceneError) -> Self {
+        Self {
+            path,
+            error_code: PipelineErrorInfo::from(err).error_code,
+            message: err.to_string(),
+            stack: ErrorStack::new(err).map(|err| err.to_string()).collect(),
+        }
+    }
+}
+
+impl From<ComponentConversionError> for ComponentErrorResponse {
+    fn from(err: ComponentConversionError) -> Self {
+        Self::malformed(err.path, &err.error)
+    }
+}
+
 pub(super) async fn handle_keyframe_request(
     State(api): State<ApiState>,
     Path(output_id): Path<OutputId>,
@@ -1,14 +1,107 @@
-use std::thread;
+use std::{collections::HashSet, thread, time::Duration};
 
-use axum::extract::ws::{Message, WebSocket};
+use axum::{
+    extract::ws::{Message, WebSocket},
+    http::StatusCode,
+};
+use compositor_api::error::ApiError;
 use compositor_render::event_handler::{subscribe, Event};
 use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
 use tokio::sync::mpsc::channel;
 use tracing::debug;
 
-pub(super) async fn handle_ws_upgrade(socket: WebSocket) {
+use crate::state::ApiState;
+
+use self::stats::StatsCollector;
+
+mod stats;
+
+const MIN_STATS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Options passed as query parameters of the WebSocket handshake request.
+#[derive(Debug, Deserialize)]
+pub(super) struct WsParams {
+    stats_interval_ms: Option<u64>,
+    events: Option<String>,
+    input_ids: Option<String>,
+    output_ids: Option<String>,
+}
+
+pub(super) struct WsOptions {
+    /// `None` if stats events should not be sent.
+    stats_interval: Option<Duration>,
+    filter: EventFilter,
+}
+
+/// Filter applied to all events sent over a single connection. `None` means
+/// that a given property is not filtered.
+struct EventFilter {
+    kinds: Option<HashSet<String>>,
+    input_ids: Option<HashSet<String>>,
+    output_ids: Option<HashSet<String>>,
+}
+
+impl TryFrom<WsParams> for WsOptions {
+    type Error = ApiError;
+
+    fn try_from(params: WsParams) -> Result<Self, Self::Error> {
+        let stats_interval = params.stats_interval_ms.map(Duration::from_millis);
+        if stats_interval.is_some_and(|interval| interval < MIN_STATS_INTERVAL) {
+            return Err(ApiError::new(
+                "INVALID_STATS_INTERVAL",
+                format!(
+                    "\"stats_interval_ms\" has to be at least {}.",
+                    MIN_STATS_INTERVAL.as_millis()
+                ),
+                StatusCode::BAD_REQUEST,
+            ));
+        }
+        fn parse_list(list: Option<String>) -> Option<HashSet<String>> {
+            list.map(|list| {
+                list.split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+        }
+        Ok(Self {
+            stats_interval,
+            filter: EventFilter {
+                kinds: parse_list(params.events),
+                input_ids: parse_list(params.input_ids),
+                output_ids: parse_list(params.output_ids),
+            },
+        })
+    }
+}
+
+impl EventFilter {
+    fn matches_kind(&self, kind: &str) -> bool {
+        self.kinds
+            .as_ref()
+            .map_or(true, |kinds| kinds.contains(kind))
+    }
+
+    fn matches(&self, event: &serde_json::Value) -> bool {
+        let matches_id = |key: &str, ids: &Option<HashSet<String>>| match (
+            event.get(key).and_then(|id| id.as_str()),
+            ids,
+        ) {
+            (Some(id), Some(ids)) => ids.contains(id),
+            _ => true,
+        };
+        let kind = event.get("type").and_then(|kind| kind.as_str());
+        kind.map_or(true, |kind| self.matches_kind(kind))
+            && matches_id("input_id", &self.input_ids)
+            && matches_id("output_id", &self.output_ids)
+    }
+}
+
+pub(super) async fn handle_ws_upgrade(socket: WebSocket, api: ApiState, options: WsOptions) {
     enum InternalMessage {
-        Event(Event),
+        Event(serde_json::Value),
         Close,
         Pong(Vec<u8>),
     }
@@ -22,7 +115,7 @@ pub(super) async fn handle_ws_upgrade(socket: WebSocket) {
             let receiver = subscribe();
             for event in receiver {
                 if event_sender_2
-                    .blocking_send(InternalMessage::Event(event))
+                    .blocking_send(InternalMessage::Event(event_to_json(event)))
                     .is_err()
                 {
                     return;
@@ -31,12 +124,39 @@ pub(super) async fn handle_ws_upgrade(socket: WebSocket) {
         })
         .unwrap();
 
+    let stats_enabled = options.filter.matches_kind(stats::INPUT_STATS)
+        || options.filter.matches_kind(stats::OUTPUT_STATS);
+    if let (Some(stats_interval), true) = (options.stats_interval, stats_enabled) {
+        let event_sender = event_sender.clone();
+        tokio::spawn(async move {
+            let mut collector = StatsCollector::new(&api);
+            let mut interval = tokio::time::interval(stats_interval);
+            // first tick completes immediately
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                for event in collector.collect(&api) {
+                    if event_sender
+                        .send(InternalMessage::Event(event))
+                        .await
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+        });
+    }
+
+    let filter = options.filter;
     tokio::spawn(async move {
         while let Some(event) = event_receiver.recv().await {
             match event {
                 InternalMessage::Event(event) => {
-                    let serialized = event_to_json(event).to_string();
-                    if let Err(err) = socket_sender.send(Message::Text(serialized)).await {
+                    if !filter.matches(&event) {
+                        continue;
+                    }
+                    if let Err(err) = socket_sender.send(Message::Text(event.to_string())).await {
                         debug!(%err, "WebSocket send error.");
                         return;
                     }
@@ -80,7 +200,92 @@ fn event_to_json(event: Event) -> serde_json::Value {
     let mut map = serde_json::Map::new();
     map.insert("type".to_string(), event.kind.into());
     for (key, value) in event.properties {
-        map.insert(key, value.into());
+        map.insert(key, value);
     }
     map.into()
 }
+
+#[cfg(test)]
+mod test {
+    use axum::{extract::Query, http::Uri};
+    use serde_json::json;
+
+    use super::*;
+
+    fn try_params(query: &str) -> Option<WsParams> {
+        let uri: Uri = format!("/ws?{query}").parse().unwrap();
+        Query::try_from_uri(&uri).ok().map(|Query(params)| params)
+    }
+
+    fn params(query: &str) -> WsParams {
+        try_params(query).unwrap()
+    }
+
+    fn filter(query: &str) -> EventFilter {
+        WsOptions::try_from(params(query)).unwrap().filter
+    }
+
+    #[test]
+    fn test_ws_options_defaults() {
+        let options = WsOptions::try_from(params("")).unwrap();
+        assert_eq!(options.stats_interval, None);
+        assert_eq!(options.filter.kinds, None);
+        assert_eq!(options.filter.input_ids, None);
+        assert_eq!(options.filter.output_ids, None);
+    }
+
+    #[test]
+    fn test_ws_options_parse_lists() {
+        let options = WsOptions::try_from(params(
+            "stats_interval_ms=1000&events=INPUT_STATS,%20OUTPUT_STATS,&input_ids=input_1",
+        ))
+        .unwrap();
+        assert_eq!(options.stats_interval, Some(Duration::from_secs(1)));
+        assert_eq!(
+            options.filter.kinds,
+            Some(HashSet::from([
+                "INPUT_STATS".to_string(),
+                "OUTPUT_STATS".to_string()
+            ]))
+        );
+        assert_eq!(
+            options.filter.input_ids,
+            Some(HashSet::from(["input_1".to_string()]))
+        );
+        assert_eq!(options.filter.output_ids, None);
+    }
+
+    #[test]
+    fn test_ws_options_stats_interval_too_short() {
+        assert!(WsOptions::try_from(params("stats_interval_ms=99")).is_err());
+        assert!(WsOptions::try_from(params("stats_interval_ms=100")).is_ok());
+        assert!(try_params("stats_interval_ms=abc").is_none());
+    }
+
+    #[test]
+    fn test_event_filter_without_filters() {
+        let filter = filter("");
+        assert!(filter.matches(&json!({ "type": "VIDEO_INPUT_EOS", "input_id": "input_1" })));
+        assert!(filter.matches(&json!({ "type": "OUTPUT_DONE", "output_id": "output_1" })));
+    }
+
+    #[test]
+    fn test_event_filter_kinds() {
+        let filter = filter("events=INPUT_STATS");
+        assert!(filter.matches_kind("INPUT_STATS"));
+        assert!(!filter.matches_kind("OUTPUT_STATS"));
+        assert!(filter.matches(&json!({ "type": "INPUT_STATS", "input_id": "input_1" })));
+        assert!(!filter.matches(&json!({ "type": "OUTPUT_STATS", "output_id": "output_1" })));
+    }
+
+    #[test]
+    fn test_event_filter_ids() {
+        let filter = filter("input_ids=input_1,input_2&output_ids=output_1");
+        assert!(filter.matches(&json!({ "type": "VIDEO_INPUT_EOS", "input_id": "input_2" })));
+        assert!(!filter.matches(&json!({ "type": "VIDEO_INPUT_EOS", "input_id": "input_3" })));
+        assert!(filter.matches(&json!({ "type": "OUTPUT_DONE", "output_id": "output_1" })));
+        assert!(!filter.matches(&json!({ "type": "OUTPUT_DONE", "output_id": "output_2" })));
+        // Events that are not related to any input or output are not filtered by IDs.
+        assert!(filter.matches(&json!({ "type": "RENDERER_ERROR" })));
+    }
+}
@@ -0,0 +1,360 @@
+use std::{collections::HashMap, time::Instant};
+
+use compositor_pipeline::{
+    metrics::{InputMetrics, OutputMetrics},
+    pipeline::{InputInfo, InputType, OutputInfo},
+};
+use compositor_render::{InputId, OutputId, Resolution};
+use serde_json::json;
+
+use crate::state::ApiState;
+
+pub(super) const INPUT_STATS: &str = "INPUT_STATS";
+pub(super) const OUTPUT_STATS: &str = "OUTPUT_STATS";
+
+/// Produces stats events based on the difference between the pipeline counters
+/// sampled in consecutive calls.
+pub(super) struct StatsCollector {
+    sampled_at: Instant,
+    inputs: HashMap<InputId, InputCounters>,
+    outputs: HashMap<OutputId, OutputCounters>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct InputCounters {
+    received_bytes: u64,
+    decoded_video_frames: u64,
+    late_frames: u64,
+    packets_lost: u64,
+    jitter_micros: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct OutputCounters {
+    encoded_bytes: u64,
+    encoded_frames: u64,
+    dropped_frames: u64,
+}
+
+struct Sample {
+    inputs: Vec<(InputInfo, InputCounters)>,
+    outputs: Vec<(OutputInfo, OutputCounters)>,
+}
+
+impl StatsCollector {
+    pub(super) fn new(api: &ApiState) -> Self {
+        Self::from_sample(Sample::new(api), Instant::now())
+    }
+
+    /// Returns stats events for the period since the previous call. Inputs and
+    /// outputs registered in the meantime are reported starting from the next call.
+    pub(super) fn collect(&mut self, api: &ApiState) -> Vec<serde_json::Value> {
+        self.collect_sample(Sample::new(api), Instant::now())
+    }
+
+    fn from_sample(sample: Sample, now: Instant) -> Self {
+        Self {
+            sampled_at: now,
+            inputs: counters_by_id(sample.inputs, |info| info.input_id.clone()),
+            outputs: counters_by_id(sample.outputs, |info| info.output_id.clone()),
+        }
+    }
+
+    fn collect_sample(&mut self, sample: Sample, now: Instant) -> Vec<serde_json::Value> {
+        let elapsed_secs = now.duration_since(self.sampled_at).as_secs_f64();
+        self.sampled_at = now;
+        let per_second = |current: u64, previous: u64| {
+            current.saturating_sub(previous) as f64 / f64::max(elapsed_secs, f64::EPSILON)
+        };
+
+        let mut events = Vec::new();
+        for (info, counters) in sample.inputs.iter() {
+            let Some(previous) = self.inputs.get(&info.input_id) else {
+                continue;
+            };
+            let is_rtp = info.input_type == InputType::Rtp;
+            let video = info.video.as_ref();
+            let bitrate = per_second(counters.received_bytes, previous.received_bytes) * 8.0;
+            let fps = per_second(counters.decoded_video_frames, previous.decoded_video_frames);
+            let packets_lost = counters.packets_lost.saturating_sub(previous.packets_lost);
+            let late_frames = counters.late_frames.saturating_sub(previous.late_frames);
+            events.push(json!({
+                "type": INPUT_STATS,
+                "input_id": info.input_id.to_string(),
+                "bitrate_bps": bitrate.round() as u64,
+                "fps": video.map(|_| fps),
+                "resolution": video.and_then(|video| video.resolution).map(resolution_json),
+                "packets_lost": is_rtp.then_some(packets_lost),
+                "jitter_ms": is_rtp.then_some(counters.jitter_micros as f64 / 1000.0),
+                "late_frames": video.map(|_| late_frames),
+            }));
+        }
+        for (info, counters) in sample.outputs.iter() {
+            let Some(previous) = self.outputs.get(&info.output_id) else {
+                continue;
+            };
+            let video = info.video.as_ref();
+            let bitrate = per_second(counters.encoded_bytes, previous.encoded_bytes) * 8.0;
+            let fps = per_second(counters.encoded_frames, previous.encoded_frames);
+            let dropped_frames = counters
+                .dropped_frames
+                .saturating_sub(previous.dropped_frames);
+            events.push(json!({
+                "type": OUTPUT_STATS,
+                "output_id": info.output_id.to_string(),
+                "bitrate_bps": bitrate.round() as u64,
+                "fps": video.map(|_| fps),
+                "resolution": video.and_then(|video| video.resolution).map(resolution_json),
+                "dropped_frames": video.map(|_| dropped_frames),
+            }));
+        }
+
+        self.inputs = counters_by_id(sample.inputs, |info| info.input_id.clone());
+        self.outputs = counters_by_id(sample.outputs, |info| info.output_id.clone());
+        events
+    }
+}
+
+impl Sample {
+    fn new(api: &ApiState) -> Self {
+        let (inputs_info, outputs_info, metrics) = {
+            let pipeline = api.pipeline();
+            (
+                pipeline.inputs_info(),
+                pipeline.outputs_info(),
+                pipeline.metrics().clone(),
+            )
+        };
+        let input_metrics: HashMap<_, _> = metrics.inputs().into_iter().collect();
+        let output_metrics: HashMap<_, _> = metrics.outputs().into_iter().collect();
+
+        let mut inputs: Vec<_> = inputs_info
+            .into_iter()
+            .filter_map(|info| {
+                let counters = InputCounters::from(input_metrics.get(&info.input_id)?.as_ref());
+                Some((info, counters))
+            })
+            .collect();
+        inputs.sort_by(|(a, _), (b, _)| a.input_id.0.cmp(&b.input_id.0));
+
+        let mut outputs: Vec<_> = outputs_info
+            .into_iter()
+            .filter_map(|info| {
+                let counters = OutputCounters::from(output_metrics.get(&info.output_id)?.as_ref());
+                Some((info, counters))
+            })
+            .collect();
+        outputs.sort_by(|(a, _), (b, _)| a.output_id.0.cmp(&b.output_id.0));
+
+        Self { inputs, outputs }
+    }
+}
+
+impl From<&InputMetrics> for InputCounters {
+    fn from(metrics: &InputMetrics) -> Self {
+        Self {
+            received_bytes: metrics.received_bytes.get(),
+            decoded_video_frames: metrics.decoded_video_frames.get(),
+            late_frames: metrics.late_frames.get(),
+            packets_lost: metrics.packets_lost.get(),
+            jitter_micros: metrics.jitter_micros.get(),
+        }
+    }
+}
+
+impl From<&OutputMetrics> for OutputCounters {
+    fn from(metrics: &OutputMetrics) -> Self {
+        Self {
+            encoded_bytes: metrics.encoded_bytes.get(),
+            encoded_frames: metrics.encoded_frames.get(),
+            dropped_frames: metrics.dropped_frames.get(),
+        }
+    }
+}
+
+fn counters_by_id<Info, Id, Counters>(
+    items: Vec<(Info, Counters)>,
+    id: impl Fn(&Info) -> Id,
+) -> HashMap<Id, Counters>
+where
+    Id: std::hash::Hash + Eq,
+{
+    items
+        .into_iter()
+        .map(|(info, counters)| (id(&info), counters))
+        .collect()
+}
+
+fn resolution_json(resolution: Resolution) -> serde_json::Value {
+    json!({
+        "width": resolution.width,
+        "height": resolution.height,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use compositor_pipeline::pipeline::{InputVideoInfo, OutputType, OutputVideoInfo};
+
+    use super::*;
+
+    fn input(input_id: &str, input_type: InputType, counters: InputCounters) -> Sample {
+        let info = InputInfo {
+            input_id: InputId(input_id.into()),
+            input_type,
+            port: None,
+            video: Some(InputVideoInfo {
+                decoder: None,
+                resolution: None,
+                state: None,
+                eos_received: false,
+            }),
+            audio: None,
+            clock_drift_ppm: None,
+        };
+        Sample {
+            inputs: vec![(info, counters)],
+            outputs: vec![],
+        }
+    }
+
+    fn output(output_id: &str, counters: OutputCounters) -> Sample {
+        let info = OutputInfo {
+            output_id: OutputId(output_id.into()),
+            output_type: OutputType::Rtp,
+            port: None,
+            video: Some(OutputVideoInfo {
+                resolution: Some(Resolution {
+                    width: 1280,
+                    height: 720,
+                }),
+                framerate: None,
+                encoder: None,
+                eos_sent: false,
+            }),
+            audio: None,
+        };
+        Sample {
+            inputs: vec![],
+            outputs: vec![(info, counters)],
+        }
+    }
+
+    fn input_counters(received_bytes: u64, decoded_video_frames: u64) -> InputCounters {
+        InputCounters {
+            received_bytes,
+            decoded_video_frames,
+            late_frames: 0,
+            packets_lost: 0,
+            jitter_micros: 0,
+        }
+    }
+
+    #[test]
+    fn test_input_stats_rates() {
+        let start = Instant::now();
+        let mut collector = StatsCollector::from_sample(
+            input("input_1", InputType::Rtp, input_counters(1000, 10)),
+            start,
+        );
+
+        let counters = InputCounters {
+            late_frames: 3,
+            packets_lost: 5,
+            jitter_micros: 2500,
+            ..input_counters(251_000, 60)
+        };
+        let events = collector.collect_sample(
+            input("input_1", InputType::Rtp, counters),
+            start + Duration::from_secs(2),
+        );
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["type"], INPUT_STATS);
+        assert_eq!(events[0]["input_id"], "input_1");
+        assert_eq!(events[0]["bitrate_bps"], 1_000_000);
+        assert_eq!(events[0]["fps"], 25.0);
+        assert_eq!(events[0]["packets_lost"], 5);
+        assert_eq!(events[0]["jitter_ms"], 2.5);
+        assert_eq!(events[0]["late_frames"], 3);
+    }
+
+    #[test]
+    fn test_input_stats_are_relative_to_previous_call() {
+        let start = Instant::now();
+        let mut collector = StatsCollector::from_sample(
+            input("input_1", InputType::Mp4, input_counters(0, 0)),
+            start,
+        );
+        collector.collect_sample(
+            input("input_1", InputType::Mp4, input_counters(1000, 30)),
+            start + Duration::from_secs(1),
+        );
+
+        let events = collector.collect_sample(
+            input("input_1", InputType::Mp4, input_counters(1500, 45)),
+            start + Duration::from_secs(2),
+        );
+        assert_eq!(events[0]["bitrate_bps"], 4000);
+        assert_eq!(events[0]["fps"], 15.0);
+        // Only reported for RTP inputs.
+        assert!(events[0]["packets_lost"].is_null());
+        assert!(events[0]["jitter_ms"].is_null());
+    }
+
+    #[test]
+    fn test_output_stats_rates() {
+        let start = Instant::now();
+        let counters = |encoded_bytes, encoded_frames, dropped_frames| OutputCounters {
+            encoded_bytes,
+            encoded_frames,
+            dropped_frames,
+        };
+        let mut collector =
+            StatsCollector::from_sample(output("output_1", counters(0, 0, 2)), start);
+
+        let events = collector.collect_sample(
+            output("output_1", counters(50_000, 15, 6)),
+            start + Duration::from_millis(500),
+        );
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["type"], OUTPUT_STATS);
+        assert_eq!(events[0]["output_id"], "output_1");
+        assert_eq!(events[0]["bitrate_bps"], 800_000);
+        assert_eq!(events[0]["fps"], 30.0);
+        assert_eq!(events[0]["dropped_frames"], 4);
+        assert_eq!(
+            events[0]["resolution"],
+            json!({ "width": 1280, "height": 720 })
+        );
+    }
+
+    #[test]
+    fn test_new_entities_are_reported_from_next_call() {
+        let start = Instant::now();
+        let mut collector = StatsCollector::from_sample(
+            Sample {
+                inputs: vec![],
+                outputs: vec![],
+            },
+            start,
+        );
+
+        let events = collector.collect_sample(
+            input("input_1", InputType::Rtp, input_counters(1000, 10)),
+            start + Duration::from_secs(1),
+        );
+        assert!(events.is_empty());
+
+        let events = collector.collect_sample(
+            input("input_1", InputType::Rtp, input_counters(2000, 20)),
+            start + Duration::from_secs(2),
+        );
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["bitrate_bps"], 8000);
+        assert_eq!(events[0]["fps"], 10.0);
+    }
+}
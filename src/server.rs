@@ -1,17 +1,50 @@
-use compositor_render::error::ErrorStack;
+use compositor_pipeline::error::{RegisterInputError, RegisterOutputError};
+use compositor_render::error::{ErrorStack, RegisterRendererError};
 use crossbeam_channel::Receiver;
+use glyphon::fontdb::Source;
 use log::info;
 use signal_hook::{consts, iterator::Signals};
 use tracing::error;
 
-use std::{net::SocketAddr, process, sync::Arc, thread};
+use std::{net::SocketAddr, path::Path, process, sync::Arc, thread};
 use tokio::runtime::Runtime;
 
-use crate::{config::read_config, logger::init_logger, routes::routes, state::ApiState};
+use crate::{
+    config::{read_config, read_config_file, Config, StartupOutput, StartupResources},
+    logger::init_logger,
+    routes::routes,
+    state::{ApiState, Pipeline},
+};
 
 pub fn run() {
+    start(read_config(), StartupResources::default())
+}
+
+/// Starts the server with the config and entities defined in the config file.
+pub fn run_with_config_file(path: &Path) {
+    match read_config_file(path) {
+        Ok((config, resources)) => start(config, resources),
+        Err(err) => {
+            eprintln!("{}", ErrorStack::new(&err).into_string());
+            process::exit(1);
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum StartupError {
+    #[error(transparent)]
+    RegisterRenderer(#[from] RegisterRendererError),
+
+    #[error(transparent)]
+    RegisterInput(#[from] RegisterInputError),
+
+    #[error(transparent)]
+    RegisterOutput(#[from] RegisterOutputError),
+}
+
+fn start(config: Config, resources: StartupResources) {
     listen_for_parent_termination();
-    let config = read_config();
     init_logger(config.logger.clone());
 
     info!("Starting Smelter with config:\n{:#?}", config);
@@ -26,6 +59,13 @@ pub fn run() {
     thread::Builder::new()
         .name("HTTP server startup thread".to_string())
         .spawn(move || {
+            if let Err(err) = register_startup_resources(&state, resources) {
+                error!(
+                    "Failed to register entities from the config file.\n{}",
+                    ErrorStack::new(&err).into_string()
+                );
+                process::exit(1);
+            }
             let (_should_close_sender, should_close_receiver) = crossbeam_channel::bounded(1);
             if let Err(err) = run_api(state, runtime, should_close_receiver) {
                 error!(%err);
@@ -45,6 +85,47 @@ pub fn run() {
     }
 }
 
+fn register_startup_resources(
+    state: &ApiState,
+    resources: StartupResources,
+) -> Result<(), StartupError> {
+    for font in resources.fonts {
+        Pipeline::register_font(
+            &state.pipeline.lock().unwrap(),
+            Source::Binary(Arc::new(font.clone())),
+        );
+        state.registrations.lock().fonts.push(font);
+    }
+    for (renderer_id, spec, renderer) in resources.renderers {
+        Pipeline::register_renderer(&state.pipeline, renderer_id.clone(), spec)?;
+        let key = (renderer_id, renderer.registry_type());
+        state.registrations.lock().renderers.insert(key, renderer);
+    }
+    for (input_id, options, input) in resources.inputs {
+        Pipeline::register_input(&state.pipeline, input_id.clone(), options)?;
+        state.registrations.lock().inputs.insert(input_id, input);
+    }
+    for StartupOutput {
+        output_id,
+        options,
+        scene,
+        request,
+    } in resources.outputs
+    {
+        Pipeline::register_output(&state.pipeline, output_id.clone(), options)?;
+        state.output_scenes.insert(output_id.clone(), scene);
+        state
+            .registrations
+            .lock()
+            .outputs
+            .insert(output_id, request);
+    }
+    if resources.start {
+        Pipeline::start(&state.pipeline);
+    }
+    Ok(())
+}
+
 pub fn run_api(
     state: ApiState,
     runtime: Arc<Runtime>,
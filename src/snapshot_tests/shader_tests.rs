@@ -4,7 +4,7 @@ use compositor_render::{
     scene::{
         Component, InputStreamComponent, ShaderComponent, ShaderParam, ShaderParamStructField,
     },
-    shader::ShaderSpec,
+    shader::{ShaderPassInput, ShaderPassResolution, ShaderPassSpec, ShaderSpec},
     InputId, RendererId, RendererSpec,
 };
 
@@ -26,6 +26,10 @@ fn shader_tests() {
         RendererId("base_params_plane_id".into()),
         RendererSpec::Shader(ShaderSpec {
             source: include_str!("../../snapshot_tests/shader/layout_planes.wgsl").into(),
+            previous_frame: false,
+            input_history_len: 0,
+            inputs: None,
+            passes: Vec::new(),
         }),
     );
 
@@ -33,6 +37,10 @@ fn shader_tests() {
         RendererId("base_params_time".into()),
         RendererSpec::Shader(ShaderSpec {
             source: include_str!("../../snapshot_tests/shader/fade_to_ball.wgsl").into(),
+            previous_frame: false,
+            input_history_len: 0,
+            inputs: None,
+            passes: Vec::new(),
         }),
     );
 
@@ -43,6 +51,10 @@ fn shader_tests() {
                 "../../snapshot_tests/shader/color_output_with_texture_count.wgsl"
             )
             .into(),
+            previous_frame: false,
+            input_history_len: 0,
+            inputs: None,
+            passes: Vec::new(),
         }),
     );
 
@@ -50,6 +62,10 @@ fn shader_tests() {
         RendererId("base_params_output_resolution".into()),
         RendererSpec::Shader(ShaderSpec {
             source: include_str!("../../snapshot_tests/shader/red_border.wgsl").into(),
+            previous_frame: false,
+            input_history_len: 0,
+            inputs: None,
+            passes: Vec::new(),
         }),
     );
 
@@ -126,6 +142,63 @@ fn shader_tests() {
         ..Default::default()
     });
 
+    let frame_history_shader = (
+        RendererId("frame_history".into()),
+        RendererSpec::Shader(ShaderSpec {
+            source: include_str!("../../snapshot_tests/shader/frame_history.wgsl").into(),
+            previous_frame: true,
+            input_history_len: 1,
+            inputs: None,
+            passes: Vec::new(),
+        }),
+    );
+
+    runner.add(TestCase {
+        name: "shader/frame_history",
+        scene_updates: scene_from_json(include_str!(
+            "../../snapshot_tests/shader/frame_history.scene.json"
+        )),
+        renderers: vec![frame_history_shader],
+        inputs: vec![input1.clone()],
+        // History is updated on every rendered frame, so consecutive frames differ
+        // even though the input and the scene do not change.
+        timestamps: vec![
+            Duration::from_millis(0),
+            Duration::from_millis(20),
+            Duration::from_millis(40),
+        ],
+        ..Default::default()
+    });
+
+    let multi_pass_shader = (
+        RendererId("multi_pass".into()),
+        RendererSpec::Shader(ShaderSpec {
+            source: include_str!("../../snapshot_tests/shader/multi_pass_main.wgsl").into(),
+            previous_frame: false,
+            input_history_len: 0,
+            inputs: Some(vec![
+                ShaderPassInput::Pass("invert".into()),
+                ShaderPassInput::Input(0),
+            ]),
+            passes: vec![ShaderPassSpec {
+                name: "invert".into(),
+                source: include_str!("../../snapshot_tests/shader/multi_pass_invert.wgsl").into(),
+                resolution: ShaderPassResolution::Scale(0.5),
+                inputs: None,
+            }],
+        }),
+    );
+
+    runner.add(TestCase {
+        name: "shader/multi_pass",
+        scene_updates: scene_from_json(include_str!(
+            "../../snapshot_tests/shader/multi_pass.scene.json"
+        )),
+        renderers: vec![multi_pass_shader],
+        inputs: vec![input1.clone()],
+        ..Default::default()
+    });
+
     user_params_snapshot_tests(&mut runner);
 
     runner.run()
@@ -254,6 +327,10 @@ fn user_params_snapshot_tests(runner: &mut TestRunner) {
             shader_id.clone(),
             RendererSpec::Shader(ShaderSpec {
                 source: include_str!("../../snapshot_tests/shader/circle_layout.wgsl").into(),
+                previous_frame: false,
+                input_history_len: 0,
+                inputs: None,
+                passes: Vec::new(),
             }),
         )],
         inputs: vec![input1, input2, input3, input4],
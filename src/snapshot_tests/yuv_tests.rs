@@ -49,6 +49,10 @@ fn yuv_test_gradient() {
             shader_id.clone(),
             RendererSpec::Shader(ShaderSpec {
                 source: include_str!("./yuv_tests/gradient.wgsl").into(),
+                previous_frame: false,
+                input_history_len: 0,
+                inputs: None,
+                passes: Vec::new(),
             }),
         )],
         resolution: Resolution { width, height },
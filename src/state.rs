@@ -1,13 +1,22 @@
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
 
 use axum::response::IntoResponse;
-use compositor_pipeline::{error::InitPipelineError, pipeline};
-use compositor_render::EventLoop;
+use bytes::Bytes;
+use compositor_api::types::{Audio, Video};
+use compositor_pipeline::{error::InitPipelineError, pipeline, queue::ScheduledEventId};
+use compositor_render::{EventLoop, InputId, OutputId, RegistryType, RendererId};
 
 use serde::Serialize;
 use tokio::runtime::Runtime;
 
-use crate::config::Config;
+use crate::{
+    config::Config,
+    routes::{ProjectRenderer, RegisterInput, RegisterOutput},
+};
 
 pub type Pipeline = compositor_pipeline::Pipeline;
 
@@ -25,6 +34,10 @@ pub enum Response {
     BearerToken {
         bearer_token: String,
     },
+    /// Request was scheduled, the event can be cancelled by its id.
+    Scheduled {
+        event_id: u64,
+    },
 }
 
 impl IntoResponse for Response {
@@ -37,6 +50,93 @@ impl IntoResponse for Response {
 pub struct ApiState {
     pub pipeline: Arc<Mutex<Pipeline>>,
     pub config: Config,
+    pub output_scenes: OutputScenes,
+    pub timelines: Timelines,
+    pub registrations: Registrations,
+}
+
+/// Scenes of the outputs in the form they were sent to the API. Pipeline only
+/// stores scenes after conversion, so they are tracked here to be returned
+/// by the introspection endpoints.
+#[derive(Clone, Default)]
+pub struct OutputScenes(Arc<Mutex<HashMap<OutputId, OutputScene>>>);
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OutputScene {
+    pub video: Option<Video>,
+    pub audio: Option<Audio>,
+}
+
+impl OutputScenes {
+    pub(crate) fn insert(&self, output_id: OutputId, scene: OutputScene) {
+        self.0.lock().unwrap().insert(output_id, scene);
+    }
+
+    /// Sets parts of the scene that are `Some`, the rest stays unchanged.
+    pub(crate) fn update(&self, output_id: &OutputId, video: Option<Video>, audio: Option<Audio>) {
+        let mut guard = self.0.lock().unwrap();
+        let scene = guard.entry(output_id.clone()).or_default();
+        if video.is_some() {
+            scene.video = video;
+        }
+        if audio.is_some() {
+            scene.audio = audio;
+        }
+    }
+
+    pub(crate) fn remove(&self, output_id: &OutputId) {
+        self.0.lock().unwrap().remove(output_id);
+    }
+
+    pub(crate) fn get(&self, output_id: &OutputId) -> OutputScene {
+        self.0
+            .lock()
+            .unwrap()
+            .get(output_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Timelines uploaded for the outputs. Each entry is applied by a separate
+/// scheduled event, so entries can be edited until their event is executed.
+#[derive(Clone, Default)]
+pub struct Timelines(Arc<Mutex<HashMap<OutputId, Timeline>>>);
+
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+    pub entries: BTreeMap<Duration, TimelineEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TimelineEntry {
+    pub scene: OutputScene,
+    pub event_id: ScheduledEventId,
+}
+
+impl Timelines {
+    pub(crate) fn lock(&self) -> MutexGuard<'_, HashMap<OutputId, Timeline>> {
+        self.0.lock().unwrap()
+    }
+}
+
+/// Requests of the registered entities in the form they were sent to the API.
+/// They are used to export the state, so it can be recreated on another instance.
+#[derive(Clone, Default)]
+pub struct Registrations(Arc<Mutex<RegisteredRequests>>);
+
+#[derive(Debug, Default)]
+pub struct RegisteredRequests {
+    pub inputs: HashMap<InputId, RegisterInput>,
+    pub outputs: HashMap<OutputId, RegisterOutput>,
+    pub renderers: HashMap<(RendererId, RegistryType), ProjectRenderer>,
+    pub fonts: Vec<Bytes>,
+}
+
+impl Registrations {
+    pub(crate) fn lock(&self) -> MutexGuard<'_, RegisteredRequests> {
+        self.0.lock().unwrap()
+    }
 }
 
 impl ApiState {
@@ -77,6 +177,9 @@ impl ApiState {
             ApiState {
                 pipeline: Mutex::new(pipeline).into(),
                 config,
+                output_scenes: OutputScenes::default(),
+                timelines: Timelines::default(),
+                registrations: Registrations::default(),
             },
             event_loop,
         ))
@@ -85,4 +188,15 @@ impl ApiState {
     pub(crate) fn pipeline(&self) -> MutexGuard<'_, Pipeline> {
         self.pipeline.lock().unwrap()
     }
+
+    /// Records requests of registered entities, so they are included in the state
+    /// export. Pipeline is locked in the meantime, the same as during the export,
+    /// so it never contains a partially recorded entity.
+    pub(crate) fn record_registration(
+        &self,
+        record: impl FnOnce(&mut RegisteredRequests, &OutputScenes),
+    ) {
+        let _pipeline = self.pipeline();
+        record(&mut self.registrations.lock(), &self.output_scenes);
+    }
 }